@@ -9,48 +9,22 @@
 
 use toolsearch::{
     search_tools, search_tools_with_keywords, search_tools_with_regex, SearchCriteria, SearchFields,
-    SearchMode, ServerConfig, TransportConfig,
+    SearchMode, ServerConfig,
 };
-use std::collections::HashMap;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Configure multiple MCP servers with complex settings
     let servers = vec![
-        ServerConfig {
-            name: "file_operations_server".to_string(),
-            transport: TransportConfig::Stdio {
-                command: "mcp-file-server".to_string(),
-                args: vec!["--verbose".to_string(), "--log-level=debug".to_string()],
-                env: {
-                    let mut env = HashMap::new();
-                    env.insert("RUST_LOG".to_string(), "debug".to_string());
-                    env.insert("MCP_SERVER_PORT".to_string(), "8080".to_string());
-                    env
-                },
-            },
-        },
-        ServerConfig {
-            name: "database_server".to_string(),
-            transport: TransportConfig::Stdio {
-                command: "mcp-db-server".to_string(),
-                args: vec![],
-                env: HashMap::new(),
-            },
-        },
-        ServerConfig {
-            name: "api_integration_server".to_string(),
-            transport: TransportConfig::Stdio {
-                command: "mcp-api-server".to_string(),
-                args: vec!["--config".to_string(), "/etc/mcp/config.json".to_string()],
-                env: {
-                    let mut env = HashMap::new();
-                    env.insert("API_KEY".to_string(), "secret-key".to_string());
-                    env.insert("ENVIRONMENT".to_string(), "production".to_string());
-                    env
-                },
-            },
-        },
+        ServerConfig::stdio("file_operations_server", "mcp-file-server")
+            .args(["--verbose", "--log-level=debug"])
+            .env("RUST_LOG", "debug")
+            .env("MCP_SERVER_PORT", "8080"),
+        ServerConfig::stdio("database_server", "mcp-db-server"),
+        ServerConfig::stdio("api_integration_server", "mcp-api-server")
+            .args(["--config", "/etc/mcp/config.json"])
+            .env("API_KEY", "secret-key")
+            .env("ENVIRONMENT", "production"),
     ];
 
     println!("=== Example 1: Regex Pattern Matching ===\n");
@@ -92,6 +66,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             title: true,
             description: false,
             input_schema: false,
+            output_schema: false,
+            annotations: false,
         });
     match search_tools(&servers, &criteria).await {
         Ok(results) => {
@@ -113,6 +89,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             title: false,
             description: true,
             input_schema: true, // Enable schema search
+            output_schema: false,
+            annotations: false,
         });
     match search_tools(&servers, &criteria).await {
         Ok(results) => {
@@ -166,6 +144,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             title: true,
             description: true,
             input_schema: false,
+            output_schema: false,
+            annotations: false,
         });
     match search_tools(&servers, &criteria).await {
         Ok(results) => {
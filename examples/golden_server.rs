@@ -0,0 +1,115 @@
+//! A tiny MCP server with a known tool set, for end-to-end tests
+//!
+//! Serves the twelve tools of `tests/golden/catalog.json` on stdio, so
+//! tests (and anyone trying toolsearch out) have a real server to connect
+//! to. Point a configuration at the built example:
+//!
+//! ```json
+//! [{ "name": "golden", "transport": { "type": "stdio", "command": "target/debug/examples/golden_server", "args": ["--page-size", "5"] } }]
+//! ```
+//!
+//! Options:
+//! - `--page-size N`: list the tools N per page, with offset cursors
+//!   (default: all on one page)
+//! - `--delay-ms N`: wait this long before answering each `tools/list`
+//! - `--name NAME`: implementation name reported in the handshake
+//!
+//! Calling a tool answers with the tool's name and arguments.
+
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, Content, ErrorData, Implementation, ListToolsResult,
+    PaginatedRequestParam, ServerCapabilities, ServerInfo, Tool,
+};
+use rmcp::service::RequestContext;
+use rmcp::{RoleServer, ServerHandler, ServiceExt};
+use std::time::Duration;
+
+/// The tools served, shared with the tests that check what is found
+const CATALOG: &str = include_str!("../tests/golden/catalog.json");
+
+struct GoldenServer {
+    tools: Vec<Tool>,
+    page_size: usize,
+    delay: Duration,
+    name: String,
+}
+
+impl ServerHandler for GoldenServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: self.name.clone(),
+                version: "1.0.0".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        tokio::time::sleep(self.delay).await;
+        let start = match request.and_then(|request| request.cursor) {
+            None => 0,
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .ok()
+                .filter(|start| *start <= self.tools.len())
+                .ok_or_else(|| {
+                    ErrorData::invalid_params(format!("Invalid cursor: {}", cursor), None)
+                })?,
+        };
+        let end = start.saturating_add(self.page_size).min(self.tools.len());
+        let mut page = ListToolsResult::with_all_items(self.tools[start..end].to_vec());
+        if end < self.tools.len() {
+            page.next_cursor = Some(end.to_string());
+        }
+        Ok(page)
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !self.tools.iter().any(|tool| tool.name == request.name) {
+            return Err(ErrorData::invalid_params(
+                format!("Unknown tool: {}", request.name),
+                None,
+            ));
+        }
+        let arguments = serde_json::Value::Object(request.arguments.unwrap_or_default());
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{} {}",
+            request.name, arguments
+        ))]))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = GoldenServer {
+        tools: serde_json::from_str(CATALOG)?,
+        page_size: usize::MAX,
+        delay: Duration::ZERO,
+        name: "golden".to_string(),
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--page-size" => server.page_size = value()?.parse::<usize>()?.max(1),
+            "--delay-ms" => server.delay = Duration::from_millis(value()?.parse()?),
+            "--name" => server.name = value()?,
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+
+    let service = server.serve((tokio::io::stdin(), tokio::io::stdout())).await?;
+    service.waiting().await?;
+    Ok(())
+}
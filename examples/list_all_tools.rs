@@ -6,7 +6,6 @@
 //! 3. Display results grouped by server
 
 use toolsearch::{load_servers, SearchBuilder};
-use serde_json;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
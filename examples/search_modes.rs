@@ -6,21 +6,11 @@
 //! - Keyword matching
 //! - Word boundary matching
 
-use toolsearch::{search_tools, SearchCriteria, SearchFields, SearchMode, ServerConfig, TransportConfig};
-use std::collections::HashMap;
+use toolsearch::{search_tools, SearchCriteria, SearchFields, SearchMode, ServerConfig};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let servers = vec![
-        ServerConfig {
-            name: "example_server".to_string(),
-            transport: TransportConfig::Stdio {
-                command: "mcp-server".to_string(),
-                args: vec![],
-                env: HashMap::new(),
-            },
-        },
-    ];
+    let servers = vec![ServerConfig::stdio("example_server", "mcp-server")];
 
     let search_term = "read";
 
@@ -77,6 +67,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             title: false,
             description: false,
             input_schema: false,
+            output_schema: false,
+            annotations: false,
         });
     match search_tools(&servers, &criteria).await {
         Ok(results) => println!("   Found {} result(s)\n", results.len()),
@@ -0,0 +1,34 @@
+/*
+ * C interface to toolsearch (build with `cargo build --release --features ffi`
+ * and link against libtoolsearch).
+ *
+ * Search functions return a JSON string owned by the library:
+ *   {"ok": true, "matches": [...]}  or  {"ok": false, "error": "..."}
+ * Release it with toolsearch_string_free().
+ */
+#ifndef TOOLSEARCH_H
+#define TOOLSEARCH_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Search servers given as a JSON array (config file format). limit 0 = no limit. */
+char *toolsearch_search(const char *servers_json, const char *query, size_t limit);
+
+/* Search servers loaded from a JSON config file. limit 0 = no limit. */
+char *toolsearch_search_file(const char *config_path, const char *query, size_t limit);
+
+/* Release a string returned by toolsearch_search*(). NULL is ignored. */
+void toolsearch_string_free(char *s);
+
+/* Library version; static, do not free. */
+const char *toolsearch_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TOOLSEARCH_H */
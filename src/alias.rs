@@ -0,0 +1,139 @@
+//! Shorthand names for tools
+//!
+//! Servers name the same kind of tool differently, and people rarely
+//! remember any of those names. An [`AliasMap`] maps shorthand to the
+//! canonical names servers use, so a search for `grep` also finds
+//! `search_files`. The file is a JSON object whose values are one name or a
+//! list of names:
+//!
+//! ```json
+//! { "grep": "search_files", "ls": ["list_directory", "list_dir"] }
+//! ```
+//!
+//! [`SearchBuilder::aliases`](crate::SearchBuilder::aliases) consults the
+//! map before matching: a query or exact name that is an alias also matches
+//! the tools named by it. The CLI reads the [default
+//! file](AliasMap::default_path) when it exists.
+//!
+//! ```
+//! use toolsearch::alias::AliasMap;
+//!
+//! let mut aliases = AliasMap::new();
+//! aliases.insert("grep", "search_files");
+//! assert_eq!(aliases.resolve("GREP"), ["search_files"]);
+//! assert!(aliases.resolve("find").is_empty());
+//! ```
+
+use crate::{casefold, read_json_file, user_config_file, ToolSearchError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Shorthand names and the tool names they stand for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, Names>", into = "BTreeMap<String, Names>")]
+pub struct AliasMap {
+    /// Tool names by casefolded alias
+    aliases: BTreeMap<String, Vec<String>>,
+}
+
+/// One tool name or several, as written in the alias file
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Names {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<BTreeMap<String, Names>> for AliasMap {
+    fn from(entries: BTreeMap<String, Names>) -> Self {
+        let mut map = AliasMap::new();
+        for (alias, names) in entries {
+            let names = match names {
+                Names::One(name) => vec![name],
+                Names::Many(names) => names,
+            };
+            for name in names {
+                map.insert(&alias, name);
+            }
+        }
+        map
+    }
+}
+
+impl From<AliasMap> for BTreeMap<String, Names> {
+    fn from(map: AliasMap) -> Self {
+        map.aliases
+            .into_iter()
+            .map(|(alias, mut names)| {
+                let names = if names.len() == 1 {
+                    Names::One(names.remove(0))
+                } else {
+                    Names::Many(names)
+                };
+                (alias, names)
+            })
+            .collect()
+    }
+}
+
+impl AliasMap {
+    /// Map without aliases
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let `alias` stand for the tool `name` too
+    ///
+    /// Aliases are matched ignoring case and surrounding whitespace.
+    pub fn insert(&mut self, alias: &str, name: impl Into<String>) {
+        let name = name.into();
+        let names = self.aliases.entry(fold_alias(alias)).or_default();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    /// Tool names `shorthand` stands for; empty if it isn't an alias
+    pub fn resolve(&self, shorthand: &str) -> &[String] {
+        self.aliases
+            .get(&fold_alias(shorthand))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Number of aliases
+    pub fn len(&self) -> usize {
+        self.aliases.len()
+    }
+
+    /// Whether the map has no aliases
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// Load an alias file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ToolSearchError> {
+        Ok(read_json_file(path.as_ref())?)
+    }
+
+    /// Load the [default alias file](Self::default_path); empty if there is
+    /// none
+    pub fn load_default() -> Result<Self, ToolSearchError> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::new()),
+        }
+    }
+
+    /// Default alias file: `$XDG_CONFIG_HOME/toolsearch/aliases.json`, or
+    /// `~/.config/toolsearch/aliases.json`
+    ///
+    /// `None` if neither variable is set.
+    pub fn default_path() -> Option<PathBuf> {
+        user_config_file("aliases.json")
+    }
+}
+
+fn fold_alias(alias: &str) -> String {
+    casefold::fold(alias.trim()).into_owned()
+}
@@ -0,0 +1,79 @@
+//! Tools and servers hidden from every search
+//!
+//! Some tools should never reach an agent, whatever it searches for: admin
+//! tools that drop databases, say. A [`Blocklist`] names them, each entry a
+//! tool name pattern (`*` stands for any run of characters) or
+//! `server/pattern` for one server's tools. `server/*` blocks a whole
+//! server, which then isn't contacted at all. A server's own config can
+//! block its tools too, with `"blocked_tools": ["drop_*"]`.
+//!
+//! Blocked tools are dropped as they are listed, so they don't count toward
+//! any limit, and they never show up in results, catalogs, or a proxy's
+//! tool list.
+//!
+//! ```
+//! use toolsearch::block::Blocklist;
+//!
+//! let blocklist = Blocklist::new(["drop_*", "admin/*", "db/truncate"]);
+//! assert!(blocklist.blocks("files", "drop_table"));
+//! assert!(blocklist.blocks("db", "truncate"));
+//! assert!(!blocklist.blocks("files", "truncate"));
+//! assert!(blocklist.blocks_server("admin"));
+//! assert!(!blocklist.blocks_server("db"));
+//! ```
+
+use crate::glob_matches;
+use serde::{Deserialize, Serialize};
+
+/// Tool name patterns, optionally for one server, that no search returns
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Blocklist {
+    entries: Vec<String>,
+}
+
+impl Blocklist {
+    /// Block the tools and servers `entries` name; see the
+    /// [module docs](self)
+    pub fn new(entries: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            entries: entries.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Also block what `entry` names
+    pub fn push(&mut self, entry: impl Into<String>) {
+        self.entries.push(entry.into());
+    }
+
+    /// The entries, as given
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Whether nothing is blocked
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Tool name patterns that apply to server `server_name`
+    pub fn tool_patterns<'a>(&'a self, server_name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.entries
+            .iter()
+            .filter_map(move |entry| match entry.split_once('/') {
+                Some((server, pattern)) => (server == server_name).then_some(pattern),
+                None => Some(entry.as_str()),
+            })
+    }
+
+    /// Whether the tool `tool_name` of server `server_name` is blocked
+    pub fn blocks(&self, server_name: &str, tool_name: &str) -> bool {
+        self.tool_patterns(server_name)
+            .any(|pattern| glob_matches(pattern, tool_name))
+    }
+
+    /// Whether every tool of server `server_name` is blocked
+    pub fn blocks_server(&self, server_name: &str) -> bool {
+        self.tool_patterns(server_name).any(|pattern| pattern == "*")
+    }
+}
@@ -0,0 +1,146 @@
+//! Circuit breaker for repeatedly failing servers
+//!
+//! After `failure_threshold` consecutive failures a server's circuit opens and
+//! connections to it are no longer attempted. Once the cooldown has passed the
+//! circuit becomes half-open and a single trial attempt is allowed: success
+//! closes the circuit, failure opens it for another cooldown.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Circuit breaker settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures before the circuit opens
+    pub failure_threshold: u32,
+    /// How long an open circuit waits before allowing a trial attempt
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+/// State of a server's circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests are blocked until the cooldown passes
+    Open,
+    /// Cooldown passed; the next request is a trial
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Per-server circuit breaker
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreaker {
+    /// Create a circuit breaker with the given settings
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Settings of this breaker
+    pub fn config(&self) -> CircuitBreakerConfig {
+        self.config
+    }
+
+    /// Current state of a server's circuit
+    pub fn state(&self, server_name: &str) -> CircuitState {
+        let circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        match circuits.get(server_name).and_then(|c| c.opened_at) {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() >= self.config.cooldown => CircuitState::HalfOpen,
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    /// Whether a connection attempt to the server should be made now
+    ///
+    /// In the half-open state only one trial attempt is allowed until its
+    /// outcome is recorded.
+    pub fn allow(&self, server_name: &str) -> bool {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(circuit) = circuits.get_mut(server_name) else {
+            return true;
+        };
+        match circuit.opened_at {
+            None => true,
+            Some(opened_at) if opened_at.elapsed() >= self.config.cooldown => {
+                if circuit.trial_in_flight {
+                    false
+                } else {
+                    circuit.trial_in_flight = true;
+                    true
+                }
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Record a successful attempt, closing the circuit
+    pub fn record_success(&self, server_name: &str) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        circuits.remove(server_name);
+    }
+
+    /// Record a failed attempt, opening the circuit once the threshold is reached
+    pub fn record_failure(&self, server_name: &str) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let circuit = circuits.entry(server_name.to_string()).or_default();
+        circuit.consecutive_failures += 1;
+        circuit.trial_in_flight = false;
+        if circuit.opened_at.is_some()
+            || circuit.consecutive_failures >= self.config.failure_threshold
+        {
+            circuit.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// Record an attempt abandoned before it completed (e.g. cut off by a
+    /// search deadline), counting as neither success nor failure
+    pub fn record_abandoned(&self, server_name: &str) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(circuit) = circuits.get_mut(server_name) {
+            circuit.trial_in_flight = false;
+        }
+    }
+
+    /// Forget the recorded failures of one server
+    pub fn forget(&self, server_name: &str) {
+        self.circuits
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(server_name);
+    }
+
+    /// Forget all recorded failures
+    pub fn reset(&self) {
+        self.circuits
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
@@ -0,0 +1,61 @@
+//! Case-insensitive text comparison
+//!
+//! Uses Unicode default case folding, so `Straße` matches `STRASSE` and
+//! `ΣΊΣΥΦΟΣ` matches `σίσυφος`. ASCII text takes a fast path that avoids
+//! allocating when it is already lowercase.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Case-fold `text` for caseless comparison
+pub(crate) fn fold(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() {
+        if text.bytes().any(|b| b.is_ascii_uppercase()) {
+            Cow::Owned(text.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(text)
+        }
+    } else {
+        Cow::Owned(caseless::default_case_fold_str(text))
+    }
+}
+
+/// Whether `a` and `b` are equal ignoring case
+pub(crate) fn eq_ignore_case(a: &str, b: &str) -> bool {
+    if a.is_ascii() && b.is_ascii() {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        fold(a) == fold(b)
+    }
+}
+
+/// Byte ranges in `haystack` of the non-overlapping caseless matches of
+/// `needle`
+///
+/// Folding can change byte lengths (`ß` folds to `ss`), so the search runs on
+/// the folded text and maps each hit back to whole characters of `haystack`.
+pub(crate) fn find_all(haystack: &str, needle: &str) -> Vec<Range<usize>> {
+    let needle = fold(needle);
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    if haystack.is_ascii() {
+        let folded = fold(haystack);
+        return folded
+            .match_indices(&*needle)
+            .map(|(start, hit)| start..start + hit.len())
+            .collect();
+    }
+
+    // Original character span behind every byte of the folded text
+    let mut folded = String::with_capacity(haystack.len());
+    let mut spans = Vec::with_capacity(haystack.len());
+    for (start, c) in haystack.char_indices() {
+        folded.push_str(&caseless::default_case_fold_str(c.encode_utf8(&mut [0; 4])));
+        spans.resize(folded.len(), start..start + c.len_utf8());
+    }
+    folded
+        .match_indices(&*needle)
+        .map(|(start, hit)| spans[start].start..spans[start + hit.len() - 1].end)
+        .collect()
+}
@@ -0,0 +1,339 @@
+//! Fetched tool catalogs
+//!
+//! A search fetches tools and throws them away once matched. A [`Catalog`]
+//! keeps every tool of every server instead, so any number of criteria can
+//! run against one fetch, and the tools can be saved and searched later
+//! without connecting again (it serializes with serde). Catalogs from
+//! different configurations, say staging and production, can be combined
+//! with [`merge`](Catalog::merge) or compared with
+//! [`difference`](Catalog::difference) and [`intersect`](Catalog::intersect).
+//! [`changes`](Catalog::changes) lists what happened between two fetches of
+//! the same servers as [`CatalogEvent`]s, for dashboards and agents that
+//! follow a fleet's tools over time.
+//!
+//! ```no_run
+//! use toolsearch::{fetch_catalog, load_servers, SearchCriteria, SearchOptions};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let servers = load_servers("servers.json")?;
+//! let catalog = fetch_catalog(&servers, &SearchOptions::default()).await?;
+//! println!("{} tools", catalog.len());
+//!
+//! for query in ["read", "write"] {
+//!     let matches = catalog.search(&SearchCriteria::with_query(query.to_string()));
+//!     println!("{}: {} match(es)", query, matches.len());
+//! }
+//! std::fs::write("catalog.json", serde_json::to_string(&catalog)?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::report::SearchWarning;
+use crate::{
+    notify_complete, report_from_listings, Boosts, Redaction, SearchCriteria, SearchOptions,
+    ToolSearchMatch,
+};
+use rmcp::model::{ServerInfo, Tool};
+use serde::{Deserialize, Serialize};
+
+/// Every tool of one server in a [`Catalog`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogServer {
+    /// Name of the server (or other tool source)
+    pub name: String,
+    /// Ranking priority of the server, used by
+    /// [`SortOrder::ServerPriority`](crate::SortOrder::ServerPriority)
+    #[serde(default, skip_serializing_if = "is_default_priority")]
+    pub priority: u32,
+    /// Score multipliers for the server's matches
+    #[serde(default, skip_serializing_if = "Boosts::is_empty")]
+    pub boosts: Boosts,
+    /// Tools the server listed
+    pub tools: Vec<Tool>,
+    /// Initialize response, for MCP servers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_info: Option<ServerInfo>,
+}
+
+fn is_default_priority(priority: &u32) -> bool {
+    *priority == 0
+}
+
+/// A change to one tool between two catalogs, from [`Catalog::changes`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CatalogEvent {
+    /// A server lists a tool it didn't list before
+    ToolAdded {
+        /// Name of the server
+        server_name: String,
+        /// The new tool
+        tool: Tool,
+    },
+    /// A server no longer lists a tool
+    ToolRemoved {
+        /// Name of the server
+        server_name: String,
+        /// Name of the tool that is gone
+        tool_name: String,
+    },
+    /// A server lists a tool of the same name with a different definition
+    ToolChanged {
+        /// Name of the server
+        server_name: String,
+        /// The tool as it is now
+        tool: Tool,
+    },
+}
+
+impl CatalogEvent {
+    /// Name of the server whose tool changed
+    pub fn server_name(&self) -> &str {
+        match self {
+            CatalogEvent::ToolAdded { server_name, .. }
+            | CatalogEvent::ToolRemoved { server_name, .. }
+            | CatalogEvent::ToolChanged { server_name, .. } => server_name,
+        }
+    }
+
+    /// Name of the tool that changed
+    pub fn tool_name(&self) -> &str {
+        match self {
+            CatalogEvent::ToolAdded { tool, .. } | CatalogEvent::ToolChanged { tool, .. } => {
+                tool.name.as_ref()
+            }
+            CatalogEvent::ToolRemoved { tool_name, .. } => tool_name,
+        }
+    }
+}
+
+/// Tools of several servers, fetched once and searched any number of times
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Catalog {
+    servers: Vec<CatalogServer>,
+    /// Problems skipped over while fetching; not serialized
+    #[serde(skip)]
+    warnings: Vec<SearchWarning>,
+}
+
+impl Catalog {
+    /// Catalog of the given servers, in order
+    pub fn new(servers: Vec<CatalogServer>) -> Self {
+        Self {
+            servers,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_warnings(mut self, warnings: Vec<SearchWarning>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Servers in the catalog, in configuration order
+    pub fn servers(&self) -> &[CatalogServer] {
+        &self.servers
+    }
+
+    /// The server named `name`, if it is in the catalog
+    pub fn server(&self, name: &str) -> Option<&CatalogServer> {
+        self.servers.iter().find(|server| server.name == name)
+    }
+
+    /// Servers that were skipped while fetching (failed, or without tools)
+    pub fn warnings(&self) -> &[SearchWarning] {
+        &self.warnings
+    }
+
+    /// Every tool, with the name of its server, in configuration order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Tool)> {
+        self.servers
+            .iter()
+            .flat_map(|server| server.tools.iter().map(|tool| (server.name.as_str(), tool)))
+    }
+
+    /// Number of tools across all servers
+    pub fn len(&self) -> usize {
+        self.servers.iter().map(|server| server.tools.len()).sum()
+    }
+
+    /// Whether the catalog has no tools
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Tools of both catalogs
+    ///
+    /// Servers keep `self`'s order, followed by servers only in `other`. A
+    /// server in both gets the tools of `other` it doesn't already have (by
+    /// name); where both have a tool, `self`'s definition is kept.
+    pub fn merge(&self, other: &Catalog) -> Catalog {
+        let mut merged = self.clone();
+        for server in &other.servers {
+            match merged.servers.iter_mut().find(|s| s.name == server.name) {
+                Some(existing) => {
+                    let added: Vec<Tool> = server
+                        .tools
+                        .iter()
+                        .filter(|tool| !existing.tools.iter().any(|t| t.name == tool.name))
+                        .cloned()
+                        .collect();
+                    existing.tools.extend(added);
+                }
+                None => merged.servers.push(server.clone()),
+            }
+        }
+        merged.warnings.extend(other.warnings.iter().cloned());
+        merged
+    }
+
+    /// Tools of `self` that `other` doesn't have
+    ///
+    /// A tool counts as in `other` only if the same server has an identical
+    /// definition of it there, so tools whose definition changed are part
+    /// of the difference. Servers left without tools are dropped.
+    pub fn difference(&self, other: &Catalog) -> Catalog {
+        self.retain(|server, tool| !other.contains(&server.name, tool))
+    }
+
+    /// Tools with identical definitions on the same server in both catalogs
+    ///
+    /// Servers left without tools are dropped.
+    pub fn intersect(&self, other: &Catalog) -> Catalog {
+        self.retain(|server, tool| other.contains(&server.name, tool))
+    }
+
+    /// What changed from `self` to `newer`, a later fetch of the same
+    /// servers
+    ///
+    /// Tools are told apart by server and name. Events come server by
+    /// server, in `newer`'s order and then for servers only in `self`:
+    /// changed and added tools in listing order, then removed ones. Servers
+    /// that `newer` couldn't fetch in full (see its
+    /// [`warnings`](Self::warnings)) are left out, so a server going down
+    /// doesn't read as all its tools being removed.
+    pub fn changes(&self, newer: &Catalog) -> Vec<CatalogEvent> {
+        let names = newer
+            .servers
+            .iter()
+            .chain(self.servers.iter().filter(|old| newer.server(&old.name).is_none()))
+            .map(|server| server.name.as_str());
+
+        let mut events = Vec::new();
+        for name in names {
+            if newer.is_incomplete(name) {
+                continue;
+            }
+            let old_tools = self.server(name).map_or(&[][..], |server| &server.tools[..]);
+            let new_tools = newer.server(name).map_or(&[][..], |server| &server.tools[..]);
+            for tool in new_tools {
+                match old_tools.iter().find(|old| old.name == tool.name) {
+                    Some(old) if old == tool => {}
+                    Some(_) => events.push(CatalogEvent::ToolChanged {
+                        server_name: name.to_string(),
+                        tool: tool.clone(),
+                    }),
+                    None => events.push(CatalogEvent::ToolAdded {
+                        server_name: name.to_string(),
+                        tool: tool.clone(),
+                    }),
+                }
+            }
+            for old in old_tools {
+                if !new_tools.iter().any(|tool| tool.name == old.name) {
+                    events.push(CatalogEvent::ToolRemoved {
+                        server_name: name.to_string(),
+                        tool_name: old.name.to_string(),
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    /// Blank out schema values that `redaction` names in every tool, for
+    /// a catalog about to be saved or shared
+    pub fn redact(&mut self, redaction: &Redaction) {
+        for server in &mut self.servers {
+            server.tools.iter_mut().for_each(|tool| redaction.redact_tool(tool));
+        }
+    }
+
+    /// Tools that match `criteria`, keeping the warnings
+    ///
+    /// Servers left without tools are dropped. Since the warnings are kept,
+    /// the [`changes`](Self::changes) between two filtered catalogs still
+    /// leave out servers that couldn't be fetched.
+    pub fn matching(&self, criteria: &SearchCriteria) -> Catalog {
+        self.retain(|_, tool| criteria.matches(tool))
+            .with_warnings(self.warnings.clone())
+    }
+
+    /// Whether the fetch of server `name` failed or stopped early
+    pub(crate) fn is_incomplete(&self, name: &str) -> bool {
+        self.warnings.iter().any(|warning| {
+            warning.server_name() == name
+                && matches!(
+                    warning,
+                    SearchWarning::InvalidConfig { .. }
+                        | SearchWarning::ServerFailed { .. }
+                        | SearchWarning::ToolListTruncated { .. }
+                )
+        })
+    }
+
+    /// Put `server` in place of the server of the same name, or last
+    pub(crate) fn replace_server(&mut self, server: CatalogServer) {
+        match self.servers.iter_mut().find(|s| s.name == server.name) {
+            Some(existing) => *existing = server,
+            None => self.servers.push(server),
+        }
+    }
+
+    /// Whether server `server_name` has exactly this tool
+    fn contains(&self, server_name: &str, tool: &Tool) -> bool {
+        self.server(server_name)
+            .is_some_and(|server| server.tools.contains(tool))
+    }
+
+    /// Catalog of the tools `keep` accepts, without warnings
+    fn retain(&self, keep: impl Fn(&CatalogServer, &Tool) -> bool) -> Catalog {
+        let servers = self
+            .servers
+            .iter()
+            .filter_map(|server| {
+                let tools: Vec<Tool> = server
+                    .tools
+                    .iter()
+                    .filter(|tool| keep(server, tool))
+                    .cloned()
+                    .collect();
+                (!tools.is_empty()).then(|| CatalogServer {
+                    tools,
+                    ..server.clone()
+                })
+            })
+            .collect();
+        Catalog::new(servers)
+    }
+
+    /// Search the catalog with default options
+    pub fn search(&self, criteria: &SearchCriteria) -> Vec<ToolSearchMatch> {
+        self.search_with_options(criteria, &SearchOptions::default())
+    }
+
+    /// Search the catalog with options
+    ///
+    /// Only the options that apply to matching (sorting, limits, scoring
+    /// and the observer's `on_match` and `on_complete`) have an effect.
+    pub fn search_with_options(
+        &self,
+        criteria: &SearchCriteria,
+        options: &SearchOptions,
+    ) -> Vec<ToolSearchMatch> {
+        let mut report = report_from_listings(&self.servers, Vec::new(), criteria, options);
+        report.warnings = self.warnings.clone();
+        notify_complete(&report, options);
+        report.matches
+    }
+}
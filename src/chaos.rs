@@ -0,0 +1,192 @@
+//! Fault injection for resilience testing
+//!
+//! Timeouts, the circuit breaker, pooled reconnects and partial results only
+//! come into play when a server misbehaves, which a test server rarely does
+//! on cue. With the `chaos` feature, [`Faults`] attached to a server with
+//! [`ServerConfig::with_faults`](crate::ServerConfig::with_faults) make its
+//! transport misbehave on a fixed schedule, so a test fails the same way on
+//! every run:
+//!
+//! - [`delay`](Faults::delay) holds back each message from the server
+//! - [`refuse_connections`](Faults::refuse_connections) fails the first
+//!   connections before the server is started
+//! - [`drop_after`](Faults::drop_after) cuts each connection after a number
+//!   of messages from the server
+//! - [`malformed_page`](Faults::malformed_page) breaks the schema of every
+//!   tool on a `tools/list` page, as if the server had sent garbage
+//!
+//! Clones of a `Faults` share their counts, so a schedule runs across all
+//! the connections made to the server, from any copy of its configuration.
+//! Faults apply to stdio servers; they are not part of configuration files.
+//!
+//! ```no_run
+//! use toolsearch::chaos::Faults;
+//! use toolsearch::{SearchCriteria, SearchOptions, ServerConfig, ToolSearchClient};
+//!
+//! # async fn example() -> Result<(), toolsearch::ToolSearchError> {
+//! let faults = Faults::new().refuse_connections(2);
+//! let server = ServerConfig::stdio("files", "mcp-file-server").with_faults(faults.clone());
+//! let client = ToolSearchClient::new(vec![server]).with_options(SearchOptions {
+//!     continue_on_error: true,
+//!     ..Default::default()
+//! });
+//! for _ in 0..3 {
+//!     client.search(&SearchCriteria::match_all()).await?;
+//! }
+//! // Two refused connections, then the server answered
+//! assert_eq!(faults.connections(), 3);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::ToolSearchError;
+#[cfg(feature = "stdio")]
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(feature = "stdio")]
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+
+/// Faults to inject into a server's transport; see the [module docs](self)
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    delay: Option<Duration>,
+    refused: usize,
+    drop_after: Option<usize>,
+    malformed_pages: Vec<usize>,
+    counts: Arc<Counts>,
+}
+
+/// What has happened so far, shared by clones
+#[derive(Debug, Default)]
+struct Counts {
+    connections: AtomicUsize,
+    pages: AtomicUsize,
+}
+
+impl PartialEq for Faults {
+    fn eq(&self, other: &Self) -> bool {
+        self.delay == other.delay
+            && self.refused == other.refused
+            && self.drop_after == other.drop_after
+            && self.malformed_pages == other.malformed_pages
+            && Arc::ptr_eq(&self.counts, &other.counts)
+    }
+}
+
+impl Faults {
+    /// No faults yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold back each message from the server this long
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Fail the first `count` connections with a connection error, without
+    /// starting the server
+    pub fn refuse_connections(mut self, count: usize) -> Self {
+        self.refused = count;
+        self
+    }
+
+    /// Close each connection after the server's first `messages` messages,
+    /// counting the handshake response (`drop_after(1)` connects, then
+    /// loses the connection on the first request)
+    pub fn drop_after(mut self, messages: usize) -> Self {
+        self.drop_after = Some(messages);
+        self
+    }
+
+    /// Break every tool of the `page`th `tools/list` page, counting from 1
+    /// across all connections; the tools are reported as
+    /// [malformed](crate::source::MalformedTool)
+    pub fn malformed_page(mut self, page: usize) -> Self {
+        self.malformed_pages.push(page);
+        self
+    }
+
+    /// Connections attempted so far, refused ones included
+    pub fn connections(&self) -> usize {
+        self.counts.connections.load(Ordering::SeqCst)
+    }
+
+    /// `tools/list` pages the server has sent so far
+    pub fn pages(&self) -> usize {
+        self.counts.pages.load(Ordering::SeqCst)
+    }
+
+    /// Count a connection to `server`, failing it if it is to be refused
+    pub(crate) fn connect(&self, server: &str) -> Result<(), ToolSearchError> {
+        let connection = self.counts.connections.fetch_add(1, Ordering::SeqCst) + 1;
+        if connection <= self.refused {
+            return Err(ToolSearchError::Connection(format!(
+                "Connection {} refused by fault injection for server: {}",
+                connection, server
+            )));
+        }
+        Ok(())
+    }
+
+    /// Relay a server's output line by line with the faults applied
+    #[cfg(feature = "stdio")]
+    pub(crate) fn inject(
+        &self,
+        output: impl AsyncRead + Send + Unpin + 'static,
+    ) -> tokio::io::DuplexStream {
+        let faults = self.clone();
+        let (reader, mut writer) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(output).lines();
+            let mut relayed = 0;
+            while let Ok(Some(line)) = lines.next_line().await {
+                if faults.drop_after.is_some_and(|max| relayed >= max) {
+                    tracing::debug!(messages = relayed, "fault injection dropped the connection");
+                    break;
+                }
+                if let Some(delay) = faults.delay {
+                    tokio::time::sleep(delay).await;
+                }
+                let line = faults.corrupt(line);
+                if writer.write_all(line.as_bytes()).await.is_err()
+                    || writer.write_all(b"\n").await.is_err()
+                {
+                    break;
+                }
+                relayed += 1;
+            }
+        });
+        reader
+    }
+
+    /// `line` with its tools broken if it is a page to be malformed
+    #[cfg(feature = "stdio")]
+    fn corrupt(&self, line: String) -> String {
+        if !line.contains("\"tools\"") {
+            return line;
+        }
+        let Ok(mut message) = serde_json::from_str::<Value>(&line) else {
+            return line;
+        };
+        let Some(tools) = message
+            .get_mut("result")
+            .and_then(|result| result.get_mut("tools"))
+            .and_then(Value::as_array_mut)
+        else {
+            return line;
+        };
+        let page = self.counts.pages.fetch_add(1, Ordering::SeqCst) + 1;
+        if !self.malformed_pages.contains(&page) {
+            return line;
+        }
+        for tool in tools.iter_mut().filter_map(Value::as_object_mut) {
+            let broken = Value::String("malformed by fault injection".to_string());
+            tool.insert("inputSchema".to_string(), broken);
+        }
+        message.to_string()
+    }
+}
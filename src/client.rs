@@ -0,0 +1,433 @@
+//! Persistent search client
+//!
+//! [`ToolSearchClient`] is meant to live for the duration of an application.
+//! It keeps the server list and search options together and can run periodic
+//! health checks in the background, skipping unhealthy servers in searches
+//! until they recover. A [`CircuitBreaker`] stops connecting to servers that
+//! keep failing during searches. [`prefetch`](ToolSearchClient::prefetch)
+//! loads every server's tools ahead of the first query, and
+//! [`reload`](ToolSearchClient::reload) swaps in a new server list without
+//! losing the state of servers that didn't change.
+//! [`subscribe`](ToolSearchClient::subscribe) follows the tools matching some
+//! criteria as servers come and go, for agents that adjust their toolset
+//! while they run.
+
+use crate::breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+use crate::health::{ping_all, HealthStatus, PingResult};
+use crate::report::SearchReport;
+use crate::source::{ToolListing, ToolSource};
+use crate::{
+    catalog_from_fetched, fetch_sources, report_from_fetched, Catalog, CatalogEvent, Fetched,
+    FetchedSource, SearchCriteria, SearchOptions, ConfigError, ServerConfig, ToolSearchError,
+    ToolSearchMatch,
+};
+use futures::stream::{self, Stream};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Long-lived client for repeated searches over the same servers
+pub struct ToolSearchClient {
+    servers: Vec<ServerConfig>,
+    options: SearchOptions,
+    health: Arc<RwLock<HashMap<String, HealthStatus>>>,
+    health_task: Option<JoinHandle<()>>,
+    health_interval: Option<Duration>,
+    breaker: CircuitBreaker,
+    /// Prefetched tools by [identity hash](ServerConfig::identity_hash), so
+    /// a server whose transport changes is listed again
+    prefetched: RwLock<HashMap<u64, ToolListing>>,
+}
+
+impl ToolSearchClient {
+    /// Create a client for the given servers with default search options
+    pub fn new(servers: Vec<ServerConfig>) -> Self {
+        Self {
+            servers,
+            options: SearchOptions::default(),
+            health: Arc::new(RwLock::new(HashMap::new())),
+            health_task: None,
+            health_interval: None,
+            breaker: CircuitBreaker::default(),
+            prefetched: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Set the search options used by [`search`](Self::search)
+    pub fn with_options(mut self, options: SearchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the circuit breaker thresholds used by [`search`](Self::search)
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.breaker = CircuitBreaker::new(config);
+        self
+    }
+
+    /// Current circuit state of a server
+    pub fn circuit_state(&self, server_name: &str) -> CircuitState {
+        self.breaker.state(server_name)
+    }
+
+    /// Servers this client searches
+    pub fn servers(&self) -> &[ServerConfig] {
+        &self.servers
+    }
+
+    /// Last known health of each checked server
+    pub fn health(&self) -> HashMap<String, HealthStatus> {
+        self.health.read().map(|h| h.clone()).unwrap_or_default()
+    }
+
+    /// Whether a server is considered healthy (servers never checked are)
+    pub fn is_healthy(&self, server_name: &str) -> bool {
+        self.health
+            .read()
+            .map(|h| h.get(server_name).is_none_or(HealthStatus::is_healthy))
+            .unwrap_or(true)
+    }
+
+    /// Ping every server now and record the results
+    pub async fn check_health(&self) -> Vec<PingResult> {
+        let results = ping_all(&self.servers, self.options.timeout).await;
+        record_health(&self.health, &results);
+        results
+    }
+
+    /// Ping every server periodically in the background
+    ///
+    /// Servers that fail a check are skipped by [`search`](Self::search) until
+    /// a later check succeeds. Calling this again replaces the previous task.
+    pub fn start_health_checks(&mut self, interval: Duration) {
+        self.stop_health_checks();
+
+        let servers = self.servers.clone();
+        let health = Arc::clone(&self.health);
+        let timeout_dur = self.options.timeout;
+        self.health_task = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let results = ping_all(&servers, timeout_dur).await;
+                record_health(&health, &results);
+            }
+        }));
+        self.health_interval = Some(interval);
+    }
+
+    /// Stop background health checks, if running
+    pub fn stop_health_checks(&mut self) {
+        if let Some(task) = self.health_task.take() {
+            task.abort();
+        }
+        self.health_interval = None;
+    }
+
+    /// Replace the server list, e.g. after the configuration file changed
+    ///
+    /// The new servers are validated first; if any is invalid the client
+    /// keeps its current list. Health and circuit state of removed and
+    /// changed servers are dropped, while unchanged servers keep theirs.
+    /// Prefetched tools are kept only for servers reached the same way as
+    /// before, even if renamed. Background health checks restart with the
+    /// new list.
+    pub fn reload(&mut self, servers: Vec<ServerConfig>) -> Result<ConfigDelta, ToolSearchError> {
+        for server in &servers {
+            server.validate().map_err(|message| ConfigError::Validation {
+                server: server.name.clone(),
+                message,
+            })?;
+        }
+
+        let delta = ConfigDelta::between(&self.servers, &servers);
+        let stale = || delta.removed.iter().chain(&delta.changed);
+        if let Ok(mut prefetched) = self.prefetched.write() {
+            prefetched.retain(|hash, _| servers.iter().any(|s| s.identity_hash() == *hash));
+        }
+        if let Ok(mut health) = self.health.write() {
+            for name in stale() {
+                health.remove(name);
+            }
+        }
+        for name in stale() {
+            self.breaker.forget(name);
+        }
+
+        self.servers = servers;
+        if let Some(interval) = self.health_interval {
+            self.start_health_checks(interval);
+        }
+        Ok(delta)
+    }
+
+    /// Connect to and list every server concurrently ahead of the first query
+    ///
+    /// Searches answer from the prefetched tools instead of connecting again;
+    /// call this again to refresh them. Returns how many servers were loaded.
+    pub async fn prefetch(&self) -> Result<usize, ToolSearchError> {
+        let fetched = self.fetch_servers(|_| true).await?;
+        let mut loaded = 0;
+        if let Ok(mut prefetched) = self.prefetched.write() {
+            for FetchedSource { name, result, .. } in fetched.sources {
+                match result {
+                    Ok(listing) => {
+                        if let Some(server) = self.servers.iter().find(|s| s.name == name) {
+                            prefetched.insert(server.identity_hash(), listing);
+                            loaded += 1;
+                        }
+                    }
+                    Err(e) if !self.options.continue_on_error => return Err(e),
+                    Err(_) => {}
+                }
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Names of servers whose tools are currently prefetched
+    pub fn prefetched_servers(&self) -> Vec<String> {
+        let Ok(prefetched) = self.prefetched.read() else {
+            return Vec::new();
+        };
+        self.servers
+            .iter()
+            .filter(|server| prefetched.contains_key(&server.identity_hash()))
+            .map(|server| server.name.clone())
+            .collect()
+    }
+
+    /// Drop prefetched tools so searches connect to servers again
+    pub fn clear_prefetched(&self) {
+        if let Ok(mut prefetched) = self.prefetched.write() {
+            prefetched.clear();
+        }
+    }
+
+    /// Search healthy servers with the client's options
+    ///
+    /// Prefetched servers are answered from memory. Servers whose circuit is
+    /// open are skipped; each fetch outcome is recorded with the circuit breaker.
+    pub async fn search(
+        &self,
+        criteria: &SearchCriteria,
+    ) -> Result<Vec<ToolSearchMatch>, ToolSearchError> {
+        Ok(self.search_report(criteria).await?.matches)
+    }
+
+    /// Like [`search`](Self::search), also returning timings of the servers
+    /// contacted (prefetched servers have none) and how many servers
+    /// responded; skipped servers count against
+    /// [`min_response_rate`](SearchOptions::min_response_rate)
+    pub async fn search_report(
+        &self,
+        criteria: &SearchCriteria,
+    ) -> Result<SearchReport, ToolSearchError> {
+        let prefetched = self.prefetched.read().map(|p| p.clone()).unwrap_or_default();
+        let mut fetched = self
+            .fetch_servers(|server| !prefetched.contains_key(&server.identity_hash()))
+            .await?;
+
+        fetched.sources.extend(
+            self.servers
+                .iter()
+                .filter(|server| self.is_healthy(&server.name))
+                .filter_map(|server| {
+                    let listing = prefetched.get(&server.identity_hash())?;
+                    Some(FetchedSource {
+                        name: server.name.clone(),
+                        result: Ok(listing.clone()),
+                        elapsed: None,
+                        timeout: None,
+                    })
+                }),
+        );
+        fetched.skipped += self
+            .servers
+            .iter()
+            .filter(|server| prefetched.contains_key(&server.identity_hash()))
+            .filter(|server| !self.is_healthy(&server.name))
+            .count();
+        // Keep results in configuration order regardless of where they came from
+        fetched
+            .sources
+            .sort_by_key(|f| self.servers.iter().position(|s| s.name == f.name));
+
+        let sources = self.sources(|_| true);
+        report_from_fetched(fetched, &sources, criteria, &self.options)
+    }
+
+    /// Fetch every tool of the healthy servers whose circuit allows it
+    ///
+    /// Servers are always contacted, even if their tools are prefetched.
+    pub async fn catalog(&self) -> Result<Catalog, ToolSearchError> {
+        let fetched = self.fetch_servers(|_| true).await?;
+        let sources = self.sources(|_| true);
+        catalog_from_fetched(fetched, &sources, &self.options)
+    }
+
+    /// Follow the tools matching `criteria`, fetching a
+    /// [`catalog`](Self::catalog) every `interval`
+    ///
+    /// The stream yields a [`CatalogEvent`] when a matching tool appears,
+    /// disappears or stops matching, or changes; the first fetch reports
+    /// every matching tool as added. A server that couldn't be fetched in
+    /// full, or was skipped as unhealthy or for its open circuit, keeps the
+    /// tools it had until it answers again. A fetch that fails as a whole
+    /// is skipped. The stream never ends; drop it to stop.
+    ///
+    /// # Panics
+    ///
+    /// If `interval` is zero.
+    pub fn subscribe(
+        &self,
+        criteria: SearchCriteria,
+        interval: Duration,
+    ) -> impl Stream<Item = CatalogEvent> + '_ {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let state = (ticker, Catalog::default(), VecDeque::new());
+        stream::unfold(state, move |(mut ticker, mut previous, mut pending)| {
+            let criteria = criteria.clone();
+            async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((event, (ticker, previous, pending)));
+                    }
+                    ticker.tick().await;
+                    let Ok(mut current) = self.catalog().await else {
+                        continue;
+                    };
+                    for server in previous.servers() {
+                        let configured = self.servers.iter().any(|s| s.name == server.name);
+                        let missing = current.server(&server.name).is_none()
+                            || current.is_incomplete(&server.name);
+                        if configured && missing {
+                            current.replace_server(server.clone());
+                        }
+                    }
+                    let current = current.matching(&criteria);
+                    pending.extend(previous.changes(&current));
+                    previous = current;
+                }
+            }
+        })
+    }
+
+    /// Healthy servers selected by `filter`, as tool sources
+    fn sources(&self, filter: impl Fn(&ServerConfig) -> bool) -> Vec<Arc<dyn ToolSource>> {
+        self.servers
+            .iter()
+            .filter(|server| filter(server) && self.is_healthy(&server.name))
+            .map(|server| Arc::new(server.clone()) as Arc<dyn ToolSource>)
+            .collect()
+    }
+
+    /// Fetch selected healthy servers whose circuit allows it, recording outcomes
+    async fn fetch_servers(
+        &self,
+        filter: impl Fn(&ServerConfig) -> bool,
+    ) -> Result<Fetched, ToolSearchError> {
+        // Health first: a half-open circuit allows a single trial, which must
+        // end in an outcome once `allow` hands it out
+        let sources: Vec<_> = self
+            .sources(&filter)
+            .into_iter()
+            .filter(|source| self.breaker.allow(source.name()))
+            .collect();
+        let mut fetched = match fetch_sources(&sources, &self.options, None).await {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                for source in &sources {
+                    self.breaker.record_abandoned(source.name());
+                }
+                return Err(e);
+            }
+        };
+        fetched.skipped = self.servers.iter().filter(|server| filter(server)).count() - sources.len();
+        for source in &sources {
+            match fetched.sources.iter().find(|f| f.name == source.name()) {
+                Some(FetchedSource { result: Ok(_), .. }) => {
+                    self.breaker.record_success(source.name())
+                }
+                Some(FetchedSource { result: Err(_), .. }) => {
+                    self.breaker.record_failure(source.name())
+                }
+                // Cut off by the search deadline
+                None => self.breaker.record_abandoned(source.name()),
+            }
+        }
+        Ok(fetched)
+    }
+}
+
+/// Servers that differ between two configurations, by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDelta {
+    /// Servers only in the new configuration
+    pub added: Vec<String>,
+    /// Servers only in the old configuration
+    pub removed: Vec<String>,
+    /// Servers in both whose settings differ
+    pub changed: Vec<String>,
+}
+
+impl ConfigDelta {
+    /// Compare two server lists
+    pub fn between(old: &[ServerConfig], new: &[ServerConfig]) -> Self {
+        let mut delta = Self::default();
+        for server in new {
+            match old.iter().find(|s| s.name == server.name) {
+                None => delta.added.push(server.name.clone()),
+                Some(previous) if previous != server => delta.changed.push(server.name.clone()),
+                Some(_) => {}
+            }
+        }
+        delta.removed = old
+            .iter()
+            .filter(|s| !new.iter().any(|n| n.name == s.name))
+            .map(|s| s.name.clone())
+            .collect();
+        delta
+    }
+
+    /// Whether the configurations have the same servers
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for ConfigDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no changes");
+        }
+        let parts: Vec<String> = [
+            ("added", &self.added),
+            ("removed", &self.removed),
+            ("changed", &self.changed),
+        ]
+        .into_iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(label, names)| format!("{}: {}", label, names.join(", ")))
+        .collect();
+        f.write_str(&parts.join("; "))
+    }
+}
+
+impl Drop for ToolSearchClient {
+    fn drop(&mut self) {
+        self.stop_health_checks();
+    }
+}
+
+/// Store ping results in the shared health map
+fn record_health(health: &RwLock<HashMap<String, HealthStatus>>, results: &[PingResult]) {
+    if let Ok(mut health) = health.write() {
+        for result in results {
+            health.insert(result.server_name.clone(), result.status.clone());
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Risky patterns in a server configuration
+//!
+//! A configuration can be valid and still be a bad idea: a token written
+//! into the file, a server reached over plain HTTP. [`lint_config`] flags
+//! such patterns in a list of servers, one [`ConfigLintIssue`] per finding,
+//! each with a [`Severity`]. Some have an obvious fix, which
+//! [`fix_config`] applies; `toolsearch config lint --fix` saves the result.
+//!
+//! ```
+//! use toolsearch::configlint::{fix_config, lint_config, ConfigRule};
+//! use toolsearch::ServerConfig;
+//!
+//! let mut servers = vec![
+//!     ServerConfig::stdio("files", "files-server").with_timeout(10),
+//!     ServerConfig::stdio("files", "other-server").with_timeout(10),
+//! ];
+//! let rules: Vec<_> = lint_config(&servers).into_iter().map(|issue| issue.rule).collect();
+//! assert_eq!(rules, [ConfigRule::DuplicateName]);
+//!
+//! fix_config(&mut servers);
+//! assert_eq!(servers[1].name, "files-2");
+//! assert!(lint_config(&servers).is_empty());
+//! ```
+
+use crate::search::disambiguate_names;
+use crate::{ServerConfig, TransportConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Timeout [`fix_config`] gives servers without one, the search default
+pub const FIX_TIMEOUT_SECS: u64 = 30;
+
+/// Parts of variable and header names that suggest a secret
+const SECRET_MARKERS: &[&str] = &[
+    "TOKEN", "SECRET", "PASSWORD", "PASSWD", "API_KEY", "APIKEY", "ACCESS_KEY", "PRIVATE_KEY",
+    "CREDENTIAL", "AUTHORIZATION",
+];
+
+/// How much a [`ConfigLintIssue`] matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth knowing; doesn't fail `toolsearch config lint`
+    Info,
+    /// Likely a mistake
+    Warning,
+    /// Should be fixed before the configuration is shared or used
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Kind of risky pattern a [`ConfigLintIssue`] reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigRule {
+    /// A secret-looking environment variable or header is written out in
+    /// the file
+    PlaintextSecret,
+    /// Another server earlier in the file has the same name
+    DuplicateName,
+    /// An SSE server outside this machine is reached over `http://`
+    InsecureUrl,
+    /// The server has no `timeout_secs`
+    MissingTimeout,
+    /// An absolute path in the command or its arguments doesn't exist
+    MissingPath,
+}
+
+impl ConfigRule {
+    /// Name of the rule, as printed and serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigRule::PlaintextSecret => "plaintext-secret",
+            ConfigRule::DuplicateName => "duplicate-name",
+            ConfigRule::InsecureUrl => "insecure-url",
+            ConfigRule::MissingTimeout => "missing-timeout",
+            ConfigRule::MissingPath => "missing-path",
+        }
+    }
+
+    /// How much a finding of this rule matters
+    pub fn severity(&self) -> Severity {
+        match self {
+            ConfigRule::PlaintextSecret | ConfigRule::DuplicateName => Severity::Error,
+            ConfigRule::InsecureUrl | ConfigRule::MissingPath => Severity::Warning,
+            ConfigRule::MissingTimeout => Severity::Info,
+        }
+    }
+
+    /// Whether [`fix_config`] fixes findings of this rule
+    pub fn is_fixable(&self) -> bool {
+        matches!(self, ConfigRule::DuplicateName | ConfigRule::MissingTimeout)
+    }
+}
+
+impl fmt::Display for ConfigRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One risky pattern in one server's configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigLintIssue {
+    /// Name of the server
+    pub server_name: String,
+    /// Which check failed
+    pub rule: ConfigRule,
+    /// How much it matters
+    pub severity: Severity,
+    /// What is wrong, for people
+    pub message: String,
+    /// Whether [`fix_config`] fixes it
+    pub fixable: bool,
+}
+
+impl ConfigLintIssue {
+    fn new(server_name: &str, rule: ConfigRule, message: String) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            rule,
+            severity: rule.severity(),
+            message,
+            fixable: rule.is_fixable(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigLintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}: {}: {}",
+            self.severity, self.server_name, self.rule, self.message
+        )?;
+        if self.fixable {
+            f.write_str(" (fixable with --fix)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Check every server of a configuration, in order
+pub fn lint_config(servers: &[ServerConfig]) -> Vec<ConfigLintIssue> {
+    let mut issues = Vec::new();
+    let mut names = HashSet::new();
+    for server in servers {
+        let issue = |rule, message| ConfigLintIssue::new(&server.name, rule, message);
+        if !names.insert(server.name.as_str()) {
+            issues.push(issue(
+                ConfigRule::DuplicateName,
+                "Another server earlier in the file has this name".to_string(),
+            ));
+        }
+        if server.timeout_secs.is_none() {
+            issues.push(issue(
+                ConfigRule::MissingTimeout,
+                format!("No timeout_secs; searches wait the default {}s", FIX_TIMEOUT_SECS),
+            ));
+        }
+        match &server.transport {
+            TransportConfig::Stdio {
+                command, args, env, ..
+            } => {
+                let mut secrets: Vec<_> = env
+                    .iter()
+                    .filter(|(name, value)| looks_secret(name) && !value.is_empty())
+                    .map(|(name, _)| name)
+                    .collect();
+                secrets.sort();
+                for name in secrets {
+                    issues.push(issue(
+                        ConfigRule::PlaintextSecret,
+                        format!(
+                            "env {} holds a secret; export it where toolsearch runs instead",
+                            name
+                        ),
+                    ));
+                }
+                for path in std::iter::once(command).chain(args) {
+                    let path = Path::new(path);
+                    if path.is_absolute() && !path.exists() {
+                        issues.push(issue(
+                            ConfigRule::MissingPath,
+                            format!("{} doesn't exist", path.display()),
+                        ));
+                    }
+                }
+            }
+            TransportConfig::Sse { url, headers, .. } => {
+                let mut secrets: Vec<_> = headers
+                    .iter()
+                    .filter(|(name, value)| looks_secret(name) && !value.is_empty())
+                    .map(|(name, _)| name)
+                    .collect();
+                secrets.sort();
+                for name in secrets {
+                    issues.push(issue(
+                        ConfigRule::PlaintextSecret,
+                        format!("header {} holds a secret written into the file", name),
+                    ));
+                }
+                if let Some(rest) = url.strip_prefix("http://")
+                    && !is_loopback(rest)
+                {
+                    issues.push(issue(
+                        ConfigRule::InsecureUrl,
+                        format!("{} isn't encrypted; use https://", url),
+                    ));
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// Fix the [fixable](ConfigRule::is_fixable) issues of a configuration,
+/// returning the issues fixed
+///
+/// Later servers with a taken name get the first free `-2`, `-3`, ...
+/// suffix, and servers without a timeout get [`FIX_TIMEOUT_SECS`].
+pub fn fix_config(servers: &mut [ServerConfig]) -> Vec<ConfigLintIssue> {
+    let fixed: Vec<_> = lint_config(servers)
+        .into_iter()
+        .filter(|issue| issue.fixable)
+        .collect();
+    disambiguate_names(servers);
+    for server in servers.iter_mut() {
+        if server.timeout_secs.is_none() {
+            server.timeout_secs = Some(FIX_TIMEOUT_SECS);
+        }
+    }
+    fixed
+}
+
+fn looks_secret(name: &str) -> bool {
+    let name = name.to_uppercase().replace('-', "_");
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// Whether the URL after `http://` is on this machine
+pub(crate) fn is_loopback(rest: &str) -> bool {
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost") || host == "::1" || host.starts_with("127.")
+}
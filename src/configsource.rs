@@ -0,0 +1,437 @@
+//! Where a configuration is read from
+//!
+//! A server list doesn't have to be a local file. Other tools can generate
+//! one and pipe it in, and a team can host one centrally. A
+//! [`ConfigSource`] is parsed from the same string the CLI's `--config`
+//! takes:
+//!
+//! - `-` reads the JSON from stdin
+//! - `https://…` fetches it, with any headers (such as `Authorization`)
+//!   added with [`with_header`](ConfigSource::with_header); plain `http://`
+//!   is only accepted for this machine, so credentials never travel
+//!   unencrypted (needs the `remote-config` feature)
+//! - anything else is a file path
+//!
+//! Fetched configurations are cached, by default under
+//! `$XDG_CACHE_HOME/toolsearch/configs` (or `~/.cache/toolsearch/configs`),
+//! one copy per URL and set of headers. Each fetch sends the cached copy's
+//! `ETag` in `If-None-Match`, so an unchanged configuration costs the server
+//! no more than a `304`. When the server can't be reached, fails, or doesn't
+//! answer within the [timeout](ConfigSource::with_timeout), the cached copy
+//! is used instead and flagged [stale](ConfigText::stale), so work goes on
+//! offline.
+//!
+//! ```
+//! use toolsearch::configsource::ConfigSource;
+//!
+//! assert_eq!(ConfigSource::parse("-"), ConfigSource::Stdin);
+//! assert!(ConfigSource::parse("https://example.com/servers.json").is_remote());
+//! assert!(ConfigSource::parse("servers.json").is_file());
+//! ```
+
+use crate::error::ConfigError;
+use crate::include::{has_includes, IncludeBase};
+use crate::search::{parse_servers, read_config_file};
+use crate::{stable_hash, DuplicatePolicy, ServerConfig, ToolSearchError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long fetching a remote configuration may take by default
+pub const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long connecting to a configuration's host may take, at most
+#[cfg(feature = "remote-config")]
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A configuration file, stdin, or URL; see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// A local file
+    File(String),
+    /// The process's standard input
+    Stdin,
+    /// A configuration fetched over HTTPS
+    Url {
+        /// Address of the configuration
+        url: String,
+        /// Headers sent with the request, as `(name, value)`
+        headers: Vec<(String, String)>,
+        /// Directory of cached copies; `None` to always fetch
+        cache_dir: Option<PathBuf>,
+        /// How long the request may take, connecting included
+        timeout: Duration,
+    },
+}
+
+/// A configuration's JSON, as read by [`ConfigSource::read`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigText {
+    /// Where it was read from, for error messages
+    pub origin: String,
+    /// The JSON
+    pub data: String,
+    /// Set when a remote configuration couldn't be fetched and this is the
+    /// cached copy
+    pub stale: Option<StaleCopy>,
+    pub(crate) base: IncludeBase,
+}
+
+impl ConfigText {
+    /// Parse and validate the servers, handling servers that share a name
+    /// per `duplicates`
+    pub fn servers(
+        &self,
+        duplicates: DuplicatePolicy,
+    ) -> Result<Vec<ServerConfig>, ToolSearchError> {
+        parse_servers(&self.data, &self.origin, &self.base, duplicates)
+    }
+
+    /// Whether the configuration includes other files, so saving its
+    /// servers back would flatten it into one
+    pub fn has_includes(&self) -> bool {
+        has_includes(&self.data)
+    }
+}
+
+/// A cached configuration used in place of one that couldn't be fetched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleCopy {
+    /// Why fetching failed
+    pub error: String,
+    /// How long ago the copy was fetched or last revalidated
+    pub age: Duration,
+}
+
+impl fmt::Display for StaleCopy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}; using the copy cached {}s ago",
+            self.error,
+            self.age.as_secs()
+        )
+    }
+}
+
+/// A fetched configuration as stored in the cache
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedConfig {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    /// Seconds since the Unix epoch of the last fetch or revalidation
+    fetched_at: u64,
+    body: String,
+}
+
+impl ConfigSource {
+    /// Source named by a `--config` value
+    pub fn parse(location: &str) -> Self {
+        if location == "-" {
+            ConfigSource::Stdin
+        } else if location.starts_with("https://") || location.starts_with("http://") {
+            ConfigSource::Url {
+                url: location.to_string(),
+                headers: Vec::new(),
+                cache_dir: Self::default_cache_dir(),
+                timeout: DEFAULT_FETCH_TIMEOUT,
+            }
+        } else {
+            ConfigSource::File(location.to_string())
+        }
+    }
+
+    /// Default directory of cached configurations:
+    /// `$XDG_CACHE_HOME/toolsearch/configs`, or
+    /// `~/.cache/toolsearch/configs`
+    ///
+    /// `None` if neither variable is set.
+    pub fn default_cache_dir() -> Option<PathBuf> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .filter(|dir| !dir.is_empty())
+                    .map(|home| PathBuf::from(home).join(".cache"))
+            })?;
+        Some(cache_home.join("toolsearch").join("configs"))
+    }
+
+    /// Send a header with the request; ignored for files and stdin
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        if let ConfigSource::Url { headers, .. } = &mut self {
+            headers.push((name.into(), value.into()));
+        }
+        self
+    }
+
+    /// Cache fetched copies in `dir`, or not at all with `None`; ignored for
+    /// files and stdin
+    pub fn with_cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        if let ConfigSource::Url { cache_dir, .. } = &mut self {
+            *cache_dir = dir;
+        }
+        self
+    }
+
+    /// Give up on the request after `limit` (default:
+    /// [`DEFAULT_FETCH_TIMEOUT`]); ignored for files and stdin
+    pub fn with_timeout(mut self, limit: Duration) -> Self {
+        if let ConfigSource::Url { timeout, .. } = &mut self {
+            *timeout = limit;
+        }
+        self
+    }
+
+    /// Whether this is a local file
+    pub fn is_file(&self) -> bool {
+        matches!(self, ConfigSource::File(_))
+    }
+
+    /// Whether this is fetched from a URL
+    pub fn is_remote(&self) -> bool {
+        matches!(self, ConfigSource::Url { .. })
+    }
+
+    /// Read the configuration's JSON
+    pub async fn read(&self) -> Result<ConfigText, ToolSearchError> {
+        let text = |data| ConfigText {
+            origin: self.to_string(),
+            data,
+            stale: None,
+            base: match self {
+                ConfigSource::File(path) => IncludeBase::File(path.into()),
+                ConfigSource::Stdin => IncludeBase::WorkingDir,
+                ConfigSource::Url { .. } => IncludeBase::None,
+            },
+        };
+        match self {
+            ConfigSource::File(path) => Ok(text(read_config_file(path)?)),
+            ConfigSource::Stdin => {
+                use std::io::Read;
+
+                // Read once, at startup, so blocking is fine
+                let mut data = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut data)
+                    .map_err(|e| ConfigError::Read {
+                        path: self.to_string(),
+                        source: e,
+                    })?;
+                Ok(text(data))
+            }
+            ConfigSource::Url {
+                url,
+                headers,
+                cache_dir,
+                timeout,
+            } => {
+                let cache = cache_dir.as_deref().map(|dir| cache_file(dir, url, headers));
+                fetch_cached(url, headers, *timeout, cache.as_deref()).await
+            }
+        }
+    }
+
+    /// Read, parse, and validate the servers, handling servers that share a
+    /// name per `duplicates`
+    pub async fn load(
+        &self,
+        duplicates: DuplicatePolicy,
+    ) -> Result<Vec<ServerConfig>, ToolSearchError> {
+        self.read().await?.servers(duplicates)
+    }
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::File(path) => f.write_str(path),
+            ConfigSource::Stdin => f.write_str("<stdin>"),
+            ConfigSource::Url { url, .. } => f.write_str(url),
+        }
+    }
+}
+
+/// Cache file of the configuration at `url` fetched with `headers`, so
+/// copies fetched with different credentials are kept apart
+fn cache_file(dir: &Path, url: &str, headers: &[(String, String)]) -> PathBuf {
+    let mut headers: Vec<_> = headers
+        .iter()
+        .map(|(name, value)| format!("\n{}: {}", name.to_ascii_lowercase(), value))
+        .collect();
+    headers.sort();
+    let key = format!("{}{}", url, headers.concat());
+    dir.join(format!("{:016x}.json", stable_hash(key.as_bytes())))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Read the cached copy of `url`, if there is a readable one
+fn read_cache(path: &Path, url: &str) -> Option<CachedConfig> {
+    let data = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<CachedConfig>(&data)
+        .ok()
+        .filter(|cached| cached.url == url)
+}
+
+/// Store a copy in the cache; a cache that can't be written is skipped
+fn write_cache(path: &Path, cached: &CachedConfig) {
+    let Some(dir) = path.parent() else {
+        return;
+    };
+    let Ok(data) = serde_json::to_string(cached) else {
+        return;
+    };
+    // Written aside and renamed, so readers never see half a file
+    let partial = path.with_extension("json.partial");
+    let _ = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&partial, data))
+        .and_then(|_| std::fs::rename(&partial, path));
+}
+
+/// Outcome of one request for a remote configuration
+#[cfg_attr(not(feature = "remote-config"), allow(dead_code))]
+enum Fetch {
+    /// The configuration, with its `ETag`
+    Body(String, Option<String>),
+    /// `304`: the cached copy is current
+    NotModified,
+    /// The server couldn't be reached or failed; a cached copy may stand in
+    Unavailable(String),
+}
+
+async fn fetch_cached(
+    url: &str,
+    headers: &[(String, String)],
+    timeout: Duration,
+    cache: Option<&Path>,
+) -> Result<ConfigText, ToolSearchError> {
+    let failed = |message: String| ConfigError::Fetch {
+        url: url.to_string(),
+        message,
+    };
+    if let Some(rest) = url.strip_prefix("http://")
+        && !crate::configlint::is_loopback(rest)
+    {
+        let message = "plain http:// is only accepted for this machine; use https://";
+        return Err(failed(message.to_string()).into());
+    }
+
+    let cached = cache.and_then(|path| read_cache(path, url));
+    let etag = cached.as_ref().and_then(|cached| cached.etag.as_deref());
+    let text = |data, stale| ConfigText {
+        origin: url.to_string(),
+        data,
+        stale,
+        base: IncludeBase::None,
+    };
+    match (fetch(url, headers, timeout, etag).await?, cached) {
+        (Fetch::Body(body, etag), _) => {
+            // Only copies that parse are worth falling back to
+            if let Some(path) = cache
+                && serde_json::from_str::<Vec<serde_json::Value>>(&body).is_ok()
+            {
+                let cached = CachedConfig {
+                    url: url.to_string(),
+                    etag,
+                    fetched_at: now_secs(),
+                    body,
+                };
+                write_cache(path, &cached);
+                return Ok(text(cached.body, None));
+            }
+            Ok(text(body, None))
+        }
+        (Fetch::NotModified, Some(mut cached)) => {
+            cached.fetched_at = now_secs();
+            if let Some(path) = cache {
+                write_cache(path, &cached);
+            }
+            Ok(text(cached.body, None))
+        }
+        (Fetch::NotModified, None) => {
+            Err(failed("304 Not Modified without a cached copy".to_string()).into())
+        }
+        (Fetch::Unavailable(error), Some(cached)) => {
+            let age = Duration::from_secs(now_secs().saturating_sub(cached.fetched_at));
+            Ok(text(cached.body, Some(StaleCopy { error, age })))
+        }
+        (Fetch::Unavailable(error), None) => Err(failed(error).into()),
+    }
+}
+
+#[cfg(feature = "remote-config")]
+async fn fetch(
+    url: &str,
+    headers: &[(String, String)],
+    timeout: Duration,
+    etag: Option<&str>,
+) -> Result<Fetch, ToolSearchError> {
+    // A host that accepts the connection and never answers counts as
+    // unreachable, like one that refuses it
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT.min(timeout))
+        .timeout(timeout)
+        .build()
+        .map_err(|e| ConfigError::Fetch {
+            url: url.to_string(),
+            message: e.to_string(),
+        })?;
+    let mut request = client.get(url);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => return Ok(Fetch::Unavailable(e.to_string())),
+    };
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(Fetch::NotModified);
+    }
+    if status.is_server_error() {
+        return Ok(Fetch::Unavailable(format!("server answered {}", status)));
+    }
+    // Client errors, such as rejected credentials, need fixing rather than
+    // papering over with the cached copy
+    if !status.is_success() {
+        return Err(ConfigError::Fetch {
+            url: url.to_string(),
+            message: format!("server answered {}", status),
+        }
+        .into());
+    }
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string);
+    match response.text().await {
+        Ok(body) => Ok(Fetch::Body(body, etag)),
+        Err(e) => Ok(Fetch::Unavailable(e.to_string())),
+    }
+}
+
+#[cfg(not(feature = "remote-config"))]
+async fn fetch(
+    url: &str,
+    _headers: &[(String, String)],
+    _timeout: Duration,
+    _etag: Option<&str>,
+) -> Result<Fetch, ToolSearchError> {
+    Err(ConfigError::Fetch {
+        url: url.to_string(),
+        message: "fetching configurations needs the remote-config feature".to_string(),
+    }
+    .into())
+}
@@ -0,0 +1,319 @@
+//! Search daemon
+//!
+//! `toolsearch daemon` keeps the servers of one configuration file running
+//! (see [`PooledServer`]) and answers searches over a Unix domain socket, so
+//! repeated searches from a shell skip every server's cold start. The
+//! protocol is JSON Lines: each line sent is a [`DaemonRequest`] and is
+//! answered by one line holding a [`DaemonResponse`].
+//!
+//! The socket of a configuration file is at [`socket_path`], which is how
+//! `toolsearch search` finds a daemon for the same file on its own.
+//!
+//! ```no_run
+//! use toolsearch::daemon::{request, socket_path, DaemonRequest};
+//!
+//! # async fn example() -> Result<(), toolsearch::ToolSearchError> {
+//! let socket = socket_path("servers.json").unwrap();
+//! let response = request(&socket, &DaemonRequest::query("read")).await?;
+//! println!("{} match(es)", response.matches.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::alias::AliasMap;
+use crate::block::Blocklist;
+use crate::history::History;
+use crate::pool::PooledServer;
+use crate::report::SearchReport;
+use crate::{load_servers, stable_hash, SearchBuilder, ToolSearchError, ToolSearchMatch};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+
+/// A search sent to the daemon
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonRequest {
+    /// Search query (mode auto-detected, as on the command line)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Further queries whose matches are added
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<String>,
+    /// Exact tool name to find instead of searching with a query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Tags the tools must all declare
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Shorthand the query or name may be an alias for
+    #[serde(default, skip_serializing_if = "AliasMap::is_empty")]
+    pub aliases: AliasMap,
+    /// Maximum number of results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Sort by tool name instead of server name
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub sort_by_tool: bool,
+    /// Sort by server priority (highest first)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub sort_by_priority: bool,
+    /// Sort by relevance score (highest first)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub sort_by_score: bool,
+    /// Include each server's initialize info in the matches
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub server_info: bool,
+    /// Tools to sort first, by name or `server/tool`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<String>,
+    /// Tools and servers to leave out of the results
+    #[serde(default, skip_serializing_if = "Blocklist::is_empty")]
+    pub blocked: Blocklist,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl DaemonRequest {
+    /// Search with a query
+    pub fn query(query: impl Into<String>) -> Self {
+        Self {
+            query: Some(query.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Find the tool with exactly this name
+    pub fn name(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// The daemon's answer to a [`DaemonRequest`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonResponse {
+    /// Matching tools
+    #[serde(default)]
+    pub matches: Vec<ToolSearchMatch>,
+    /// Servers that were skipped, as in [`SearchReport::warnings`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Why the search failed, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DaemonResponse {
+    fn from_report(report: SearchReport) -> Self {
+        Self {
+            warnings: report.warnings.iter().map(ToString::to_string).collect(),
+            matches: report.matches,
+            error: None,
+        }
+    }
+
+    fn failed(error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Socket of the daemon for the configuration file at `config`
+///
+/// Sockets live in `$XDG_RUNTIME_DIR/toolsearch`, or else next to the
+/// [search history](History::default_path), and are named after a hash of
+/// the configuration's absolute path. `None` if none of those variables is
+/// set.
+pub fn socket_path(config: impl AsRef<Path>) -> Option<PathBuf> {
+    let config = config.as_ref();
+    let config = std::fs::canonicalize(config).unwrap_or_else(|_| config.to_path_buf());
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| PathBuf::from(dir).join("toolsearch"))
+        .or_else(|| Some(History::default_path()?.parent()?.to_path_buf()))?;
+    let hash = stable_hash(config.as_os_str().as_encoded_bytes());
+    Some(dir.join(format!("{:016x}.sock", hash)))
+}
+
+/// Listen on `socket`, creating its directory and replacing a socket file
+/// left behind by a daemon that is no longer running
+///
+/// Fails if another daemon is still listening there.
+pub fn bind(socket: &Path) -> Result<UnixListener, ToolSearchError> {
+    if let Some(dir) = socket.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if socket.exists() {
+        if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+            return Err(ToolSearchError::Connection(format!(
+                "A daemon is already listening on {}",
+                socket.display()
+            )));
+        }
+        std::fs::remove_file(socket)?;
+    }
+    Ok(UnixListener::bind(socket)?)
+}
+
+/// Send one request to the daemon listening on `socket`
+pub async fn request(
+    socket: &Path,
+    request: &DaemonRequest,
+) -> Result<DaemonResponse, ToolSearchError> {
+    let mut stream = UnixStream::connect(socket).await?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).await?;
+
+    let mut answer = String::new();
+    BufReader::new(stream).read_line(&mut answer).await?;
+    if answer.is_empty() {
+        return Err(ToolSearchError::Connection(format!(
+            "Daemon on {} closed the connection without answering",
+            socket.display()
+        )));
+    }
+    Ok(serde_json::from_str(&answer)?)
+}
+
+/// Pooled servers of a configuration file, and when the file was loaded
+struct Pool {
+    /// Modification times of the configuration and the files it includes
+    modified: Vec<Option<SystemTime>>,
+    servers: Vec<Arc<PooledServer>>,
+}
+
+/// Searches the servers of one configuration file, keeping them running
+///
+/// When the file, or a file it includes, changes, the next search reloads
+/// it. Servers whose configuration is unchanged keep their connection.
+pub struct Daemon {
+    config: PathBuf,
+    pool: Mutex<Pool>,
+}
+
+impl Daemon {
+    /// Load the servers of the configuration file at `config`; they are
+    /// started by the first search
+    pub fn new(config: impl Into<PathBuf>) -> Result<Self, ToolSearchError> {
+        let config = config.into();
+        let modified = modified(&config);
+        let servers = load_servers(&config.to_string_lossy())?
+            .into_iter()
+            .map(|server| Arc::new(PooledServer::new(server)))
+            .collect();
+        Ok(Self {
+            config,
+            pool: Mutex::new(Pool { modified, servers }),
+        })
+    }
+
+    /// Servers to search, reloading the configuration first if it changed
+    async fn servers(&self) -> Result<Vec<Arc<PooledServer>>, ToolSearchError> {
+        let mut pool = self.pool.lock().await;
+        let modified = modified(&self.config);
+        if modified != pool.modified {
+            let servers = load_servers(&self.config.to_string_lossy())?
+                .into_iter()
+                .map(|server| {
+                    pool.servers
+                        .iter()
+                        .find(|pooled| *pooled.config() == server)
+                        .cloned()
+                        .unwrap_or_else(|| Arc::new(PooledServer::new(server)))
+                })
+                .collect();
+            *pool = Pool { modified, servers };
+        }
+        Ok(pool.servers.clone())
+    }
+
+    /// Run a search over the pooled servers
+    pub async fn search(&self, request: &DaemonRequest) -> Result<SearchReport, ToolSearchError> {
+        let mut builder = SearchBuilder::new(vec![]);
+        for server in self.servers().await? {
+            builder = builder.source(server);
+        }
+        builder = match (&request.name, &request.query) {
+            (Some(name), _) => builder.name(name),
+            (None, Some(query)) => builder.query(query),
+            (None, None) => builder,
+        };
+        for query in &request.queries {
+            builder = builder.or_query(query);
+        }
+        for tag in &request.tags {
+            builder = builder.tag(tag);
+        }
+        builder = builder.aliases(request.aliases.clone());
+        if let Some(max) = request.limit {
+            builder = builder.limit(max);
+        }
+        if request.sort_by_tool {
+            builder = builder.sort_by_tool();
+        } else if request.sort_by_priority {
+            builder = builder.sort_by_priority();
+        } else if request.sort_by_score {
+            builder = builder.sort_by_score();
+        }
+        if request.server_info {
+            builder = builder.include_server_info();
+        }
+        for tool in &request.pinned {
+            builder = builder.pin(tool);
+        }
+        for entry in request.blocked.entries() {
+            builder = builder.block(entry);
+        }
+        builder.search_report().await
+    }
+
+    /// Answer requests on `listener` until the returned future is dropped
+    pub async fn serve(self: Arc<Self>, listener: UnixListener) -> Result<(), ToolSearchError> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let daemon = Arc::clone(&self);
+            tokio::spawn(async move {
+                // A client that goes away mid-answer only loses its own answer
+                let _ = daemon.handle(stream).await;
+            });
+        }
+    }
+
+    async fn handle(&self, stream: UnixStream) -> Result<(), ToolSearchError> {
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        while let Some(line) = lines.next_line().await? {
+            let response = match serde_json::from_str::<DaemonRequest>(&line) {
+                Ok(request) => match self.search(&request).await {
+                    Ok(report) => DaemonResponse::from_report(report),
+                    Err(e) => DaemonResponse::failed(e.to_string()),
+                },
+                Err(e) => DaemonResponse::failed(format!("Invalid request: {}", e)),
+            };
+            let mut answer = serde_json::to_string(&response)?;
+            answer.push('\n');
+            write.write_all(answer.as_bytes()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Modification times of `config` and the files it includes, which change
+/// when any of them is edited, or an include is added or removed
+fn modified(config: &Path) -> Vec<Option<SystemTime>> {
+    crate::include::config_files(config)
+        .iter()
+        .map(|file| std::fs::metadata(file).and_then(|m| m.modified()).ok())
+        .collect()
+}
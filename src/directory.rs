@@ -0,0 +1,122 @@
+//! Local directory tool source
+//!
+//! Reads statically declared tools from `*.tool.json` files in a directory.
+//! Each file holds one tool (or an array of tools) in the same shape MCP
+//! servers return from `tools/list`, so internal tools can be discovered
+//! without running a server.
+
+use crate::{ToolSearchError, ToolSource};
+use anyhow::Context;
+use futures::future::BoxFuture;
+use rmcp::model::Tool;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File suffix identifying tool definition files
+pub const TOOL_FILE_SUFFIX: &str = ".tool.json";
+
+/// Tool source backed by a directory of `*.tool.json` files
+#[derive(Debug, Clone)]
+pub struct DirectorySource {
+    name: String,
+    path: PathBuf,
+}
+
+/// A tool file may contain a single tool or a list of tools
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ToolFile {
+    One(Box<Tool>),
+    Many(Vec<Tool>),
+}
+
+impl DirectorySource {
+    /// Create a source for the given directory, named after the directory
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Self { name, path }
+    }
+
+    /// Override the source name used in search results
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Directory the tools are read from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read every tool definition file in the directory, in file name order
+    async fn read_tools(&self) -> Result<Vec<Tool>, ToolSearchError> {
+        let mut entries = tokio::fs::read_dir(&self.path)
+            .await
+            .with_context(|| format!("Failed to read tool directory: {}", self.path.display()))?;
+
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let is_tool_file = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(TOOL_FILE_SUFFIX));
+            if is_tool_file && entry.file_type().await?.is_file() {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        let mut tools = Vec::new();
+        for file in files {
+            let data = tokio::fs::read_to_string(&file).await?;
+            let parsed: ToolFile = serde_json::from_str(&data)
+                .with_context(|| format!("Invalid tool definition: {}", file.display()))?;
+            match parsed {
+                ToolFile::One(tool) => tools.push(*tool),
+                ToolFile::Many(many) => tools.extend(many),
+            }
+        }
+
+        Ok(tools)
+    }
+}
+
+impl ToolSource for DirectorySource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.path.is_dir() {
+            Ok(())
+        } else {
+            Err(format!("Tool directory does not exist: {}", self.path.display()))
+        }
+    }
+
+    fn list_tools(
+        &self,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+        Box::pin(async move {
+            match timeout {
+                Some(timeout_dur) => tokio::time::timeout(timeout_dur, self.read_tools())
+                    .await
+                    .map_err(|_| {
+                        ToolSearchError::Connection(format!(
+                            "Reading tool directory timed out after {:?}: {}",
+                            timeout_dur,
+                            self.path.display()
+                        ))
+                    })?,
+                None => self.read_tools().await,
+            }
+        })
+    }
+}
@@ -17,16 +17,79 @@ pub enum ToolSearchError {
     #[error("Unsupported transport: {0}")]
     UnsupportedTransport(String),
 
+    #[error("Configuration error: {0}")]
+    Config(#[from] ConfigError),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error(
+        "Only {responded} of {queried} servers responded, fewer than the {:.0}% required",
+        required * 100.0
+    )]
+    TooFewResponded {
+        responded: usize,
+        queried: usize,
+        required: f32,
+    },
+
     #[error("Other error: {0}")]
     Other(#[from] anyhow::Error),
 }
 
+/// Errors that can occur while loading a server configuration file
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("configuration file not found: {path}")]
+    NotFound { path: String },
+
+    #[error("failed to read {path}: {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("failed to fetch {url}: {message}")]
+    Fetch { url: String, message: String },
+
+    #[error("failed to write {path}: {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("failed to parse {path} at line {line}, column {column}: {message}")]
+    Parse {
+        path: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("invalid include in {path}: {message}")]
+    Include { path: String, message: String },
+
+    #[error("invalid server configuration '{server}': {message}")]
+    Validation { server: String, message: String },
+
+    #[error("more than one server is named '{server}' in {path}")]
+    DuplicateServer { path: String, server: String },
+
+    #[error("no server named '{server}' in the configuration")]
+    UnknownServer { server: String },
+
+    #[error("no query template named '{name}'")]
+    UnknownTemplate { name: String },
+
+    #[error("query template parameter '{name}' is not set")]
+    MissingParameter { name: String },
+}
+
 impl From<rmcp::ErrorData> for ToolSearchError {
     fn from(err: rmcp::ErrorData) -> Self {
         ToolSearchError::McpProtocol(err.to_string())
@@ -0,0 +1,135 @@
+//! C ABI
+//!
+//! `extern "C"` entry points for applications that link the library from C,
+//! C++, Go, Node and so on. Every search function takes NUL-terminated UTF-8
+//! strings and returns a newly allocated JSON string that must be released
+//! with [`toolsearch_string_free`]:
+//!
+//! ```json
+//! {"ok": true, "matches": [{"server_name": "files", "tool": {...}}]}
+//! {"ok": false, "error": "Configuration error: ..."}
+//! ```
+//!
+//! The matching C declarations live in `include/toolsearch.h`. Searches block
+//! the calling thread on a private Tokio runtime.
+
+use crate::search::{self, SearchBuilder};
+use crate::{ServerConfig, ToolSearchError, ToolSearchMatch};
+use serde_json::json;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Read a required string argument
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string.
+unsafe fn arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{} must not be null", name));
+    }
+    // SAFETY: non-null and NUL-terminated per the caller's contract
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+fn run_search(
+    servers: Vec<ServerConfig>,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<ToolSearchMatch>, ToolSearchError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let mut builder = SearchBuilder::new(servers).query(query);
+    if limit > 0 {
+        builder = builder.limit(limit);
+    }
+    runtime.block_on(builder.search())
+}
+
+/// Serialize an outcome into the response envelope, catching panics so they
+/// never unwind across the C boundary
+fn respond(f: impl FnOnce() -> Result<Vec<ToolSearchMatch>, String>) -> *mut c_char {
+    let response = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(matches)) => json!({ "ok": true, "matches": matches }),
+        Ok(Err(error)) => json!({ "ok": false, "error": error }),
+        Err(_) => json!({ "ok": false, "error": "toolsearch panicked" }),
+    };
+    // serde_json escapes control characters, so the output has no interior NUL
+    CString::new(response.to_string()).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Search servers given as a JSON array (the config file format)
+///
+/// `limit` of 0 returns every match.
+///
+/// # Safety
+///
+/// `servers_json` and `query` must be null or point to NUL-terminated
+/// strings. The result must be released with [`toolsearch_string_free`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn toolsearch_search(
+    servers_json: *const c_char,
+    query: *const c_char,
+    limit: usize,
+) -> *mut c_char {
+    respond(|| {
+        // SAFETY: forwarded from this function's contract
+        let servers_json = unsafe { arg(servers_json, "servers_json") }?;
+        let query = unsafe { arg(query, "query") }?;
+        let servers: Vec<ServerConfig> = serde_json::from_str(servers_json)
+            .map_err(|e| format!("Invalid servers JSON: {}", e))?;
+        for server in &servers {
+            server
+                .validate()
+                .map_err(|e| format!("Invalid server configuration {}: {}", server.name, e))?;
+        }
+        run_search(servers, query, limit).map_err(|e| e.to_string())
+    })
+}
+
+/// Search servers loaded from a JSON configuration file
+///
+/// `limit` of 0 returns every match.
+///
+/// # Safety
+///
+/// `config_path` and `query` must be null or point to NUL-terminated
+/// strings. The result must be released with [`toolsearch_string_free`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn toolsearch_search_file(
+    config_path: *const c_char,
+    query: *const c_char,
+    limit: usize,
+) -> *mut c_char {
+    respond(|| {
+        // SAFETY: forwarded from this function's contract
+        let config_path = unsafe { arg(config_path, "config_path") }?;
+        let query = unsafe { arg(query, "query") }?;
+        let servers = search::load_servers(config_path).map_err(|e| e.to_string())?;
+        run_search(servers, query, limit).map_err(|e| e.to_string())
+    })
+}
+
+/// Release a string returned by this library; null is ignored
+///
+/// # Safety
+///
+/// `s` must be null or a pointer returned by a `toolsearch_*` function that
+/// has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn toolsearch_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: allocated by CString::into_raw in `respond`
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Library version as a static NUL-terminated string (do not free)
+#[unsafe(no_mangle)]
+pub extern "C" fn toolsearch_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
@@ -0,0 +1,104 @@
+//! HTTP headers of SSE servers
+//!
+//! A header set in a configuration is checked when the configuration is
+//! loaded, so a name with a space or a value with a line break fails there,
+//! naming the server and header, instead of deep inside the HTTP client
+//! when the server is first reached. A header can have several values,
+//! written as a list, which are sent as one header each:
+//!
+//! ```json
+//! { "name": "hosted", "transport": { "type": "sse", "url": "https://example.com/sse", "headers": { "Authorization": "Bearer …", "Accept": ["text/event-stream", "application/json"] } } }
+//! ```
+//!
+//! ```
+//! use toolsearch::header::{check_header_name, check_header_value};
+//!
+//! assert!(check_header_name("X-Api-Key").is_ok());
+//! assert!(check_header_name("X Api Key").is_err());
+//! assert!(check_header_value("Bearer abc\r\nX-Injected: 1").is_err());
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Characters besides letters and digits allowed in a header name (the
+/// `tchar`s of RFC 9110)
+const NAME_SYMBOLS: &str = "!#$%&'*+-.^_`|~";
+
+/// Value or values of one header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum HeaderValue {
+    /// A single value, written as a string
+    One(String),
+    /// Several values, written as a list, each sent as its own header
+    Many(Vec<String>),
+}
+
+impl HeaderValue {
+    /// The values, in order
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        let values = match self {
+            HeaderValue::One(value) => std::slice::from_ref(value),
+            HeaderValue::Many(values) => values.as_slice(),
+        };
+        values.iter().map(String::as_str)
+    }
+
+    /// Add a value after the existing ones
+    pub fn push(&mut self, value: impl Into<String>) {
+        match self {
+            HeaderValue::One(first) => {
+                *self = HeaderValue::Many(vec![std::mem::take(first), value.into()]);
+            }
+            HeaderValue::Many(values) => values.push(value.into()),
+        }
+    }
+
+    /// Whether no value has any content
+    pub fn is_empty(&self) -> bool {
+        self.iter().all(str::is_empty)
+    }
+}
+
+impl From<String> for HeaderValue {
+    fn from(value: String) -> Self {
+        HeaderValue::One(value)
+    }
+}
+
+impl From<&str> for HeaderValue {
+    fn from(value: &str) -> Self {
+        HeaderValue::One(value.to_string())
+    }
+}
+
+impl From<Vec<String>> for HeaderValue {
+    fn from(values: Vec<String>) -> Self {
+        HeaderValue::Many(values)
+    }
+}
+
+/// Check that `name` is a valid header name: letters, digits, and
+/// `!#$%&'*+-.^_`|~`
+pub fn check_header_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Header name cannot be empty".to_string());
+    }
+    match name
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !NAME_SYMBOLS.contains(*c))
+    {
+        Some(c) => Err(format!("Header name {:?} contains {:?}", name, c)),
+        None => Ok(()),
+    }
+}
+
+/// Check that `value` is a valid header value: no line breaks or other
+/// control characters besides tab
+pub fn check_header_value(value: &str) -> Result<(), String> {
+    match value.chars().find(|c| c.is_control() && *c != '\t') {
+        // The value isn't repeated, as it may be a credential
+        Some(c) => Err(format!("Header value contains {:?}", c)),
+        None => Ok(()),
+    }
+}
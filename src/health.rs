@@ -0,0 +1,117 @@
+//! Server health checks
+//!
+//! Pings MCP servers to measure their latency and find the ones that are down.
+
+use crate::{connect_with_timeout, ServerConfig, ToolSearchError};
+use futures::future::join_all;
+use rmcp::model::{ClientRequest, PingRequest, ServerResult};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+
+/// Health of a server as of its last check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// The server completed the handshake and answered a ping
+    Healthy,
+    /// The server could not be reached or did not answer
+    Unhealthy {
+        /// Why the check failed
+        error: String,
+    },
+}
+
+impl HealthStatus {
+    /// Whether the server is healthy
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy)
+    }
+}
+
+/// Result of pinging a single server
+#[derive(Debug, Clone)]
+pub struct PingResult {
+    /// Name of the server that was pinged
+    pub server_name: String,
+    /// Outcome of the ping
+    pub status: HealthStatus,
+    /// Time taken to connect and answer the ping (or to fail)
+    pub latency: Duration,
+    /// MCP protocol version agreed in the handshake, if it completed
+    pub protocol_version: Option<String>,
+}
+
+/// Ping a single MCP server
+pub async fn ping_server(config: &ServerConfig) -> PingResult {
+    ping_server_with_timeout(config, None).await
+}
+
+/// Ping a single MCP server, failing if connecting or answering exceeds the timeout
+pub async fn ping_server_with_timeout(
+    config: &ServerConfig,
+    timeout_duration: Option<Duration>,
+) -> PingResult {
+    let started = Instant::now();
+    let (status, protocol_version) = match ping(config, timeout_duration).await {
+        Ok(version) => (HealthStatus::Healthy, version),
+        Err(e) => (
+            HealthStatus::Unhealthy {
+                error: e.to_string(),
+            },
+            None,
+        ),
+    };
+
+    PingResult {
+        server_name: config.name.clone(),
+        status,
+        latency: started.elapsed(),
+        protocol_version,
+    }
+}
+
+/// Ping all servers in parallel, returning results in server order
+pub async fn ping_all(
+    servers: &[ServerConfig],
+    timeout_duration: Option<Duration>,
+) -> Vec<PingResult> {
+    join_all(
+        servers
+            .iter()
+            .map(|server| ping_server_with_timeout(server, timeout_duration)),
+    )
+    .await
+}
+
+/// Connect to a server and send it a ping request, returning the negotiated
+/// protocol version
+async fn ping(
+    config: &ServerConfig,
+    timeout_duration: Option<Duration>,
+) -> Result<Option<String>, ToolSearchError> {
+    let service = connect_with_timeout(config, timeout_duration, None).await?;
+    let request = service
+        .peer()
+        .send_request(ClientRequest::PingRequest(PingRequest::default()));
+
+    let response = match timeout_duration {
+        Some(timeout_dur) => timeout(timeout_dur, request).await.map_err(|_| {
+            ToolSearchError::Connection(format!(
+                "Ping timeout after {:?} for server: {}",
+                timeout_dur, config.name
+            ))
+        })?,
+        None => request.await,
+    }?;
+
+    match response {
+        ServerResult::EmptyResult(_) => Ok(service
+            .peer_info()
+            .map(|info| info.protocol_version.to_string())),
+        _ => Err(ToolSearchError::McpProtocol(format!(
+            "Unexpected ping response from server: {}",
+            config.name
+        ))),
+    }
+}
@@ -0,0 +1,167 @@
+//! Search history
+//!
+//! A [`History`] is a JSON Lines file with one [`HistoryEntry`] per search,
+//! oldest first. The CLI appends to the default file after each search so
+//! `toolsearch history` can list past searches and `toolsearch rerun <n>` can
+//! repeat one.
+//!
+//! ```no_run
+//! use toolsearch::history::History;
+//!
+//! # fn example() -> Result<(), toolsearch::ToolSearchError> {
+//! if let Some(history) = History::open_default() {
+//!     for (n, entry) in history.entries()?.iter().enumerate() {
+//!         println!("{} {} ({} hits)", n + 1, entry.target(), entry.hits);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{SearchMode, ToolSearchError};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One recorded search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the search ran, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Configuration file the servers were loaded from
+    pub config: String,
+    /// Search query, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Further queries whose matches were added
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<String>,
+    /// Exact tool name searched for, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Tags the tools had to declare
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Resolved search mode
+    pub mode: SearchMode,
+    /// Number of matches found
+    pub hits: usize,
+    /// Result limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// OpenAPI documents searched alongside the servers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub openapi: Vec<String>,
+    /// Tool definition directories searched alongside the servers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools_dir: Vec<String>,
+}
+
+impl HistoryEntry {
+    /// Start an entry for a search run now
+    pub fn new(config: impl Into<String>, mode: SearchMode, hits: usize) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self {
+            timestamp,
+            config: config.into(),
+            query: None,
+            queries: Vec::new(),
+            name: None,
+            tags: Vec::new(),
+            mode,
+            hits,
+            limit: None,
+            openapi: Vec::new(),
+            tools_dir: Vec::new(),
+        }
+    }
+
+    /// What was searched for: the exact name, the query, or `*` for a listing
+    pub fn target(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.query.as_deref())
+            .unwrap_or("*")
+    }
+}
+
+/// Search history file
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    /// History stored in `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `$XDG_DATA_HOME/toolsearch/history.jsonl`, falling back to
+    /// `~/.local/share/toolsearch/history.jsonl`; `None` if neither
+    /// variable is set
+    pub fn default_path() -> Option<PathBuf> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .filter(|dir| !dir.is_empty())
+                    .map(|home| PathBuf::from(home).join(".local").join("share"))
+            })?;
+        Some(data_home.join("toolsearch").join("history.jsonl"))
+    }
+
+    /// History in the [default location](Self::default_path)
+    pub fn open_default() -> Option<Self> {
+        Self::default_path().map(Self::new)
+    }
+
+    /// Path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry, creating the file and its directory if needed
+    pub fn append(&self, entry: &HistoryEntry) -> Result<(), ToolSearchError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// All entries, oldest first
+    ///
+    /// A missing file is an empty history. Lines that don't parse (for
+    /// example one cut short by a crash) are skipped.
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>, ToolSearchError> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(data
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Entry number `n`, counting from 1 for the oldest
+    pub fn get(&self, n: usize) -> Result<Option<HistoryEntry>, ToolSearchError> {
+        let Some(index) = n.checked_sub(1) else {
+            return Ok(None);
+        };
+        Ok(self.entries()?.into_iter().nth(index))
+    }
+}
@@ -0,0 +1,167 @@
+//! `{"include": [...]}` entries of a configuration file
+//!
+//! A large fleet is easier to maintain split across files. Besides
+//! servers, a configuration's array can hold include entries naming other
+//! configuration files, which are read in their place:
+//!
+//! ```json
+//! [
+//!   { "include": ["./fs-servers.json", "./db-servers.json"] },
+//!   { "name": "github", "transport": { "type": "sse", "url": "https://example.com/sse" } }
+//! ]
+//! ```
+//!
+//! Paths are relative to the including file (to the working directory for
+//! a configuration read from stdin), included files may include others,
+//! and a file that ends up including itself is an error rather than a
+//! loop. Remote configurations can't include files.
+
+use crate::error::ConfigError;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// What the includes of a configuration are resolved against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum IncludeBase {
+    /// The configuration file itself
+    File(PathBuf),
+    /// The working directory, for stdin
+    WorkingDir,
+    /// Nothing: includes are an error
+    None,
+}
+
+/// Whether `entry` is an include entry rather than a server
+pub(crate) fn is_include(entry: &Value) -> bool {
+    entry
+        .as_object()
+        .is_some_and(|entry| entry.contains_key("include") && !entry.contains_key("transport"))
+}
+
+/// Whether the configuration `data` has include entries
+pub(crate) fn has_includes(data: &str) -> bool {
+    serde_json::from_str::<Vec<Value>>(data).is_ok_and(|entries| entries.iter().any(is_include))
+}
+
+/// Replace the include entries of `entries`, read from `origin`, with the
+/// entries of the files they name, recursively
+pub(crate) fn expand_includes(
+    entries: Vec<Value>,
+    origin: &str,
+    base: &IncludeBase,
+) -> Result<Vec<Value>, ConfigError> {
+    let (dir, mut stack) = match base {
+        IncludeBase::File(path) => (
+            path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            path.canonicalize().into_iter().collect(),
+        ),
+        IncludeBase::WorkingDir => (PathBuf::new(), Vec::new()),
+        IncludeBase::None => {
+            if entries.iter().any(is_include) {
+                return Err(ConfigError::Include {
+                    path: origin.to_string(),
+                    message: "only local configuration files can include others".to_string(),
+                });
+            }
+            return Ok(entries);
+        }
+    };
+    let mut expanded = Vec::new();
+    expand_into(entries, origin, &dir, &mut stack, &mut expanded)?;
+    Ok(expanded)
+}
+
+/// The configuration file at `path` and every file it includes, directly
+/// or not, skipping those that can't be read
+#[cfg(all(feature = "daemon", unix))]
+pub(crate) fn config_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![path.to_path_buf()];
+    let mut seen: Vec<PathBuf> = path.canonicalize().into_iter().collect();
+    let mut next = 0;
+    while next < files.len() {
+        let file = files[next].clone();
+        next += 1;
+        let Some(entries) = std::fs::read_to_string(&file)
+            .ok()
+            .and_then(|data| serde_json::from_str::<Vec<Value>>(&data).ok())
+        else {
+            continue;
+        };
+        let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        for entry in entries.iter().filter(|entry| is_include(entry)) {
+            let paths = match &entry["include"] {
+                Value::String(path) => vec![path.as_str()],
+                Value::Array(paths) => paths.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            for path in paths {
+                if let Ok(canonical) = dir.join(path).canonicalize()
+                    && !seen.contains(&canonical)
+                {
+                    seen.push(canonical.clone());
+                    files.push(canonical);
+                }
+            }
+        }
+    }
+    files
+}
+
+fn expand_into(
+    entries: Vec<Value>,
+    origin: &str,
+    dir: &Path,
+    stack: &mut Vec<PathBuf>,
+    expanded: &mut Vec<Value>,
+) -> Result<(), ConfigError> {
+    let invalid = |message: String| ConfigError::Include {
+        path: origin.to_string(),
+        message,
+    };
+    for entry in entries {
+        if !is_include(&entry) {
+            expanded.push(entry);
+            continue;
+        }
+        let paths = match &entry["include"] {
+            Value::String(path) => vec![path.clone()],
+            Value::Array(paths) => paths
+                .iter()
+                .map(|path| path.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid("\"include\" must list file paths".to_string()))?,
+            _ => return Err(invalid("\"include\" must list file paths".to_string())),
+        };
+        for path in paths {
+            let file = dir.join(&path);
+            let display = file.display().to_string();
+            let canonical = file
+                .canonicalize()
+                .map_err(|e| invalid(format!("{}: {}", display, e)))?;
+            if let Some(start) = stack.iter().position(|seen| *seen == canonical) {
+                let cycle: Vec<_> = stack[start..]
+                    .iter()
+                    .chain([&canonical])
+                    .map(|path| path.display().to_string())
+                    .collect();
+                return Err(invalid(format!("include cycle: {}", cycle.join(" -> "))));
+            }
+            let data = std::fs::read_to_string(&canonical).map_err(|e| ConfigError::Read {
+                path: display.clone(),
+                source: e,
+            })?;
+            let entries: Vec<Value> =
+                serde_json::from_str(&data).map_err(|e| ConfigError::Parse {
+                    path: display.clone(),
+                    line: e.line(),
+                    column: e.column(),
+                    message: e.to_string(),
+                })?;
+            let parent = canonical.parent().map(Path::to_path_buf).unwrap_or_default();
+            stack.push(canonical);
+            expand_into(entries, &display, &parent, stack, expanded)?;
+            stack.pop();
+        }
+    }
+    Ok(())
+}
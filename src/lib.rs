@@ -43,53 +43,643 @@
 //! # }
 //! ```
 
+#[cfg(feature = "stdio")]
 use anyhow::Context;
 use futures::future::join_all;
-use rmcp::model::Tool;
+use futures::stream::{FuturesUnordered, StreamExt};
+use rmcp::model::{ClientInfo, ProtocolVersion, ServerInfo, Tool};
+#[cfg(feature = "stdio")]
 use rmcp::ServiceExt;
+#[cfg(feature = "regex-mode")]
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "stdio")]
 use std::process::Stdio;
-use std::time::Duration;
-use tokio::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::time::timeout;
 
+pub mod alias;
+pub mod block;
+pub mod breaker;
+mod casefold;
+pub mod catalog;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod client;
+pub mod configlint;
+pub mod configsource;
+#[cfg(all(feature = "daemon", unix))]
+pub mod daemon;
+pub mod directory;
 pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod header;
+pub mod health;
+pub mod history;
+mod include;
+pub mod lint;
+pub mod matcher;
+pub mod observer;
+pub mod openapi;
+pub mod output;
+pub mod pins;
+pub mod pool;
+pub mod popularity;
+pub mod projection;
+pub mod proxy;
+pub mod redact;
+mod protocol;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod quality;
+pub mod ratelimit;
+pub mod record;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod report;
 pub mod search;
-pub use error::ToolSearchError;
-pub use search::{load_servers, simple_search, SearchBuilder};
+mod snippet;
+pub mod spawn;
+pub mod source;
+pub mod template;
+pub mod testing;
+pub mod validate;
+pub use alias::AliasMap;
+pub use breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+pub use catalog::{Catalog, CatalogEvent, CatalogServer};
+pub use configsource::ConfigSource;
+pub use client::{ConfigDelta, ToolSearchClient};
+pub use directory::DirectorySource;
+pub use error::{ConfigError, ToolSearchError};
+pub use header::HeaderValue;
+pub use health::{ping_all, ping_server, HealthStatus, PingResult};
+pub use matcher::Matcher;
+pub use observer::Observer;
+pub use block::Blocklist;
+pub use pins::Pins;
+pub use popularity::Popularity;
+pub use projection::{OutputField, Projection};
+pub use ratelimit::{RateLimit, RateLimiter};
+pub use redact::Redaction;
+pub use openapi::OpenApiSource;
+#[cfg(feature = "registry")]
+pub use registry::{RegistryClient, RegistryServer};
+pub use report::{Health, SearchReport, SearchWarning, ServerTiming};
+pub use search::{load_servers, load_servers_with, save_servers, DuplicatePolicy, simple_search, PlannedSource, SearchBuilder, SearchPlan};
+pub use source::{FetchOptions, MalformedTool, ToolListing, ToolSource};
+pub use template::{QueryTemplate, TemplateSet};
 
 /// Configuration for an MCP server
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Fields toolsearch doesn't know are kept in `extra` (and in the transport's
+/// `extra`), so a loaded configuration saves back without losing settings that
+/// other MCP clients store in the same file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServerConfig {
     /// Name identifier for the server
     pub name: String,
     /// Transport configuration
     pub transport: TransportConfig,
+    /// Ranking priority; matches from higher-priority servers sort first
+    /// with [`SortOrder::ServerPriority`]
+    #[serde(default, skip_serializing_if = "is_default_priority")]
+    pub priority: u32,
+    /// Multiplier for the relevance scores of this server's matches, e.g.
+    /// `2` to prefer its tools or `0.5` to demote them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<f32>,
+    /// Score multipliers for this server's tools whose names match a pattern
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_boosts: Vec<ToolBoost>,
+    /// Patterns of this server's tools that no search returns (see
+    /// [`block`]); `*` blocks the whole server
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_tools: Vec<String>,
+    /// MCP protocol version to advertise in the handshake, e.g. `2024-11-05`
+    ///
+    /// Unset, the latest version is offered first and older ones are tried
+    /// if the handshake fails (see [`protocol_versions`](Self::protocol_versions)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    /// Timeout in seconds for this server, replacing the search's
+    /// [`timeout`](SearchOptions::timeout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Limits on how fast and how many requests are sent to this server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Seconds a kept-open connection (see [`PooledServer`](pool::PooledServer))
+    /// may sit unused before it is closed and the next request reconnects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<u64>,
+    /// Unrecognized fields, preserved on save
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+    /// Faults injected into this server's transport (see
+    /// [`chaos`](crate::chaos)); never read from or written to
+    /// configuration files
+    #[cfg(feature = "chaos")]
+    #[serde(skip)]
+    pub faults: Option<chaos::Faults>,
+}
+
+/// Protocol versions offered, newest first, to servers that don't pin one;
+/// older servers reject handshakes with versions they don't know
+const PROTOCOL_FALLBACK: [ProtocolVersion; 2] = [ProtocolVersion::LATEST, ProtocolVersion::V_2024_11_05];
+
+fn is_default_priority(priority: &u32) -> bool {
+    *priority == 0
+}
+
+/// Serialize a map with sorted keys, so saved configs diff cleanly
+fn serialize_sorted<S: serde::Serializer, V: Serialize>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().collect::<std::collections::BTreeMap<_, _>>())
 }
 
 impl ServerConfig {
+    /// Create a stdio server configuration that spawns `command`
+    ///
+    /// ```
+    /// use toolsearch::ServerConfig;
+    ///
+    /// let server = ServerConfig::stdio("files", "mcp-file-server")
+    ///     .arg("--verbose")
+    ///     .env("RUST_LOG", "debug");
+    /// assert!(server.validate().is_ok());
+    /// ```
+    pub fn stdio(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            transport: TransportConfig::Stdio {
+                command: command.into(),
+                args: Vec::new(),
+                env: HashMap::new(),
+                env_policy: EnvPolicy::Inherit,
+                shell: false,
+                runner: None,
+                package: None,
+                extra: Map::new(),
+            },
+            priority: 0,
+            boost: None,
+            tool_boosts: Vec::new(),
+            blocked_tools: Vec::new(),
+            protocol_version: None,
+            timeout_secs: None,
+            rate_limit: None,
+            idle_timeout_secs: None,
+            extra: Map::new(),
+            #[cfg(feature = "chaos")]
+            faults: None,
+        }
+    }
+
+    /// Create a stdio server configuration that runs `package` with `runner`
+    ///
+    /// ```
+    /// use toolsearch::{Runner, ServerConfig};
+    ///
+    /// let server = ServerConfig::package("fs", Runner::Npx, "@modelcontextprotocol/server-filesystem")
+    ///     .arg("/tmp");
+    /// let (command, args) = server.command_line().unwrap();
+    /// assert_eq!(command, "npx");
+    /// assert_eq!(args, ["-y", "@modelcontextprotocol/server-filesystem", "/tmp"]);
+    /// ```
+    pub fn package(name: impl Into<String>, runner: Runner, package: impl Into<String>) -> Self {
+        let mut config = Self::stdio(name, "");
+        if let TransportConfig::Stdio {
+            runner: ref mut r,
+            package: ref mut p,
+            ..
+        } = config.transport
+        {
+            *r = Some(runner);
+            *p = Some(package.into());
+        }
+        config
+    }
+
+    /// Program and arguments a stdio server is started with, with
+    /// `runner`/`package` expanded; `None` for other transports
+    pub fn command_line(&self) -> Option<(String, Vec<String>)> {
+        match &self.transport {
+            TransportConfig::Stdio {
+                command,
+                args,
+                runner,
+                package,
+                ..
+            } => Some(match (runner, package) {
+                (Some(runner), Some(package)) => {
+                    let (program, mut full_args) = runner.command_line(package);
+                    full_args.extend(args.iter().cloned());
+                    (program.to_string(), full_args)
+                }
+                _ => (command.clone(), args.clone()),
+            }),
+            TransportConfig::Sse { .. } => None,
+        }
+    }
+
+    /// Create an SSE server configuration connecting to `url`
+    ///
+    /// ```
+    /// use toolsearch::ServerConfig;
+    ///
+    /// let server = ServerConfig::sse("remote", "https://example.com/sse")
+    ///     .header("Authorization", "Bearer token");
+    /// assert!(server.validate().is_ok());
+    /// ```
+    pub fn sse(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            transport: TransportConfig::Sse {
+                url: url.into(),
+                headers: HashMap::new(),
+                extra: Map::new(),
+            },
+            priority: 0,
+            boost: None,
+            tool_boosts: Vec::new(),
+            blocked_tools: Vec::new(),
+            protocol_version: None,
+            timeout_secs: None,
+            rate_limit: None,
+            idle_timeout_secs: None,
+            extra: Map::new(),
+            #[cfg(feature = "chaos")]
+            faults: None,
+        }
+    }
+
+    /// Set the ranking priority used by [`SortOrder::ServerPriority`]
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Multiply the relevance scores of this server's matches by `factor`
+    pub fn with_boost(mut self, factor: f32) -> Self {
+        self.boost = Some(factor);
+        self
+    }
+
+    /// Multiply the relevance scores of tools named like `pattern` by
+    /// `factor` (see [`ToolBoost`])
+    pub fn with_tool_boost(mut self, pattern: impl Into<String>, factor: f32) -> Self {
+        self.tool_boosts.push(ToolBoost::new(pattern, factor));
+        self
+    }
+
+    /// Hide this server's tools named like `pattern` from every search
+    /// (see [`block`])
+    pub fn with_blocked_tool(mut self, pattern: impl Into<String>) -> Self {
+        self.blocked_tools.push(pattern.into());
+        self
+    }
+
+    /// Score multipliers configured for this server
+    pub fn boosts(&self) -> Boosts {
+        Boosts {
+            server: self.boost,
+            tools: self.tool_boosts.clone(),
+        }
+    }
+
+    /// Give this server `seconds` to answer, whatever the search's timeout
+    pub fn with_timeout(mut self, seconds: u64) -> Self {
+        self.timeout_secs = Some(seconds);
+        self
+    }
+
+    /// Timeout configured for this server, if it has its own
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.map(Duration::from_secs)
+    }
+
+    /// Close a kept-open connection to this server after `seconds` unused
+    pub fn with_idle_timeout(mut self, seconds: u64) -> Self {
+        self.idle_timeout_secs = Some(seconds);
+        self
+    }
+
+    /// How long a kept-open connection may sit unused, if limited
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout_secs.map(Duration::from_secs)
+    }
+
+    /// Inject `faults` into this server's transport (see
+    /// [`chaos`](crate::chaos))
+    #[cfg(feature = "chaos")]
+    pub fn with_faults(mut self, faults: chaos::Faults) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Limit the requests sent to this server (see [`RateLimit`])
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Limiter enforcing this server's rate limit; admits every request
+    /// at once if it has none
+    pub fn rate_limiter(&self) -> RateLimiter {
+        RateLimiter::new(self.rate_limit.unwrap_or_default())
+    }
+
+    /// Advertise only this MCP protocol version, with no fallback
+    pub fn with_protocol_version(mut self, version: impl Into<String>) -> Self {
+        self.protocol_version = Some(version.into());
+        self
+    }
+
+    /// Protocol versions offered in the handshake, in the order they are tried
+    ///
+    /// ```
+    /// use toolsearch::ServerConfig;
+    ///
+    /// let server = ServerConfig::stdio("legacy", "legacy-mcp");
+    /// let versions: Vec<_> = server.protocol_versions().iter().map(|v| v.to_string()).collect();
+    /// assert_eq!(versions, ["2025-03-26", "2024-11-05"]);
+    ///
+    /// let pinned = server.with_protocol_version("2024-11-05");
+    /// assert_eq!(pinned.protocol_versions().len(), 1);
+    /// ```
+    pub fn protocol_versions(&self) -> Vec<ProtocolVersion> {
+        use serde::de::IntoDeserializer;
+
+        match &self.protocol_version {
+            Some(version) => {
+                let version: Result<_, serde::de::value::Error> =
+                    ProtocolVersion::deserialize(version.as_str().into_deserializer());
+                // Any string is a valid (if unknown) protocol version
+                version.into_iter().collect()
+            }
+            None => PROTOCOL_FALLBACK.to_vec(),
+        }
+    }
+
+    /// Deterministic hash of how the server is reached: its transport
+    /// (command, arguments and environment, or URL and headers) and pinned
+    /// protocol version
+    ///
+    /// Unlike the name, it changes whenever the server behind it may have,
+    /// so it keys cached tool lists. Renaming a server keeps its hash.
+    ///
+    /// ```
+    /// use toolsearch::ServerConfig;
+    ///
+    /// let server = ServerConfig::stdio("files", "mcp-file-server");
+    /// let renamed = ServerConfig::stdio("fs", "mcp-file-server");
+    /// assert_eq!(server.identity_hash(), renamed.identity_hash());
+    /// assert_ne!(server.identity_hash(), server.clone().arg("--verbose").identity_hash());
+    /// ```
+    pub fn identity_hash(&self) -> u64 {
+        // Maps serialize with sorted keys, so the bytes are canonical
+        let identity = (&self.transport, &self.protocol_version);
+        stable_hash(&serde_json::to_vec(&identity).unwrap_or_default())
+    }
+
+    /// Append a command argument (stdio transport only, ignored otherwise)
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        if let TransportConfig::Stdio { ref mut args, .. } = self.transport {
+            args.push(arg.into());
+        }
+        self
+    }
+
+    /// Append several command arguments (stdio transport only, ignored otherwise)
+    pub fn args<I, S>(mut self, new_args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if let TransportConfig::Stdio { ref mut args, .. } = self.transport {
+            args.extend(new_args.into_iter().map(Into::into));
+        }
+        self
+    }
+
+    /// Set an environment variable (stdio transport only, ignored otherwise)
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        if let TransportConfig::Stdio { ref mut env, .. } = self.transport {
+            env.insert(key.into(), value.into());
+        }
+        self
+    }
+
+    /// Set which of this process's environment variables the server inherits
+    /// (stdio transport only, ignored otherwise)
+    ///
+    /// ```
+    /// use toolsearch::{EnvPolicy, ServerConfig};
+    ///
+    /// // Only PATH and HOME from the parent, plus the token set here
+    /// let server = ServerConfig::stdio("github", "github-mcp")
+    ///     .env_policy(EnvPolicy::Allowlist(vec!["PATH".to_string(), "HOME".to_string()]))
+    ///     .env("GITHUB_TOKEN", "ghp_example");
+    /// assert!(server.validate().is_ok());
+    /// ```
+    pub fn env_policy(mut self, policy: EnvPolicy) -> Self {
+        if let TransportConfig::Stdio { ref mut env_policy, .. } = self.transport {
+            *env_policy = policy;
+        }
+        self
+    }
+
+    /// Run the command through the system shell (stdio transport only,
+    /// ignored otherwise)
+    pub fn shell(mut self, enabled: bool) -> Self {
+        if let TransportConfig::Stdio { ref mut shell, .. } = self.transport {
+            *shell = enabled;
+        }
+        self
+    }
+
+    /// Set an HTTP header to a value, or a `Vec` of values (SSE transport
+    /// only, ignored otherwise)
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<HeaderValue>) -> Self {
+        if let TransportConfig::Sse { ref mut headers, .. } = self.transport {
+            headers.insert(key.into(), value.into());
+        }
+        self
+    }
+
     /// Validate the server configuration
     pub fn validate(&self) -> Result<(), String> {
+        self.check().map_err(|(_, message)| message)
+    }
+
+    /// Validate the server configuration, failing with a
+    /// [`validate::codes`] code as well as a message
+    pub(crate) fn check(&self) -> Result<(), (&'static str, String)> {
+        use validate::codes;
+
         if self.name.is_empty() {
-            return Err("Server name cannot be empty".to_string());
+            return Err((codes::EMPTY_NAME, "Server name cannot be empty".to_string()));
+        }
+        if self.protocol_version.as_deref().is_some_and(str::is_empty) {
+            return Err((
+                codes::EMPTY_PROTOCOL_VERSION,
+                format!("Protocol version cannot be empty for server: {}", self.name),
+            ));
+        }
+        if self.timeout_secs == Some(0) {
+            return Err((
+                codes::ZERO_TIMEOUT,
+                format!("Timeout cannot be zero for server: {}", self.name),
+            ));
+        }
+        if self.idle_timeout_secs == Some(0) {
+            return Err((
+                codes::ZERO_IDLE_TIMEOUT,
+                format!("Idle timeout cannot be zero for server: {}", self.name),
+            ));
+        }
+        if let Some(limit) = &self.rate_limit {
+            limit.validate().map_err(|e| {
+                (
+                    codes::INVALID_RATE_LIMIT,
+                    format!("Invalid rate limit for server {}: {}", self.name, e),
+                )
+            })?;
+        }
+        if let Some(factor) = self.boost
+            && !is_valid_factor(factor)
+        {
+            return Err((
+                codes::INVALID_BOOST,
+                format!("Invalid boost {} for server: {}", factor, self.name),
+            ));
+        }
+        if let Some(boost) = self
+            .tool_boosts
+            .iter()
+            .find(|boost| boost.pattern.is_empty() || !is_valid_factor(boost.factor))
+        {
+            return Err((
+                codes::INVALID_TOOL_BOOST,
+                format!(
+                    "Invalid tool boost {:?} (factor {}) for server: {}",
+                    boost.pattern, boost.factor, self.name
+                ),
+            ));
+        }
+        if self.blocked_tools.iter().any(|pattern| pattern.is_empty()) {
+            return Err((
+                codes::EMPTY_BLOCKED_TOOL,
+                format!("Blocked tool pattern cannot be empty for server: {}", self.name),
+            ));
         }
 
         match &self.transport {
-            TransportConfig::Stdio { command, .. } => {
-                if command.is_empty() {
-                    return Err(format!("Command cannot be empty for server: {}", self.name));
+            TransportConfig::Stdio {
+                command,
+                env_policy,
+                runner,
+                package,
+                ..
+            } => {
+                match (runner, package) {
+                    (Some(_), Some(_)) if !command.is_empty() => {
+                        return Err((
+                            codes::CONFLICTING_COMMAND,
+                            format!(
+                                "Set either command or runner and package for server: {}",
+                                self.name
+                            ),
+                        ));
+                    }
+                    (Some(_), Some(package)) if package.is_empty() => {
+                        return Err((
+                            codes::EMPTY_PACKAGE,
+                            format!("Package cannot be empty for server: {}", self.name),
+                        ));
+                    }
+                    (Some(_), Some(_)) => {}
+                    (Some(_), None) | (None, Some(_)) => {
+                        return Err((
+                            codes::INCOMPLETE_RUNNER,
+                            format!(
+                                "runner and package must be set together for server: {}",
+                                self.name
+                            ),
+                        ));
+                    }
+                    (None, None) if command.is_empty() => {
+                        return Err((
+                            codes::EMPTY_COMMAND,
+                            format!("Command cannot be empty for server: {}", self.name),
+                        ));
+                    }
+                    (None, None) => {}
+                }
+                if let EnvPolicy::Allowlist(names) = env_policy
+                    && let Some(name) = names.iter().find(|n| n.is_empty() || n.contains('='))
+                {
+                    return Err((
+                        codes::INVALID_ENV_NAME,
+                        format!(
+                            "Invalid environment variable name in env_policy for server {}: {:?}",
+                            self.name, name
+                        ),
+                    ));
                 }
             }
-            TransportConfig::Sse { url, .. } => {
+            TransportConfig::Sse { url, headers, .. } => {
                 if url.is_empty() {
-                    return Err(format!("URL cannot be empty for server: {}", self.name));
+                    return Err((
+                        codes::EMPTY_URL,
+                        format!("URL cannot be empty for server: {}", self.name),
+                    ));
                 }
                 // Basic URL validation
                 if !url.starts_with("http://") && !url.starts_with("https://") {
-                    return Err(format!("Invalid URL format for server {}: {}", self.name, url));
+                    return Err((
+                        codes::INVALID_URL,
+                        format!("Invalid URL format for server {}: {}", self.name, url),
+                    ));
+                }
+                let mut names: Vec<_> = headers.keys().collect();
+                names.sort();
+                let mut seen = HashSet::new();
+                for name in names {
+                    header::check_header_name(name).map_err(|e| {
+                        (
+                            codes::INVALID_HEADER_NAME,
+                            format!("Invalid header for server {}: {}", self.name, e),
+                        )
+                    })?;
+                    for value in headers[name].iter() {
+                        header::check_header_value(value).map_err(|e| {
+                            (
+                                codes::INVALID_HEADER_VALUE,
+                                format!("Invalid header {} for server {}: {}", name, self.name, e),
+                            )
+                        })?;
+                    }
+                    // Header names are case-insensitive
+                    if !seen.insert(name.to_ascii_lowercase()) {
+                        return Err((
+                            codes::DUPLICATE_HEADER,
+                            format!(
+                                "Header {} is set more than once for server: {}",
+                                name, self.name
+                            ),
+                        ));
+                    }
                 }
             }
         }
@@ -99,31 +689,208 @@ impl ServerConfig {
 }
 
 /// Transport configuration for connecting to MCP servers
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum TransportConfig {
     /// Standard I/O transport (spawns a process)
     #[serde(rename = "stdio")]
     Stdio {
-        /// Command to execute
+        /// Command to execute (left out when `runner` and `package` are set)
+        #[serde(default, skip_serializing_if = "String::is_empty")]
         command: String,
-        /// Command arguments
+        /// Command arguments (after the package's, with `runner`)
+        #[serde(default)]
         args: Vec<String>,
         /// Environment variables (optional)
-        #[serde(default)]
+        #[serde(default, serialize_with = "serialize_sorted")]
         env: HashMap<String, String>,
+        /// Which variables of this process's environment the server inherits
+        #[serde(default, skip_serializing_if = "EnvPolicy::is_inherit")]
+        env_policy: EnvPolicy,
+        /// Run `command` through the system shell (`cmd /C` on Windows,
+        /// `sh -c` elsewhere)
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        shell: bool,
+        /// Launcher that runs `package`, in place of `command`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        runner: Option<Runner>,
+        /// Package started by `runner`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        package: Option<String>,
+        /// Unrecognized fields, preserved on save
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
     /// SSE (Server-Sent Events) transport
     #[serde(rename = "sse")]
     Sse {
         /// URL endpoint
         url: String,
-        /// Headers (optional)
-        #[serde(default)]
-        headers: HashMap<String, String>,
+        /// Headers (optional), each with one value or a list of them
+        #[serde(default, serialize_with = "serialize_sorted")]
+        headers: HashMap<String, HeaderValue>,
+        /// Unrecognized fields, preserved on save
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
 }
 
+/// Launcher for a packaged MCP server
+///
+/// Lets a config say `"runner": "npx", "package": "@scope/server"` instead
+/// of spelling out the command and its flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Runner {
+    /// `npx -y <package>` (npm)
+    Npx,
+    /// `uvx <package>` (PyPI, via uv)
+    Uvx,
+    /// `pipx run <package>` (PyPI)
+    Pipx,
+    /// `docker run -i --rm <package>` (container image)
+    Docker,
+}
+
+impl Runner {
+    /// Program and leading arguments that start `package`
+    pub fn command_line(self, package: &str) -> (&'static str, Vec<String>) {
+        let (program, flags): (&str, &[&str]) = match self {
+            Runner::Npx => ("npx", &["-y"]),
+            Runner::Uvx => ("uvx", &[]),
+            Runner::Pipx => ("pipx", &["run"]),
+            Runner::Docker => ("docker", &["run", "-i", "--rm"]),
+        };
+        let mut args: Vec<String> = flags.iter().map(|flag| flag.to_string()).collect();
+        args.push(package.to_string());
+        (program, args)
+    }
+}
+
+/// Which of this process's environment variables a spawned server inherits
+///
+/// Variables in the server's own `env` map are always set on top. In a
+/// config file this is `"env_policy": "inherit"`, `"clean"` or
+/// `{"allowlist": ["PATH", "HOME"]}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvPolicy {
+    /// The whole environment (the default)
+    #[default]
+    Inherit,
+    /// Nothing, so shell secrets never reach the server
+    Clean,
+    /// Only the named variables
+    Allowlist(Vec<String>),
+}
+
+impl EnvPolicy {
+    /// Whether the server inherits the variable `name`
+    pub fn allows(&self, name: &str) -> bool {
+        match self {
+            EnvPolicy::Inherit => true,
+            EnvPolicy::Clean => false,
+            EnvPolicy::Allowlist(names) => names.iter().any(|n| n == name),
+        }
+    }
+
+    fn is_inherit(&self) -> bool {
+        *self == EnvPolicy::Inherit
+    }
+}
+
+/// Score multiplier for tools whose names match a pattern
+///
+/// The pattern must match the whole tool name, with `*` standing for any
+/// run of characters. In a config file this is
+/// `"tool_boosts": [{"pattern": "legacy_*", "factor": 0.5}]`.
+///
+/// ```
+/// use toolsearch::ToolBoost;
+///
+/// let legacy = ToolBoost::new("legacy_*", 0.5);
+/// assert!(legacy.applies_to("legacy_search"));
+/// assert!(!legacy.applies_to("search"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolBoost {
+    /// Tool name pattern
+    pub pattern: String,
+    /// Multiplier for the scores of matching tools; below 1 demotes them
+    pub factor: f32,
+}
+
+impl ToolBoost {
+    /// Multiply the scores of tools named like `pattern` by `factor`
+    pub fn new(pattern: impl Into<String>, factor: f32) -> Self {
+        Self {
+            pattern: pattern.into(),
+            factor,
+        }
+    }
+
+    /// Whether the boost applies to the tool `tool_name`
+    pub fn applies_to(&self, tool_name: &str) -> bool {
+        glob_matches(&self.pattern, tool_name)
+    }
+}
+
+/// Whether `pattern` matches the whole of `name`, with `*` standing for any
+/// run of characters
+pub(crate) fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcard: the whole name must match
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Score multipliers of one server's matches
+///
+/// A match's relevance score (1 for matches without one) is multiplied by
+/// the server boost and by every tool boost that applies, so organizations
+/// can steer which tools rank first with [`SortOrder::Score`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Boosts {
+    /// Multiplier for every match of the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<f32>,
+    /// Multipliers for tools whose names match a pattern
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolBoost>,
+}
+
+impl Boosts {
+    /// Whether no boost is set
+    pub fn is_empty(&self) -> bool {
+        self.server.is_none() && self.tools.is_empty()
+    }
+
+    /// Combined multiplier for the tool `tool_name`
+    pub fn factor(&self, tool_name: &str) -> f32 {
+        self.tools
+            .iter()
+            .filter(|boost| boost.applies_to(tool_name))
+            .fold(self.server.unwrap_or(1.0), |factor, boost| factor * boost.factor)
+    }
+}
+
+fn is_valid_factor(factor: f32) -> bool {
+    factor.is_finite() && factor >= 0.0
+}
+
 /// Result of a tool search
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolSearchMatch {
@@ -131,13 +898,241 @@ pub struct ToolSearchMatch {
     pub server_name: String,
     /// The tool that matched the search
     pub tool: Tool,
+    /// Initialize info of the server (when requested via
+    /// [`SearchOptions::include_server_info`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_info: Option<Arc<ServerInfo>>,
+    /// Score from [`SearchOptions::custom_matcher`], if one is set, times
+    /// the server's [`Boosts`] and the tool's
+    /// [`popularity`](SearchOptions::popularity); unscored matches of
+    /// boosted searches score 1 before boosting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    /// One-line excerpt of the description around the hit (see
+    /// [`SearchCriteria::snippet`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Categories the server declared for the tool (see [`tool_tags`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Queries of a multi-query search the tool matched (see
+    /// [`SearchBuilder::or_query`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<String>,
+    /// Whether the tool is one of [`SearchOptions::pinned`]
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
 }
 
 impl ToolSearchMatch {
+    /// Create a match for a tool found on a server
+    pub fn new(server_name: impl Into<String>, tool: Tool) -> Self {
+        Self {
+            server_name: server_name.into(),
+            tags: tool_tags(&tool),
+            tool,
+            server_info: None,
+            score: None,
+            snippet: None,
+            queries: Vec::new(),
+            pinned: false,
+        }
+    }
+
     /// Get the tool name as a string
     pub fn tool_name(&self) -> &str {
         self.tool.name.as_ref()
     }
+
+    /// Human-readable labels for the tool's annotations
+    pub fn annotation_labels(&self) -> Vec<String> {
+        annotation_labels(&self.tool)
+    }
+
+    /// Deterministic hash of the tool description (stable across runs)
+    pub fn description_hash(&self) -> u64 {
+        stable_hash(
+            self.tool
+                .description
+                .as_ref()
+                .map(|d| d.as_bytes())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Deterministic hash of the whole tool definition (stable across runs)
+    pub fn content_hash(&self) -> u64 {
+        // serde_json maps are ordered by key, so the serialization is canonical
+        stable_hash(&serde_json::to_vec(&self.tool).unwrap_or_default())
+    }
+
+    /// Compare two matches with a total order for the given sort order
+    ///
+    /// After the primary keys (server and tool name), ties are broken by
+    /// title, description hash, and finally the hash of the whole tool, so
+    /// equal-looking entries always come out in the same order.
+    pub fn total_cmp(&self, other: &Self, order: SortOrder) -> Ordering {
+        let primary = match order {
+            // Priority is not stored on the match; the search pipeline ranks
+            // by priority first and uses this order for ties
+            SortOrder::ServerThenTool | SortOrder::ServerPriority => self
+                .server_name
+                .cmp(&other.server_name)
+                .then_with(|| self.tool_name().cmp(other.tool_name())),
+            SortOrder::ToolThenServer => self
+                .tool_name()
+                .cmp(other.tool_name())
+                .then_with(|| self.server_name.cmp(&other.server_name)),
+            // Highest score first; unscored matches last
+            SortOrder::Score => match (self.score, other.score) {
+                (Some(a), Some(b)) => b.total_cmp(&a),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+            .then_with(|| self.server_name.cmp(&other.server_name))
+            .then_with(|| self.tool_name().cmp(other.tool_name())),
+            SortOrder::None | SortOrder::Custom => return Ordering::Equal,
+        };
+        primary
+            .then_with(|| self.tool.title.cmp(&other.tool.title))
+            .then_with(|| self.description_hash().cmp(&other.description_hash()))
+            .then_with(|| self.content_hash().cmp(&other.content_hash()))
+    }
+}
+
+/// FNV-1a hash, used where hashes must not vary between runs or platforms
+fn stable_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Path of `file` in the user's toolsearch config directory,
+/// `$XDG_CONFIG_HOME/toolsearch` or `~/.config/toolsearch`; `None` if
+/// neither variable is set
+pub(crate) fn user_config_file(file: &str) -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join("toolsearch").join(file))
+}
+
+/// Read and parse a JSON file, with its path in any error
+pub(crate) fn read_json_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, ConfigError> {
+    let display = path.display().to_string();
+    let data = std::fs::read_to_string(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ConfigError::NotFound { path: display.clone() },
+        _ => ConfigError::Read {
+            path: display.clone(),
+            source: e,
+        },
+    })?;
+    serde_json::from_str(&data).map_err(|e| ConfigError::Parse {
+        path: display,
+        line: e.line(),
+        column: e.column(),
+        message: e.to_string(),
+    })
+}
+
+/// Render a tool's annotations as labels: the annotation title followed by
+/// the behavior hints that are enabled (e.g. `read-only`, `destructive`)
+pub fn annotation_labels(tool: &Tool) -> Vec<String> {
+    let Some(ref annotations) = tool.annotations else {
+        return Vec::new();
+    };
+
+    let mut labels = Vec::new();
+    if let Some(ref title) = annotations.title {
+        labels.push(title.clone());
+    }
+    let hints = [
+        (annotations.read_only_hint, "read-only"),
+        (annotations.destructive_hint, "destructive"),
+        (annotations.idempotent_hint, "idempotent"),
+        (annotations.open_world_hint, "open-world"),
+    ];
+    for (hint, label) in hints {
+        if hint == Some(true) {
+            labels.push(label.to_string());
+        }
+    }
+    labels
+}
+
+/// Input schema keywords servers can declare tool categories with
+///
+/// MCP has no field for categories, and the `_meta` of tools doesn't
+/// survive parsing, but JSON Schema allows extension keywords.
+pub const TAG_KEYWORDS: [&str; 2] = ["x-tags", "x-category"];
+
+/// Categories a tool declares through the [`TAG_KEYWORDS`] of its input
+/// schema, normalized: each keyword may hold a string (comma-separated) or
+/// an array of strings, and tags are trimmed, case-folded, and deduplicated
+///
+/// ```
+/// use serde_json::json;
+/// use toolsearch::testing::ToolFixture;
+/// use toolsearch::tool_tags;
+///
+/// let tool = ToolFixture::new("read_file")
+///     .schema(json!({ "type": "object", "x-tags": ["Files", "IO"], "x-category": "files, local" }))
+///     .build();
+/// assert_eq!(tool_tags(&tool), ["files", "io", "local"]);
+/// ```
+pub fn tool_tags(tool: &Tool) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for keyword in TAG_KEYWORDS {
+        let declared = match tool.input_schema.get(keyword) {
+            Some(Value::String(text)) => text.split(',').collect(),
+            Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        for tag in declared.into_iter().map(normalize_tag) {
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+    }
+    tags
+}
+
+/// A tag as compared by [`tool_tags`] and [`SearchCriteria::with_tags`]
+fn normalize_tag(tag: &str) -> String {
+    casefold::fold(tag.trim()).into_owned()
+}
+
+/// Whether `word` occurs in `haystack` as a whole word
+#[cfg(feature = "regex-mode")]
+fn contains_word(haystack: &str, word: &str) -> bool {
+    let pattern = format!(r"\b{}\b", regex::escape(word));
+    match Regex::new(&pattern) {
+        Ok(regex) => regex.is_match(haystack),
+        Err(_) => haystack.contains(word),
+    }
+}
+
+/// Whether `word` occurs in `haystack` as a whole word
+#[cfg(not(feature = "regex-mode"))]
+fn contains_word(haystack: &str, word: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    haystack.match_indices(word).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + word.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
 }
 
 /// Sort order for search results
@@ -149,9 +1144,81 @@ pub enum SortOrder {
     ToolThenServer,
     /// No sorting (keep original order)
     None,
+    /// Sort by server priority (highest first), then server name, then tool name
+    ServerPriority,
+    /// Sort with the comparator set via [`SearchOptions::sort_with`]
+    Custom,
+    /// Sort by score (highest first), then server name, then tool name; see
+    /// [`ToolSearchMatch::score`]
+    Score,
+}
+
+/// Which matches `max_per_server` keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Truncation {
+    /// Each server's best matches in sort order, so a limit keeps the top
+    /// matches by score under [`SortOrder::Score`]; every page is read
+    #[default]
+    Ranked,
+    /// The first matches in the order the server lists its tools; paging
+    /// stops as soon as a server has that many
+    FirstListed,
+}
+
+/// Which matches are dropped as duplicates of a better-ranked match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dedupe {
+    /// Keep every match
+    #[default]
+    None,
+    /// Matches whose tool definition is identical to one ranked before it,
+    /// e.g. from a server configured twice
+    Definition,
+    /// Matches whose tool name equals that of one ranked before it
+    Name,
+}
+
+/// Signature of a custom result comparison function
+type CompareFn = dyn Fn(&ToolSearchMatch, &ToolSearchMatch) -> Ordering + Send + Sync;
+
+/// Comparator used by [`SortOrder::Custom`]
+#[derive(Clone)]
+pub struct SortComparator(Arc<CompareFn>);
+
+impl SortComparator {
+    /// Wrap a comparison function
+    pub fn new(
+        compare: impl Fn(&ToolSearchMatch, &ToolSearchMatch) -> Ordering + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(compare))
+    }
+
+    /// Compare two matches
+    pub fn compare(&self, a: &ToolSearchMatch, b: &ToolSearchMatch) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+impl std::fmt::Debug for SortComparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SortComparator(..)")
+    }
 }
 
 /// Options for search operations
+///
+/// Matches go through the same stages in a fixed order:
+///
+/// 1. Matching and scoring: the criteria, the custom matcher and its
+///    `min_score`, then the server's [`Boosts`]
+/// 2. Sorting by `sort_order`, then moving `pinned` tools first
+/// 3. Dropping duplicates (`dedupe`), keeping the best-ranked match
+/// 4. Limits: `max_per_server` (see `truncation`), then `max_results`
+///
+/// Limits therefore keep the top matches in sort order. Only
+/// [`Truncation::FirstListed`] and [`SortOrder::None`] apply limits while
+/// listing, which lets servers stop paging early, and only when nothing is
+/// pinned.
 #[derive(Debug, Clone)]
 pub struct SearchOptions {
     /// Timeout for server connections and queries
@@ -162,10 +1229,103 @@ pub struct SearchOptions {
     pub continue_on_error: bool,
     /// Maximum number of results to return
     pub max_results: Option<usize>,
+    /// Comparator used when `sort_order` is [`SortOrder::Custom`]
+    pub comparator: Option<SortComparator>,
+    /// Attach each server's initialize info to its matches
+    pub include_server_info: bool,
+    /// Receives telemetry events while the search runs
+    pub observer: Option<Arc<dyn Observer>>,
+    /// Wall-clock budget for the whole search; when it expires, matches from
+    /// the sources that answered so far are returned and the report is
+    /// marked truncated (a budget too large to add to the current time,
+    /// like `Duration::MAX`, never expires)
+    pub deadline: Option<Duration>,
+    /// Runs on every tool that passes the criteria, rejecting or scoring it
+    pub custom_matcher: Option<Arc<dyn Matcher>>,
+    /// Drop scored matches below this score; unscored matches are kept
+    pub min_score: Option<f32>,
+    /// Stop listing a server's tools after this many pages (default 1000)
+    pub max_pages: Option<usize>,
+    /// Stop listing a server's tools once it has returned this many
+    pub max_tools: Option<usize>,
+    /// Pages requested at once from a server whose cursors are offsets
+    /// (default 4; 1 lists one page at a time)
+    pub parallel_pages: usize,
+    /// Keep at most this many matches from each server: the best ranked,
+    /// or the first listed (see `truncation`)
+    pub max_per_server: Option<usize>,
+    /// Which matches `max_per_server` keeps
+    pub truncation: Truncation,
+    /// Which matches are dropped as duplicates
+    pub dedupe: Dedupe,
+    /// Environment variables set for every stdio server this search
+    /// starts, over the server's own `env`; a server kept running between
+    /// searches (see [`PooledServer`](pool::PooledServer)) is restarted
+    /// for a search with other overrides than the one that started it
+    pub env_overrides: HashMap<String, String>,
+    /// Record the MCP session with each stdio server this search starts in
+    /// this directory, one file per server (see [`record`])
+    pub record_dir: Option<PathBuf>,
+    /// Warn about servers that answer but use more than this fraction of
+    /// their timeout (default 0.8), suggesting a timeout with headroom
+    pub slow_server_fraction: Option<f32>,
+    /// Clear the fields of each match this doesn't keep, once the matches
+    /// are final, for lighter payloads
+    pub projection: Option<Projection>,
+    /// Schema keys whose values are blanked out of each match once the
+    /// matches are final (see [`redact`])
+    pub redaction: Redaction,
+    /// Usage weights blended into match scores (see [`popularity`])
+    pub popularity: Option<Popularity>,
+    /// Tools and servers no search returns (see [`block`])
+    pub blocklist: Blocklist,
+    /// Tools that sort before all others and are flagged
+    /// [`pinned`](ToolSearchMatch::pinned), by name or `server/tool` (see
+    /// [`pins`])
+    pub pinned: Vec<String>,
+    /// Fail the search with [`ToolSearchError::TooFewResponded`] when fewer
+    /// than this fraction of the servers respond (see [`Health`]), rather
+    /// than searching a partial catalog
+    pub min_response_rate: Option<f32>,
+}
+
+impl SearchOptions {
+    /// Sort results with a custom comparator
+    ///
+    /// Ties left by the comparator are broken with the default server-then-tool
+    /// total order, so results stay deterministic.
+    ///
+    /// ```
+    /// use toolsearch::SearchOptions;
+    ///
+    /// // Rank first-party servers above everything else
+    /// let options = SearchOptions::default()
+    ///     .sort_with(|a, b| (b.server_name == "internal").cmp(&(a.server_name == "internal")));
+    /// ```
+    pub fn sort_with(
+        mut self,
+        compare: impl Fn(&ToolSearchMatch, &ToolSearchMatch) -> Ordering + Send + Sync + 'static,
+    ) -> Self {
+        self.sort_order = SortOrder::Custom;
+        self.comparator = Some(SortComparator::new(compare));
+        self
+    }
+
+    /// Report search events to an observer
+    pub fn with_observer(mut self, observer: impl Observer + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Filter and score matches with a custom matcher
+    pub fn with_custom_matcher(mut self, matcher: impl Matcher + 'static) -> Self {
+        self.custom_matcher = Some(Arc::new(matcher));
+        self
+    }
 }
 
 /// Search mode for pattern matching
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SearchMode {
     /// Simple substring matching (case-insensitive by default)
     Substring,
@@ -188,6 +1348,10 @@ pub struct SearchFields {
     pub description: bool,
     /// Search in input schema (property names and descriptions)
     pub input_schema: bool,
+    /// Search in output schema (property names and descriptions)
+    pub output_schema: bool,
+    /// Search in tool annotations (title and behavior hints)
+    pub annotations: bool,
 }
 
 impl Default for SearchFields {
@@ -197,10 +1361,26 @@ impl Default for SearchFields {
             title: true,
             description: true,
             input_schema: false,
+            output_schema: false,
+            annotations: false,
         }
     }
 }
 
+/// Which parts of a JSON schema are searched when schema fields are enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaScope {
+    /// Every string in the schema, flattened (names, descriptions, enums, defaults)
+    #[default]
+    Flattened,
+    /// Property names only (including nested properties)
+    PropertyNames,
+    /// Property descriptions only (including nested properties)
+    PropertyDescriptions,
+    /// Names of required properties only (including nested objects)
+    RequiredProperties,
+}
+
 /// Search criteria for filtering tools
 #[derive(Debug, Clone)]
 pub struct SearchCriteria {
@@ -212,15 +1392,42 @@ pub struct SearchCriteria {
     pub mode: SearchMode,
     /// Fields to search in
     pub fields: SearchFields,
+    /// Which parts of input/output schemas are searched
+    pub schema_scope: SchemaScope,
     /// Case sensitive search
     pub case_sensitive: bool,
     /// Minimum description length
     pub min_description_length: Option<usize>,
+    /// Maximum description length
+    pub max_description_length: Option<usize>,
+    /// Only match tools that show an example (see [`quality::has_example`])
+    pub require_example: bool,
+    /// Only match tools described in this language, an ISO 639-1 code
+    /// such as `en` (see [`quality::detect_language`])
+    pub language: Option<String>,
     /// Keywords for keyword matching mode
     pub keywords: Vec<String>,
+    /// Tags the tool must all carry (see [`tool_tags`]), in addition to
+    /// matching the rest of the criteria
+    pub tags: Vec<String>,
+    /// Tool names that match whatever the query, such as the names the
+    /// query is an [alias](crate::alias) for (tags still apply)
+    pub aliases: Vec<String>,
     /// Compiled regex pattern (cached for performance)
+    #[cfg(feature = "regex-mode")]
     #[allow(clippy::type_complexity)]
     regex: Option<Result<Regex, regex::Error>>,
+    /// Set for criteria built with [`and`](Self::and), [`or`](Self::or) or
+    /// [`not`](Self::not), which match by combining other criteria
+    combinator: Option<Combinator>,
+}
+
+/// How a composed [`SearchCriteria`] combines other criteria
+#[derive(Debug, Clone)]
+enum Combinator {
+    And(Box<SearchCriteria>, Box<SearchCriteria>),
+    Or(Box<SearchCriteria>, Box<SearchCriteria>),
+    Not(Box<SearchCriteria>),
 }
 
 impl SearchCriteria {
@@ -228,56 +1435,37 @@ impl SearchCriteria {
     pub fn with_query(query: String) -> Self {
         Self {
             query: Some(query),
-            name: None,
-            mode: SearchMode::Substring,
-            fields: SearchFields::default(),
-            case_sensitive: false,
-            min_description_length: None,
-            keywords: vec![],
-            regex: None,
+            ..Self::match_all()
         }
     }
 
     /// Create a search criteria for exact name match
     pub fn with_name(name: String) -> Self {
         Self {
-            query: None,
             name: Some(name),
-            mode: SearchMode::Substring,
-            fields: SearchFields::default(),
-            case_sensitive: false,
-            min_description_length: None,
-            keywords: vec![],
-            regex: None,
+            ..Self::match_all()
         }
     }
 
     /// Create a search criteria with regex pattern
+    ///
+    /// Without the `regex-mode` feature, regex criteria match nothing.
     pub fn with_regex(pattern: String) -> Self {
-        let regex = Regex::new(&pattern);
         Self {
+            #[cfg(feature = "regex-mode")]
+            regex: Some(Regex::new(&pattern)),
             query: Some(pattern),
-            name: None,
             mode: SearchMode::Regex,
-            fields: SearchFields::default(),
-            case_sensitive: false,
-            min_description_length: None,
-            keywords: vec![],
-            regex: Some(regex),
+            ..Self::match_all()
         }
     }
 
     /// Create a search criteria with keywords (all must match)
     pub fn with_keywords(keywords: Vec<String>) -> Self {
         Self {
-            query: None,
-            name: None,
             mode: SearchMode::Keywords,
-            fields: SearchFields::default(),
-            case_sensitive: false,
-            min_description_length: None,
             keywords,
-            regex: None,
+            ..Self::match_all()
         }
     }
 
@@ -288,23 +1476,79 @@ impl SearchCriteria {
             name: None,
             mode: SearchMode::Substring,
             fields: SearchFields::default(),
+            schema_scope: SchemaScope::Flattened,
             case_sensitive: false,
             min_description_length: None,
+            max_description_length: None,
+            require_example: false,
+            language: None,
             keywords: vec![],
+            tags: vec![],
+            aliases: vec![],
+            #[cfg(feature = "regex-mode")]
             regex: None,
+            combinator: None,
         }
     }
 
-    /// Set search mode
-    pub fn with_mode(mut self, mode: SearchMode) -> Self {
-        self.mode = mode;
-        // Recompile regex if needed
-        if mode == SearchMode::Regex {
-            if let Some(ref query) = self.query {
-                self.regex = Some(Regex::new(query));
-            }
-        }
-        self
+    /// Criteria matching tools that match both `self` and `other`
+    ///
+    /// ```
+    /// use toolsearch::{SearchCriteria, SearchFields};
+    ///
+    /// let annotations_only = SearchFields {
+    ///     name: false,
+    ///     title: false,
+    ///     description: false,
+    ///     input_schema: false,
+    ///     annotations: true,
+    ///     output_schema: false,
+    /// };
+    /// // Tools about files that are not marked destructive
+    /// let safe_file_tools = SearchCriteria::with_query("file".to_string()).and(
+    ///     SearchCriteria::with_query("destructive".to_string())
+    ///         .with_fields(annotations_only)
+    ///         .not(),
+    /// );
+    /// ```
+    pub fn and(self, other: SearchCriteria) -> Self {
+        Self::combined(Combinator::And(Box::new(self), Box::new(other)))
+    }
+
+    /// Criteria matching tools that match `self`, `other`, or both
+    pub fn or(self, other: SearchCriteria) -> Self {
+        Self::combined(Combinator::Or(Box::new(self), Box::new(other)))
+    }
+
+    /// Criteria matching tools that `self` does not match
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Self::combined(Combinator::Not(Box::new(self)))
+    }
+
+    /// Whether these criteria combine others with `and`, `or` or `not`
+    pub fn is_composed(&self) -> bool {
+        self.combinator.is_some()
+    }
+
+    fn combined(combinator: Combinator) -> Self {
+        Self {
+            combinator: Some(combinator),
+            ..Self::match_all()
+        }
+    }
+
+    /// Set search mode
+    pub fn with_mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        // Recompile regex if needed
+        #[cfg(feature = "regex-mode")]
+        if mode == SearchMode::Regex
+            && let Some(ref query) = self.query
+        {
+            self.regex = Some(Regex::new(query));
+        }
+        self
     }
 
     /// Set fields to search in
@@ -313,6 +1557,79 @@ impl SearchCriteria {
         self
     }
 
+    /// Set which parts of schemas are searched
+    pub fn with_schema_scope(mut self, scope: SchemaScope) -> Self {
+        self.schema_scope = scope;
+        self
+    }
+
+    /// Only match tools carrying every one of `tags` (compared as
+    /// [`tool_tags`] normalizes them)
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.tags = tags
+            .into_iter()
+            .map(|tag| normalize_tag(tag.as_ref()))
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        self
+    }
+
+    /// Only match tools whose description is at least `len` bytes long
+    pub fn with_min_description_length(mut self, len: usize) -> Self {
+        self.min_description_length = Some(len);
+        self
+    }
+
+    /// Only match tools whose description is at most `len` bytes long
+    pub fn with_max_description_length(mut self, len: usize) -> Self {
+        self.max_description_length = Some(len);
+        self
+    }
+
+    /// Only match tools that show an example of their use
+    pub fn with_example_required(mut self) -> Self {
+        self.require_example = true;
+        self
+    }
+
+    /// Only match tools described in `language`, an ISO 639-1 code such
+    /// as `en`; a region, as in `en-US`, is ignored
+    ///
+    /// ```
+    /// use toolsearch::testing::ToolFixture;
+    /// use toolsearch::SearchCriteria;
+    ///
+    /// let tool = ToolFixture::new("lire_fichier")
+    ///     .description("Lit le contenu d'un fichier sur le disque")
+    ///     .build();
+    /// assert!(SearchCriteria::match_all().language("fr").matches(&tool));
+    /// assert!(!SearchCriteria::match_all().language("en").matches(&tool));
+    /// ```
+    pub fn language(mut self, language: impl AsRef<str>) -> Self {
+        self.language = Some(quality::normalize_language(language.as_ref()));
+        self
+    }
+
+    /// Only match tools described in English; short for
+    /// [`language("en")`](Self::language)
+    pub fn with_english_only(self) -> Self {
+        self.language("en")
+    }
+
+    /// Also match tools named any of `names` (ignoring case)
+    pub fn with_aliases<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.aliases = names.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// Set case sensitivity
     pub fn case_sensitive(mut self, sensitive: bool) -> Self {
         self.case_sensitive = sensitive;
@@ -325,12 +1642,12 @@ impl SearchCriteria {
         
         if let Some(obj) = schema.as_object() {
             // Extract property names
-            if let Some(properties) = obj.get("properties") {
-                if let Some(props_obj) = properties.as_object() {
-                    for key in props_obj.keys() {
-                        text.push_str(key);
-                        text.push(' ');
-                    }
+            if let Some(properties) = obj.get("properties")
+                && let Some(props_obj) = properties.as_object()
+            {
+                for key in props_obj.keys() {
+                    text.push_str(key);
+                    text.push(' ');
                 }
             }
             
@@ -354,93 +1671,262 @@ impl SearchCriteria {
         text
     }
 
-    /// Check if text matches the query based on search mode
-    fn text_matches(&self, text: &str) -> bool {
-        let search_text = if self.case_sensitive {
-            text.to_string()
-        } else {
-            text.to_lowercase()
+    /// Extract text from a schema according to the configured scope
+    fn schema_text(&self, schema: &Value) -> String {
+        match self.schema_scope {
+            SchemaScope::Flattened => Self::extract_schema_text(schema),
+            scope => {
+                let mut text = String::new();
+                Self::collect_schema_properties(schema, scope, &mut text);
+                text
+            }
+        }
+    }
+
+    /// Walk schema properties (recursing into nested objects and array items)
+    /// collecting the parts selected by a structured scope
+    fn collect_schema_properties(schema: &Value, scope: SchemaScope, text: &mut String) {
+        let Some(obj) = schema.as_object() else {
+            return;
         };
 
-        match self.mode {
-            SearchMode::Substring => {
-                let query = if self.case_sensitive {
-                    self.query.as_ref().unwrap().clone()
-                } else {
-                    self.query.as_ref().unwrap().to_lowercase()
-                };
-                search_text.contains(&query)
+        if scope == SchemaScope::RequiredProperties
+            && let Some(required) = obj.get("required").and_then(Value::as_array)
+        {
+            for name in required.iter().filter_map(Value::as_str) {
+                text.push_str(name);
+                text.push(' ');
             }
-            SearchMode::Regex => {
-                if let Some(ref regex_result) = self.regex {
-                    match regex_result {
-                        Ok(regex) => regex.is_match(text),
-                        Err(_) => false,
+        }
+
+        if let Some(props) = obj.get("properties").and_then(Value::as_object) {
+            for (name, prop) in props {
+                match scope {
+                    SchemaScope::PropertyNames => {
+                        text.push_str(name);
+                        text.push(' ');
                     }
-                } else if let Some(ref query) = self.query {
-                    // Fallback: compile regex on the fly
-                    match Regex::new(query) {
-                        Ok(regex) => regex.is_match(text),
-                        Err(_) => false,
+                    SchemaScope::PropertyDescriptions => {
+                        if let Some(desc) = prop.get("description").and_then(Value::as_str) {
+                            text.push_str(desc);
+                            text.push(' ');
+                        }
                     }
-                } else {
-                    false
+                    _ => {}
                 }
+                Self::collect_schema_properties(prop, scope, text);
             }
+        }
+
+        if let Some(items) = obj.get("items") {
+            Self::collect_schema_properties(items, scope, text);
+        }
+    }
+
+    /// Text as compared by this criteria: case-folded unless case sensitive
+    fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.case_sensitive {
+            Cow::Borrowed(text)
+        } else {
+            casefold::fold(text)
+        }
+    }
+
+    /// Check if text matches the query based on search mode
+    fn text_matches(&self, text: &str) -> bool {
+        let query = self.query.as_deref().unwrap_or_default();
+
+        match self.mode {
+            SearchMode::Substring => self.normalize(text).contains(&*self.normalize(query)),
+            SearchMode::Regex => self.regex_matches(text),
             SearchMode::Keywords => {
-                let keywords = if self.case_sensitive {
-                    self.keywords.clone()
-                } else {
-                    self.keywords.iter().map(|k| k.to_lowercase()).collect()
-                };
-                keywords.iter().all(|keyword| search_text.contains(keyword))
+                let text = self.normalize(text);
+                self.keywords
+                    .iter()
+                    .all(|keyword| text.contains(&*self.normalize(keyword)))
             }
             SearchMode::WordBoundary => {
-                let query = if self.case_sensitive {
-                    self.query.as_ref().unwrap().clone()
-                } else {
-                    self.query.as_ref().unwrap().to_lowercase()
-                };
-                // Simple word boundary check
-                let pattern = format!(r"\b{}\b", regex::escape(&query));
-                match Regex::new(&pattern) {
-                    Ok(regex) => {
-                        if self.case_sensitive {
-                            regex.is_match(text)
-                        } else {
-                            regex.is_match(&search_text)
-                        }
-                    }
-                    Err(_) => search_text.contains(&query),
-                }
+                contains_word(&self.normalize(text), &self.normalize(query))
+            }
+        }
+    }
+
+    /// Match text against the regex query
+    #[cfg(feature = "regex-mode")]
+    fn regex_matches(&self, text: &str) -> bool {
+        if let Some(ref regex_result) = self.regex {
+            match regex_result {
+                Ok(regex) => regex.is_match(text),
+                Err(_) => false,
+            }
+        } else if let Some(ref query) = self.query {
+            // Fallback: compile regex on the fly
+            match Regex::new(query) {
+                Ok(regex) => regex.is_match(text),
+                Err(_) => false,
+            }
+        } else {
+            false
+        }
+    }
+
+    /// Regex search needs the `regex-mode` feature; without it nothing matches
+    #[cfg(not(feature = "regex-mode"))]
+    fn regex_matches(&self, _text: &str) -> bool {
+        false
+    }
+
+    /// Byte range of the first regex match in `text`
+    #[cfg(feature = "regex-mode")]
+    fn regex_hit(&self, text: &str) -> Option<Range<usize>> {
+        let found = match self.regex {
+            Some(Ok(ref regex)) => regex.find(text),
+            Some(Err(_)) => None,
+            None => Regex::new(self.query.as_deref()?).ok()?.find(text),
+        };
+        found.map(|m| m.range())
+    }
+
+    #[cfg(not(feature = "regex-mode"))]
+    fn regex_hit(&self, _text: &str) -> Option<Range<usize>> {
+        None
+    }
+
+    /// Byte ranges of `needle` in `text`, honoring case sensitivity
+    fn find_all(&self, text: &str, needle: &str) -> Vec<Range<usize>> {
+        if self.case_sensitive {
+            text.match_indices(needle)
+                .map(|(start, hit)| start..start + hit.len())
+                .collect()
+        } else {
+            casefold::find_all(text, needle)
+        }
+    }
+
+    /// Byte range of the first hit of the query or keywords in `text`
+    fn hit(&self, text: &str) -> Option<Range<usize>> {
+        if let Some(ref combinator) = self.combinator {
+            return match combinator {
+                Combinator::And(a, b) | Combinator::Or(a, b) => [a.hit(text), b.hit(text)]
+                    .into_iter()
+                    .flatten()
+                    .min_by_key(|hit| hit.start),
+                Combinator::Not(_) => None,
+            };
+        }
+        if self.name.is_some() {
+            return None;
+        }
+        match self.mode {
+            SearchMode::Substring => self.find_all(text, self.query.as_deref()?).into_iter().next(),
+            SearchMode::Regex => self.regex_hit(text),
+            SearchMode::Keywords => self
+                .keywords
+                .iter()
+                .filter_map(|keyword| self.find_all(text, keyword).into_iter().next())
+                .min_by_key(|hit| hit.start),
+            SearchMode::WordBoundary => {
+                let is_word = |c: char| c.is_alphanumeric() || c == '_';
+                self.find_all(text, self.query.as_deref()?)
+                    .into_iter()
+                    .find(|hit| {
+                        !text[..hit.start].chars().next_back().is_some_and(is_word)
+                            && !text[hit.end..].chars().next().is_some_and(is_word)
+                    })
             }
         }
     }
 
+    /// One-line excerpt of the tool's description around the first hit
+    ///
+    /// When the hit is elsewhere (say, in the name) the excerpt is the start
+    /// of the description. `None` if the tool has no description.
+    ///
+    /// ```
+    /// use toolsearch::testing::ToolFixture;
+    /// use toolsearch::SearchCriteria;
+    ///
+    /// let tool = ToolFixture::new("fetch")
+    ///     .description(
+    ///         "Fetches a URL over HTTP and returns the body. Redirects are \
+    ///          followed, and responses larger than the configured limit are \
+    ///          streamed to a temporary file on disk instead.",
+    ///     )
+    ///     .build();
+    /// let snippet = SearchCriteria::with_query("temporary".to_string()).snippet(&tool);
+    /// assert_eq!(
+    ///     snippet.as_deref(),
+    ///     Some("...limit are streamed to a temporary file on disk instead."),
+    /// );
+    /// ```
+    pub fn snippet(&self, tool: &Tool) -> Option<String> {
+        let description = tool.description.as_deref()?;
+        Some(snippet::excerpt(description, self.hit(description)))
+    }
+
+    /// Whether the tool's documentation passes the description filters
+    /// (length, example, language), which apply whatever the query
+    fn meets_description_quality(&self, tool: &Tool) -> bool {
+        let description = tool.description.as_deref();
+        let len = description.map_or(0, str::len);
+        if let Some(min_len) = self.min_description_length
+            && (description.is_none() || len < min_len)
+        {
+            return false;
+        }
+        if self.max_description_length.is_some_and(|max_len| len > max_len) {
+            return false;
+        }
+        if self.require_example && !quality::has_example(tool) {
+            return false;
+        }
+        match &self.language {
+            Some(language) => description
+                .and_then(quality::detect_language)
+                .is_some_and(|detected| detected == language),
+            None => true,
+        }
+    }
+
     /// Check if a tool matches the search criteria
     pub fn matches(&self, tool: &Tool) -> bool {
+        if !self.tags.is_empty() {
+            let declared = tool_tags(tool);
+            if !self.tags.iter().all(|tag| declared.contains(tag)) {
+                return false;
+            }
+        }
+
+        if !self.meets_description_quality(tool) {
+            return false;
+        }
+
+        if self
+            .aliases
+            .iter()
+            .any(|name| casefold::eq_ignore_case(&tool.name, name))
+        {
+            return true;
+        }
+
+        if let Some(ref combinator) = self.combinator {
+            return match combinator {
+                Combinator::And(a, b) => a.matches(tool) && b.matches(tool),
+                Combinator::Or(a, b) => a.matches(tool) || b.matches(tool),
+                Combinator::Not(criteria) => !criteria.matches(tool),
+            };
+        }
+
         // Exact name match takes precedence
         if let Some(ref name) = self.name {
             let tool_name: &str = tool.name.as_ref();
             return if self.case_sensitive {
                 tool_name == name
             } else {
-                tool_name.eq_ignore_ascii_case(name)
+                casefold::eq_ignore_case(tool_name, name)
             };
         }
 
-        // Check minimum description length
-        if let Some(min_len) = self.min_description_length {
-            if tool
-                .description
-                .as_ref()
-                .map(|d| d.len() < min_len)
-                .unwrap_or(true)
-            {
-                return false;
-            }
-        }
-
         // If no query or keywords, match all (unless we have other filters)
         if self.query.is_none() && self.keywords.is_empty() {
             return true;
@@ -453,28 +1939,42 @@ impl SearchCriteria {
             searchable_texts.push(("name", tool.name.as_ref().to_string()));
         }
 
-        if self.fields.title {
-            if let Some(ref title) = tool.title {
-                searchable_texts.push(("title", title.to_string()));
-            }
+        if self.fields.title
+            && let Some(ref title) = tool.title
+        {
+            searchable_texts.push(("title", title.to_string()));
         }
 
-        if self.fields.description {
-            if let Some(ref desc) = tool.description {
-                searchable_texts.push(("description", desc.as_ref().to_string()));
-            }
+        if self.fields.description
+            && let Some(ref desc) = tool.description
+        {
+            searchable_texts.push(("description", desc.as_ref().to_string()));
         }
 
         if self.fields.input_schema {
             // Convert Arc<Map> to Value for extraction
             let schema_value: Value = serde_json::to_value(&*tool.input_schema)
                 .unwrap_or(Value::Object(serde_json::Map::new()));
-            let schema_text = Self::extract_schema_text(&schema_value);
+            let schema_text = self.schema_text(&schema_value);
             if !schema_text.is_empty() {
                 searchable_texts.push(("input_schema", schema_text));
             }
         }
 
+        if self.fields.annotations && tool.annotations.is_some() {
+            searchable_texts.push(("annotations", annotation_labels(tool).join(" ")));
+        }
+
+        if self.fields.output_schema
+            && let Some(ref output_schema) = tool.output_schema
+        {
+            let schema_value = Value::Object((**output_schema).clone());
+            let schema_text = self.schema_text(&schema_value);
+            if !schema_text.is_empty() {
+                searchable_texts.push(("output_schema", schema_text));
+            }
+        }
+
         // Check if any field matches
         for (_field_name, text) in searchable_texts {
             if self.text_matches(&text) {
@@ -486,42 +1986,143 @@ impl SearchCriteria {
     }
 }
 
-/// Connect to an MCP server using the provided transport configuration
-/// Returns a RunningService that can be used to interact with the server
+/// A live connection to an MCP server
+///
+/// For stdio servers it also owns the server's process tree, which is
+/// stopped when the connection is dropped, including when a timeout
+/// abandons it.
+pub(crate) struct ServerConnection {
+    service: rmcp::service::RunningService<rmcp::RoleClient, ClientInfo>,
+    /// Malformed tools the server sent, not reported yet
+    pub(crate) malformed: protocol::MalformedTools,
+    #[cfg(feature = "stdio")]
+    _process: Option<spawn::ProcessTree>,
+}
+
+impl std::ops::Deref for ServerConnection {
+    type Target = rmcp::service::RunningService<rmcp::RoleClient, ClientInfo>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.service
+    }
+}
+
+/// Connect to an MCP server using the provided transport configuration,
+/// recording the session of a stdio server in `record_dir` if set (see
+/// [`record`])
 async fn connect_to_server(
     config: &ServerConfig,
-) -> Result<rmcp::service::RunningService<rmcp::RoleClient, ()>, ToolSearchError> {
+    #[cfg_attr(not(feature = "stdio"), allow(unused_variables))] record_dir: Option<&Path>,
+) -> Result<ServerConnection, ToolSearchError> {
+    #[cfg(feature = "chaos")]
+    if let Some(faults) = &config.faults {
+        faults.connect(&config.name)?;
+    }
     match &config.transport {
-        TransportConfig::Stdio { command, args, env } => {
-            let mut cmd = Command::new(command);
-            cmd.args(args);
-            cmd.stdin(Stdio::piped());
-            cmd.stdout(Stdio::piped());
-            cmd.stderr(Stdio::piped());
-            cmd.envs(env);
-
-            let mut child = cmd
-                .spawn()
-                .with_context(|| format!("Failed to spawn command: {}", command))?;
-
-            // Get stdin/stdout from child process
-            // Note: tuple order is (read, write) = (stdout, stdin)
-            let stdin = child.stdin.take().ok_or_else(|| {
-                ToolSearchError::Connection("Failed to get stdin from child process".to_string())
-            })?;
-            let stdout = child.stdout.take().ok_or_else(|| {
-                ToolSearchError::Connection("Failed to get stdout from child process".to_string())
-            })?;
+        #[cfg(feature = "stdio")]
+        TransportConfig::Stdio {
+            env,
+            env_policy,
+            shell,
+            ..
+        } => {
+            let (command, args) = config.command_line().unwrap_or_default();
+            let mut handshake_error = None;
+            // A server that rejects the handshake is restarted for the next
+            // (older) version; failing to start it at all is not retried
+            for version in config.protocol_versions() {
+                tracing::debug!(
+                    server = %config.name,
+                    %command,
+                    ?args,
+                    %version,
+                    "starting server"
+                );
+                let mut cmd = spawn::command(&command, &args, *shell);
+                cmd.stdin(Stdio::piped());
+                cmd.stdout(Stdio::piped());
+                cmd.stderr(Stdio::piped());
+                if *env_policy != EnvPolicy::Inherit {
+                    cmd.env_clear();
+                    cmd.envs(std::env::vars_os().filter(|(name, _)| {
+                        name.to_str().is_some_and(|name| env_policy.allows(name))
+                    }));
+                }
+                cmd.envs(env);
+
+                // Guard the process right away, so a timeout during the
+                // handshake stops it too
+                let mut process = spawn::ProcessTree::new(
+                    cmd.spawn()
+                        .with_context(|| format!("Failed to spawn command: {}", command))?,
+                );
+
+                // Note: tuple order is (read, write) = (stdout, stdin)
+                let (stdout, stdin) = process.take_stdio().ok_or_else(|| {
+                    ToolSearchError::Connection("Failed to get stdio of child process".to_string())
+                })?;
+                process.log_stderr(&config.name);
+                #[cfg(feature = "chaos")]
+                let stdout: Box<dyn tokio::io::AsyncRead + Send + Unpin> = match &config.faults {
+                    Some(faults) => Box::new(faults.inject(stdout)),
+                    None => Box::new(stdout),
+                };
+                let recorder = record_dir
+                    .map(|dir| record::Recorder::create(dir, &config.name))
+                    .transpose()?;
+                let malformed = protocol::MalformedTools::default();
+                let stdout =
+                    protocol::filter_tool_lists(stdout, malformed.clone(), recorder.clone());
+                let stdin: Box<dyn tokio::io::AsyncWrite + Send + Unpin> = match recorder {
+                    Some(recorder) => Box::new(recorder.relay_sent(stdin)),
+                    None => Box::new(stdin),
+                };
 
-            // Create a basic client service and serve it with stdio transport
-            // The unit type () implements Service<RoleClient> as a basic client
-            // Tuple order: (read, write) = (stdout, stdin)
-            let service = ().serve((stdout, stdin))
-                .await
-                .map_err(|e| ToolSearchError::Connection(format!("Failed to initialize client: {}", e)))?;
-            Ok(service)
+                // ClientInfo implements Service<RoleClient> as a basic client
+                // that advertises the given protocol version
+                let client = ClientInfo {
+                    protocol_version: version.clone(),
+                    ..Default::default()
+                };
+                match client.serve((stdout, stdin)).await {
+                    Ok(service) => {
+                        if let Some(info) = service.peer_info() {
+                            tracing::info!(
+                                server = %config.name,
+                                implementation = %info.server_info.name,
+                                protocol_version = %info.protocol_version,
+                                tools = info.capabilities.tools.is_some(),
+                                "handshake complete"
+                            );
+                        }
+                        return Ok(ServerConnection {
+                            service,
+                            malformed,
+                            _process: Some(process),
+                        });
+                    }
+                    Err(e) => {
+                        tracing::info!(
+                            server = %config.name,
+                            %version,
+                            error = %e,
+                            "handshake failed"
+                        );
+                        handshake_error = Some(e);
+                    }
+                }
+            }
+            Err(ToolSearchError::Connection(format!(
+                "Failed to initialize client: {}",
+                handshake_error.map_or_else(|| "no protocol version to offer".to_string(), |e| e.to_string())
+            )))
         }
-        TransportConfig::Sse { url, headers: _ } => {
+        #[cfg(not(feature = "stdio"))]
+        TransportConfig::Stdio { .. } => Err(ToolSearchError::UnsupportedTransport(format!(
+            "stdio transport disabled (built without the stdio feature): {}",
+            config.command_line().unwrap_or_default().0
+        ))),
+        TransportConfig::Sse { url, .. } => {
             // SSE transport implementation would go here
             // For now, return an error as SSE support may need additional setup
             Err(ToolSearchError::UnsupportedTransport(
@@ -543,9 +2144,39 @@ pub async fn list_tools_from_server_with_timeout(
     config: &ServerConfig,
     timeout_duration: Option<Duration>,
 ) -> Result<Vec<Tool>, ToolSearchError> {
-    let connect_future = connect_to_server(config);
+    Ok(fetch_from_server_with_timeout(config, timeout_duration).await?.tools)
+}
+
+/// Get the initialize response (implementation, protocol version, capabilities)
+/// of a single MCP server
+pub async fn server_info(config: &ServerConfig) -> Result<ServerInfo, ToolSearchError> {
+    server_info_with_timeout(config, None).await
+}
+
+/// Get the initialize response of a single MCP server with timeout
+pub async fn server_info_with_timeout(
+    config: &ServerConfig,
+    timeout_duration: Option<Duration>,
+) -> Result<ServerInfo, ToolSearchError> {
+    let service = connect_with_timeout(config, timeout_duration, None).await?;
+    service.peer_info().cloned().ok_or_else(|| {
+        ToolSearchError::McpProtocol(format!(
+            "Server did not report initialize info: {}",
+            config.name
+        ))
+    })
+}
+
+/// Connect to a server, failing if the handshake exceeds the timeout, and
+/// recording the session in `record_dir` if set
+pub(crate) async fn connect_with_timeout(
+    config: &ServerConfig,
+    timeout_duration: Option<Duration>,
+    record_dir: Option<&Path>,
+) -> Result<ServerConnection, ToolSearchError> {
+    let connect_future = connect_to_server(config, record_dir);
     
-    let service = if let Some(timeout_dur) = timeout_duration {
+    if let Some(timeout_dur) = timeout_duration {
         timeout(timeout_dur, connect_future)
             .await
             .map_err(|_| ToolSearchError::Connection(format!(
@@ -554,40 +2185,241 @@ pub async fn list_tools_from_server_with_timeout(
             )))?
     } else {
         connect_future.await
-    }?;
-    
+    }
+}
+
+/// List all tools from a single MCP server together with its initialize info,
+/// using one connection
+pub async fn fetch_from_server_with_timeout(
+    config: &ServerConfig,
+    timeout_duration: Option<Duration>,
+) -> Result<ToolListing, ToolSearchError> {
+    let options = FetchOptions {
+        timeout: timeout_duration,
+        ..Default::default()
+    };
+    fetch_from_server(config, &options).await
+}
+
+/// `config` with `overrides` set in its environment, over its own `env`
+pub(crate) fn with_env_overrides<'a>(
+    config: &'a ServerConfig,
+    overrides: &HashMap<String, String>,
+) -> std::borrow::Cow<'a, ServerConfig> {
+    if overrides.is_empty() {
+        return std::borrow::Cow::Borrowed(config);
+    }
+    std::borrow::Cow::Owned(
+        overrides
+            .iter()
+            .fold(config.clone(), |config, (key, value)| config.env(key, value)),
+    )
+}
+
+/// Connect to a server and list its tools within the limits of `options`,
+/// starting a stdio server with the `options`' environment overrides
+pub(crate) async fn fetch_from_server(
+    config: &ServerConfig,
+    options: &FetchOptions,
+) -> Result<ToolListing, ToolSearchError> {
+    let config = with_env_overrides(config, &options.env_overrides);
+    let config = config.as_ref();
+    let started = Instant::now();
+    let record_dir = options.record_dir.as_deref();
+    let service = connect_with_timeout(config, options.timeout, record_dir).await.inspect_err(|e| {
+        let elapsed = started.elapsed();
+        tracing::info!(server = %config.name, ?elapsed, error = %e, "connect failed");
+    })?;
+    let connect_time = Some(started.elapsed());
+    tracing::info!(server = %config.name, elapsed = ?started.elapsed(), "connected");
+    let server_info = service.peer_info().cloned();
+    let listing_started = Instant::now();
+    let (tools, truncated) = list_connected_tools(&service, &config.name, options, &config.rate_limiter()).await?;
+    tracing::info!(
+        server = %config.name,
+        kept = tools.len(),
+        truncated,
+        elapsed = ?listing_started.elapsed(),
+        "listed tools"
+    );
+
+    Ok(ToolListing {
+        tools,
+        server_info,
+        connect_time,
+        truncated,
+        malformed: protocol::take_malformed(&service.malformed),
+    })
+}
+
+/// List the tools of `server` kept by the filter in `options` over an open connection,
+/// each page subject to the timeout and admitted by `limiter`, and whether
+/// the page or tool limit cut the list short
+///
+/// Listed tools that break the MCP schema are noted in the connection's
+/// `malformed`, next to the ones dropped because they didn't parse.
+///
+/// Servers that don't declare the tools capability (e.g. ones that only
+/// serve resources or prompts) have no tools, and aren't asked for any.
+/// A server handing back the cursor it was just sent is treated as having
+/// no more pages.
+///
+/// When a server's cursor is the number of tools listed so far, its cursors
+/// are taken to be offsets, and the next `parallel_pages` pages are
+/// requested at once. Pages are still matched in order; if a server's next
+/// cursor isn't the expected offset, the pages requested after it are
+/// dropped and listing continues one page at a time from that cursor.
+pub(crate) async fn list_connected_tools(
+    service: &ServerConnection,
+    server: &str,
+    options: &FetchOptions,
+    limiter: &RateLimiter,
+) -> Result<(Vec<Tool>, bool), ToolSearchError> {
+    if service.peer_info().is_some_and(|info| info.capabilities.tools.is_none()) {
+        tracing::info!(server = %server, "no tools capability declared; not listing tools");
+        return Ok((Vec::new(), false));
+    }
     let peer = service.peer();
 
     // List all tools (handling pagination)
     let mut tools = Vec::new();
-    let mut cursor = None;
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    let mut listed = 0;
+    // Text before the offset and tools per page, once the cursors have
+    // turned out to end in offsets
+    let mut offset_cursors: Option<(String, usize)> = None;
 
     loop {
-        let list_future = peer.list_tools(Some(rmcp::model::PaginatedRequestParam { cursor }));
-        
-        let result = if let Some(timeout_dur) = timeout_duration {
-            timeout(timeout_dur, list_future)
-                .await
-                .map_err(|_| ToolSearchError::Connection(format!(
-                    "List tools timeout after {:?} for server: {}",
-                    timeout_dur, config.name
-                )))?
-        } else {
-            list_future.await
-        }?;
-
-        tools.extend(result.tools);
+        let batch: Vec<Option<String>> = match (&cursor, &offset_cursors) {
+            (Some(next), Some((prefix, page_size))) => {
+                let remaining = options.max_pages.map_or(usize::MAX, |max| max.saturating_sub(pages));
+                let offset = listed;
+                (0..options.parallel_pages.clamp(1, remaining.max(1)))
+                    .map(|k| match k {
+                        0 => Some(next.clone()),
+                        k => Some(format!("{}{}", prefix, offset + k * page_size)),
+                    })
+                    .collect()
+            }
+            _ => vec![cursor.clone()],
+        };
+        let results = join_all(
+            batch
+                .iter()
+                .map(|requested| list_tools_page_from(peer, server, requested.clone(), options.timeout, limiter)),
+        )
+        .await;
+
+        let mut expected = batch.into_iter().skip(1);
+        for result in results {
+            let result = result?;
+
+            // Match page by page, so only kept tools are held on to and listing
+            // can stop as soon as enough of them are found
+            pages += 1;
+            let page_size = result.tools.len();
+            let mut stop = None;
+            for tool in result.tools {
+                if options.max_tools.is_some_and(|max| listed >= max) {
+                    stop = Some(true);
+                    break;
+                }
+                listed += 1;
+                if let Some(problem) = protocol::check_tool(&tool) {
+                    service.malformed.lock().unwrap_or_else(|e| e.into_inner()).push(problem);
+                }
+                if options.keeps(&tool) {
+                    tools.push(tool);
+                    if options.max_matches.is_some_and(|max| tools.len() >= max) {
+                        stop = Some(false);
+                        break;
+                    }
+                }
+            }
+            tracing::debug!(
+                server = %server,
+                page = pages,
+                tools = page_size,
+                listed,
+                kept = tools.len(),
+                next_cursor = ?result.next_cursor,
+                "tools/list page"
+            );
+            if let Some(observer) = &options.observer {
+                observer.on_page(server, pages, listed);
+            }
+            if let Some(truncated) = stop {
+                return Ok((tools, truncated));
+            }
 
-        if result.next_cursor.is_some() {
-            cursor = result.next_cursor;
-        } else {
-            break;
+            match result.next_cursor {
+                None => return Ok((tools, false)),
+                Some(next) if cursor.as_ref() == Some(&next) => {
+                    tracing::debug!(server = %server, cursor = %next, "cursor repeated");
+                    return Ok((tools, true));
+                }
+                Some(_) if options.max_tools.is_some_and(|max| listed >= max) => return Ok((tools, true)),
+                Some(_) if options.max_pages.is_some_and(|max| pages >= max) => return Ok((tools, true)),
+                Some(next) => {
+                    offset_cursors = offset_cursor_prefix(&next, listed)
+                        .filter(|_| page_size > 0 && options.parallel_pages > 1)
+                        .map(|prefix| (prefix.to_string(), page_size));
+                    let requested = expected.next().flatten().is_some_and(|c| c == next);
+                    cursor = Some(next);
+                    if !requested {
+                        break;
+                    }
+                }
+            }
         }
     }
+}
 
-    Ok(tools)
+/// What comes before the offset in `cursor`, if it ends in `offset`, such
+/// as `3:` in the cursor `3:100` of a [`ToolProxy`](proxy::ToolProxy)
+/// snapshot
+fn offset_cursor_prefix(cursor: &str, offset: usize) -> Option<&str> {
+    cursor
+        .strip_suffix(offset.to_string().as_str())
+        .filter(|prefix| !prefix.ends_with(|c: char| c.is_ascii_digit()))
 }
 
+/// Request one page of tools once `limiter` admits it, subject to the
+/// timeout
+async fn list_tools_page_from(
+    peer: &rmcp::Peer<rmcp::RoleClient>,
+    server: &str,
+    cursor: Option<String>,
+    timeout_duration: Option<Duration>,
+    limiter: &RateLimiter,
+) -> Result<rmcp::model::ListToolsResult, ToolSearchError> {
+    let _permit = limiter.acquire().await;
+    tracing::debug!(server = %server, ?cursor, "requesting tools/list page");
+    let list_future = peer.list_tools(Some(rmcp::model::PaginatedRequestParam { cursor }));
+    let result = if let Some(timeout_dur) = timeout_duration {
+        timeout(timeout_dur, list_future)
+            .await
+            .map_err(|_| ToolSearchError::Connection(format!(
+                "List tools timeout after {:?} for server: {}",
+                timeout_dur, server
+            )))?
+    } else {
+        list_future.await
+    }?;
+    Ok(result)
+}
+
+/// Pages of tools listed per server before a search gives up on the rest
+pub const DEFAULT_MAX_PAGES: usize = 1000;
+
+/// Pages requested at once from servers whose cursors are offsets
+pub const DEFAULT_PARALLEL_PAGES: usize = 4;
+
+/// Fraction of its timeout a server may use before a search warns that it
+/// is slow
+pub const DEFAULT_SLOW_SERVER_FRACTION: f32 = 0.8;
+
 impl Default for SearchOptions {
     fn default() -> Self {
         Self {
@@ -595,6 +2427,27 @@ impl Default for SearchOptions {
             sort_order: SortOrder::ServerThenTool,
             continue_on_error: true,
             max_results: None,
+            comparator: None,
+            include_server_info: false,
+            observer: None,
+            deadline: None,
+            custom_matcher: None,
+            min_score: None,
+            max_pages: Some(DEFAULT_MAX_PAGES),
+            max_tools: None,
+            parallel_pages: DEFAULT_PARALLEL_PAGES,
+            max_per_server: None,
+            truncation: Truncation::default(),
+            dedupe: Dedupe::default(),
+            slow_server_fraction: Some(DEFAULT_SLOW_SERVER_FRACTION),
+            env_overrides: HashMap::new(),
+            record_dir: None,
+            projection: None,
+            redaction: Redaction::default(),
+            popularity: None,
+            pinned: Vec::new(),
+            blocklist: Blocklist::default(),
+            min_response_rate: None,
         }
     }
 }
@@ -613,88 +2466,627 @@ pub async fn search_tools_with_options(
     criteria: &SearchCriteria,
     options: &SearchOptions,
 ) -> Result<Vec<ToolSearchMatch>, ToolSearchError> {
-    
-    // Validate all server configurations first
-    for server in servers {
-        if let Err(e) = server.validate() {
-            if !options.continue_on_error {
-                return Err(ToolSearchError::Connection(e));
-            }
-            eprintln!("Warning: Invalid server configuration {}: {}", server.name, e);
+    let sources: Vec<Arc<dyn ToolSource>> = servers
+        .iter()
+        .map(|server| Arc::new(server.clone()) as Arc<dyn ToolSource>)
+        .collect();
+    search_sources_with_options(&sources, criteria, options).await
+}
+
+/// Search for tools across arbitrary tool sources with options
+pub async fn search_sources_with_options(
+    sources: &[Arc<dyn ToolSource>],
+    criteria: &SearchCriteria,
+    options: &SearchOptions,
+) -> Result<Vec<ToolSearchMatch>, ToolSearchError> {
+    let fetched = fetch_sources(sources, options, Some(criteria)).await?;
+    Ok(report_from_fetched(fetched, sources, criteria, options)?.matches)
+}
+
+/// Search arbitrary tool sources, returning matches with per-server timings
+pub async fn search_sources_report(
+    sources: &[Arc<dyn ToolSource>],
+    criteria: &SearchCriteria,
+    options: &SearchOptions,
+) -> Result<SearchReport, ToolSearchError> {
+    let fetched = fetch_sources(sources, options, Some(criteria)).await?;
+    report_from_fetched(fetched, sources, criteria, options)
+}
+
+/// Tools fetched from one source (or the error fetching them)
+pub(crate) struct FetchedSource {
+    pub(crate) name: String,
+    pub(crate) result: Result<ToolListing, ToolSearchError>,
+    /// Time spent fetching; `None` for tools served from memory
+    pub(crate) elapsed: Option<Duration>,
+    /// Timeout the fetch ran with
+    pub(crate) timeout: Option<Duration>,
+}
+
+/// Sources fetched by one search
+pub(crate) struct Fetched {
+    /// Sources that answered, in configuration order
+    pub(crate) sources: Vec<FetchedSource>,
+    /// Whether the deadline expired before every source answered
+    pub(crate) truncated: bool,
+    /// Sources skipped because their configuration is invalid
+    pub(crate) warnings: Vec<SearchWarning>,
+    /// Sources that hadn't answered when the deadline expired
+    pub(crate) unanswered: usize,
+    /// Sources left out without being contacted
+    pub(crate) skipped: usize,
+}
+
+impl Fetched {
+    /// How many of the sources responded
+    pub(crate) fn health(&self) -> Health {
+        let mut health = Health {
+            skipped: self.skipped,
+            ..Default::default()
+        };
+        health.failed += self.warnings.len() + self.unanswered;
+        for source in &self.sources {
+            tally(&mut health, source);
         }
+        health.queried = health.responded() + health.failed + health.skipped;
+        health
     }
-    
-    // Query all servers in parallel
-    let server_futures: Vec<_> = servers
-        .iter()
-        .filter_map(|server_config| {
-            // Skip invalid configurations if continuing on error
-            if server_config.validate().is_err() && options.continue_on_error {
-                return None;
-            }
-            let config = server_config.clone();
-            let timeout_dur = options.timeout;
-            Some(async move {
-                let result = list_tools_from_server_with_timeout(&config, timeout_dur).await;
-                (config.name.clone(), result)
+}
+
+/// Count one fetched source into `health`, except in `queried`
+fn tally(health: &mut Health, source: &FetchedSource) {
+    match (&source.result, source.elapsed) {
+        (Ok(_), None) => health.cached += 1,
+        (Ok(_), Some(_)) => health.succeeded += 1,
+        (Err(_), _) => health.failed += 1,
+    }
+}
+
+/// Fail per [`SearchOptions::min_response_rate`] if too few sources responded
+fn check_response_rate(health: &Health, options: &SearchOptions) -> Result<(), ToolSearchError> {
+    match options.min_response_rate {
+        Some(required) if health.response_rate() < required => {
+            Err(ToolSearchError::TooFewResponded {
+                responded: health.responded(),
+                queried: health.queried,
+                required,
             })
-        })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Validate and fetch all sources in parallel, within the deadline if set
+///
+/// With `criteria`, sources keep only the tools the search can use (see
+/// [`search_fetch_options`]); without, they list every tool.
+pub(crate) async fn fetch_sources(
+    sources: &[Arc<dyn ToolSource>],
+    options: &SearchOptions,
+    criteria: Option<&SearchCriteria>,
+) -> Result<Fetched, ToolSearchError> {
+    let mut warnings = Vec::new();
+    let valid = validated_sources(sources, options, &mut warnings)?;
+    let fetch_options = match criteria {
+        Some(criteria) => search_fetch_options(criteria, options),
+        None => options.into(),
+    };
+    let fetches = valid
+        .into_iter()
+        .map(|source| fetch_source(source, fetch_options.clone(), &options.blocklist));
+
+    // Query all sources in parallel
+    // A budget too large to add to the current time is no deadline at all
+    let expires = options.deadline.and_then(|d| tokio::time::Instant::now().checked_add(d));
+    let Some(expires) = expires else {
+        return Ok(Fetched {
+            sources: join_all(fetches).await,
+            truncated: false,
+            warnings,
+            unanswered: 0,
+            skipped: 0,
+        });
+    };
+
+    let mut pending: FuturesUnordered<_> = fetches
+        .enumerate()
+        .map(|(index, fetch)| async move { (index, fetch.await) })
         .collect();
+    let mut done = Vec::new();
+    let mut truncated = false;
+    loop {
+        match tokio::time::timeout_at(expires, pending.next()).await {
+            Ok(Some(fetched)) => done.push(fetched),
+            Ok(None) => break,
+            Err(_) => {
+                truncated = true;
+                break;
+            }
+        }
+    }
+    done.sort_by_key(|(index, _)| *index);
+
+    Ok(Fetched {
+        sources: done.into_iter().map(|(_, fetched)| fetched).collect(),
+        truncated,
+        warnings,
+        unanswered: pending.len(),
+        skipped: 0,
+    })
+}
 
-    let server_results = join_all(server_futures).await;
-    
-    let mut results = Vec::new();
-    let mut errors = Vec::new();
+/// Fetch options for one search with `criteria`
+///
+/// Tools the criteria (or custom matcher) reject are dropped while paging.
+/// When limits apply while listing (see [`limits_first_listed`]), a source
+/// stops listing once it has `max_per_server` matches, or `max_results`
+/// when results aren't sorted or deduplicated (later tools can't make the
+/// cut then).
+fn search_fetch_options(criteria: &SearchCriteria, options: &SearchOptions) -> FetchOptions {
+    let unsorted_limit = match (options.sort_order, options.dedupe) {
+        (SortOrder::None, Dedupe::None) if options.pinned.is_empty() => options.max_results,
+        _ => None,
+    };
+    let per_server = options.max_per_server.filter(|_| limits_first_listed(options));
+    FetchOptions {
+        filter: Some(Arc::new(SearchFilter {
+            criteria: criteria.clone(),
+            matcher: options.custom_matcher.clone(),
+            min_score: options.min_score,
+        })),
+        max_matches: match (per_server, unsorted_limit) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        },
+        ..options.into()
+    }
+}
+
+/// Whether `max_per_server` keeps the first matches a server lists, which
+/// are also its best ranked when results aren't sorted (or pinned)
+fn limits_first_listed(options: &SearchOptions) -> bool {
+    options.pinned.is_empty()
+        && (options.truncation == Truncation::FirstListed || options.sort_order == SortOrder::None)
+}
+
+/// The tests a tool must pass to be a match
+struct SearchFilter {
+    criteria: SearchCriteria,
+    matcher: Option<Arc<dyn Matcher>>,
+    min_score: Option<f32>,
+}
+
+impl SearchFilter {
+    /// `None` if the tool is rejected, otherwise its score, if scored
+    fn accept(&self, tool: &Tool) -> Option<Option<f32>> {
+        if !self.criteria.matches(tool) {
+            return None;
+        }
+        let score = match &self.matcher {
+            Some(matcher) => Some(matcher.matches(tool)?),
+            None => None,
+        };
+        match (score, self.min_score) {
+            (Some(score), Some(min)) if score < min => None,
+            _ => Some(score),
+        }
+    }
+}
 
-    for (server_name, server_result) in server_results {
+impl Matcher for SearchFilter {
+    fn matches(&self, tool: &Tool) -> Option<f32> {
+        self.accept(tool).map(Option::unwrap_or_default)
+    }
+}
+
+/// Validate source configurations, dropping invalid ones if continuing on error
+fn validated_sources<'a>(
+    sources: &'a [Arc<dyn ToolSource>],
+    options: &SearchOptions,
+    warnings: &mut Vec<SearchWarning>,
+) -> Result<Vec<&'a Arc<dyn ToolSource>>, ToolSearchError> {
+    let mut valid = Vec::with_capacity(sources.len());
+    for source in sources {
+        // Blocked servers are left out as if they weren't configured
+        if options.blocklist.blocks_server(source.name())
+            || source.blocked_tools().iter().any(|pattern| pattern == "*")
+        {
+            continue;
+        }
+        match source.validate() {
+            Ok(()) => valid.push(source),
+            Err(e) if !options.continue_on_error => return Err(ToolSearchError::Connection(e)),
+            Err(message) => warn(
+                warnings,
+                SearchWarning::InvalidConfig {
+                    server_name: source.name().to_string(),
+                    message,
+                },
+                options,
+            ),
+        }
+    }
+    Ok(valid)
+}
+
+/// Fetch one source, timing the fetch
+async fn fetch_source(
+    source: &Arc<dyn ToolSource>,
+    mut options: FetchOptions,
+    blocklist: &Blocklist,
+) -> FetchedSource {
+    if let Some(timeout) = source.timeout() {
+        options.timeout = Some(timeout);
+    }
+    options.blocked_tools = source.blocked_tools();
+    options
+        .blocked_tools
+        .extend(blocklist.tool_patterns(source.name()).map(str::to_string));
+    let started = Instant::now();
+    let result = source.fetch_with(&options).await;
+    FetchedSource {
+        name: source.name().to_string(),
+        result,
+        elapsed: Some(started.elapsed()),
+        timeout: options.timeout,
+    }
+}
+
+/// Find one matching tool, returning as soon as any source yields a match
+///
+/// Sources are fetched in parallel; once one of them has a match, fetches
+/// still in flight are cancelled. When a source has several matches, the
+/// first one in the configured sort order is returned.
+pub async fn search_sources_first(
+    sources: &[Arc<dyn ToolSource>],
+    criteria: &SearchCriteria,
+    options: &SearchOptions,
+) -> Result<Option<ToolSearchMatch>, ToolSearchError> {
+    let per_source = SearchOptions {
+        max_results: Some(1),
+        ..options.clone()
+    };
+    let fetch_options = search_fetch_options(criteria, &per_source);
+    let mut warnings = Vec::new();
+    let mut pending: FuturesUnordered<_> = validated_sources(sources, options, &mut warnings)?
+        .into_iter()
+        .map(|source| fetch_source(source, fetch_options.clone(), &options.blocklist))
+        .collect();
+
+    let expires = options.deadline.and_then(|d| tokio::time::Instant::now().checked_add(d));
+    let mut timings = Vec::new();
+    let mut found = None;
+    let mut truncated = false;
+    let mut health = Health {
+        failed: warnings.len(),
+        ..Default::default()
+    };
+    loop {
+        let next = match expires {
+            Some(expires) => match tokio::time::timeout_at(expires, pending.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    truncated = true;
+                    None
+                }
+            },
+            None => pending.next().await,
+        };
+        let Some(fetched) = next else {
+            break;
+        };
+        tally(&mut health, &fetched);
+        let (listings, new_timings) = collect_fetched(vec![fetched], options, &mut warnings)?;
+        timings.extend(new_timings);
+        let servers = catalog_servers(listings, sources);
+        found = report_from_listings(&servers, Vec::new(), criteria, &per_source)
+            .matches
+            .pop();
+        if found.is_some() {
+            break;
+        }
+    }
+    // Fetches cancelled once there is a match don't count; those the
+    // deadline cut off failed
+    if truncated {
+        health.failed += pending.len();
+    }
+    drop(pending);
+    health.queried = health.responded() + health.failed;
+    check_response_rate(&health, options)?;
+
+    let report = SearchReport {
+        matches: found.into_iter().collect(),
+        timings,
+        criteria: criteria.clone(),
+        truncated,
+        warnings,
+        health,
+    };
+    notify_complete(&report, options);
+    Ok(report.matches.into_iter().next())
+}
+
+/// Filter, sort, and limit fetched tools into a search report
+pub(crate) fn report_from_fetched(
+    fetched: Fetched,
+    sources: &[Arc<dyn ToolSource>],
+    criteria: &SearchCriteria,
+    options: &SearchOptions,
+) -> Result<SearchReport, ToolSearchError> {
+    let health = fetched.health();
+    let mut warnings = fetched.warnings;
+    let (listings, timings) = collect_fetched(fetched.sources, options, &mut warnings)?;
+    check_response_rate(&health, options)?;
+    let servers = catalog_servers(listings, sources);
+    let mut report = report_from_listings(&servers, timings, criteria, options);
+    report.truncated = fetched.truncated;
+    report.warnings = warnings;
+    report.health = health;
+    notify_complete(&report, options);
+    Ok(report)
+}
+
+/// Send the final matches and report to the observer, if any
+pub(crate) fn notify_complete(report: &SearchReport, options: &SearchOptions) {
+    if let Some(observer) = &options.observer {
+        for result in &report.matches {
+            observer.on_match(result);
+        }
+        observer.on_complete(report);
+    }
+}
+
+/// Tools listed by one source, keyed by source name
+type NamedListing = (String, ToolListing);
+
+/// Split fetched sources into successful listings and timings, handling
+/// failures per `continue_on_error` (skipped failures become warnings)
+fn collect_fetched(
+    fetched: Vec<FetchedSource>,
+    options: &SearchOptions,
+    warnings: &mut Vec<SearchWarning>,
+) -> Result<(Vec<NamedListing>, Vec<ServerTiming>), ToolSearchError> {
+    let mut listings = Vec::new();
+    let mut timings = Vec::new();
+
+    for FetchedSource {
+        name: server_name,
+        result: server_result,
+        elapsed,
+        timeout,
+    } in fetched
+    {
+        if let Some(total) = elapsed {
+            let connect = server_result.as_ref().ok().and_then(|l| l.connect_time);
+            timings.push(ServerTiming {
+                server_name: server_name.clone(),
+                connect,
+                list: server_result
+                    .as_ref()
+                    .ok()
+                    .map(|_| total.saturating_sub(connect.unwrap_or_default())),
+                total,
+                succeeded: server_result.is_ok(),
+                timeout,
+                protocol_version: server_result
+                    .as_ref()
+                    .ok()
+                    .and_then(|l| l.server_info.as_ref())
+                    .map(|info| info.protocol_version.to_string()),
+            });
+        }
+        if let Some(observer) = &options.observer {
+            match (&server_result, elapsed) {
+                (Ok(_), Some(elapsed)) => observer.on_server_connected(&server_name, elapsed),
+                (Ok(_), None) => {}
+                (Err(e), _) => observer.on_server_failed(&server_name, e),
+            }
+        }
         match server_result {
-            Ok(tools) => {
-                for tool in tools {
-                    if criteria.matches(&tool) {
-                        results.push(ToolSearchMatch {
+            Ok(listing) => {
+                for tool in &listing.malformed {
+                    warn(
+                        warnings,
+                        SearchWarning::MalformedTool {
                             server_name: server_name.clone(),
-                            tool,
-                        });
-                    }
+                            tool: tool.clone(),
+                        },
+                        options,
+                    );
                 }
-            }
-            Err(e) => {
-                let error_msg = format!("Error connecting to server {}: {}", server_name, e);
-                if options.continue_on_error {
-                    errors.push(error_msg);
-                } else {
-                    return Err(e);
+                if let (Some(elapsed), Some(timeout), Some(fraction)) =
+                    (elapsed, timeout, options.slow_server_fraction)
+                    // In floating point, as any fraction may be set (a NaN
+                    // one warns about nothing)
+                    && elapsed.as_secs_f64() > timeout.as_secs_f64() * f64::from(fraction)
+                {
+                    warn(
+                        warnings,
+                        SearchWarning::SlowServer {
+                            server_name: server_name.clone(),
+                            elapsed,
+                            timeout,
+                            suggested_timeout: suggested_timeout(elapsed),
+                        },
+                        options,
+                    );
                 }
+                if listing.truncated {
+                    warn(
+                        warnings,
+                        SearchWarning::ToolListTruncated {
+                            server_name: server_name.clone(),
+                            tools: listing.tools.len(),
+                        },
+                        options,
+                    );
+                }
+                if !listing.has_tools_capability() {
+                    warn(
+                        warnings,
+                        SearchWarning::NoToolsCapability {
+                            server_name: server_name.clone(),
+                        },
+                        options,
+                    );
+                }
+                listings.push((server_name, listing))
             }
+            Err(e) if !options.continue_on_error => return Err(e),
+            Err(e) => warn(
+                warnings,
+                SearchWarning::ServerFailed {
+                    server_name,
+                    error: e.to_string(),
+                },
+                options,
+            ),
         }
     }
 
-    // Log errors if continuing on error
-    if !errors.is_empty() && options.continue_on_error {
-        for error in &errors {
-            eprintln!("{}", error);
+    Ok((listings, timings))
+}
+
+/// Turn listings into catalog entries, with the priority of their source
+fn catalog_servers(listings: Vec<NamedListing>, sources: &[Arc<dyn ToolSource>]) -> Vec<CatalogServer> {
+    listings
+        .into_iter()
+        .map(|(name, listing)| {
+            let source = sources.iter().find(|source| source.name() == name);
+            CatalogServer {
+                priority: source.map_or(0, |source| source.priority()),
+                boosts: source.map(|source| source.boosts()).unwrap_or_default(),
+                name,
+                tools: listing.tools,
+                server_info: listing.server_info,
+            }
+        })
+        .collect()
+}
+
+/// Timeout with headroom for a server that took `elapsed`: twice as long,
+/// in whole seconds
+fn suggested_timeout(elapsed: Duration) -> Duration {
+    Duration::from_secs((elapsed.as_secs_f64() * 2.0).ceil().max(1.0) as u64)
+}
+
+/// Record a warning and pass it to the observer, if any
+fn warn(warnings: &mut Vec<SearchWarning>, warning: SearchWarning, options: &SearchOptions) {
+    if let Some(observer) = &options.observer {
+        observer.on_warning(&warning);
+    }
+    warnings.push(warning);
+}
+
+/// Match, sort, dedupe, and limit listed tools into a search report, in
+/// the order [`SearchOptions`] documents
+pub(crate) fn report_from_listings(
+    servers: &[CatalogServer],
+    timings: Vec<ServerTiming>,
+    criteria: &SearchCriteria,
+    options: &SearchOptions,
+) -> SearchReport {
+    let mut results = Vec::new();
+    let filter = SearchFilter {
+        criteria: criteria.clone(),
+        matcher: options.custom_matcher.clone(),
+        min_score: options.min_score,
+    };
+    // Once any server is boosted or usage weighs in, unscored matches score
+    // 1 so that boosts rank them against each other
+    let boosted = options.popularity.is_some()
+        || servers.iter().any(|server| !server.boosts.is_empty());
+    let first_listed = limits_first_listed(options);
+
+    for server in servers {
+        let server_info = if options.include_server_info {
+            server.server_info.clone().map(Arc::new)
+        } else {
+            None
+        };
+        let accepted = server
+            .tools
+            .iter()
+            .filter(|tool| !options.blocklist.blocks(&server.name, &tool.name))
+            .filter_map(|tool| Some((tool, filter.accept(tool)?)))
+            .take(options.max_per_server.filter(|_| first_listed).unwrap_or(usize::MAX));
+        for (tool, score) in accepted {
+            let mut result = ToolSearchMatch::new(server.name.clone(), tool.clone());
+            result.server_info = server_info.clone();
+            let factor = server.boosts.factor(result.tool_name())
+                * options
+                    .popularity
+                    .as_ref()
+                    .map_or(1.0, |popularity| popularity.factor(&server.name, result.tool_name()));
+            result.score = match score {
+                Some(score) => Some(score * factor),
+                None if boosted => Some(factor),
+                None => None,
+            };
+            result.snippet = criteria.snippet(tool);
+            result.pinned = options
+                .pinned
+                .iter()
+                .any(|entry| pins::pins_tool(entry, &server.name, result.tool_name()));
+            results.push(result);
         }
     }
 
-    // Sort results
-    match options.sort_order {
-        SortOrder::ServerThenTool => {
+    // Sort results (stable, with deterministic tie-breaking)
+    match (options.sort_order, &options.comparator) {
+        (SortOrder::None, _) | (SortOrder::Custom, None) => {}
+        (SortOrder::ServerPriority, _) => {
+            let priorities: HashMap<&str, u32> = servers
+                .iter()
+                .map(|server| (server.name.as_str(), server.priority))
+                .collect();
+            let priority = |m: &ToolSearchMatch| {
+                priorities.get(m.server_name.as_str()).copied().unwrap_or(0)
+            };
             results.sort_by(|a, b| {
-                a.server_name
-                    .cmp(&b.server_name)
-                    .then_with(|| a.tool_name().cmp(b.tool_name()))
+                priority(b)
+                    .cmp(&priority(a))
+                    .then_with(|| a.total_cmp(b, SortOrder::ServerPriority))
             });
         }
-        SortOrder::ToolThenServer => {
+        (SortOrder::Custom, Some(comparator)) => {
             results.sort_by(|a, b| {
-                a.tool_name()
-                    .cmp(b.tool_name())
-                    .then_with(|| a.server_name.cmp(&b.server_name))
+                comparator
+                    .compare(a, b)
+                    .then_with(|| a.total_cmp(b, SortOrder::ServerThenTool))
             });
         }
-        SortOrder::None => {
-            // Keep original order
+        (order, _) => results.sort_by(|a, b| a.total_cmp(b, order)),
+    }
+    // Pinned tools first, in sort order among themselves
+    if !options.pinned.is_empty() {
+        results.sort_by_key(|m| !m.pinned);
+    }
+
+    // Drop duplicates, keeping the best-ranked match
+    match options.dedupe {
+        Dedupe::None => {}
+        Dedupe::Definition => {
+            let mut seen = HashSet::new();
+            results.retain(|m| seen.insert(m.content_hash()));
         }
+        Dedupe::Name => {
+            let mut seen = HashSet::new();
+            results.retain(|m| seen.insert(m.tool_name().to_string()));
+        }
+    }
+
+    // Keep each server's best-ranked matches
+    if let Some(max) = options.max_per_server
+        && !first_listed
+    {
+        let mut kept: HashMap<String, usize> = HashMap::new();
+        results.retain(|m| {
+            let count = kept.entry(m.server_name.clone()).or_default();
+            *count += 1;
+            *count <= max
+        });
     }
 
     // Limit results if specified
@@ -702,7 +3094,107 @@ pub async fn search_tools_with_options(
         results.truncate(max);
     }
 
-    Ok(results)
+    if !options.redaction.is_empty() {
+        results.iter_mut().for_each(|m| options.redaction.apply(m));
+    }
+    if let Some(projection) = &options.projection {
+        results.iter_mut().for_each(|m| projection.apply(m));
+    }
+
+    SearchReport {
+        matches: results,
+        timings,
+        criteria: criteria.clone(),
+        truncated: false,
+        warnings: Vec::new(),
+        health: Health::default(),
+    }
+}
+
+/// Run several searches against a single fetch of each server
+///
+/// Each server is contacted once and every criteria is evaluated against the
+/// same tools, which is much cheaper than independent searches. Results are
+/// returned in the order of `criteria`.
+pub async fn search_many(
+    servers: &[ServerConfig],
+    criteria: &[SearchCriteria],
+) -> Result<Vec<Vec<ToolSearchMatch>>, ToolSearchError> {
+    search_many_with_options(servers, criteria, &SearchOptions::default()).await
+}
+
+/// Run several searches against a single fetch of each server, with options
+pub async fn search_many_with_options(
+    servers: &[ServerConfig],
+    criteria: &[SearchCriteria],
+    options: &SearchOptions,
+) -> Result<Vec<Vec<ToolSearchMatch>>, ToolSearchError> {
+    let sources: Vec<Arc<dyn ToolSource>> = servers
+        .iter()
+        .map(|server| Arc::new(server.clone()) as Arc<dyn ToolSource>)
+        .collect();
+    search_sources_many(&sources, criteria, options).await
+}
+
+/// Run several searches against a single fetch of each tool source
+pub async fn search_sources_many(
+    sources: &[Arc<dyn ToolSource>],
+    criteria: &[SearchCriteria],
+    options: &SearchOptions,
+) -> Result<Vec<Vec<ToolSearchMatch>>, ToolSearchError> {
+    let fetched = fetch_sources(sources, options, None).await?;
+    let health = fetched.health();
+    let mut warnings = fetched.warnings;
+    let (listings, timings) = collect_fetched(fetched.sources, options, &mut warnings)?;
+    check_response_rate(&health, options)?;
+    let servers = catalog_servers(listings, sources);
+    Ok(criteria
+        .iter()
+        .map(|criteria| {
+            let mut report = report_from_listings(&servers, timings.clone(), criteria, options);
+            report.truncated = fetched.truncated;
+            report.warnings = warnings.clone();
+            report.health = health;
+            notify_complete(&report, options);
+            report.matches
+        })
+        .collect())
+}
+
+/// Fetch every tool of every server once, to search any number of times
+///
+/// Servers are queried in parallel. Failing servers are left out (or fail
+/// the fetch without `continue_on_error`) and listed in
+/// [`Catalog::warnings`]; the search-only options don't apply here.
+pub async fn fetch_catalog(
+    servers: &[ServerConfig],
+    options: &SearchOptions,
+) -> Result<Catalog, ToolSearchError> {
+    let sources: Vec<Arc<dyn ToolSource>> = servers
+        .iter()
+        .map(|server| Arc::new(server.clone()) as Arc<dyn ToolSource>)
+        .collect();
+    fetch_sources_catalog(&sources, options).await
+}
+
+/// Fetch every tool of arbitrary tool sources once, as a [`Catalog`]
+pub async fn fetch_sources_catalog(
+    sources: &[Arc<dyn ToolSource>],
+    options: &SearchOptions,
+) -> Result<Catalog, ToolSearchError> {
+    let fetched = fetch_sources(sources, options, None).await?;
+    catalog_from_fetched(fetched, sources, options)
+}
+
+/// Catalog of the sources that answered, with the warnings of the fetch
+pub(crate) fn catalog_from_fetched(
+    fetched: Fetched,
+    sources: &[Arc<dyn ToolSource>],
+    options: &SearchOptions,
+) -> Result<Catalog, ToolSearchError> {
+    let mut warnings = fetched.warnings;
+    let (listings, _) = collect_fetched(fetched.sources, options, &mut warnings)?;
+    Ok(Catalog::new(catalog_servers(listings, sources)).with_warnings(warnings))
 }
 
 /// Convenience function to search tools with a query string
@@ -736,19 +3228,39 @@ pub async fn search_tools_with_keywords(
 pub async fn list_all_tools(
     servers: &[ServerConfig],
 ) -> Result<Vec<ToolSearchMatch>, ToolSearchError> {
-    let criteria = SearchCriteria {
-        query: None,
-        name: None,
-        mode: SearchMode::Substring,
-        fields: SearchFields::default(),
-        case_sensitive: false,
-        min_description_length: None,
-        keywords: vec![],
-        regex: None,
-    };
+    let criteria = SearchCriteria::match_all();
     search_tools(servers, &criteria).await
 }
 
+/// List the tools of each server, keyed by server name
+///
+/// Servers are queried in parallel; servers that fail are left out of the map.
+pub async fn list_tools_by_server(
+    servers: &[ServerConfig],
+) -> Result<HashMap<String, Vec<Tool>>, ToolSearchError> {
+    list_tools_by_server_with_options(servers, &SearchOptions::default()).await
+}
+
+/// List the tools of each server, keyed by server name, with options
+///
+/// Only `timeout`, `continue_on_error`, and `observer` apply.
+pub async fn list_tools_by_server_with_options(
+    servers: &[ServerConfig],
+    options: &SearchOptions,
+) -> Result<HashMap<String, Vec<Tool>>, ToolSearchError> {
+    let sources: Vec<Arc<dyn ToolSource>> = servers
+        .iter()
+        .map(|server| Arc::new(server.clone()) as Arc<dyn ToolSource>)
+        .collect();
+    let fetched = fetch_sources(&sources, options, None).await?;
+    let mut warnings = fetched.warnings;
+    let (listings, _) = collect_fetched(fetched.sources, options, &mut warnings)?;
+    Ok(listings
+        .into_iter()
+        .map(|(name, listing)| (name, listing.tools))
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -783,8 +3295,11 @@ mod tests {
         assert!(!criteria.matches(&tool));
 
         // Test regex matching
-        let criteria = SearchCriteria::with_regex(r"test.*tool".to_string());
-        assert!(criteria.matches(&tool));
+        #[cfg(feature = "regex-mode")]
+        {
+            let criteria = SearchCriteria::with_regex(r"test.*tool".to_string());
+            assert!(criteria.matches(&tool));
+        }
 
         // Test keyword matching
         let criteria = SearchCriteria::with_keywords(vec!["test".to_string(), "tool".to_string()]);
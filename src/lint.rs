@@ -0,0 +1,177 @@
+//! Quality checks for tool catalogs
+//!
+//! Agents pick tools by their names, descriptions and schemas, so a tool
+//! that is hard to tell apart is hard to use well. [`lint_catalog`] audits a
+//! [`Catalog`] for the usual shortcomings and returns one [`LintIssue`] per
+//! finding; `toolsearch lint` prints them (or JSON for CI) and fails when
+//! there are any.
+//!
+//! ```
+//! use toolsearch::lint::{lint_catalog, LintRule};
+//! use toolsearch::testing::ToolFixture;
+//! use toolsearch::{Catalog, CatalogServer};
+//!
+//! let catalog = Catalog::new(vec![CatalogServer {
+//!     name: "files".to_string(),
+//!     priority: 0,
+//!     boosts: Default::default(),
+//!     tools: vec![ToolFixture::new("run").build()],
+//!     server_info: None,
+//! }]);
+//! let rules: Vec<_> = lint_catalog(&catalog).into_iter().map(|issue| issue.rule).collect();
+//! assert_eq!(rules, [LintRule::GenericName, LintRule::MissingDescription]);
+//! ```
+
+use crate::report::SearchWarning;
+use crate::Catalog;
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Names that say nothing about what a tool does
+pub const GENERIC_NAMES: &[&str] = &[
+    "action", "call", "do", "exec", "execute", "function", "handle", "handler", "invoke", "main",
+    "process", "query", "request", "run", "test", "tool",
+];
+
+/// Kind of problem a [`LintIssue`] reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// The tool definition breaks the MCP schema
+    Malformed,
+    /// The tool's name is one of [`GENERIC_NAMES`]
+    GenericName,
+    /// The tool has no description
+    MissingDescription,
+    /// A property of the input schema has no description
+    UndocumentedProperty,
+    /// The server lists more than one tool with this name
+    DuplicateName,
+}
+
+impl LintRule {
+    /// Name of the rule, as printed and serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintRule::Malformed => "malformed",
+            LintRule::GenericName => "generic-name",
+            LintRule::MissingDescription => "missing-description",
+            LintRule::UndocumentedProperty => "undocumented-property",
+            LintRule::DuplicateName => "duplicate-name",
+        }
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One quality problem with one tool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintIssue {
+    /// Name of the server listing the tool
+    pub server_name: String,
+    /// Name of the tool; absent for tools without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    /// Which check failed
+    pub rule: LintRule,
+    /// What is wrong, for people
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}: {}: {}",
+            self.server_name,
+            self.tool_name.as_deref().unwrap_or("(unnamed)"),
+            self.rule,
+            self.message
+        )
+    }
+}
+
+/// Audit every tool of `catalog`, server by server in catalog order
+///
+/// Malformed tools noted while the catalog was fetched are reported first.
+/// Names shared by different servers are not duplicates: a proxy tells
+/// them apart by server.
+pub fn lint_catalog(catalog: &Catalog) -> Vec<LintIssue> {
+    let mut issues: Vec<LintIssue> = catalog
+        .warnings()
+        .iter()
+        .filter_map(|warning| match warning {
+            SearchWarning::MalformedTool { server_name, tool } => Some(LintIssue {
+                server_name: server_name.clone(),
+                tool_name: tool.name.clone(),
+                rule: LintRule::Malformed,
+                message: tool.problem.clone(),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    for server in catalog.servers() {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for tool in &server.tools {
+            *counts.entry(tool.name.as_ref()).or_default() += 1;
+        }
+        for tool in &server.tools {
+            let issue = |rule, message: String| LintIssue {
+                server_name: server.name.clone(),
+                tool_name: Some(tool.name.to_string()),
+                rule,
+                message,
+            };
+            issues.extend(lint_tool(tool).into_iter().map(|(rule, message)| issue(rule, message)));
+            match counts.get_mut(tool.name.as_ref()) {
+                // Reported once, at the first of the duplicates
+                Some(count) if *count > 1 => {
+                    issues.push(issue(
+                        LintRule::DuplicateName,
+                        format!("Listed {} times by this server", count),
+                    ));
+                    *count = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+    issues
+}
+
+/// Problems with one tool on its own
+fn lint_tool(tool: &Tool) -> Vec<(LintRule, String)> {
+    let mut problems = Vec::new();
+    let folded = tool.name.trim().to_lowercase();
+    if GENERIC_NAMES.contains(&folded.as_str()) {
+        problems.push((
+            LintRule::GenericName,
+            format!("Name \"{}\" doesn't say what the tool does", tool.name),
+        ));
+    }
+    if tool.description.as_deref().is_none_or(|d| d.trim().is_empty()) {
+        problems.push((LintRule::MissingDescription, "Tool has no description".to_string()));
+    }
+    if let Some(properties) = tool.input_schema.get("properties").and_then(|p| p.as_object()) {
+        for (name, property) in properties {
+            let described = property
+                .get("description")
+                .and_then(|d| d.as_str())
+                .is_some_and(|d| !d.trim().is_empty());
+            if !described {
+                problems.push((
+                    LintRule::UndocumentedProperty,
+                    format!("Property \"{}\" has no description", name),
+                ));
+            }
+        }
+    }
+    problems
+}
@@ -1,10 +1,34 @@
 use clap::{Parser, Subcommand};
-use toolsearch::{load_servers, SearchBuilder};
+#[cfg(unix)]
+use toolsearch::daemon::{self, DaemonRequest, DaemonResponse};
+use toolsearch::history::{History, HistoryEntry};
+use toolsearch::configlint::{fix_config, lint_config, Severity};
+use toolsearch::lint::lint_catalog;
+use toolsearch::validate::validate_source;
+use toolsearch::proxy::ToolProxy;
+use toolsearch::record::ReplaySource;
+use toolsearch::{
+    fetch_catalog, output, save_servers, ConfigSource, DuplicatePolicy, ping_all, AliasMap, Blocklist, ConfigError, DirectorySource, HealthStatus, Observer, OpenApiSource,
+    Pins, Popularity, Projection, Redaction, RegistryClient, SearchBuilder, SearchOptions, SearchWarning, TemplateSet,
+};
 
 #[derive(Parser)]
 #[command(name = "toolsearch")]
 #[command(about = "Search tools across MCP servers", long_about = None)]
 struct Cli {
+    /// What to do with servers that share a name: error, rename (adds a
+    /// -2, -3, ... suffix), or keep
+    #[arg(long, global = true, default_value = "error")]
+    duplicates: DuplicatePolicy,
+    /// Header sent when --config is a URL, e.g. "Authorization: Bearer $TOKEN"
+    /// (repeatable)
+    #[arg(long, global = true, value_name = "NAME: VALUE", value_parser = parse_header)]
+    config_header: Vec<(String, String)>,
+    /// Print connection diagnostics to stderr: -v for handshakes, timings and
+    /// tool counts (and the resolved search), -vv adds pagination cursors and
+    /// MCP messages, -vvv everything
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
     #[command(subcommand)]
     command: Commands,
 }
@@ -17,57 +41,482 @@ enum Commands {
         #[arg(short, long)]
         config: String,
         /// Search query (auto-detects: regex if contains ^$|*, keywords if comma-separated)
-        query: String,
-        /// Output format: json, text, or table
+        #[arg(required_unless_present_any = ["name", "tag", "profile", "queries"])]
+        query: Option<String>,
+        /// Another query whose matches are added, in the same fetch (repeatable);
+        /// each match lists the queries it matched
+        #[arg(short = 'q', long = "query", conflicts_with = "profile")]
+        queries: Vec<String>,
+        /// Find the tool with exactly this name instead of searching with a query
+        #[arg(long, conflicts_with = "query")]
+        name: Option<String>,
+        /// Only match tools declaring this tag (repeatable; all must be present)
+        #[arg(long)]
+        tag: Vec<String>,
+        /// Alias file mapping shorthand to tool names [default: ~/.config/toolsearch/aliases.json]
+        #[arg(long)]
+        aliases: Option<String>,
+        /// File of tools to sort first [default: ~/.config/toolsearch/pins.json]
+        #[arg(long)]
+        pins: Option<String>,
+        /// Leave out tools named like this pattern, or server/pattern; server/*
+        /// leaves out a whole server (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        block: Vec<String>,
+        /// Run the saved query template with this name instead of a query
+        #[arg(long, conflicts_with_all = ["query", "name"])]
+        profile: Option<String>,
+        /// Value for a template placeholder, as KEY=VALUE (repeatable)
+        #[arg(long, requires = "profile", value_parser = parse_param)]
+        param: Vec<(String, String)>,
+        /// Query template file [default: ~/.config/toolsearch/templates.json]
+        #[arg(long, requires = "profile")]
+        templates: Option<String>,
+        /// Return a single match, stopping as soon as any server has one
+        /// (servers still answering have no timings to show)
+        #[arg(long, conflicts_with = "timings")]
+        first: bool,
+        /// Output format: text, table, json, csv, markdown, html, langchain, or llamaindex
         #[arg(short, long, default_value = "text")]
         format: String,
+        /// Truncate table descriptions to this many columns (0 disables truncation)
+        #[arg(long, default_value_t = 50)]
+        description_width: usize,
+        /// Keep only these fields of each match, e.g. name,server,description
+        /// (the name and server are always kept)
+        #[arg(long, value_parser = Projection::parse)]
+        fields_out: Option<Projection>,
+        /// Blank out the values of schema keys named like this pattern,
+        /// such as default or *_token (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        redact: Vec<String>,
+        /// Print JSON on one line instead of pretty-printed
+        #[arg(long)]
+        compact: bool,
+        /// Name tools server__tool in langchain and llamaindex specs, as
+        /// `toolsearch proxy` serves them (always done when two servers have
+        /// a tool of the same name)
+        #[arg(long)]
+        namespaced: bool,
+        /// Write the results to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
         /// Maximum number of results to return
         #[arg(short, long)]
         limit: Option<usize>,
         /// Sort by tool name instead of server name
         #[arg(long)]
         sort_by_tool: bool,
+        /// Sort by server priority (highest first)
+        #[arg(long, conflicts_with = "sort_by_tool")]
+        sort_by_priority: bool,
+        /// Sort by relevance score (highest first), with the config's boosts applied
+        #[arg(long, conflicts_with_all = ["sort_by_tool", "sort_by_priority"])]
+        sort_by_score: bool,
+        /// JSON file of usage weights by tool name or server/tool, blended into
+        /// scores (use with --sort-by-score)
+        #[arg(long, value_name = "FILE")]
+        popularity: Option<String>,
+        /// Include each server's implementation name, version, and protocol version
+        #[arg(long)]
+        server_info: bool,
+        /// OpenAPI (JSON) documents whose operations are searched as tools
+        #[arg(long)]
+        openapi: Vec<String>,
+        /// Directories of *.tool.json definitions to search as tools
+        #[arg(long)]
+        tools_dir: Vec<String>,
+        /// Record the MCP messages exchanged with each stdio server in
+        /// DIR/<server>.jsonl
+        #[arg(long, value_name = "DIR")]
+        record: Option<String>,
+        /// Answer each server from its recording in DIR instead of connecting
+        #[arg(long, value_name = "DIR", conflicts_with = "record")]
+        replay: Option<String>,
+        /// Show how long each server took to connect and list its tools
+        /// (with --format text, table or json)
+        #[arg(long)]
+        timings: bool,
+        /// Fail unless at least this percentage of servers respond
+        #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(0..=100))]
+        min_responding: Option<u8>,
+        /// Print what the search would do without connecting to any server
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't record this search in the history
+        #[arg(long)]
+        no_history: bool,
+        /// Connect to the servers directly even if a daemon is running for this configuration
+        #[arg(long)]
+        no_daemon: bool,
+    },
+    /// Show past searches, numbered for `rerun`
+    History {
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Show only the most recent searches
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+    /// Run a search from the history again
+    Rerun {
+        /// Number of the search, as shown by `history`
+        n: usize,
+        /// Output format: text, table, json, csv, markdown, html, langchain, or llamaindex
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Truncate table descriptions to this many columns (0 disables truncation)
+        #[arg(long, default_value_t = 50)]
+        description_width: usize,
+        /// Don't record this search in the history again
+        #[arg(long)]
+        no_history: bool,
     },
     /// List all tools from all servers
     List {
         /// Path to JSON configuration file with server configurations
         #[arg(short, long)]
         config: String,
-        /// Output format: json, text, or table
+        /// Only list tools of these servers (repeatable)
+        #[arg(short, long)]
+        server: Vec<String>,
+        /// Only list tools declaring this tag (repeatable; all must be present)
+        #[arg(long)]
+        tag: Vec<String>,
+        /// File of tools to list first [default: ~/.config/toolsearch/pins.json]
+        #[arg(long)]
+        pins: Option<String>,
+        /// Leave out tools named like this pattern, or server/pattern; server/*
+        /// leaves out a whole server (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        block: Vec<String>,
+        /// Output format: text, table, json, csv, markdown, html, langchain, or llamaindex
         #[arg(short, long, default_value = "text")]
         format: String,
+        /// Truncate table descriptions to this many columns (0 disables truncation)
+        #[arg(long, default_value_t = 50)]
+        description_width: usize,
+        /// Keep only these fields of each match, e.g. name,server,description
+        /// (the name and server are always kept)
+        #[arg(long, value_parser = Projection::parse)]
+        fields_out: Option<Projection>,
+        /// Blank out the values of schema keys named like this pattern,
+        /// such as default or *_token (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        redact: Vec<String>,
+        /// Print JSON on one line instead of pretty-printed
+        #[arg(long)]
+        compact: bool,
+        /// Name tools server__tool in langchain and llamaindex specs, as
+        /// `toolsearch proxy` serves them (always done when two servers have
+        /// a tool of the same name)
+        #[arg(long)]
+        namespaced: bool,
+        /// Write the results to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
         /// Maximum number of results to return
         #[arg(short, long)]
         limit: Option<usize>,
         /// Sort by tool name instead of server name
         #[arg(long)]
         sort_by_tool: bool,
+        /// Sort by server priority (highest first)
+        #[arg(long, conflicts_with = "sort_by_tool")]
+        sort_by_priority: bool,
+        /// Sort by relevance score (highest first), with the config's boosts applied
+        #[arg(long, conflicts_with_all = ["sort_by_tool", "sort_by_priority"])]
+        sort_by_score: bool,
+        /// JSON file of usage weights by tool name or server/tool, blended into
+        /// scores (use with --sort-by-score)
+        #[arg(long, value_name = "FILE")]
+        popularity: Option<String>,
+        /// Include each server's implementation name, version, and protocol version
+        #[arg(long)]
+        server_info: bool,
+        /// OpenAPI (JSON) documents whose operations are searched as tools
+        #[arg(long)]
+        openapi: Vec<String>,
+        /// Directories of *.tool.json definitions to search as tools
+        #[arg(long)]
+        tools_dir: Vec<String>,
+        /// Record the MCP messages exchanged with each stdio server in
+        /// DIR/<server>.jsonl
+        #[arg(long, value_name = "DIR")]
+        record: Option<String>,
+        /// Answer each server from its recording in DIR instead of connecting
+        #[arg(long, value_name = "DIR", conflicts_with = "record")]
+        replay: Option<String>,
+        /// Show how long each server took to connect and list its tools
+        /// (with --format text, table or json)
+        #[arg(long)]
+        timings: bool,
+        /// Fail unless at least this percentage of servers respond
+        #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(0..=100))]
+        min_responding: Option<u8>,
+        /// Print what the search would do without connecting to any server
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Keep the servers of a configuration running and answer searches from them
+    Daemon {
+        /// Path to JSON configuration file with server configurations
+        #[arg(short, long)]
+        config: String,
+    },
+    /// Serve the tools of all configured servers as one MCP server on stdio
+    Proxy {
+        /// Path to JSON configuration file with server configurations
+        #[arg(short, long)]
+        config: String,
+        /// Only expose tools matching this query (auto-detects search mode)
+        #[arg(short, long)]
+        query: Option<String>,
+        /// Never expose tools named like this pattern, or server/pattern;
+        /// server/* hides a whole server (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        block: Vec<String>,
+        /// Blank out the values of schema keys named like this pattern
+        /// (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        redact: Vec<String>,
+        /// Timeout in seconds for connecting to and listing each server
+        #[arg(long, default_value_t = 30)]
+        timeout: u64,
     },
     /// Validate server configuration file
     Validate {
         /// Path to JSON configuration file with server configurations
         #[arg(short, long)]
         config: String,
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Audit the tools of each server for missing descriptions, generic names and other issues
+    Lint {
+        /// Path to JSON configuration file with server configurations
+        #[arg(short, long)]
+        config: String,
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Timeout in seconds for each server
+        #[arg(long, default_value_t = 30)]
+        timeout: u64,
+    },
+    /// Check that each configured server responds, and how quickly
+    Ping {
+        /// Path to JSON configuration file with server configurations
+        #[arg(short, long)]
+        config: String,
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Timeout in seconds for each server
+        #[arg(long, default_value_t = 30)]
+        timeout: u64,
+    },
+    /// Measure connect, list, and search latency per server and overall
+    Bench {
+        /// Path to JSON configuration file with server configurations
+        #[arg(short, long)]
+        config: String,
+        /// Number of searches to run
+        #[arg(short, long, default_value_t = 5)]
+        iterations: usize,
+        /// Query to search for (lists all tools if omitted)
+        #[arg(short, long)]
+        query: Option<String>,
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Timeout in seconds for each server
+        #[arg(long, default_value_t = 30)]
+        timeout: u64,
+    },
+    /// Discover servers in a public MCP registry
+    Registry {
+        #[command(subcommand)]
+        command: RegistryCommands,
+    },
+    /// Inspect a configuration file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Flag risky patterns: plaintext secrets, duplicate names, plain HTTP URLs,
+    /// missing timeouts and missing paths
+    Lint {
+        /// Path to JSON configuration file with server configurations
+        #[arg(short, long)]
+        config: String,
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Fix duplicate names and missing timeouts, and save the file
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum RegistryCommands {
+    /// Search the registry for servers matching a query
+    Search {
+        /// Search query
+        query: String,
+        /// Base URL of the registry API
+        #[arg(long, default_value = toolsearch::registry::DEFAULT_REGISTRY_URL)]
+        registry_url: String,
+        /// Optional configuration file; servers already configured are marked as installed
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Maximum number of results to return
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    let duplicates = cli.duplicates;
+    let verbose = cli.verbose > 0;
+    if verbose {
+        tracing::subscriber::set_global_default(StderrDiagnostics::new(cli.verbose))?;
+    }
+    let config_headers = cli.config_header;
+    let source = |location: &str| {
+        config_headers
+            .iter()
+            .fold(ConfigSource::parse(location), |source, (name, value)| source.with_header(name, value))
+    };
+
+    let command = match cli.command {
+        Commands::Rerun {
+            n,
+            format,
+            description_width,
+            no_history,
+        } => rerun_command(n, format, description_width, no_history)?,
+        command => command,
+    };
 
-    match cli.command {
+    match command {
         Commands::Search {
             config,
             query,
+            mut queries,
+            name,
+            tag,
+            aliases,
+            pins,
+            block,
+            profile,
+            param,
+            templates,
+            first,
             format,
+            description_width,
+            fields_out,
+            redact,
+            compact,
+            namespaced,
+            output,
             limit,
             sort_by_tool,
+            sort_by_priority,
+            sort_by_score,
+            popularity,
+            server_info,
+            openapi,
+            tools_dir,
+            record,
+            replay,
+            timings,
+            min_responding,
+            dry_run,
+            no_history,
+            no_daemon,
         } => {
+            let query = match &profile {
+                Some(profile) => {
+                    let templates = match &templates {
+                        Some(path) => TemplateSet::load(path)?,
+                        None => TemplateSet::load_default()?,
+                    };
+                    Some(templates.render(profile, &param.into_iter().collect())?)
+                }
+                None => query,
+            };
+            // Without a query or name, the first -q query takes their place
+            let query = match query {
+                None if name.is_none() && !queries.is_empty() => Some(queries.remove(0)),
+                query => query,
+            };
+
             // Load and validate servers
-            let servers = load_servers(&config)?;
+            let servers = load_config(&source(&config), duplicates).await?;
+            let target = name
+                .iter()
+                .chain(&query)
+                .chain(&queries)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("' or '");
+            let target = if target.is_empty() {
+                format!("tag {}", tag.join(", "))
+            } else {
+                target
+            };
+
+            let aliases = match &aliases {
+                Some(path) => AliasMap::load(path)?,
+                None => AliasMap::load_default()?,
+            };
+            let pinned = match &pins {
+                Some(path) => Pins::load(path)?,
+                None => Pins::load_default()?,
+            }
+            .pinned(profile.as_deref());
 
             // Build search with simple API
-            let mut builder = SearchBuilder::new(servers).query(&query);
+            let (servers, replayed) = split_replayed(servers, replay.as_deref());
+            let mut builder = SearchBuilder::new(servers)
+                .observer(StderrWarnings)
+                .aliases(aliases.clone());
+            for source in replayed {
+                builder = builder.source(source);
+            }
+            if let Some(dir) = &record {
+                builder = builder.record(dir);
+            }
+            builder = match (&name, &query) {
+                (Some(name), _) => builder.name(name),
+                (None, Some(query)) => builder.query(query),
+                (None, None) => builder,
+            };
+            for q in &queries {
+                builder = builder.or_query(q);
+            }
+            for t in &tag {
+                builder = builder.tag(t);
+            }
+            for tool in &pinned {
+                builder = builder.pin(tool);
+            }
+            for entry in &block {
+                builder = builder.block(entry);
+            }
             
             if let Some(max) = limit {
                 builder = builder.limit(max);
@@ -75,22 +524,239 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             
             if sort_by_tool {
                 builder = builder.sort_by_tool();
+            } else if sort_by_priority {
+                builder = builder.sort_by_priority();
+            } else if sort_by_score {
+                builder = builder.sort_by_score();
+            }
+
+            if server_info {
+                builder = builder.include_server_info();
+            }
+
+            for pattern in &redact {
+                builder = builder.redact(pattern);
+            }
+
+            if let Some(projection) = &fields_out {
+                builder = builder.projection(projection.clone());
+            }
+
+            if let Some(percent) = min_responding {
+                builder = builder.min_response_rate(f32::from(percent) / 100.0);
+            }
+
+            if let Some(path) = &popularity {
+                builder = builder.popularity(Popularity::load(path)?);
+            }
+
+            for spec in &openapi {
+                builder = builder.source(OpenApiSource::from_file(spec)?);
+            }
+
+            for dir in &tools_dir {
+                builder = builder.source(DirectorySource::new(dir));
+            }
+
+            check_timings_format(timings, &format)?;
+            let results_output = ResultsOutput {
+                format: &format,
+                description_width,
+                compact,
+                namespaced,
+                file: output.as_deref(),
+            };
+            let plan = builder.explain_plan();
+            if dry_run {
+                print_plan(&plan, &format)?;
+                return Ok(());
+            }
+
+            let entry = |hits| HistoryEntry {
+                query: query.clone(),
+                queries: queries.clone(),
+                name: name.clone(),
+                tags: tag.clone(),
+                limit,
+                openapi: openapi.iter().map(|p| absolute(p)).collect(),
+                tools_dir: tools_dir.iter().map(|p| absolute(p)).collect(),
+                ..HistoryEntry::new(
+                    if source(&config).is_file() { absolute(&config) } else { config.clone() },
+                    plan.mode,
+                    hits,
+                )
+            };
+
+            // A daemon for this configuration answers without starting the servers
+            #[cfg(unix)]
+            if !no_daemon
+                && !first
+                && !timings
+                && !verbose
+                && duplicates == DuplicatePolicy::Error
+                && source(&config).is_file()
+                && min_responding.is_none()
+                && popularity.is_none()
+                && openapi.is_empty()
+                && tools_dir.is_empty()
+                && record.is_none()
+                && replay.is_none()
+            {
+                let request = DaemonRequest {
+                    query: query.clone(),
+                    queries: queries.clone(),
+                    name: name.clone(),
+                    tags: tag.clone(),
+                    aliases,
+                    limit,
+                    sort_by_tool,
+                    sort_by_priority,
+                    sort_by_score,
+                    server_info,
+                    pinned: pinned.clone(),
+                    blocked: Blocklist::new(block.clone()),
+                };
+                if let Some(response) = daemon_search(&config, &request).await {
+                    if let Some(error) = response.error {
+                        return Err(error.into());
+                    }
+                    for warning in &response.warnings {
+                        eprintln!("Warning: {}", warning);
+                    }
+                    let mut results = response.matches;
+                    let redaction = Redaction::new(&redact);
+                    results.iter_mut().for_each(|m| redaction.apply(m));
+                    if let Some(projection) = &fields_out {
+                        results.iter_mut().for_each(|m| projection.apply(m));
+                    }
+                    let results = &results;
+                    print_results(results, None, &results_output, &format!("Found {} tool(s) matching '{}'", results.len(), target))?;
+                    if !no_history {
+                        record_history(&entry(results.len()));
+                    }
+                    return Ok(());
+                }
+            }
+
+            if first {
+                let results: Vec<_> = builder.first().await?.into_iter().collect();
+                print_results(&results, None, &results_output, &format!("Found a tool matching '{}'", target))?;
+                if !no_history {
+                    record_history(&entry(results.len()));
+                }
+                return Ok(());
             }
 
-            let results = builder.search().await?;
-            print_results(&results, &format, &format!("Found {} tool(s) matching '{}'", results.len(), query))?;
+            let report = builder.search_report().await?;
+            if verbose {
+                print_resolved(&report);
+            }
+            let results = &report.matches;
+            let timings = timings.then_some(report.timings.as_slice());
+            print_results(results, timings, &results_output, &format!("Found {} tool(s) matching '{}'", results.len(), target))?;
+            if !no_history {
+                record_history(&entry(results.len()));
+            }
+        }
+        Commands::History { format, limit } => {
+            let history = History::open_default().ok_or(NO_HISTORY_LOCATION)?;
+            let entries = history.entries()?;
+            let skip = limit.map_or(0, |max| entries.len().saturating_sub(max));
+            let numbered = entries.iter().enumerate().skip(skip).map(|(i, e)| (i + 1, e));
+
+            if format == "json" {
+                let json: Vec<_> = numbered
+                    .map(|(n, entry)| serde_json::json!({ "n": n, "entry": entry }))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else if entries.is_empty() {
+                println!("No search history");
+            } else {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                for (n, entry) in numbered {
+                    let mode = match entry.name {
+                        Some(_) => "Name".to_string(),
+                        None => format!("{:?}", entry.mode),
+                    };
+                    println!(
+                        "{:>4}  {:>8}  {} ({}, {} hit(s), {})",
+                        n,
+                        age(now.saturating_sub(entry.timestamp)),
+                        entry.target(),
+                        mode,
+                        entry.hits,
+                        entry.config
+                    );
+                }
+            }
         }
+        Commands::Rerun { .. } => unreachable!("rerun is resolved to a search above"),
         Commands::List {
             config,
+            server,
+            tag,
+            pins,
+            block,
             format,
+            description_width,
+            fields_out,
+            redact,
+            compact,
+            namespaced,
+            output,
             limit,
             sort_by_tool,
+            sort_by_priority,
+            sort_by_score,
+            popularity,
+            server_info,
+            openapi,
+            tools_dir,
+            record,
+            replay,
+            timings,
+            min_responding,
+            dry_run,
         } => {
+            let pinned = match &pins {
+                Some(path) => Pins::load(path)?,
+                None => Pins::load_default()?,
+            }
+            .pinned(None);
+
             // Load and validate servers
-            let servers = load_servers(&config)?;
+            let mut servers = load_config(&source(&config), duplicates).await?;
+            if !server.is_empty() {
+                let known = |name: &String| servers.iter().any(|s| s.name == *name);
+                if let Some(unknown) = server.iter().find(|name| !known(name)) {
+                    return Err(ConfigError::UnknownServer {
+                        server: unknown.clone(),
+                    }
+                    .into());
+                }
+                servers.retain(|s| server.contains(&s.name));
+            }
 
             // Build search to list all tools
-            let mut builder = SearchBuilder::new(servers);
+            let (servers, replayed) = split_replayed(servers, replay.as_deref());
+            let mut builder = SearchBuilder::new(servers).observer(StderrWarnings);
+            for source in replayed {
+                builder = builder.source(source);
+            }
+            if let Some(dir) = &record {
+                builder = builder.record(dir);
+            }
+            for t in &tag {
+                builder = builder.tag(t);
+            }
+            for tool in &pinned {
+                builder = builder.pin(tool);
+            }
+            for entry in &block {
+                builder = builder.block(entry);
+            }
             
             if let Some(max) = limit {
                 builder = builder.limit(max);
@@ -98,91 +764,858 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             
             if sort_by_tool {
                 builder = builder.sort_by_tool();
+            } else if sort_by_priority {
+                builder = builder.sort_by_priority();
+            } else if sort_by_score {
+                builder = builder.sort_by_score();
+            }
+
+            if server_info {
+                builder = builder.include_server_info();
+            }
+
+            for pattern in &redact {
+                builder = builder.redact(pattern);
+            }
+
+            if let Some(projection) = &fields_out {
+                builder = builder.projection(projection.clone());
+            }
+
+            if let Some(percent) = min_responding {
+                builder = builder.min_response_rate(f32::from(percent) / 100.0);
+            }
+
+            if let Some(path) = &popularity {
+                builder = builder.popularity(Popularity::load(path)?);
+            }
+
+            for spec in &openapi {
+                builder = builder.source(OpenApiSource::from_file(spec)?);
             }
 
-            let results = builder.search().await?;
-            print_results(&results, &format, &format!("Found {} tool(s) across all servers", results.len()))?;
+            for dir in &tools_dir {
+                builder = builder.source(DirectorySource::new(dir));
+            }
+
+            check_timings_format(timings, &format)?;
+            if dry_run {
+                print_plan(&builder.explain_plan(), &format)?;
+                return Ok(());
+            }
+
+            let results_output = ResultsOutput {
+                format: &format,
+                description_width,
+                compact,
+                namespaced,
+                file: output.as_deref(),
+            };
+            let report = builder.search_report().await?;
+            if verbose {
+                print_resolved(&report);
+            }
+            let results = &report.matches;
+            let timings = timings.then_some(report.timings.as_slice());
+            print_results(results, timings, &results_output, &format!("Found {} tool(s) across all servers", results.len()))?;
+        }
+        Commands::Daemon { config } => {
+            if !source(&config).is_file() {
+                return Err("The daemon watches a configuration file; --config can't be - or a URL".into());
+            }
+            run_daemon(&config).await?
+        }
+        Commands::Proxy {
+            config,
+            query,
+            block,
+            redact,
+            timeout,
+        } => {
+            let servers = load_config(&source(&config), duplicates).await?;
+            // stdout carries the protocol; warnings go to stderr
+            let options = SearchOptions {
+                timeout: Some(std::time::Duration::from_secs(timeout)),
+                observer: Some(std::sync::Arc::new(StderrWarnings)),
+                blocklist: Blocklist::new(block),
+                redaction: Redaction::new(redact),
+                ..Default::default()
+            };
+            let mut proxy = ToolProxy::new(servers).with_options(options);
+            if let Some(query) = query {
+                let criteria = SearchBuilder::new(vec![])
+                    .query(query)
+                    .aliases(AliasMap::load_default()?)
+                    .explain_plan()
+                    .criteria;
+                proxy = proxy.with_criteria(criteria);
+            }
+            proxy.serve_stdio().await?;
         }
-        Commands::Validate { config } => {
-            match load_servers(&config) {
-                Ok(servers) => {
-                    println!("✓ Configuration file is valid!");
-                    println!("✓ Found {} server(s)", servers.len());
-                    for server in &servers {
-                        println!("  - {}", server.name);
+        Commands::Validate { config, format } => {
+            let report = validate_source(&source(&config)).await;
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if let Some(error) = &report.error {
+                eprintln!("✗ {}", error.message);
+                if let Some(suggestion) = &error.suggestion {
+                    eprintln!("  {}", suggestion);
+                }
+            } else if report.valid {
+                println!("✓ Configuration file is valid!");
+                println!("✓ Found {} server(s)", report.servers.len());
+                for server in &report.servers {
+                    println!("  - {}", server.name);
+                }
+            } else {
+                for server in &report.servers {
+                    match &server.error {
+                        Some(error) => {
+                            println!("  ✗ {}: {} [{}]", server.name, error.message, error.code);
+                            if let Some(suggestion) = &error.suggestion {
+                                println!("    {}", suggestion);
+                            }
+                        }
+                        None => println!("  - {}", server.name),
                     }
                 }
-                Err(e) => {
-                    eprintln!("✗ Configuration error: {}", e);
-                    std::process::exit(1);
+                let invalid = report.invalid_servers().count();
+                eprintln!("✗ {} of {} server(s) are invalid", invalid, report.servers.len());
+            }
+            if !report.valid {
+                std::process::exit(1);
+            }
+        }
+        Commands::Lint {
+            config,
+            format,
+            timeout,
+        } => {
+            let servers = load_config(&source(&config), duplicates).await?;
+            let options = SearchOptions {
+                timeout: Some(std::time::Duration::from_secs(timeout)),
+                ..Default::default()
+            };
+            let catalog = fetch_catalog(&servers, &options).await?;
+            // Malformed tools are reported as issues; servers that failed or
+            // were cut short left tools unaudited
+            let mut unaudited = false;
+            for warning in catalog.warnings() {
+                match warning {
+                    SearchWarning::MalformedTool { .. } => {}
+                    SearchWarning::InvalidConfig { .. }
+                    | SearchWarning::ServerFailed { .. }
+                    | SearchWarning::ToolListTruncated { .. } => {
+                        unaudited = true;
+                        eprintln!("Warning: {}", warning);
+                    }
+                    _ => eprintln!("Warning: {}", warning),
                 }
             }
+            let issues = lint_catalog(&catalog);
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&issues)?);
+            } else if issues.is_empty() {
+                println!("✓ No issues in {} tool(s)", catalog.len());
+            } else {
+                for issue in &issues {
+                    println!("{}", issue);
+                }
+                println!("\n✗ {} issue(s) in {} tool(s)", issues.len(), catalog.len());
+            }
+
+            if !issues.is_empty() || unaudited {
+                std::process::exit(1);
+            }
         }
-    }
+        Commands::Ping {
+            config,
+            format,
+            timeout,
+        } => {
+            let servers = load_config(&source(&config), duplicates).await?;
+            let results = ping_all(&servers, Some(std::time::Duration::from_secs(timeout))).await;
 
-    Ok(())
-}
+            if format == "json" {
+                let json: Vec<_> = results
+                    .iter()
+                    .map(|r| {
+                        serde_json::json!({
+                            "server_name": r.server_name,
+                            "status": r.status,
+                            "latency_ms": r.latency.as_millis(),
+                            "protocol_version": r.protocol_version,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else {
+                for result in &results {
+                    match &result.status {
+                        HealthStatus::Healthy => match &result.protocol_version {
+                            Some(version) => println!(
+                                "✓ {} ({} ms, protocol {})",
+                                result.server_name,
+                                result.latency.as_millis(),
+                                version
+                            ),
+                            None => println!("✓ {} ({} ms)", result.server_name, result.latency.as_millis()),
+                        },
+                        HealthStatus::Unhealthy { error } => println!(
+                            "✗ {} ({} ms): {}",
+                            result.server_name,
+                            result.latency.as_millis(),
+                            error
+                        ),
+                    }
+                }
+            }
 
-/// Print search results in the specified format
-fn print_results(
-    results: &[toolsearch::ToolSearchMatch],
-    format: &str,
-    header: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    match format {
-        "json" => {
-            println!("{}", serde_json::to_string_pretty(results)?);
+            if results.iter().any(|r| !r.status.is_healthy()) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Bench {
+            config,
+            iterations,
+            query,
+            format,
+            timeout,
+        } => {
+            let servers = load_config(&source(&config), duplicates).await?;
+            let mut overall = Vec::new();
+            let mut per_server: Vec<(String, ServerSamples)> = servers
+                .iter()
+                .map(|s| (s.name.clone(), ServerSamples::default()))
+                .collect();
+
+            for _ in 0..iterations.max(1) {
+                let mut builder = SearchBuilder::new(servers.clone())
+                    .timeout(timeout)
+                    .observer(StderrWarnings);
+                if let Some(query) = &query {
+                    builder = builder.query(query);
+                }
+                let started = std::time::Instant::now();
+                let report = builder.search_report().await?;
+                overall.push(started.elapsed());
+
+                for timing in &report.timings {
+                    if let Some((_, samples)) =
+                        per_server.iter_mut().find(|(name, _)| *name == timing.server_name)
+                    {
+                        samples.record(timing);
+                    }
+                }
+            }
+
+            print_bench(&per_server, &LatencyStats::from_samples(&overall), &format)?;
         }
-        "table" => {
-            if results.is_empty() {
-                println!("No results found");
+        Commands::Config {
+            command: ConfigCommands::Lint { config, format, fix },
+        } => {
+            let mut servers = load_config(&source(&config), DuplicatePolicy::Keep).await?;
+            if fix {
+                if !source(&config).is_file() {
+                    return Err("--fix needs a configuration file to save to".into());
+                }
+                if source(&config).read().await?.has_includes() {
+                    return Err("--fix can't save a configuration that includes other files".into());
+                }
+                let fixed = fix_config(&mut servers);
+                if !fixed.is_empty() {
+                    save_servers(&config, &servers)?;
+                    eprintln!("Fixed {} issue(s) in {}", fixed.len(), config);
+                }
+            }
+            let issues = lint_config(&servers);
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&issues)?);
+            } else if issues.is_empty() {
+                println!("✓ No issues in {} server(s)", servers.len());
             } else {
-                println!("{}\n", header);
-                println!("{:<30} {:<40} {}", "SERVER", "TOOL NAME", "DESCRIPTION");
-                println!("{}", "-".repeat(100));
-                for result in results {
-                    let desc = result
-                        .tool
-                        .description
-                        .as_ref()
-                        .map(|d| {
-                            let desc_str: &str = d.as_ref();
-                            if desc_str.len() > 50 {
-                                format!("{}...", &desc_str[..47])
-                            } else {
-                                desc_str.to_string()
-                            }
-                        })
-                        .unwrap_or_else(|| "N/A".to_string());
-                    println!(
-                        "{:<30} {:<40} {}",
-                        result.server_name,
-                        result.tool_name(),
-                        desc
-                    );
+                for issue in &issues {
+                    println!("{}", issue);
                 }
+                println!("\n✗ {} issue(s) in {} server(s)", issues.len(), servers.len());
+            }
+
+            if issues.iter().any(|issue| issue.severity >= Severity::Warning) {
+                std::process::exit(1);
             }
         }
-        _ => {
-            if results.is_empty() {
-                println!("No results found");
+        Commands::Registry {
+            command:
+                RegistryCommands::Search {
+                    query,
+                    registry_url,
+                    config,
+                    format,
+                    limit,
+                },
+        } => {
+            let installed = match config {
+                Some(path) => load_config(&source(&path), duplicates).await?,
+                None => Vec::new(),
+            };
+
+            let servers = RegistryClient::new(registry_url)
+                .search(&query, Some(limit), Some(std::time::Duration::from_secs(30)))
+                .await?;
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&servers)?);
+            } else if servers.is_empty() {
+                println!("No registry servers found");
             } else {
-                println!("{}\n", header);
-                for result in results {
-                    println!("Server: {}", result.server_name);
-                    println!("  Name: {}", result.tool_name());
-                    if let Some(desc) = &result.tool.description {
-                        println!("  Description: {}", desc.as_ref());
+                println!("Found {} registry server(s) matching '{}'\n", servers.len(), query);
+                for server in &servers {
+                    let marker = if server.is_installed(&installed) { " (installed)" } else { "" };
+                    println!("Server: {}{}", server.name, marker);
+                    if let Some(desc) = &server.description {
+                        println!("  Description: {}", desc);
+                    }
+                    if let Some(hint) = server.install_hint() {
+                        println!("  Install: {}", hint);
                     }
-                    if let Some(title) = &result.tool.title {
-                        let title_str: &str = title.as_ref();
-                        println!("  Title: {}", title_str);
+                    if let Some(repo) = &server.repository {
+                        println!("  Repository: {}", repo);
                     }
                     println!();
                 }
             }
         }
     }
+
+    Ok(())
+}
+
+/// Error for history commands when there is no home directory to keep it in
+const NO_HISTORY_LOCATION: &str = "Cannot locate the search history: neither XDG_DATA_HOME nor HOME is set";
+
+/// Turn `rerun <n>` into the search it repeats
+fn rerun_command(
+    n: usize,
+    format: String,
+    description_width: usize,
+    no_history: bool,
+) -> Result<Commands, Box<dyn std::error::Error>> {
+    let history = History::open_default().ok_or(NO_HISTORY_LOCATION)?;
+    let entry = history
+        .get(n)?
+        .ok_or_else(|| format!("No search number {} in the history (see `toolsearch history`)", n))?;
+    Ok(Commands::Search {
+        config: entry.config,
+        query: entry.query,
+        queries: entry.queries,
+        name: entry.name,
+        tag: entry.tags,
+        aliases: None,
+        pins: None,
+        block: Vec::new(),
+        profile: None,
+        param: Vec::new(),
+        templates: None,
+        first: false,
+        format,
+        description_width,
+        fields_out: None,
+        redact: Vec::new(),
+        compact: false,
+        namespaced: false,
+        output: None,
+        limit: entry.limit,
+        sort_by_tool: false,
+        sort_by_priority: false,
+        sort_by_score: false,
+        popularity: None,
+        server_info: false,
+        openapi: entry.openapi,
+        tools_dir: entry.tools_dir,
+        record: None,
+        replay: None,
+        timings: false,
+        min_responding: None,
+        dry_run: false,
+        no_history,
+        no_daemon: false,
+    })
+}
+
+/// Answer from the daemon for `config`, or `None` if no daemon is running
+#[cfg(unix)]
+async fn daemon_search(config: &str, request: &DaemonRequest) -> Option<DaemonResponse> {
+    let socket = daemon::socket_path(config)?;
+    if !socket.exists() {
+        return None;
+    }
+    daemon::request(&socket, request).await.ok()
+}
+
+/// Serve searches for `config` until interrupted, then stop the servers
+#[cfg(unix)]
+async fn run_daemon(config: &str) -> Result<(), Box<dyn std::error::Error>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let daemon = std::sync::Arc::new(daemon::Daemon::new(config)?);
+    let socket = daemon::socket_path(config)
+        .ok_or("Cannot locate the daemon socket: none of XDG_RUNTIME_DIR, XDG_DATA_HOME, or HOME is set")?;
+    let listener = daemon::bind(&socket)?;
+    eprintln!("Listening on {} (Ctrl-C to stop)", socket.display());
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let result = tokio::select! {
+        result = std::sync::Arc::clone(&daemon).serve(listener) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+        _ = terminate.recv() => Ok(()),
+    };
+    let _ = std::fs::remove_file(&socket);
+    Ok(result?)
+}
+
+#[cfg(not(unix))]
+async fn run_daemon(_config: &str) -> Result<(), Box<dyn std::error::Error>> {
+    Err("The daemon needs Unix domain sockets, which this platform doesn't support".into())
+}
+
+/// Load a configuration, warning when a remote one is a stale cached copy
+async fn load_config(
+    source: &ConfigSource,
+    duplicates: DuplicatePolicy,
+) -> Result<Vec<toolsearch::ServerConfig>, toolsearch::ToolSearchError> {
+    let text = source.read().await?;
+    if let Some(stale) = &text.stale {
+        eprintln!("Warning: could not fetch {}: {}", source, stale);
+    }
+    text.servers(duplicates)
+}
+
+/// The servers to connect to, and the recordings answering for the others:
+/// with `--replay DIR`, each server is answered from its recording in DIR
+fn split_replayed(
+    servers: Vec<toolsearch::ServerConfig>,
+    replay: Option<&str>,
+) -> (Vec<toolsearch::ServerConfig>, Vec<ReplaySource>) {
+    match replay {
+        Some(dir) => {
+            let dir = std::path::Path::new(dir);
+            let replayed = servers.into_iter().map(|s| ReplaySource::for_server(s, dir)).collect();
+            (Vec::new(), replayed)
+        }
+        None => (servers, Vec::new()),
+    }
+}
+
+/// Append a search to the history, warning instead of failing if that fails
+fn record_history(entry: &HistoryEntry) {
+    let Some(history) = History::open_default() else {
+        return;
+    };
+    if let Err(e) = history.append(entry) {
+        eprintln!("Warning: could not record search history in {}: {}", history.path().display(), e);
+    }
+}
+
+/// Parse a `--config-header NAME: VALUE` argument
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    let (name, value) = header
+        .split_once(':')
+        .ok_or_else(|| format!("expected NAME: VALUE, got '{}'", header))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+/// Parse a `--param KEY=VALUE` argument
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    let (key, value) = param
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", param))?;
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// `path` made absolute, so `rerun` works from any directory
+fn absolute(path: &str) -> String {
+    std::path::absolute(path).map_or_else(|_| path.to_string(), |p| p.display().to_string())
+}
+
+/// Short human-readable age, e.g. `5m ago`
+fn age(seconds: u64) -> String {
+    match seconds {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+/// Prints search warnings (skipped servers) to stderr as they happen
+struct StderrWarnings;
+
+impl Observer for StderrWarnings {
+    fn on_warning(&self, warning: &SearchWarning) {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+/// Prints `tracing` events to stderr for `--verbose`: toolsearch's from info
+/// up, and with `-vv` and more, those of the MCP client too
+struct StderrDiagnostics {
+    toolsearch: tracing::Level,
+    others: tracing::Level,
+    started: std::time::Instant,
+    next_span: std::sync::atomic::AtomicU64,
+}
+
+impl StderrDiagnostics {
+    fn new(verbosity: u8) -> Self {
+        use tracing::Level;
+
+        let (toolsearch, others) = match verbosity {
+            0 | 1 => (Level::INFO, Level::WARN),
+            2 => (Level::DEBUG, Level::DEBUG),
+            _ => (Level::TRACE, Level::TRACE),
+        };
+        Self {
+            toolsearch,
+            others,
+            started: std::time::Instant::now(),
+            next_span: 1.into(),
+        }
+    }
+}
+
+impl tracing::Subscriber for StderrDiagnostics {
+    fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+        let max = if metadata.target().starts_with("toolsearch") {
+            self.toolsearch
+        } else {
+            self.others
+        };
+        *metadata.level() <= max
+    }
+
+    fn max_level_hint(&self) -> Option<tracing::level_filters::LevelFilter> {
+        Some(self.toolsearch.max(self.others).into())
+    }
+
+    fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        let id = self.next_span.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        tracing::span::Id::from_u64(id)
+    }
+
+    fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        struct Fields(String, String);
+
+        impl tracing::field::Visit for Fields {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = format!("{:?}", value);
+                } else {
+                    self.1.push_str(&format!(" {}={:?}", field.name(), value));
+                }
+            }
+
+            fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                if field.name() == "message" {
+                    self.0 = value.to_string();
+                } else {
+                    self.1.push_str(&format!(" {}={}", field.name(), value));
+                }
+            }
+        }
+
+        let mut fields = Fields(String::new(), String::new());
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        eprintln!(
+            "[{:>8.3}s] {:>5} {}: {}{}",
+            self.started.elapsed().as_secs_f64(),
+            metadata.level(),
+            metadata.target(),
+            fields.0,
+            fields.1
+        );
+    }
+
+    fn enter(&self, _: &tracing::span::Id) {}
+
+    fn exit(&self, _: &tracing::span::Id) {}
+}
+
+/// Latency samples collected for one server during `bench`
+#[derive(Default)]
+struct ServerSamples {
+    connect: Vec<std::time::Duration>,
+    list: Vec<std::time::Duration>,
+    total: Vec<std::time::Duration>,
+    failures: usize,
+}
+
+impl ServerSamples {
+    fn record(&mut self, timing: &toolsearch::ServerTiming) {
+        self.total.push(timing.total);
+        self.connect.extend(timing.connect);
+        self.list.extend(timing.list);
+        if !timing.succeeded {
+            self.failures += 1;
+        }
+    }
+}
+
+/// Min / mean / max of a set of latency samples, in milliseconds
+#[derive(serde::Serialize)]
+struct LatencyStats {
+    min_ms: f64,
+    mean_ms: f64,
+    max_ms: f64,
+}
+
+impl LatencyStats {
+    fn from_samples(samples: &[std::time::Duration]) -> Option<Self> {
+        let ms: Vec<f64> = samples.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        if ms.is_empty() {
+            return None;
+        }
+        Some(Self {
+            min_ms: ms.iter().copied().fold(f64::INFINITY, f64::min),
+            mean_ms: ms.iter().sum::<f64>() / ms.len() as f64,
+            max_ms: ms.iter().copied().fold(0.0, f64::max),
+        })
+    }
+
+    fn cell(stats: &Option<Self>) -> String {
+        stats.as_ref().map_or_else(
+            || "-".to_string(),
+            |s| format!("{:.1}/{:.1}/{:.1}", s.min_ms, s.mean_ms, s.max_ms),
+        )
+    }
+}
+
+/// Print a `bench` summary in the specified format
+fn print_bench(
+    per_server: &[(String, ServerSamples)],
+    overall: &Option<LatencyStats>,
+    format: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if format == "json" {
+        let servers: Vec<_> = per_server
+            .iter()
+            .map(|(name, samples)| {
+                serde_json::json!({
+                    "server_name": name,
+                    "connect": LatencyStats::from_samples(&samples.connect),
+                    "list": LatencyStats::from_samples(&samples.list),
+                    "total": LatencyStats::from_samples(&samples.total),
+                    "failures": samples.failures,
+                })
+            })
+            .collect();
+        let json = serde_json::json!({ "servers": servers, "search": overall });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        println!("Latency in ms (min/mean/max)\n");
+        println!(
+            "{:<30} {:>20} {:>20} {:>20} {:>9}",
+            "SERVER", "CONNECT", "LIST", "TOTAL", "FAILURES"
+        );
+        println!("{}", "-".repeat(103));
+        for (name, samples) in per_server {
+            println!(
+                "{:<30} {:>20} {:>20} {:>20} {:>9}",
+                name,
+                LatencyStats::cell(&LatencyStats::from_samples(&samples.connect)),
+                LatencyStats::cell(&LatencyStats::from_samples(&samples.list)),
+                LatencyStats::cell(&LatencyStats::from_samples(&samples.total)),
+                samples.failures
+            );
+        }
+        println!("\nOverall search: {}", LatencyStats::cell(overall));
+    }
+    Ok(())
+}
+
+/// Print a search plan in the specified format
+fn print_plan(plan: &toolsearch::SearchPlan, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if format == "json" {
+        let sources: Vec<_> = plan
+            .sources
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "name": s.name,
+                    "priority": s.priority,
+                    "invalid": s.invalid,
+                })
+            })
+            .collect();
+        let json = serde_json::json!({
+            "mode": format!("{:?}", plan.mode),
+            "name": plan.criteria.name,
+            "query": plan.criteria.query,
+            "keywords": plan.criteria.keywords,
+            "tags": plan.criteria.tags,
+            "aliases": plan.criteria.aliases,
+            "queries": plan.queries,
+            "regex_error": plan.regex_error,
+            "timeout_ms": plan.timeout.map(|t| t.as_millis()),
+            "sort_order": format!("{:?}", plan.sort_order),
+            "max_results": plan.max_results,
+            "sources": sources,
+        });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        print!("{}", plan);
+    }
+    Ok(())
+}
+
+/// Print the resolved search mode and criteria to stderr
+fn print_resolved(report: &toolsearch::SearchReport) {
+    let criteria = &report.criteria;
+    eprintln!("Resolved mode: {:?}", report.mode());
+    if let Some(name) = &criteria.name {
+        eprintln!("Exact name: {}", name);
+    }
+    if let Some(query) = &criteria.query {
+        eprintln!("Query: {}", query);
+    }
+    if !criteria.keywords.is_empty() {
+        eprintln!("Keywords: {}", criteria.keywords.join(", "));
+    }
+    if !criteria.tags.is_empty() {
+        eprintln!("Tags: {}", criteria.tags.join(", "));
+    }
+    if !criteria.aliases.is_empty() {
+        eprintln!("Alias for: {}", criteria.aliases.join(", "));
+    }
+    eprintln!("Fields: {:?}", criteria.fields);
+    eprintln!("Case sensitive: {}", criteria.case_sensitive);
+    eprintln!("Servers: {}", report.health);
+    eprintln!();
+}
+
+/// Per-server fetch timings as JSON values
+fn timings_json(timings: &[toolsearch::ServerTiming]) -> Vec<serde_json::Value> {
+    let millis = |d: Option<std::time::Duration>| d.map(|d| d.as_millis());
+    timings
+        .iter()
+        .map(|t| {
+            serde_json::json!({
+                "server_name": t.server_name,
+                "connect_ms": millis(t.connect),
+                "list_ms": millis(t.list),
+                "total_ms": t.total.as_millis(),
+                "succeeded": t.succeeded,
+                "protocol_version": t.protocol_version,
+            })
+        })
+        .collect()
+}
+
+/// Write per-server fetch timings as a table
+fn write_timings_table(
+    out: &mut dyn std::io::Write,
+    timings: &[toolsearch::ServerTiming],
+) -> std::io::Result<()> {
+    let cell = |d: Option<std::time::Duration>| {
+        d.map_or_else(|| "-".to_string(), |d| d.as_millis().to_string())
+    };
+    writeln!(
+        out,
+        "\n{:<30} {:>12} {:>12} {:>12}  {:<10}  STATUS",
+        "SERVER", "CONNECT MS", "LIST MS", "TOTAL MS", "PROTOCOL"
+    )?;
+    writeln!(out, "{}", "-".repeat(92))?;
+    for t in timings {
+        writeln!(
+            out,
+            "{:<30} {:>12} {:>12} {:>12}  {:<10}  {}",
+            t.server_name,
+            cell(t.connect),
+            cell(t.list),
+            t.total.as_millis(),
+            t.protocol_version.as_deref().unwrap_or("-"),
+            if t.succeeded { "ok" } else { "failed" }
+        )?;
+    }
     Ok(())
 }
 
+/// `--timings` only goes with formats it can be printed in
+fn check_timings_format(timings: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if timings && !matches!(format, "text" | "table" | "json") {
+        return Err(format!("--timings needs --format text, table or json, not {}", format).into());
+    }
+    Ok(())
+}
+
+/// How and where search results are printed
+struct ResultsOutput<'a> {
+    format: &'a str,
+    description_width: usize,
+    compact: bool,
+    namespaced: bool,
+    /// File to write to instead of stdout
+    file: Option<&'a str>,
+}
+
+/// Print search results in the specified format (text for unknown formats),
+/// followed by per-server timings if given: in JSON, one document with
+/// `matches` and `timings`; otherwise a timings table after the results
+fn print_results(
+    results: &[toolsearch::ToolSearchMatch],
+    timings: Option<&[toolsearch::ServerTiming]>,
+    out: &ResultsOutput,
+    header: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let writer: Box<dyn output::ResultWriter> = match out.format {
+        "table" => Box::new(output::TableWriter::default().description_width(out.description_width)),
+        "json" => Box::new(output::JsonWriter::default().compact(out.compact)),
+        "langchain" => Box::new(
+            output::LangChainWriter::default().compact(out.compact).namespaced(out.namespaced),
+        ),
+        "llamaindex" => Box::new(
+            output::LlamaIndexWriter::default().compact(out.compact).namespaced(out.namespaced),
+        ),
+        format => output::writer(format).unwrap_or_else(|| Box::new(output::TextWriter)),
+    };
+    let write = |w: &mut dyn std::io::Write| -> Result<(), Box<dyn std::error::Error>> {
+        match timings {
+            Some(timings) if out.format == "json" => {
+                let report = serde_json::json!({
+                    "matches": results,
+                    "timings": timings_json(timings),
+                });
+                if out.compact {
+                    serde_json::to_writer(&mut *w, &report)?;
+                } else {
+                    serde_json::to_writer_pretty(&mut *w, &report)?;
+                }
+                writeln!(w)?;
+            }
+            _ => {
+                writer.write(w, results, header)?;
+                if let Some(timings) = timings {
+                    write_timings_table(w, timings)?;
+                }
+            }
+        }
+        Ok(())
+    };
+    match out.file {
+        Some(path) => {
+            let file = std::fs::File::create(path)
+                .map_err(|e| format!("Cannot write results to {}: {}", path, e))?;
+            let mut file = std::io::BufWriter::new(file);
+            write(&mut file)?;
+            std::io::Write::flush(&mut file)?;
+        }
+        None => write(&mut std::io::stdout().lock())?,
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Custom matchers
+//!
+//! A [`Matcher`] set on [`SearchOptions`](crate::SearchOptions) runs on every
+//! tool that passes the search criteria. It can reject the tool or give it a
+//! score, which [`SortOrder::Score`](crate::SortOrder::Score) ranks by. Use it
+//! for logic the built-in criteria can't express, like company naming
+//! conventions or internal allowlists.
+
+use rmcp::model::Tool;
+
+/// Decides whether a tool matches, and how well
+///
+/// Closures of type `Fn(&Tool) -> Option<f32>` implement this trait.
+///
+/// ```
+/// use toolsearch::SearchOptions;
+///
+/// // Only tools following the `team_action` naming convention
+/// let options = SearchOptions::default().with_custom_matcher(|tool: &rmcp::model::Tool| {
+///     tool.name.starts_with("team_").then_some(1.0)
+/// });
+/// ```
+pub trait Matcher: Send + Sync {
+    /// Score of the tool (higher is better), or `None` to leave it out
+    fn matches(&self, tool: &Tool) -> Option<f32>;
+}
+
+impl<F> Matcher for F
+where
+    F: Fn(&Tool) -> Option<f32> + Send + Sync,
+{
+    fn matches(&self, tool: &Tool) -> Option<f32> {
+        self(tool)
+    }
+}
+
+impl std::fmt::Debug for dyn Matcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Matcher(..)")
+    }
+}
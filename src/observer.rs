@@ -0,0 +1,66 @@
+//! Search telemetry hooks
+//!
+//! An [`Observer`] set on [`SearchOptions`](crate::SearchOptions) is told
+//! about each server fetch, each match, and the finished search, so
+//! applications can collect their own analytics without parsing logs.
+
+use crate::{SearchReport, SearchWarning, ToolSearchError, ToolSearchMatch};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Receives events while a search runs
+///
+/// All methods default to doing nothing; implement the ones you need.
+pub trait Observer: Send + Sync {
+    /// A server was contacted and its tools were listed
+    fn on_server_connected(&self, _server_name: &str, _elapsed: Duration) {}
+
+    /// Fetching tools from a server failed
+    fn on_server_failed(&self, _server_name: &str, _error: &ToolSearchError) {}
+
+    /// A page of a server's tool list arrived; `page` counts from 1 and
+    /// `tools` is the number of tools listed so far
+    fn on_page(&self, _server_name: &str, _page: usize, _tools: usize) {}
+
+    /// A problem was skipped over (invalid configuration or failed server)
+    fn on_warning(&self, _warning: &SearchWarning) {}
+
+    /// A tool made it into the final (sorted and limited) results
+    fn on_match(&self, _result: &ToolSearchMatch) {}
+
+    /// The search finished
+    fn on_complete(&self, _report: &SearchReport) {}
+}
+
+/// Share one observer between searches while keeping a handle to read it back
+impl<T: Observer + ?Sized> Observer for Arc<T> {
+    fn on_server_connected(&self, server_name: &str, elapsed: Duration) {
+        (**self).on_server_connected(server_name, elapsed)
+    }
+
+    fn on_server_failed(&self, server_name: &str, error: &ToolSearchError) {
+        (**self).on_server_failed(server_name, error)
+    }
+
+    fn on_page(&self, server_name: &str, page: usize, tools: usize) {
+        (**self).on_page(server_name, page, tools)
+    }
+
+    fn on_warning(&self, warning: &SearchWarning) {
+        (**self).on_warning(warning)
+    }
+
+    fn on_match(&self, result: &ToolSearchMatch) {
+        (**self).on_match(result)
+    }
+
+    fn on_complete(&self, report: &SearchReport) {
+        (**self).on_complete(report)
+    }
+}
+
+impl std::fmt::Debug for dyn Observer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Observer(..)")
+    }
+}
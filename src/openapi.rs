@@ -0,0 +1,206 @@
+//! OpenAPI tool source
+//!
+//! Exposes the operations of an OpenAPI 3 document as searchable tools, so REST
+//! APIs can be discovered in the same query as MCP servers. Each operation
+//! becomes a tool named after its `operationId` (or `method_path` when absent),
+//! with the summary as title and the description as description. Operation
+//! `tags` become the tool's [tags](crate::tool_tags).
+
+use crate::{ToolSearchError, ToolSource};
+use futures::future::BoxFuture;
+use rmcp::model::Tool;
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// HTTP methods that can hold operations in an OpenAPI path item
+const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Tool source backed by an OpenAPI (JSON) document
+#[derive(Debug, Clone)]
+pub struct OpenApiSource {
+    name: String,
+    tools: Vec<Tool>,
+}
+
+impl OpenApiSource {
+    /// Load an OpenAPI document from a JSON file
+    ///
+    /// The source is named after `info.title`, falling back to the file stem.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ToolSearchError> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)?;
+        let spec: Value = serde_json::from_str(&data)?;
+        let fallback = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "openapi".to_string());
+        let name = spec
+            .pointer("/info/title")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or(fallback);
+        Self::from_value(name, &spec)
+    }
+
+    /// Build a source from an already parsed OpenAPI document
+    pub fn from_value(name: impl Into<String>, spec: &Value) -> Result<Self, ToolSearchError> {
+        let paths = spec
+            .get("paths")
+            .and_then(Value::as_object)
+            .ok_or_else(|| {
+                ToolSearchError::Transport("OpenAPI document has no 'paths' object".to_string())
+            })?;
+
+        let mut tools = Vec::new();
+        for (path, item) in paths {
+            let Some(item) = item.as_object() else {
+                continue;
+            };
+            // Parameters declared on the path item apply to every operation
+            let shared_params = item.get("parameters").and_then(Value::as_array);
+            for method in HTTP_METHODS {
+                if let Some(operation) = item.get(method).and_then(Value::as_object) {
+                    tools.push(operation_to_tool(method, path, operation, shared_params));
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.into(),
+            tools,
+        })
+    }
+
+    /// Override the source name used in search results
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Tools derived from the document's operations
+    pub fn tools(&self) -> &[Tool] {
+        &self.tools
+    }
+}
+
+impl ToolSource for OpenApiSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn list_tools(
+        &self,
+        _timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+        Box::pin(async move { Ok(self.tools.clone()) })
+    }
+}
+
+/// Convert a single OpenAPI operation into a tool
+fn operation_to_tool(
+    method: &str,
+    path: &str,
+    operation: &Map<String, Value>,
+    shared_params: Option<&Vec<Value>>,
+) -> Tool {
+    let name = operation
+        .get("operationId")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| fallback_operation_name(method, path));
+    let summary = operation.get("summary").and_then(Value::as_str);
+    let description = operation
+        .get("description")
+        .and_then(Value::as_str)
+        .or(summary);
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    let params = shared_params
+        .into_iter()
+        .flatten()
+        .chain(operation.get("parameters").and_then(Value::as_array).into_iter().flatten());
+    for param in params {
+        let Some(param_name) = param.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let mut schema = param
+            .get("schema")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        if let Some(desc) = param.get("description") {
+            schema.insert("description".to_string(), desc.clone());
+        }
+        if param.get("required").and_then(Value::as_bool).unwrap_or(false) {
+            required.push(Value::String(param_name.to_string()));
+        }
+        properties.insert(param_name.to_string(), Value::Object(schema));
+    }
+
+    if let Some(body) = operation.get("requestBody") {
+        let body_schema = body
+            .get("content")
+            .and_then(Value::as_object)
+            .and_then(|content| {
+                content
+                    .get("application/json")
+                    .or_else(|| content.values().next())
+            })
+            .and_then(|media| media.get("schema"))
+            .cloned()
+            .unwrap_or_else(|| Value::Object(Map::new()));
+        let mut body_schema = match body_schema {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        if let Some(desc) = body.get("description") {
+            body_schema.insert("description".to_string(), desc.clone());
+        }
+        if body.get("required").and_then(Value::as_bool).unwrap_or(false) {
+            required.push(Value::String("body".to_string()));
+        }
+        properties.insert("body".to_string(), Value::Object(body_schema));
+    }
+
+    let mut input_schema = Map::new();
+    input_schema.insert("type".to_string(), Value::String("object".to_string()));
+    input_schema.insert("properties".to_string(), Value::Object(properties));
+    if !required.is_empty() {
+        input_schema.insert("required".to_string(), Value::Array(required));
+    }
+    if let Some(tags) = operation.get("tags").filter(|tags| tags.is_array()) {
+        input_schema.insert("x-tags".to_string(), tags.clone());
+    }
+
+    Tool {
+        name: name.into(),
+        title: summary.map(str::to_string),
+        description: description.map(|d| d.to_string().into()),
+        input_schema: Arc::new(input_schema),
+        annotations: None,
+        icons: None,
+        output_schema: None,
+    }
+}
+
+/// Derive a tool name such as `get_users_id` from a method and path
+fn fallback_operation_name(method: &str, path: &str) -> String {
+    let mut name = method.to_string();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        let segment: String = segment
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        if !segment.is_empty() {
+            name.push('_');
+            name.push_str(&segment);
+        }
+    }
+    name
+}
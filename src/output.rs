@@ -0,0 +1,559 @@
+//! Rendering search results
+//!
+//! A [`ResultWriter`] renders matches in one output format. The CLI uses the
+//! built-in writers for its `--format` option, and other frontends can use
+//! them to render results exactly the same way. [`LangChainWriter`] and
+//! [`LlamaIndexWriter`] emit tool specs Python agent frameworks load as they
+//! are.
+//!
+//! ```
+//! use toolsearch::output::{writer, ResultWriter};
+//!
+//! # fn example(results: &[toolsearch::ToolSearchMatch]) -> Result<(), toolsearch::ToolSearchError> {
+//! let markdown = writer("markdown").unwrap().render(results, "Search results")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{ToolSearchError, ToolSearchMatch};
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::io::Write;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Renders search results in one output format
+pub trait ResultWriter {
+    /// Write `results` to `out`; `header` summarizes the search for formats
+    /// with a title line
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        header: &str,
+    ) -> Result<(), ToolSearchError>;
+
+    /// Render `results` to a string
+    fn render(&self, results: &[ToolSearchMatch], header: &str) -> Result<String, ToolSearchError> {
+        let mut buf = Vec::new();
+        self.write(&mut buf, results, header)?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+}
+
+/// Writer for a format name (`text`, `table`, `json`, `csv`, `markdown`/`md`,
+/// `html`, `langchain` or `llamaindex`), or `None` if the name is unknown
+pub fn writer(format: &str) -> Option<Box<dyn ResultWriter>> {
+    Some(match format {
+        "text" => Box::new(TextWriter),
+        "table" => Box::new(TableWriter::default()),
+        "json" => Box::new(JsonWriter::default()),
+        "csv" => Box::new(CsvWriter),
+        "markdown" | "md" => Box::new(MarkdownWriter),
+        "html" => Box::new(HtmlWriter),
+        "langchain" => Box::new(LangChainWriter::default()),
+        "llamaindex" => Box::new(LlamaIndexWriter::default()),
+        _ => return None,
+    })
+}
+
+fn description(result: &ToolSearchMatch) -> &str {
+    result.tool.description.as_deref().unwrap_or("")
+}
+
+/// Marker after the name of a pinned tool
+fn pin_mark(result: &ToolSearchMatch) -> &'static str {
+    if result.pinned { " (pinned)" } else { "" }
+}
+
+/// Shorten `text` to at most `max_width` terminal columns, ending with `...`
+/// when anything was cut
+///
+/// Cuts happen between grapheme clusters, so multi-byte and combining
+/// characters are never split, and wide (e.g. CJK) characters count as two
+/// columns.
+///
+/// ```
+/// use toolsearch::output::truncate;
+///
+/// assert_eq!(truncate("read_file", 20), "read_file");
+/// assert_eq!(truncate("Lese eine Datei über Größe", 10), "Lese ei...");
+/// assert_eq!(truncate("ファイルを読む", 8), "ファ...");
+/// ```
+pub fn truncate(text: &str, max_width: usize) -> Cow<'_, str> {
+    if text.width() <= max_width {
+        return Cow::Borrowed(text);
+    }
+    let (budget, ellipsis) = if max_width > 3 {
+        (max_width - 3, "...")
+    } else {
+        (max_width, "")
+    };
+    let mut width = 0;
+    let mut end = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        width += grapheme.width();
+        if width > budget {
+            break;
+        }
+        end = index + grapheme.len();
+    }
+    Cow::Owned(format!("{}{}", &text[..end], ellipsis))
+}
+
+/// Left-align `text` in a column `width` terminal columns wide
+fn pad(text: &str, width: usize) -> String {
+    let fill = width.saturating_sub(text.width());
+    format!("{}{}", text, " ".repeat(fill))
+}
+
+/// One block of labelled lines per match
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextWriter;
+
+impl ResultWriter for TextWriter {
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        header: &str,
+    ) -> Result<(), ToolSearchError> {
+        if results.is_empty() {
+            writeln!(out, "No results found")?;
+            return Ok(());
+        }
+        writeln!(out, "{}\n", header)?;
+        for result in results {
+            writeln!(out, "Server: {}", result.server_name)?;
+            if let Some(info) = &result.server_info {
+                writeln!(
+                    out,
+                    "  Implementation: {} {} (protocol {})",
+                    info.server_info.name, info.server_info.version, info.protocol_version
+                )?;
+            }
+            writeln!(out, "  Name: {}{}", result.tool_name(), pin_mark(result))?;
+            if let Some(desc) = &result.tool.description {
+                writeln!(out, "  Description: {}", desc)?;
+            }
+            if let Some(title) = &result.tool.title {
+                writeln!(out, "  Title: {}", title)?;
+            }
+            let annotations = result.annotation_labels();
+            if !annotations.is_empty() {
+                writeln!(out, "  Annotations: {}", annotations.join(", "))?;
+            }
+            if !result.tags.is_empty() {
+                writeln!(out, "  Tags: {}", result.tags.join(", "))?;
+            }
+            if !result.queries.is_empty() {
+                writeln!(out, "  Matched: {}", result.queries.join(" | "))?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fixed-width columns of server, tool name and (shortened) description
+///
+/// Column widths are measured in terminal columns, so names with wide or
+/// multi-byte characters stay aligned.
+#[derive(Debug, Clone, Copy)]
+pub struct TableWriter {
+    description_width: usize,
+}
+
+impl Default for TableWriter {
+    fn default() -> Self {
+        Self {
+            description_width: 50,
+        }
+    }
+}
+
+impl TableWriter {
+    /// Truncate descriptions to `width` columns (default 50; 0 disables truncation)
+    pub fn description_width(mut self, width: usize) -> Self {
+        self.description_width = width;
+        self
+    }
+}
+
+impl ResultWriter for TableWriter {
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        header: &str,
+    ) -> Result<(), ToolSearchError> {
+        if results.is_empty() {
+            writeln!(out, "No results found")?;
+            return Ok(());
+        }
+        writeln!(out, "{}\n", header)?;
+        writeln!(out, "{} {} DESCRIPTION", pad("SERVER", 30), pad("TOOL NAME", 40))?;
+        writeln!(out, "{}", "-".repeat(100))?;
+        for result in results {
+            let desc = match result.tool.description.as_deref() {
+                None => Cow::Borrowed("N/A"),
+                Some(desc) if self.description_width == 0 => Cow::Borrowed(desc),
+                Some(desc) => truncate(desc, self.description_width),
+            };
+            writeln!(
+                out,
+                "{} {} {}",
+                pad(&result.server_name, 30),
+                pad(&format!("{}{}", result.tool_name(), pin_mark(result)), 40),
+                desc
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// JSON array of matches, pretty-printed unless compact
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonWriter {
+    compact: bool,
+}
+
+impl JsonWriter {
+    /// Write the array on one line, without indentation (default: pretty)
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+}
+
+impl ResultWriter for JsonWriter {
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        _header: &str,
+    ) -> Result<(), ToolSearchError> {
+        if self.compact {
+            serde_json::to_writer(&mut *out, results)?;
+        } else {
+            serde_json::to_writer_pretty(&mut *out, results)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+/// Whether tool specs for `results` need namespaced names: if asked for,
+/// or if two servers have a tool of the same name, which agent frameworks
+/// reject
+fn namespace_specs(results: &[ToolSearchMatch], namespaced: bool) -> bool {
+    let mut names = std::collections::HashSet::new();
+    namespaced || !results.iter().all(|result| names.insert(result.tool_name()))
+}
+
+/// Name a tool spec gives `result`: the tool's name, or the name
+/// [`ToolProxy`](crate::proxy::ToolProxy) exposes it under if `namespaced`
+fn spec_name(result: &ToolSearchMatch, namespaced: bool) -> String {
+    if namespaced {
+        crate::proxy::namespaced_name(&result.server_name, result.tool_name())
+    } else {
+        result.tool_name().to_string()
+    }
+}
+
+fn write_specs(out: &mut dyn Write, specs: &[Value], compact: bool) -> Result<(), ToolSearchError> {
+    if compact {
+        serde_json::to_writer(&mut *out, specs)?;
+    } else {
+        serde_json::to_writer_pretty(&mut *out, specs)?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// JSON array of OpenAI-style tool definitions, as LangChain's
+/// `convert_to_openai_tool` produces them, for `bind_tools`
+///
+/// ```
+/// use toolsearch::output::{LangChainWriter, ResultWriter};
+/// use toolsearch::testing::ToolFixture;
+/// use toolsearch::ToolSearchMatch;
+///
+/// let read = ToolSearchMatch::new("files", ToolFixture::new("read_file").build());
+/// let specs = LangChainWriter::default().namespaced(true).render(&[read], "").unwrap();
+/// let specs: serde_json::Value = serde_json::from_str(&specs).unwrap();
+/// assert_eq!(specs[0]["type"], "function");
+/// assert_eq!(specs[0]["function"]["name"], "files__read_file");
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LangChainWriter {
+    namespaced: bool,
+    compact: bool,
+}
+
+impl LangChainWriter {
+    /// Name each tool `server__tool`, as `toolsearch proxy` serves it, so
+    /// calls can go through the proxy (default: the tool's own name, unless
+    /// two servers have a tool of that name)
+    pub fn namespaced(mut self, namespaced: bool) -> Self {
+        self.namespaced = namespaced;
+        self
+    }
+
+    /// Write the array on one line, without indentation (default: pretty)
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+}
+
+impl ResultWriter for LangChainWriter {
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        _header: &str,
+    ) -> Result<(), ToolSearchError> {
+        let namespaced = namespace_specs(results, self.namespaced);
+        let specs: Vec<Value> = results
+            .iter()
+            .map(|result| {
+                let mut function = json!({
+                    "name": spec_name(result, namespaced),
+                    "parameters": &*result.tool.input_schema,
+                });
+                if let Some(description) = &result.tool.description {
+                    function["description"] = json!(description);
+                }
+                json!({ "type": "function", "function": function })
+            })
+            .collect();
+        write_specs(out, &specs, self.compact)
+    }
+}
+
+/// JSON array of LlamaIndex `ToolMetadata` fields, with the input schema
+/// as `fn_schema`
+///
+/// Each object has `name`, `description` (empty if the tool has none),
+/// `fn_schema` and `return_direct` (always `false`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LlamaIndexWriter {
+    namespaced: bool,
+    compact: bool,
+}
+
+impl LlamaIndexWriter {
+    /// Name each tool `server__tool`, as `toolsearch proxy` serves it, so
+    /// calls can go through the proxy (default: the tool's own name, unless
+    /// two servers have a tool of that name)
+    pub fn namespaced(mut self, namespaced: bool) -> Self {
+        self.namespaced = namespaced;
+        self
+    }
+
+    /// Write the array on one line, without indentation (default: pretty)
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+}
+
+impl ResultWriter for LlamaIndexWriter {
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        _header: &str,
+    ) -> Result<(), ToolSearchError> {
+        let namespaced = namespace_specs(results, self.namespaced);
+        let specs: Vec<Value> = results
+            .iter()
+            .map(|result| {
+                json!({
+                    "name": spec_name(result, namespaced),
+                    "description": description(result),
+                    "fn_schema": &*result.tool.input_schema,
+                    "return_direct": false,
+                })
+            })
+            .collect();
+        write_specs(out, &specs, self.compact)
+    }
+}
+
+/// RFC 4180 CSV with a header row
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvWriter;
+
+impl CsvWriter {
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+impl ResultWriter for CsvWriter {
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        _header: &str,
+    ) -> Result<(), ToolSearchError> {
+        write!(out, "server,tool,title,description,annotations\r\n")?;
+        for result in results {
+            let fields = [
+                result.server_name.as_str(),
+                result.tool_name(),
+                result.tool.title.as_deref().unwrap_or(""),
+                description(result),
+                &result.annotation_labels().join("; "),
+            ];
+            let row: Vec<String> = fields.iter().map(|f| Self::field(f)).collect();
+            write!(out, "{}\r\n", row.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Markdown heading followed by a table of matches
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownWriter;
+
+impl MarkdownWriter {
+    fn cell(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace('|', "\\|")
+            .replace(['\r', '\n'], " ")
+    }
+}
+
+impl ResultWriter for MarkdownWriter {
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        header: &str,
+    ) -> Result<(), ToolSearchError> {
+        writeln!(out, "## {}\n", header)?;
+        if results.is_empty() {
+            writeln!(out, "No results found")?;
+            return Ok(());
+        }
+        writeln!(out, "| Server | Tool | Description | Annotations |")?;
+        writeln!(out, "|--------|------|-------------|-------------|")?;
+        for result in results {
+            writeln!(
+                out,
+                "| {} | `{}`{} | {} | {} |",
+                Self::cell(&result.server_name),
+                Self::cell(result.tool_name()),
+                pin_mark(result),
+                Self::cell(description(result)),
+                Self::cell(&result.annotation_labels().join(", ")),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Self-contained HTML page
+///
+/// The page has no external assets: a filter box narrows the list client-side
+/// and each tool's input schema sits in an expandable section.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HtmlWriter;
+
+impl ResultWriter for HtmlWriter {
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        results: &[ToolSearchMatch],
+        header: &str,
+    ) -> Result<(), ToolSearchError> {
+        let mut rows = String::new();
+        for result in results {
+            let schema = serde_json::to_string_pretty(&*result.tool.input_schema)?;
+            let labels = result.annotation_labels();
+            let annotations = if labels.is_empty() {
+                String::new()
+            } else {
+                format!("<p><em>{}</em></p>", escape_html(&labels.join(", ")))
+            };
+            rows.push_str(&format!(
+                concat!(
+                    "<tr class=\"tool\">",
+                    "<td>{server}</td>",
+                    "<td><code>{name}</code>{pinned}</td>",
+                    "<td>{description}{annotations}",
+                    "<details><summary>Input schema</summary><pre>{schema}</pre></details>",
+                    "</td></tr>\n"
+                ),
+                server = escape_html(&result.server_name),
+                name = escape_html(result.tool_name()),
+                pinned = pin_mark(result),
+                description = escape_html(description(result)),
+                annotations = annotations,
+                schema = escape_html(&schema),
+            ));
+        }
+
+        writeln!(
+            out,
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>toolsearch report</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+input {{ width: 100%; padding: 0.5em; margin-bottom: 1em; font-size: 1em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ text-align: left; vertical-align: top; padding: 0.4em; border-bottom: 1px solid #ddd; }}
+pre {{ background: #f6f8fa; padding: 0.5em; overflow-x: auto; }}
+</style>
+</head>
+<body>
+<h1>{header}</h1>
+<input id="filter" type="search" placeholder="Filter tools..." autofocus>
+<table>
+<thead><tr><th>Server</th><th>Tool</th><th>Description</th></tr></thead>
+<tbody>
+{rows}</tbody>
+</table>
+<script>
+document.getElementById("filter").addEventListener("input", function (e) {{
+  var needle = e.target.value.toLowerCase();
+  document.querySelectorAll("tr.tool").forEach(function (row) {{
+    row.style.display = row.textContent.toLowerCase().includes(needle) ? "" : "none";
+  }});
+}});
+</script>
+</body>
+</html>"#,
+            header = escape_html(header),
+            rows = rows,
+        )?;
+        Ok(())
+    }
+}
+
+/// Escape text for inclusion in HTML element content and attributes
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
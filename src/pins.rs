@@ -0,0 +1,112 @@
+//! Tools pinned to the top of results
+//!
+//! Among many near-duplicate tools, a team usually has blessed ones. A pins
+//! file names them, by tool name or as `server/tool` for one server's tool,
+//! optionally per [query profile](crate::template): the tools under
+//! `"tools"` are pinned in every search, and those under a profile only in
+//! searches run with it.
+//!
+//! ```json
+//! {
+//!   "tools": ["read_file", "github/search_code"],
+//!   "profiles": { "review": ["github/get_pull_request"] }
+//! }
+//! ```
+//!
+//! Pinned matches sort before the others, whatever the sort order, and are
+//! flagged with [`ToolSearchMatch::pinned`](crate::ToolSearchMatch::pinned).
+//! The CLI reads the [default file](Pins::default_path) when it exists.
+//!
+//! ```
+//! use toolsearch::pins::Pins;
+//!
+//! let pins = Pins::new(["read_file"]).with_profile("review", ["github/get_pull_request"]);
+//! assert_eq!(pins.pinned(None), ["read_file"]);
+//! assert_eq!(pins.pinned(Some("review")), ["read_file", "github/get_pull_request"]);
+//! ```
+
+use crate::{read_json_file, user_config_file, ToolSearchError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Pinned tools, for every search and by profile
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pins {
+    /// Tools pinned in every search, by name or `server/tool`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// Tools pinned only in searches run with a profile, by profile name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Vec<String>>,
+}
+
+impl Pins {
+    /// Pin `tools` in every search
+    pub fn new(tools: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            tools: tools.into_iter().map(Into::into).collect(),
+            profiles: BTreeMap::new(),
+        }
+    }
+
+    /// Also pin `tools` in searches run with `profile`
+    pub fn with_profile(
+        mut self,
+        profile: impl Into<String>,
+        tools: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.profiles
+            .entry(profile.into())
+            .or_default()
+            .extend(tools.into_iter().map(Into::into));
+        self
+    }
+
+    /// Tools pinned in a search run with `profile`, or without one
+    pub fn pinned(&self, profile: Option<&str>) -> Vec<String> {
+        let mut pinned = self.tools.clone();
+        let profile_tools = profile.and_then(|profile| self.profiles.get(profile));
+        for tool in profile_tools.into_iter().flatten() {
+            if !pinned.contains(tool) {
+                pinned.push(tool.clone());
+            }
+        }
+        pinned
+    }
+
+    /// Whether nothing is pinned
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty() && self.profiles.values().all(Vec::is_empty)
+    }
+
+    /// Load a pins file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ToolSearchError> {
+        Ok(read_json_file(path.as_ref())?)
+    }
+
+    /// Load the [default pins file](Self::default_path); empty if there is
+    /// none
+    pub fn load_default() -> Result<Self, ToolSearchError> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Default pins file: `$XDG_CONFIG_HOME/toolsearch/pins.json`, or
+    /// `~/.config/toolsearch/pins.json`
+    ///
+    /// `None` if neither variable is set.
+    pub fn default_path() -> Option<PathBuf> {
+        user_config_file("pins.json")
+    }
+}
+
+/// Whether the pin `entry` names tool `tool_name` of server `server_name`
+pub(crate) fn pins_tool(entry: &str, server_name: &str, tool_name: &str) -> bool {
+    match entry.split_once('/') {
+        Some((server, tool)) => server == server_name && tool == tool_name,
+        None => entry == tool_name,
+    }
+}
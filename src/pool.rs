@@ -0,0 +1,274 @@
+//! Persistent server connections
+//!
+//! A plain [`ServerConfig`] source starts its server for every search and
+//! stops it afterwards. A [`PooledServer`] keeps the connection (and, for
+//! stdio servers, the process) open between searches, so only the first
+//! search pays for the cold start. A connection that has gone bad is
+//! replaced on the next fetch, and so is one started with other
+//! [environment overrides](crate::SearchOptions::env_overrides) than the
+//! fetch asks for, or one left unused for longer than the server's
+//! [`idle_timeout_secs`](ServerConfig::idle_timeout_secs), before a proxy or
+//! load balancer in between drops it silently.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use toolsearch::pool::PooledServer;
+//! use toolsearch::{load_servers, SearchBuilder};
+//!
+//! # async fn example() -> Result<(), toolsearch::ToolSearchError> {
+//! let pool: Vec<_> = load_servers("servers.json")?
+//!     .into_iter()
+//!     .map(|config| Arc::new(PooledServer::new(config)))
+//!     .collect();
+//! for query in ["read", "write"] {
+//!     let mut builder = SearchBuilder::new(vec![]).query(query);
+//!     for server in &pool {
+//!         builder = builder.source(server.clone());
+//!     }
+//!     builder.search().await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::source::{FetchOptions, ToolListing, ToolSource};
+use crate::protocol::take_malformed;
+use crate::{
+    connect_with_timeout, list_connected_tools, with_env_overrides, Boosts, RateLimiter, ServerConfig,
+    ServerConnection, ToolSearchError,
+};
+use futures::future::BoxFuture;
+use rmcp::model::{CallToolRequestParam, CallToolResult, Tool};
+use rmcp::service::ServiceError;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// An MCP server whose connection stays open between fetches
+pub struct PooledServer {
+    config: ServerConfig,
+    connection: Mutex<Option<ServerConnection>>,
+    /// Shared by every fetch and call, so the rate limit holds across them
+    limiter: RateLimiter,
+    /// When the open connection was last used
+    last_used: std::sync::Mutex<Option<Instant>>,
+    /// Environment overrides of the last fetch, which the open connection
+    /// was started with; calls reconnect with them too
+    env_overrides: std::sync::Mutex<HashMap<String, String>>,
+}
+
+impl PooledServer {
+    /// Pool the server described by `config`; it is not contacted until the
+    /// first fetch
+    pub fn new(config: ServerConfig) -> Self {
+        Self {
+            limiter: config.rate_limiter(),
+            config,
+            connection: Mutex::new(None),
+            last_used: std::sync::Mutex::new(None),
+            env_overrides: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Configuration of the server
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// Whether a connection is currently open
+    pub async fn is_connected(&self) -> bool {
+        self.connection.lock().await.is_some()
+    }
+
+    /// Close the connection (stopping a stdio server); the next fetch
+    /// reconnects
+    pub async fn disconnect(&self) {
+        self.connection.lock().await.take();
+    }
+
+    /// Close the connection if it has been unused for longer than the
+    /// server's idle timeout; returns whether it was closed
+    ///
+    /// Fetches and calls do this anyway before using the connection; a
+    /// long-running host can call it now and then to stop idle servers
+    /// sooner.
+    pub async fn close_if_idle(&self) -> bool {
+        let mut connection = self.connection.lock().await;
+        if connection.is_some() && self.is_idle() {
+            *connection = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the connection has been unused for longer than the idle
+    /// timeout
+    fn is_idle(&self) -> bool {
+        let idle_timeout = self.config.idle_timeout();
+        let last_used = *self.last_used.lock().unwrap_or_else(|e| e.into_inner());
+        matches!((idle_timeout, last_used), (Some(limit), Some(used)) if used.elapsed() >= limit)
+    }
+
+    /// Record that the connection was just used
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    }
+
+    /// Environment overrides the connection is started with
+    fn env_overrides(&self) -> HashMap<String, String> {
+        self.env_overrides.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Call a tool on the server, connecting first if no connection is open
+    ///
+    /// The connection is not held while the tool runs, so slow calls don't
+    /// hold up listing or other calls. A failed call is never retried, as
+    /// the tool may already have run; if the connection broke, it is closed
+    /// and the next request reconnects.
+    pub async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        timeout: Option<Duration>,
+    ) -> Result<CallToolResult, ToolSearchError> {
+        let peer = {
+            let mut connection = self.connection.lock().await;
+            let open = match connection.take() {
+                Some(open) if !self.is_idle() => open,
+                _ => {
+                    let overrides = self.env_overrides();
+                    let config = with_env_overrides(&self.config, &overrides);
+                    connect_with_timeout(&config, timeout, None).await?
+                }
+            };
+            self.touch();
+            let peer = open.peer().clone();
+            *connection = Some(open);
+            peer
+        };
+
+        let _permit = self.limiter.acquire().await;
+        let call = peer.call_tool(request);
+        let result = match timeout {
+            Some(timeout_dur) => tokio::time::timeout(timeout_dur, call).await.map_err(|_| {
+                ToolSearchError::Connection(format!(
+                    "Tool call timeout after {:?} for server: {}",
+                    timeout_dur, self.config.name
+                ))
+            })?,
+            None => call.await,
+        };
+        self.touch();
+        match result {
+            Ok(result) => Ok(result),
+            // The server answered with an error; the connection is fine
+            Err(e @ ServiceError::McpError(_)) => Err(e.into()),
+            Err(e) => {
+                self.disconnect().await;
+                Err(e.into())
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for PooledServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PooledServer")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ToolSource for PooledServer {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.config.validate()
+    }
+
+    fn priority(&self) -> u32 {
+        self.config.priority
+    }
+
+    fn boosts(&self) -> Boosts {
+        self.config.boosts()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.config.timeout()
+    }
+
+    fn blocked_tools(&self) -> Vec<String> {
+        self.config.blocked_tools.clone()
+    }
+
+    fn list_tools(
+        &self,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+        Box::pin(async move { Ok(self.fetch(timeout).await?.tools) })
+    }
+
+    fn fetch(&self, timeout: Option<Duration>) -> BoxFuture<'_, Result<ToolListing, ToolSearchError>> {
+        Box::pin(async move {
+            let options = FetchOptions {
+                timeout,
+                ..Default::default()
+            };
+            self.fetch_with(&options).await
+        })
+    }
+
+    fn fetch_with<'a>(
+        &'a self,
+        options: &'a FetchOptions,
+    ) -> BoxFuture<'a, Result<ToolListing, ToolSearchError>> {
+        Box::pin(async move {
+            // Holding the lock serializes fetches, so concurrent searches
+            // never start the same server twice
+            let mut connection = self.connection.lock().await;
+            // A search with other overrides gets a server started with them
+            if self.is_idle() || self.env_overrides() != options.env_overrides {
+                *connection = None;
+            }
+            if let Some(open) = connection.as_ref() {
+                match list_connected_tools(open, &self.config.name, options, &self.limiter).await {
+                    Ok((tools, truncated)) => {
+                        self.touch();
+                        return Ok(ToolListing {
+                            tools,
+                            server_info: open.peer_info().cloned(),
+                            connect_time: None,
+                            truncated,
+                            malformed: take_malformed(&open.malformed),
+                        });
+                    }
+                    // The server may have exited; start over with a new connection
+                    Err(_) => *connection = None,
+                }
+            }
+
+            let started = Instant::now();
+            let record_dir = options.record_dir.as_deref();
+            *self.env_overrides.lock().unwrap_or_else(|e| e.into_inner()) =
+                options.env_overrides.clone();
+            let config = with_env_overrides(&self.config, &options.env_overrides);
+            let open = connect_with_timeout(&config, options.timeout, record_dir).await?;
+            let connect_time = Some(started.elapsed());
+            let (tools, truncated) = list_connected_tools(&open, &self.config.name, options, &self.limiter).await?;
+            let server_info = open.peer_info().cloned();
+            let malformed = take_malformed(&open.malformed);
+            *connection = Some(open);
+            self.touch();
+            Ok(ToolListing {
+                tools,
+                server_info,
+                connect_time,
+                truncated,
+                malformed,
+            })
+        })
+    }
+}
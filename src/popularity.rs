@@ -0,0 +1,125 @@
+//! Ranking by how much tools are used
+//!
+//! Relevance alone ranks a tool nobody has ever called level with one an
+//! agent calls successfully all day. A [`Popularity`] map holds a weight per
+//! tool, such as call or success counts from an agent's telemetry, and
+//! scoring blends it in: each match's score is multiplied by
+//! `1 + strength * weight / highest weight`. With the default strength the
+//! most used tool's score doubles, and tools without a weight keep theirs.
+//! Like [`Boosts`](crate::Boosts), it shapes the ranking of
+//! [`SortOrder::Score`](crate::SortOrder::Score).
+//!
+//! Weights are keyed by tool name, or by `server/tool` for one server's
+//! tool, which wins over the plain name. The file is a JSON object:
+//!
+//! ```json
+//! { "read_file": 120, "github/search_code": 40 }
+//! ```
+//!
+//! ```
+//! use toolsearch::popularity::Popularity;
+//!
+//! let popularity = Popularity::new([("read_file", 120.0), ("github/search_code", 40.0)]);
+//! assert_eq!(popularity.factor("files", "read_file"), 2.0);
+//! assert!((popularity.factor("github", "search_code") - 4.0 / 3.0).abs() < 1e-6);
+//! assert_eq!(popularity.factor("files", "write_file"), 1.0);
+//! ```
+
+use crate::{read_json_file, ToolSearchError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Default [`Popularity::strength`]: the most used tool's score doubles
+pub const DEFAULT_STRENGTH: f32 = 1.0;
+
+/// Usage weights of tools; see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, f32>", into = "BTreeMap<String, f32>")]
+pub struct Popularity {
+    /// Weights by tool name or `server/tool`
+    weights: BTreeMap<String, f32>,
+    /// Highest weight, which gains the full strength
+    highest: f32,
+    strength: f32,
+}
+
+impl Default for Popularity {
+    fn default() -> Self {
+        Self {
+            weights: BTreeMap::new(),
+            highest: 0.0,
+            strength: DEFAULT_STRENGTH,
+        }
+    }
+}
+
+impl From<BTreeMap<String, f32>> for Popularity {
+    fn from(weights: BTreeMap<String, f32>) -> Self {
+        Self::new(weights)
+    }
+}
+
+impl From<Popularity> for BTreeMap<String, f32> {
+    fn from(popularity: Popularity) -> Self {
+        popularity.weights
+    }
+}
+
+impl Popularity {
+    /// Weights by tool name or `server/tool`
+    ///
+    /// Weights that are negative or not finite are left out.
+    pub fn new<K: Into<String>>(weights: impl IntoIterator<Item = (K, f32)>) -> Self {
+        let weights: BTreeMap<String, f32> = weights
+            .into_iter()
+            .filter(|(_, weight)| weight.is_finite() && *weight >= 0.0)
+            .map(|(key, weight)| (key.into(), weight))
+            .collect();
+        let highest = weights.values().copied().fold(0.0, f32::max);
+        Self {
+            weights,
+            highest,
+            strength: DEFAULT_STRENGTH,
+        }
+    }
+
+    /// Set how much the most used tool gains: its score is multiplied by
+    /// `1 + strength` (default 1)
+    pub fn strength(mut self, strength: f32) -> Self {
+        self.strength = strength;
+        self
+    }
+
+    /// Load a weights file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ToolSearchError> {
+        Ok(read_json_file(path.as_ref())?)
+    }
+
+    /// Weight of a server's tool; 0 for tools without one
+    pub fn weight(&self, server_name: &str, tool_name: &str) -> f32 {
+        self.weights
+            .get(&format!("{}/{}", server_name, tool_name))
+            .or_else(|| self.weights.get(tool_name))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Score multiplier of a server's tool
+    pub fn factor(&self, server_name: &str, tool_name: &str) -> f32 {
+        if self.highest == 0.0 {
+            return 1.0;
+        }
+        1.0 + self.strength * self.weight(server_name, tool_name) / self.highest
+    }
+
+    /// Number of weights
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Whether there are no weights
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+}
@@ -0,0 +1,198 @@
+//! Which fields of a match to keep
+//!
+//! Full input schemas make up most of a serialized search result, and a
+//! caller showing a list of tools often needs only names and descriptions.
+//! A [`Projection`] names the [`OutputField`]s to keep; the others are
+//! cleared from each match once the search is done. The tool's name and
+//! server are always kept, and since every MCP tool has an input schema, a
+//! cleared one is left as an empty object.
+//!
+//! ```
+//! use toolsearch::projection::{OutputField, Projection};
+//! use toolsearch::testing::ToolFixture;
+//! use toolsearch::ToolSearchMatch;
+//!
+//! let projection = Projection::parse("name,server,description").unwrap();
+//! assert!(projection.includes(OutputField::Description));
+//! assert!(!projection.includes(OutputField::InputSchema));
+//!
+//! let tool = ToolFixture::new("read_file").description("Read a file").build();
+//! let mut m = ToolSearchMatch::new("files", tool);
+//! projection.apply(&mut m);
+//! assert_eq!(m.tool.description.as_deref(), Some("Read a file"));
+//! assert!(m.tool.input_schema.is_empty());
+//! ```
+
+use crate::ToolSearchMatch;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A field of a [`ToolSearchMatch`] a [`Projection`] can keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputField {
+    /// The tool's name (always kept)
+    Name,
+    /// The server's name (always kept)
+    Server,
+    /// The tool's title
+    Title,
+    /// The tool's description
+    Description,
+    /// The tool's input schema
+    InputSchema,
+    /// The tool's output schema
+    OutputSchema,
+    /// The tool's annotations
+    Annotations,
+    /// The tool's icons
+    Icons,
+    /// The server's initialize info
+    ServerInfo,
+    /// The match's score
+    Score,
+    /// The description excerpt around the hit
+    Snippet,
+    /// The tool's tags
+    Tags,
+    /// The queries the tool matched
+    Queries,
+}
+
+impl OutputField {
+    /// Every field, in serialization order
+    pub const ALL: [OutputField; 13] = [
+        OutputField::Name,
+        OutputField::Server,
+        OutputField::Title,
+        OutputField::Description,
+        OutputField::InputSchema,
+        OutputField::OutputSchema,
+        OutputField::Annotations,
+        OutputField::Icons,
+        OutputField::ServerInfo,
+        OutputField::Score,
+        OutputField::Snippet,
+        OutputField::Tags,
+        OutputField::Queries,
+    ];
+
+    /// Name of the field, as written in a projection list
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputField::Name => "name",
+            OutputField::Server => "server",
+            OutputField::Title => "title",
+            OutputField::Description => "description",
+            OutputField::InputSchema => "input_schema",
+            OutputField::OutputSchema => "output_schema",
+            OutputField::Annotations => "annotations",
+            OutputField::Icons => "icons",
+            OutputField::ServerInfo => "server_info",
+            OutputField::Score => "score",
+            OutputField::Snippet => "snippet",
+            OutputField::Tags => "tags",
+            OutputField::Queries => "queries",
+        }
+    }
+}
+
+impl fmt::Display for OutputField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Fields of a match to keep; see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Projection {
+    fields: Vec<OutputField>,
+}
+
+impl Projection {
+    /// Keep `fields`, besides the name and server
+    pub fn new(fields: impl IntoIterator<Item = OutputField>) -> Self {
+        let mut projection = Self { fields: Vec::new() };
+        for field in fields {
+            if !projection.fields.contains(&field) {
+                projection.fields.push(field);
+            }
+        }
+        projection
+    }
+
+    /// Parse a comma-separated list of field names such as
+    /// `name,server,description`
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let fields = list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                OutputField::ALL
+                    .into_iter()
+                    .find(|field| field.as_str().eq_ignore_ascii_case(name))
+                    .ok_or_else(|| {
+                        let known: Vec<_> =
+                            OutputField::ALL.iter().map(OutputField::as_str).collect();
+                        format!(
+                            "Unknown output field \"{}\" (expected one of: {})",
+                            name,
+                            known.join(", ")
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(fields))
+    }
+
+    /// Fields kept, in the order given
+    pub fn fields(&self) -> &[OutputField] {
+        &self.fields
+    }
+
+    /// Whether `field` is kept
+    pub fn includes(&self, field: OutputField) -> bool {
+        matches!(field, OutputField::Name | OutputField::Server) || self.fields.contains(&field)
+    }
+
+    /// Clear the fields of `m` this projection doesn't keep
+    pub fn apply(&self, m: &mut ToolSearchMatch) {
+        let tool = &mut m.tool;
+        if !self.includes(OutputField::Title) {
+            tool.title = None;
+        }
+        if !self.includes(OutputField::Description) {
+            tool.description = None;
+        }
+        if !self.includes(OutputField::InputSchema) {
+            tool.input_schema = Arc::new(Default::default());
+        }
+        if !self.includes(OutputField::OutputSchema) {
+            tool.output_schema = None;
+        }
+        if !self.includes(OutputField::Annotations) {
+            tool.annotations = None;
+        }
+        if !self.includes(OutputField::Icons) {
+            tool.icons = None;
+        }
+        if !self.includes(OutputField::ServerInfo) {
+            m.server_info = None;
+        }
+        if !self.includes(OutputField::Score) {
+            m.score = None;
+        }
+        if !self.includes(OutputField::Snippet) {
+            m.snippet = None;
+        }
+        if !self.includes(OutputField::Tags) {
+            m.tags.clear();
+        }
+        if !self.includes(OutputField::Queries) {
+            m.queries.clear();
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Tool definitions that break the MCP schema
+//!
+//! rmcp parses a `tools/list` response as a whole, so a single tool without
+//! a name fails the entire page with an unhelpful "unexpected response
+//! type". For stdio servers, [`filter_tool_lists`] sits between the server
+//! and rmcp and takes tools that don't parse out of each response, noting
+//! them as [`MalformedTool`]s. Tools that parse are checked by
+//! [`check_tool`] for what the schema asks beyond that; they are kept, with
+//! a note, since they can still be searched.
+
+#[cfg(feature = "stdio")]
+use crate::record::{Direction, Recorder};
+use crate::source::MalformedTool;
+use rmcp::model::Tool;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+
+/// Malformed tools noted on a connection, waiting to be reported
+pub(crate) type MalformedTools = Arc<Mutex<Vec<MalformedTool>>>;
+
+/// Take the malformed tools noted so far
+pub(crate) fn take_malformed(malformed: &MalformedTools) -> Vec<MalformedTool> {
+    std::mem::take(&mut *malformed.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// `line` without the tools that don't parse, if it is a `tools/list`
+/// response with such tools; they are added to `malformed`
+pub(crate) fn drop_unparsable_tools<'a>(line: &'a str, malformed: &MalformedTools) -> Cow<'a, str> {
+    // Most messages aren't tool lists; skip parsing them twice
+    if !line.contains("\"tools\"") {
+        return Cow::Borrowed(line);
+    }
+    let Ok(mut message) = serde_json::from_str::<Value>(line) else {
+        return Cow::Borrowed(line);
+    };
+    let Some(tools) = message
+        .pointer_mut("/result/tools")
+        .and_then(Value::as_array_mut)
+    else {
+        return Cow::Borrowed(line);
+    };
+
+    let mut dropped = Vec::new();
+    tools.retain(|tool| match serde_json::from_value::<Tool>(tool.clone()) {
+        Ok(_) => true,
+        Err(e) => {
+            dropped.push(MalformedTool {
+                name: tool.get("name").and_then(Value::as_str).map(str::to_string),
+                problem: e.to_string(),
+                dropped: true,
+            });
+            false
+        }
+    });
+    if dropped.is_empty() {
+        return Cow::Borrowed(line);
+    }
+    malformed.lock().unwrap_or_else(|e| e.into_inner()).extend(dropped);
+    Cow::Owned(message.to_string())
+}
+
+/// What is wrong with a tool that parsed, if anything
+pub(crate) fn check_tool(tool: &Tool) -> Option<MalformedTool> {
+    let problem = if tool.name.trim().is_empty() {
+        "name is empty".to_string()
+    } else if let Some(kind) = schema_type(&tool.input_schema).filter(|kind| *kind != "object") {
+        format!("inputSchema has type {:?}, expected \"object\"", kind)
+    } else if let Some(kind) = tool
+        .output_schema
+        .as_deref()
+        .and_then(schema_type)
+        .filter(|kind| *kind != "object")
+    {
+        format!("outputSchema has type {:?}, expected \"object\"", kind)
+    } else {
+        return None;
+    };
+    Some(MalformedTool {
+        name: Some(tool.name.to_string()),
+        problem,
+        dropped: false,
+    })
+}
+
+/// The `type` of a schema, if it names a single type
+fn schema_type(schema: &serde_json::Map<String, Value>) -> Option<&str> {
+    schema.get("type").and_then(Value::as_str)
+}
+
+/// Relay a stdio server's output to rmcp line by line, without the tools
+/// that don't parse (see [`drop_unparsable_tools`]), recording it as
+/// received first if `recorder` is set
+#[cfg(feature = "stdio")]
+pub(crate) fn filter_tool_lists(
+    stdout: impl tokio::io::AsyncRead + Send + Unpin + 'static,
+    malformed: MalformedTools,
+    recorder: Option<Recorder>,
+) -> tokio::io::DuplexStream {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some(recorder) = &recorder {
+                recorder.record(Direction::Received, &line);
+            }
+            let line = drop_unparsable_tools(&line, &malformed);
+            if writer.write_all(line.as_bytes()).await.is_err()
+                || writer.write_all(b"\n").await.is_err()
+            {
+                break;
+            }
+        }
+    });
+    reader
+}
@@ -0,0 +1,337 @@
+//! Answering MCP requests with search results
+//!
+//! An aggregating MCP server built on this crate can search its upstream
+//! servers and hand the matches straight back as a `tools/list` answer.
+//! [`list_tools_page`] splits the matches into pages the way MCP clients
+//! expect: each page carries a cursor for the next one, and the client sends
+//! it back to get that page.
+//!
+//! [`ToolProxy`] is such a server: it exposes the tools of every configured
+//! server (or only those matching some criteria) as one MCP server, with
+//! each tool named `<server>__<tool>`, and routes `tools/call` to the server
+//! the tool came from. `toolsearch proxy` serves it on stdio. Its cursors
+//! point into a snapshot of the listing (see [`ToolProxy::list_page`]), so
+//! upstream changes between two pages don't shift the tools a client sees.
+//!
+//! ```
+//! use toolsearch::proxy::list_tools_page;
+//! use toolsearch::testing::ToolFixture;
+//! use toolsearch::ToolSearchMatch;
+//!
+//! let matches: Vec<_> = ["read_file", "write_file", "list_dir"]
+//!     .map(|name| ToolSearchMatch::new("files", ToolFixture::new(name).build()))
+//!     .to_vec();
+//! let first = list_tools_page(&matches, None, 2).unwrap();
+//! assert_eq!(first.tools.len(), 2);
+//! let rest = list_tools_page(&matches, first.next_cursor.as_deref(), 2).unwrap();
+//! assert_eq!(rest.tools[0].name, "list_dir");
+//! assert!(rest.next_cursor.is_none());
+//! ```
+
+use crate::pool::PooledServer;
+use crate::report::SearchReport;
+use crate::source::ToolSource;
+use crate::{
+    search_sources_with_options, SearchCriteria, SearchOptions, ServerConfig, ToolSearchError,
+    ToolSearchMatch,
+};
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, ErrorData, Implementation, ListToolsResult,
+    PaginatedRequestParam, ServerCapabilities, ServerInfo,
+};
+use rmcp::service::RequestContext;
+use rmcp::{RoleServer, ServerHandler};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+/// Separator between the server and tool name in proxied tool names
+pub const NAMESPACE_SEPARATOR: &str = "__";
+
+/// Tools per `tools/list` page answered by a [`ToolProxy`]
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Listings a [`ToolProxy`] keeps for clients paging through them; cursors
+/// into older listings expire
+pub const SNAPSHOTS_KEPT: usize = 16;
+
+/// All matched tools as a single `tools/list` page
+///
+/// Tools keep their upstream names; matches from different servers can
+/// share one.
+pub fn list_tools_result(matches: &[ToolSearchMatch]) -> ListToolsResult {
+    ListToolsResult::with_all_items(matches.iter().map(|m| m.tool.clone()).collect())
+}
+
+/// One page of at most `page_size` matched tools, starting at `cursor`
+///
+/// `cursor` is `None` for the first page and otherwise the `next_cursor`
+/// of the previous page, which is `None` on the last one. Errors are
+/// `invalid_params`, as the MCP specification asks for unknown cursors, so
+/// a server handler can return them as they are. A `page_size` of 0 is
+/// treated as 1.
+pub fn list_tools_page(
+    matches: &[ToolSearchMatch],
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<ListToolsResult, ErrorData> {
+    let start = match cursor {
+        None => 0,
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .ok()
+            .filter(|start| *start <= matches.len())
+            .ok_or_else(|| ErrorData::invalid_params(format!("Invalid cursor: {}", cursor), None))?,
+    };
+    let end = start.saturating_add(page_size.max(1)).min(matches.len());
+    let mut page = list_tools_result(&matches[start..end]);
+    if end < matches.len() {
+        page.next_cursor = Some(end.to_string());
+    }
+    Ok(page)
+}
+
+impl From<SearchReport> for ListToolsResult {
+    fn from(report: SearchReport) -> Self {
+        ListToolsResult::with_all_items(report.matches.into_iter().map(|m| m.tool).collect())
+    }
+}
+
+/// Name a proxy exposes the tool `tool` of server `server` under
+pub fn namespaced_name(server: &str, tool: &str) -> String {
+    format!("{}{}{}", server, NAMESPACE_SEPARATOR, tool)
+}
+
+/// MCP server exposing the tools of several upstream servers as its own
+///
+/// Upstream servers are started by the first request and kept running (see
+/// [`PooledServer`]). Every `tools/list` without a cursor lists them again,
+/// so the answer follows upstream changes.
+pub struct ToolProxy {
+    servers: Vec<Arc<PooledServer>>,
+    criteria: SearchCriteria,
+    options: SearchOptions,
+    page_size: usize,
+    call_timeout: Option<Duration>,
+    /// Upstream server and tool name of each tool in the last listing
+    routes: RwLock<HashMap<String, (Arc<PooledServer>, String)>>,
+    /// Listings clients are paging through
+    snapshots: Mutex<Snapshots>,
+}
+
+/// The last [`SNAPSHOTS_KEPT`] listings with more than one page, by id
+#[derive(Default)]
+struct Snapshots {
+    next_id: u64,
+    kept: VecDeque<(u64, Arc<Vec<ToolSearchMatch>>)>,
+}
+
+impl Snapshots {
+    /// Keep `matches`, dropping the oldest listing if need be
+    fn insert(&mut self, matches: Arc<Vec<ToolSearchMatch>>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        if self.kept.len() >= SNAPSHOTS_KEPT {
+            self.kept.pop_front();
+        }
+        self.kept.push_back((id, matches));
+        id
+    }
+
+    fn get(&self, id: u64) -> Option<Arc<Vec<ToolSearchMatch>>> {
+        self.kept
+            .iter()
+            .find(|(kept, _)| *kept == id)
+            .map(|(_, matches)| Arc::clone(matches))
+    }
+}
+
+impl ToolProxy {
+    /// Proxy every tool of `servers`
+    pub fn new(servers: Vec<ServerConfig>) -> Self {
+        Self {
+            servers: servers
+                .into_iter()
+                .map(|server| Arc::new(PooledServer::new(server)))
+                .collect(),
+            criteria: SearchCriteria::match_all(),
+            options: SearchOptions::default(),
+            page_size: DEFAULT_PAGE_SIZE,
+            call_timeout: None,
+            routes: RwLock::new(HashMap::new()),
+            snapshots: Mutex::new(Snapshots::default()),
+        }
+    }
+
+    /// Expose only the tools matching `criteria`; others can't be called
+    pub fn with_criteria(mut self, criteria: SearchCriteria) -> Self {
+        self.criteria = criteria;
+        self
+    }
+
+    /// Set the options tools are listed (and sorted) with
+    pub fn with_options(mut self, options: SearchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set how many tools each `tools/list` page holds
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Give up on tool calls that take longer than `timeout` (unlimited by
+    /// default, as tools may legitimately run for a long time)
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.call_timeout = Some(timeout);
+        self
+    }
+
+    /// List the exposed tools, under their namespaced names
+    pub async fn tools(&self) -> Result<Vec<ToolSearchMatch>, ToolSearchError> {
+        let sources: Vec<Arc<dyn ToolSource>> = self
+            .servers
+            .iter()
+            .map(|server| Arc::clone(server) as Arc<dyn ToolSource>)
+            .collect();
+        let mut matches = search_sources_with_options(&sources, &self.criteria, &self.options).await?;
+
+        let mut routes = HashMap::new();
+        for m in &mut matches {
+            let upstream = m.tool_name().to_string();
+            let name = namespaced_name(&m.server_name, &upstream);
+            if let Some(server) = self.servers.iter().find(|s| s.config().name == m.server_name) {
+                routes.insert(name.clone(), (Arc::clone(server), upstream));
+            }
+            m.tool.name = name.into();
+        }
+        if let Ok(mut current) = self.routes.write() {
+            *current = routes;
+        }
+        Ok(matches)
+    }
+
+    /// One `tools/list` page, starting at `cursor`
+    ///
+    /// A request without a cursor lists the tools again. When they don't
+    /// fit one page, the listing is kept as a snapshot, and cursors have the
+    /// form `<snapshot>:<offset>`, so every later page comes from the same
+    /// listing however upstream tools change meanwhile. Cursors into one of
+    /// the [`SNAPSHOTS_KEPT`] latest listings are answered; older ones are
+    /// rejected as expired, and the client has to start over.
+    pub async fn list_page(&self, cursor: Option<&str>) -> Result<ListToolsResult, ErrorData> {
+        let Some(cursor) = cursor else {
+            let matches = self.tools().await.map_err(internal_error)?;
+            let mut page = list_tools_page(&matches, None, self.page_size)?;
+            if let Some(next) = page.next_cursor.take() {
+                let id = self
+                    .snapshots
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(Arc::new(matches));
+                page.next_cursor = Some(format!("{}:{}", id, next));
+            }
+            return Ok(page);
+        };
+
+        let invalid = || ErrorData::invalid_params(format!("Invalid cursor: {}", cursor), None);
+        let (id, offset) = cursor.split_once(':').ok_or_else(invalid)?;
+        let id: u64 = id.parse().map_err(|_| invalid())?;
+        let matches = self
+            .snapshots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .ok_or_else(|| {
+                ErrorData::invalid_params(
+                    format!("Expired cursor: {}; list the tools again from the start", cursor),
+                    None,
+                )
+            })?;
+        let mut page = list_tools_page(&matches, Some(offset), self.page_size).map_err(|_| invalid())?;
+        page.next_cursor = page.next_cursor.map(|next| format!("{}:{}", id, next));
+        Ok(page)
+    }
+
+    /// Call a tool by its namespaced name on the server it came from
+    ///
+    /// Names missing from the last listing are looked up with a new one
+    /// before the call is rejected as unknown.
+    pub async fn call(&self, request: CallToolRequestParam) -> Result<CallToolResult, ErrorData> {
+        let route = |name: &str| self.routes.read().ok()?.get(name).cloned();
+        let (server, upstream) = match route(&request.name) {
+            Some(found) => found,
+            None => {
+                self.tools().await.map_err(internal_error)?;
+                route(&request.name).ok_or_else(|| {
+                    ErrorData::invalid_params(format!("Unknown tool: {}", request.name), None)
+                })?
+            }
+        };
+        let upstream_request = CallToolRequestParam {
+            name: upstream.into(),
+            arguments: request.arguments,
+        };
+        server
+            .call_tool(upstream_request, self.call_timeout)
+            .await
+            .map_err(internal_error)
+    }
+
+    /// Serve the proxy on this process's stdin and stdout until the client
+    /// disconnects
+    #[cfg(feature = "proxy")]
+    pub async fn serve_stdio(self) -> Result<(), ToolSearchError> {
+        use rmcp::ServiceExt;
+
+        let service = self
+            .serve((tokio::io::stdin(), tokio::io::stdout()))
+            .await
+            .map_err(|e| ToolSearchError::Transport(e.to_string()))?;
+        service
+            .waiting()
+            .await
+            .map_err(|e| ToolSearchError::Other(e.into()))?;
+        Ok(())
+    }
+}
+
+fn internal_error(error: ToolSearchError) -> ErrorData {
+    ErrorData::internal_error(error.to_string(), None)
+}
+
+impl ServerHandler for ToolProxy {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: "toolsearch-proxy".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                ..Default::default()
+            },
+            instructions: Some(format!(
+                "Tools of several MCP servers, each named <server>{}<tool>",
+                NAMESPACE_SEPARATOR
+            )),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let cursor = request.and_then(|request| request.cursor);
+        self.list_page(cursor.as_deref()).await
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.call(request).await
+    }
+}
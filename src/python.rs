@@ -0,0 +1,256 @@
+//! Python bindings
+//!
+//! Exposes [`load_servers`](crate::load_servers),
+//! [`simple_search`](crate::simple_search) and
+//! [`SearchBuilder`](crate::SearchBuilder) as the `toolsearch` Python module.
+//! Searches block the calling thread (with the GIL released) on a private
+//! Tokio runtime, and matches are returned as plain dicts.
+//!
+//! ```python
+//! import toolsearch
+//!
+//! servers = toolsearch.load_servers("servers.json")
+//! for m in toolsearch.SearchBuilder(servers).query("read file").limit(5).search():
+//!     print(m["server_name"], m["tool"]["name"])
+//! ```
+
+use crate::search;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyRuntimeError};
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+create_exception!(toolsearch, ToolSearchError, PyException, "A tool search failed");
+
+fn to_py_err(err: crate::ToolSearchError) -> PyErr {
+    ToolSearchError::new_err(err.to_string())
+}
+
+/// Run a search future to completion without holding the GIL
+fn block_on<F, T>(py: Python<'_>, future: F) -> PyResult<T>
+where
+    F: Future<Output = Result<T, crate::ToolSearchError>> + Send,
+    T: Send,
+{
+    py.detach(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to start runtime: {}", e)))?;
+        runtime.block_on(future).map_err(to_py_err)
+    })
+}
+
+/// Convert matches to a list of dicts via their JSON form
+fn matches_to_py<'py>(
+    py: Python<'py>,
+    matches: &[crate::ToolSearchMatch],
+) -> PyResult<Bound<'py, PyList>> {
+    let json = serde_json::to_string(matches)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize matches: {}", e)))?;
+    let list = py.import("json")?.call_method1("loads", (json,))?;
+    Ok(list.downcast_into::<PyList>()?)
+}
+
+/// Configuration of one MCP server
+#[pyclass(name = "ServerConfig", module = "toolsearch", frozen)]
+#[derive(Clone)]
+pub struct PyServerConfig {
+    inner: crate::ServerConfig,
+}
+
+#[pymethods]
+impl PyServerConfig {
+    /// Create a stdio server configuration that spawns `command`
+    #[staticmethod]
+    #[pyo3(signature = (name, command, args = Vec::new(), env = HashMap::new(), priority = 0))]
+    fn stdio(
+        name: String,
+        command: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+        priority: u32,
+    ) -> Self {
+        let mut inner = crate::ServerConfig::stdio(name, command).with_priority(priority);
+        if let crate::TransportConfig::Stdio {
+            args: ref mut a,
+            env: ref mut e,
+            ..
+        } = inner.transport
+        {
+            *a = args;
+            *e = env;
+        }
+        Self { inner }
+    }
+
+    /// Name identifier for the server
+    #[getter]
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Ranking priority of the server
+    #[getter]
+    fn priority(&self) -> u32 {
+        self.inner.priority
+    }
+
+    /// Configuration as a JSON string, in the config file format
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ServerConfig(name={:?})", self.inner.name)
+    }
+}
+
+fn configs(servers: Vec<PyServerConfig>) -> Vec<crate::ServerConfig> {
+    servers.into_iter().map(|s| s.inner).collect()
+}
+
+/// Load and validate servers from a JSON configuration file
+#[pyfunction]
+fn load_servers(config_path: &str) -> PyResult<Vec<PyServerConfig>> {
+    let servers = search::load_servers(config_path).map_err(to_py_err)?;
+    Ok(servers
+        .into_iter()
+        .map(|inner| PyServerConfig { inner })
+        .collect())
+}
+
+/// Search all servers for `query`, auto-detecting the search mode
+#[pyfunction]
+fn simple_search<'py>(
+    py: Python<'py>,
+    servers: Vec<PyServerConfig>,
+    query: &str,
+) -> PyResult<Bound<'py, PyList>> {
+    let servers = configs(servers);
+    let matches = block_on(py, search::simple_search(&servers, query))?;
+    matches_to_py(py, &matches)
+}
+
+/// Search builder; every setter returns the builder for chaining
+#[pyclass(name = "SearchBuilder", module = "toolsearch")]
+pub struct PySearchBuilder {
+    inner: Option<search::SearchBuilder>,
+}
+
+impl PySearchBuilder {
+    fn update(
+        mut slf: PyRefMut<'_, Self>,
+        f: impl FnOnce(search::SearchBuilder) -> search::SearchBuilder,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.take()?;
+        slf.inner = Some(f(builder));
+        Ok(slf)
+    }
+
+    fn take(&mut self) -> PyResult<search::SearchBuilder> {
+        self.inner
+            .take()
+            .ok_or_else(|| PyRuntimeError::new_err("SearchBuilder was already used to run a search"))
+    }
+}
+
+#[pymethods]
+impl PySearchBuilder {
+    #[new]
+    fn new(servers: Vec<PyServerConfig>) -> Self {
+        Self {
+            inner: Some(search::SearchBuilder::new(configs(servers))),
+        }
+    }
+
+    /// Search query (regex, keywords or substring are auto-detected)
+    fn query(slf: PyRefMut<'_, Self>, query: String) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.query(query))
+    }
+
+    /// Require all of the given keywords
+    fn keywords(slf: PyRefMut<'_, Self>, keywords: Vec<String>) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.keywords(keywords))
+    }
+
+    /// Match the tool name exactly
+    fn name(slf: PyRefMut<'_, Self>, name: String) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.name(name))
+    }
+
+    /// Limit the number of results
+    fn limit(slf: PyRefMut<'_, Self>, max: usize) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.limit(max))
+    }
+
+    /// Per-server timeout in seconds
+    fn timeout(slf: PyRefMut<'_, Self>, seconds: u64) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.timeout(seconds))
+    }
+
+    /// Overall time budget in seconds; servers still pending are dropped
+    fn deadline(slf: PyRefMut<'_, Self>, seconds: f64) -> PyResult<PyRefMut<'_, Self>> {
+        let budget = Duration::try_from_secs_f64(seconds)
+            .map_err(|e| PyRuntimeError::new_err(format!("Invalid deadline: {}", e)))?;
+        Self::update(slf, |b| b.deadline(budget))
+    }
+
+    /// Sort results by tool name
+    fn sort_by_tool(slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.sort_by_tool())
+    }
+
+    /// Sort results by server name
+    fn sort_by_server(slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.sort_by_server())
+    }
+
+    /// Sort results by server priority
+    fn sort_by_priority(slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.sort_by_priority())
+    }
+
+    /// Include each server's initialize info in the results
+    fn include_server_info(slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        Self::update(slf, |b| b.include_server_info())
+    }
+
+    /// Run the search and return the matches as dicts
+    fn search<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let builder = self.take()?;
+        let matches = block_on(py, builder.search())?;
+        matches_to_py(py, &matches)
+    }
+
+    /// Run the search and return only the first match, or `None`
+    fn first<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let builder = self.take()?;
+        let Some(first) = block_on(py, builder.first())? else {
+            return Ok(None);
+        };
+        Ok(Some(matches_to_py(py, &[first])?.get_item(0)?))
+    }
+
+    /// Describe what the search would do without contacting any server
+    fn explain_plan(&self) -> PyResult<String> {
+        self.inner
+            .as_ref()
+            .map(|b| b.explain_plan().to_string())
+            .ok_or_else(|| PyRuntimeError::new_err("SearchBuilder was already used to run a search"))
+    }
+}
+
+/// The `toolsearch` Python module
+#[pymodule]
+fn toolsearch(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("ToolSearchError", m.py().get_type::<ToolSearchError>())?;
+    m.add_class::<PyServerConfig>()?;
+    m.add_class::<PySearchBuilder>()?;
+    m.add_function(wrap_pyfunction!(load_servers, m)?)?;
+    m.add_function(wrap_pyfunction!(simple_search, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,210 @@
+//! How well a tool is documented
+//!
+//! Beyond [`SearchCriteria::min_description_length`](crate::SearchCriteria::min_description_length),
+//! criteria can leave out tools whose descriptions are too long to fit a
+//! prompt, show no example, or are written in another language than the
+//! agent's. The checks are heuristics over the text, not a parser, so they
+//! are meant for weeding out the worst-documented tools of a catalog.
+//!
+//! ```
+//! use toolsearch::quality::{detect_language, has_example, looks_english};
+//! use toolsearch::testing::ToolFixture;
+//!
+//! let tool = ToolFixture::new("read_file")
+//!     .description("Read a file from disk, e.g. `read_file(\"notes.txt\")`")
+//!     .build();
+//! assert!(has_example(&tool));
+//! assert!(looks_english(tool.description.as_deref().unwrap()));
+//! assert_eq!(detect_language("Lee un archivo del disco y devuelve su contenido"), Some("es"));
+//! assert_eq!(detect_language("ファイルを読み込みます"), Some("ja"));
+//! ```
+
+use rmcp::model::Tool;
+use serde_json::Value;
+
+/// Phrases that introduce an example in a description
+const EXAMPLE_MARKERS: &[&str] = &["example", "e.g.", "for instance", "usage:", "```"];
+
+/// Languages [`detect_language`] can tell apart, as ISO 639-1 codes
+pub const LANGUAGES: &[&str] = &[
+    "en", "es", "fr", "de", "it", "pt", "nl", "ru", "el", "ar", "he", "hi", "th", "zh", "ja", "ko",
+];
+
+/// Short words nearly every sentence of a Latin-script language has, by
+/// language
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &[
+        "the", "a", "an", "to", "of", "and", "or", "in", "for", "with", "from", "is", "are", "by", "on",
+        "this", "that", "it", "as", "be", "all", "into", "its", "if", "not",
+    ]),
+    ("es", &[
+        "el", "la", "los", "las", "de", "del", "y", "que", "en", "un", "una", "por", "para", "con",
+        "es", "su", "se", "al", "lo", "como",
+    ]),
+    ("fr", &[
+        "le", "la", "les", "de", "des", "du", "et", "un", "une", "est", "pour", "dans", "avec",
+        "sur", "que", "qui", "en", "au", "aux", "ce",
+    ]),
+    ("de", &[
+        "der", "die", "das", "und", "ist", "ein", "eine", "mit", "für", "von", "zu", "den", "dem",
+        "nicht", "auf", "im", "in", "des", "oder", "wird", "einem", "einer", "sich",
+    ]),
+    ("it", &[
+        "il", "lo", "la", "gli", "le", "di", "e", "un", "una", "per", "con", "che", "del", "della",
+        "è", "in", "da", "non", "sono",
+    ]),
+    ("pt", &[
+        "o", "a", "os", "as", "de", "do", "da", "e", "um", "uma", "para", "com", "que", "em", "no",
+        "na", "por", "não", "é", "dos",
+    ]),
+    ("nl", &[
+        "de", "het", "een", "en", "van", "is", "op", "te", "met", "voor", "niet", "dat", "die",
+        "in", "zijn", "naar", "bij",
+    ]),
+];
+
+/// Whether `tool` shows an example of its use
+///
+/// True when the description has an example marker ("example", "e.g.",
+/// "for instance", "usage:") or a code span, or when an input schema
+/// property lists `examples`.
+pub fn has_example(tool: &Tool) -> bool {
+    let described = tool.description.as_deref().is_some_and(|description| {
+        let folded = description.to_lowercase();
+        EXAMPLE_MARKERS.iter().any(|marker| folded.contains(marker))
+            || description.matches('`').count() >= 2
+    });
+    described || schema_has_examples(&tool.input_schema)
+}
+
+/// Whether a schema or any schema nested in its properties has examples
+fn schema_has_examples(schema: &serde_json::Map<String, Value>) -> bool {
+    if schema.contains_key("examples") || schema.contains_key("example") {
+        return true;
+    }
+    let nested = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|properties| properties.values())
+        .chain(schema.get("items"));
+    nested
+        .filter_map(Value::as_object)
+        .any(schema_has_examples)
+}
+
+/// Whether `text` reads as English (see [`detect_language`])
+pub fn looks_english(text: &str) -> bool {
+    detect_language(text) == Some("en")
+}
+
+/// The language `text` is written in, as one of [`LANGUAGES`]
+///
+/// Most scripts belong to one of the languages, so the script most letters
+/// are in decides; Han characters count as Japanese when there is any
+/// kana among them. Latin-script text goes to the language whose common
+/// short words it uses most. A few plain ASCII words without any of them
+/// are taken for English, the language most tool descriptions are written
+/// in. `None` when there are no letters or the evidence is split.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let mut counts = [0usize; Script::COUNT];
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        counts[Script::of(c) as usize] += 1;
+    }
+    let (script, &letters) = counts.iter().enumerate().max_by_key(|(_, count)| **count)?;
+    if letters == 0 {
+        return None;
+    }
+    let language = match Script::ALL[script] {
+        Script::Latin => return detect_latin_language(text),
+        Script::Han if counts[Script::Kana as usize] > 0 => "ja",
+        Script::Han => "zh",
+        Script::Kana => "ja",
+        Script::Hangul => "ko",
+        Script::Cyrillic => "ru",
+        Script::Greek => "el",
+        Script::Arabic => "ar",
+        Script::Hebrew => "he",
+        Script::Devanagari => "hi",
+        Script::Thai => "th",
+    };
+    Some(language)
+}
+
+/// `language` as [`detect_language`] names it: lowercase, without region
+pub(crate) fn normalize_language(language: &str) -> String {
+    let primary = language.trim().split(['-', '_']).next().unwrap_or_default();
+    primary.to_ascii_lowercase()
+}
+
+/// Language of Latin-script `text`, by the stopwords it uses
+fn detect_latin_language(text: &str) -> Option<&'static str> {
+    // Split at whitespace only, so "e.g." isn't taken for the word "e"
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphabetic()).to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut scores: Vec<(&'static str, usize)> = STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            let hits = words.iter().filter(|word| stopwords.contains(&word.as_str())).count();
+            (*language, hits)
+        })
+        .collect();
+    scores.sort_by_key(|(_, hits)| std::cmp::Reverse(*hits));
+    match scores.as_slice() {
+        [(language, best), (_, second), ..] if *best > *second => Some(language),
+        [(_, 0), ..] if words.len() < 4 && text.is_ascii() => Some("en"),
+        _ => None,
+    }
+}
+
+/// Writing systems told apart by [`detect_language`]
+#[derive(Clone, Copy)]
+enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+    Hangul,
+    Kana,
+    Han,
+}
+
+impl Script {
+    const ALL: [Script; 10] = [
+        Script::Latin,
+        Script::Cyrillic,
+        Script::Greek,
+        Script::Arabic,
+        Script::Hebrew,
+        Script::Devanagari,
+        Script::Thai,
+        Script::Hangul,
+        Script::Kana,
+        Script::Han,
+    ];
+    const COUNT: usize = Self::ALL.len();
+
+    /// Script of a letter; letters of other scripts count as Latin
+    fn of(c: char) -> Script {
+        match c {
+            '\u{0400}'..='\u{052F}' => Script::Cyrillic,
+            '\u{0370}'..='\u{03FF}' => Script::Greek,
+            '\u{0600}'..='\u{06FF}' | '\u{0750}'..='\u{077F}' => Script::Arabic,
+            '\u{0590}'..='\u{05FF}' => Script::Hebrew,
+            '\u{0900}'..='\u{097F}' => Script::Devanagari,
+            '\u{0E00}'..='\u{0E7F}' => Script::Thai,
+            '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+                Script::Hangul
+            }
+            '\u{3040}'..='\u{30FF}' => Script::Kana,
+            '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' => Script::Han,
+            _ => Script::Latin,
+        }
+    }
+}
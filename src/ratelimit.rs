@@ -0,0 +1,122 @@
+//! Request rate limits for servers
+//!
+//! Searches fetch from every server at once, and a server with offset
+//! cursors gets several pages requested at once (see
+//! [`SearchOptions::parallel_pages`](crate::SearchOptions::parallel_pages)).
+//! Hosted servers behind API quotas or firewalls may not take such bursts.
+//! A server's [`RateLimit`] spaces its requests out and caps how many are in
+//! flight:
+//!
+//! ```json
+//! { "name": "hosted", "rate_limit": { "requests_per_second": 5, "max_concurrent": 2 }, "transport": { "type": "sse", "url": "https://example.com/sse" } }
+//! ```
+//!
+//! The limit covers `tools/list` pages and, for a
+//! [`PooledServer`](crate::pool::PooledServer), tool calls. A pooled server
+//! keeps its [`RateLimiter`] across searches; otherwise each fetch starts
+//! with a fresh one, as it starts with a fresh connection.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// Limits on the requests sent to one server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Requests started per second, spread evenly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<f64>,
+    /// Requests in flight at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+}
+
+impl RateLimit {
+    /// Start at most `requests` requests per second
+    pub fn per_second(requests: f64) -> Self {
+        Self {
+            requests_per_second: Some(requests),
+            max_concurrent: None,
+        }
+    }
+
+    /// Also keep at most `max` requests in flight at once
+    pub fn with_max_concurrent(mut self, max: usize) -> Self {
+        self.max_concurrent = Some(max);
+        self
+    }
+
+    /// Check that the rate is positive and finite, with an interval between
+    /// requests that fits in a [`Duration`], and the concurrency at least 1
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(rate) = self.requests_per_second {
+            if !(rate.is_finite() && rate > 0.0) {
+                return Err(format!("Requests per second must be positive, got {}", rate));
+            }
+            if interval(rate).is_none() {
+                return Err(format!("Requests per second is too small, got {}", rate));
+            }
+        }
+        if self.max_concurrent == Some(0) {
+            return Err("Concurrent requests must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Time between the starts of requests at `rate` per second, if it fits in
+/// a [`Duration`]
+fn interval(rate: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(1.0 / rate).ok()
+}
+
+/// Enforces a [`RateLimit`] on the requests it is asked to admit
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Option<Duration>,
+    concurrent: Option<Semaphore>,
+    /// Earliest time the next request may start
+    next_start: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Limiter for `limit`, which is expected to be [valid](RateLimit::validate)
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            interval: limit
+                .requests_per_second
+                .filter(|rate| rate.is_finite() && *rate > 0.0)
+                .and_then(interval),
+            concurrent: limit.max_concurrent.map(|max| Semaphore::new(max.max(1))),
+            next_start: Mutex::new(None),
+        }
+    }
+
+    /// Wait until a request may start
+    ///
+    /// The returned permit counts toward `max_concurrent` until it is
+    /// dropped, so hold it until the response arrives.
+    pub async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = match &self.concurrent {
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+        if let Some(interval) = self.interval {
+            let start = {
+                let mut next_start = self.next_start.lock().unwrap_or_else(|e| e.into_inner());
+                let start = next_start.map_or_else(Instant::now, |next| next.max(Instant::now()));
+                // An interval too long to add to the clock waits a century
+                *next_start = Some(
+                    start
+                        .checked_add(interval)
+                        .unwrap_or_else(|| start + Duration::from_secs(100 * 365 * 86400)),
+                );
+                start
+            };
+            tokio::time::sleep_until(start).await;
+        }
+        permit
+    }
+}
@@ -0,0 +1,372 @@
+//! Recorded MCP sessions
+//!
+//! A bug report about a server is easiest to act on with the server's exact
+//! answers. With [`SearchOptions::record_dir`](crate::SearchOptions::record_dir)
+//! (`--record DIR` on the command line), every JSON-RPC message exchanged
+//! with each stdio server is written to `DIR/<server>.jsonl`, one
+//! [`RecordedMessage`] per line, in order. A server restarted to offer an
+//! older protocol version starts its recording over.
+//!
+//! A [`ReplaySource`] serves a recording back, with no process or network:
+//! each request gets the response recorded for the same method and
+//! parameters. Searches over it are reproducible and work offline, which
+//! makes recordings usable as integration test fixtures. `--replay DIR`
+//! answers every server of a configuration from its recording.
+//!
+//! ```no_run
+//! use toolsearch::record::ReplaySource;
+//! use toolsearch::SearchBuilder;
+//!
+//! # async fn example() -> Result<(), toolsearch::ToolSearchError> {
+//! let results = SearchBuilder::new(vec![])
+//!     .source(ReplaySource::new("recordings/files.jsonl"))
+//!     .query("read")
+//!     .search()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::protocol::{drop_unparsable_tools, take_malformed, MalformedTools};
+use crate::source::{FetchOptions, ToolListing, ToolSource};
+use crate::{
+    list_connected_tools, Boosts, RateLimit, RateLimiter, ServerConfig, ServerConnection,
+    ToolSearchError,
+};
+use anyhow::Context;
+use futures::future::BoxFuture;
+use rmcp::model::{ClientInfo, Tool};
+use rmcp::ServiceExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// File suffix of recordings
+pub const RECORDING_SUFFIX: &str = ".jsonl";
+
+/// Which way a [`RecordedMessage`] went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// From toolsearch to the server
+    Sent,
+    /// From the server to toolsearch
+    Received,
+}
+
+/// One line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    /// Which way it went
+    pub direction: Direction,
+    /// The JSON-RPC message, or the line as a string if it wasn't JSON
+    pub message: Value,
+}
+
+/// Path of `server`'s recording in `dir`, with characters other than
+/// letters, digits, `-`, `_` and `.` in the name replaced by `_`
+pub fn recording_path(dir: &Path, server: &str) -> PathBuf {
+    let file: String = server
+        .chars()
+        .map(|c| if c.is_alphanumeric() || "-_.".contains(c) { c } else { '_' })
+        .collect();
+    dir.join(format!("{}{}", file, RECORDING_SUFFIX))
+}
+
+/// Writes the messages of one session to its recording
+#[cfg(feature = "stdio")]
+#[derive(Clone)]
+pub(crate) struct Recorder {
+    file: std::sync::Arc<std::sync::Mutex<std::fs::File>>,
+}
+
+#[cfg(feature = "stdio")]
+impl Recorder {
+    /// Start `server`'s recording in `dir`, replacing an earlier one
+    pub(crate) fn create(dir: &Path, server: &str) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let file = std::fs::File::create(recording_path(dir, server))?;
+        Ok(Self {
+            file: std::sync::Arc::new(std::sync::Mutex::new(file)),
+        })
+    }
+
+    /// Add one line of the session
+    pub(crate) fn record(&self, direction: Direction, line: &str) {
+        use std::io::Write;
+
+        let message =
+            serde_json::from_str(line).unwrap_or_else(|_| Value::String(line.to_string()));
+        let Ok(mut entry) = serde_json::to_string(&RecordedMessage { direction, message }) else {
+            return;
+        };
+        entry.push('\n');
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = file.write_all(entry.as_bytes()) {
+            tracing::warn!(error = %e, "could not record message");
+        }
+    }
+
+    /// Relay rmcp's output to a stdio server line by line, recording it as
+    /// sent
+    pub(crate) fn relay_sent(
+        self,
+        mut stdin: tokio::process::ChildStdin,
+    ) -> tokio::io::DuplexStream {
+        let (writer, reader) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                self.record(Direction::Sent, &line);
+                if stdin.write_all(line.as_bytes()).await.is_err()
+                    || stdin.write_all(b"\n").await.is_err()
+                    || stdin.flush().await.is_err()
+                {
+                    break;
+                }
+            }
+        });
+        writer
+    }
+}
+
+/// A [`ToolSource`] answering from a recorded session; see the
+/// [module docs](self)
+#[derive(Debug, Clone)]
+pub struct ReplaySource {
+    name: String,
+    path: PathBuf,
+    config: Option<ServerConfig>,
+}
+
+impl ReplaySource {
+    /// Replay the recording at `path`, named after the file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .map(|n| n.strip_suffix(RECORDING_SUFFIX).unwrap_or(&n).to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        Self {
+            name,
+            path,
+            config: None,
+        }
+    }
+
+    /// Answer for `server` from its recording in `dir`, keeping the
+    /// server's name, priority, boosts, timeout and blocked tools
+    pub fn for_server(server: ServerConfig, dir: &Path) -> Self {
+        Self {
+            name: server.name.clone(),
+            path: recording_path(dir, &server.name),
+            config: Some(server),
+        }
+    }
+
+    /// Override the source name used in search results
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Recording the answers come from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open a connection to a task answering from the recording
+    async fn connect(&self) -> Result<ServerConnection, ToolSearchError> {
+        let data = tokio::fs::read_to_string(&self.path)
+            .await
+            .with_context(|| format!("Failed to read recording: {}", self.path.display()))?;
+        let messages = data
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<RecordedMessage>, _>>()
+            .with_context(|| format!("Invalid recording: {}", self.path.display()))?;
+
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let malformed = MalformedTools::default();
+        tokio::spawn(answer(Exchanges::new(messages), server, malformed.clone()));
+        let service = ClientInfo::default()
+            .serve(tokio::io::split(client))
+            .await
+            .map_err(|e| {
+                ToolSearchError::Connection(format!("Failed to initialize client: {}", e))
+            })?;
+        Ok(ServerConnection {
+            service,
+            malformed,
+            #[cfg(feature = "stdio")]
+            _process: None,
+        })
+    }
+}
+
+impl ToolSource for ReplaySource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.path.is_file() {
+            Ok(())
+        } else {
+            Err(format!("Recording does not exist: {}", self.path.display()))
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        self.config.as_ref().map_or(0, |config| config.priority)
+    }
+
+    fn boosts(&self) -> Boosts {
+        self.config.as_ref().map(ServerConfig::boosts).unwrap_or_default()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.config.as_ref().and_then(ServerConfig::timeout)
+    }
+
+    fn blocked_tools(&self) -> Vec<String> {
+        self.config
+            .as_ref()
+            .map(|config| config.blocked_tools.clone())
+            .unwrap_or_default()
+    }
+
+    fn list_tools(
+        &self,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+        Box::pin(async move { Ok(self.fetch(timeout).await?.tools) })
+    }
+
+    fn fetch(&self, timeout: Option<Duration>) -> BoxFuture<'_, Result<ToolListing, ToolSearchError>> {
+        Box::pin(async move {
+            let options = FetchOptions {
+                timeout,
+                ..Default::default()
+            };
+            self.fetch_with(&options).await
+        })
+    }
+
+    fn fetch_with<'a>(
+        &'a self,
+        options: &'a FetchOptions,
+    ) -> BoxFuture<'a, Result<ToolListing, ToolSearchError>> {
+        Box::pin(async move {
+            let started = Instant::now();
+            let service = self.connect().await?;
+            let connect_time = Some(started.elapsed());
+            let limiter = RateLimiter::new(RateLimit::default());
+            let (tools, truncated) =
+                list_connected_tools(&service, &self.name, options, &limiter).await?;
+            Ok(ToolListing {
+                tools,
+                server_info: service.peer_info().cloned(),
+                connect_time,
+                truncated,
+                malformed: take_malformed(&service.malformed),
+            })
+        })
+    }
+}
+
+/// Recorded requests with the responses they got: method, params, response
+struct Exchanges(Vec<(String, Value, Value)>);
+
+impl Exchanges {
+    fn new(messages: Vec<RecordedMessage>) -> Self {
+        let id = |message: &Value| message.get("id").map(Value::to_string);
+        let mut responses: HashMap<String, Value> = messages
+            .iter()
+            .filter(|m| m.direction == Direction::Received)
+            .filter(|m| m.message.get("result").is_some() || m.message.get("error").is_some())
+            .filter_map(|m| Some((id(&m.message)?, m.message.clone())))
+            .collect();
+        Self(
+            messages
+                .iter()
+                .filter(|m| m.direction == Direction::Sent)
+                .filter_map(|m| {
+                    let method = m.message.get("method")?.as_str()?;
+                    let response = responses.remove(&id(&m.message)?)?;
+                    Some((method.to_string(), params(&m.message), response))
+                })
+                .collect(),
+        )
+    }
+
+    /// The first unused response recorded for `request`'s method and
+    /// params, given `request`'s id
+    fn answer(&mut self, request: &Value) -> Option<Value> {
+        let method = request.get("method")?.as_str()?;
+        let params = params(request);
+        // The initialize request differs from the recorded one in details
+        // such as the protocol version offered
+        let i = self
+            .0
+            .iter()
+            .position(|(m, p, _)| m == method && (method == "initialize" || *p == params))?;
+        let (_, _, mut response) = self.0.remove(i);
+        response["id"] = request["id"].clone();
+        Some(response)
+    }
+}
+
+/// A message's params without `_meta` (which holds per-connection progress
+/// tokens), with none and `{}` alike
+fn params(message: &Value) -> Value {
+    match message.get("params") {
+        Some(Value::Object(params)) => {
+            let mut params = params.clone();
+            params.remove("_meta");
+            Value::Object(params)
+        }
+        Some(params) if !params.is_null() => params.clone(),
+        _ => json!({}),
+    }
+}
+
+/// Answer the requests arriving on `transport` from `exchanges`, without
+/// the tools that don't parse, as a stdio server's answers are
+async fn answer(
+    mut exchanges: Exchanges,
+    transport: tokio::io::DuplexStream,
+    malformed: MalformedTools,
+) {
+    let (reader, mut writer) = tokio::io::split(transport);
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Ok(request) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        // Notifications get no answer
+        let Some(id) = request.get("id") else {
+            continue;
+        };
+        let response = exchanges.answer(&request).unwrap_or_else(|| {
+            let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("no recorded response to {}", method) }
+            })
+        });
+        let response = response.to_string();
+        let response = drop_unparsable_tools(&response, &malformed);
+        if writer.write_all(response.as_bytes()).await.is_err()
+            || writer.write_all(b"\n").await.is_err()
+        {
+            break;
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Schema fields blanked out of results
+//!
+//! Tool schemas sometimes embed things that shouldn't leave the machine:
+//! internal URLs as defaults, example credentials. A [`Redaction`] names
+//! schema keys by pattern (`*` stands for any run of characters), and the
+//! value under every matching key of a tool's input and output schemas, at
+//! any depth, is replaced with [`REDACTED`]. A pattern that names a
+//! property, such as `api_key`, blanks the whole property.
+//!
+//! Results are redacted once the search is done, before they are written
+//! out, so matching still sees the whole schema. Catalogs can be redacted
+//! before they are saved with [`Catalog::redact`](crate::Catalog::redact).
+//!
+//! ```
+//! use serde_json::json;
+//! use toolsearch::redact::{Redaction, REDACTED};
+//! use toolsearch::testing::ToolFixture;
+//!
+//! let mut tool = ToolFixture::new("fetch")
+//!     .schema(json!({
+//!         "type": "object",
+//!         "properties": {
+//!             "url": { "type": "string", "default": "https://wiki.internal/" }
+//!         }
+//!     }))
+//!     .build();
+//! Redaction::new(["default"]).redact_tool(&mut tool);
+//! assert_eq!(tool.input_schema["properties"]["url"]["default"], REDACTED);
+//! assert_eq!(tool.input_schema["properties"]["url"]["type"], "string");
+//! ```
+
+use crate::{glob_matches, ToolSearchMatch};
+use rmcp::model::{JsonObject, Tool};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// What redacted values are replaced with
+pub const REDACTED: &str = "[redacted]";
+
+/// Patterns of schema keys whose values are redacted; see the
+/// [module docs](self)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Redaction {
+    patterns: Vec<String>,
+}
+
+impl Redaction {
+    /// Redact the values of schema keys matching `patterns`
+    pub fn new(patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            patterns: patterns.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Also redact keys matching `pattern`
+    pub fn push(&mut self, pattern: impl Into<String>) {
+        self.patterns.push(pattern.into());
+    }
+
+    /// The patterns, as given
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Whether nothing is redacted
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether the value under `key` is redacted
+    pub fn redacts(&self, key: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| glob_matches(pattern, key))
+    }
+
+    /// Redact the schemas of a match's tool
+    pub fn apply(&self, m: &mut ToolSearchMatch) {
+        self.redact_tool(&mut m.tool);
+    }
+
+    /// Redact a tool's input and output schemas
+    pub fn redact_tool(&self, tool: &mut Tool) {
+        if self.is_empty() {
+            return;
+        }
+        self.redact_object(Arc::make_mut(&mut tool.input_schema));
+        if let Some(schema) = &mut tool.output_schema {
+            self.redact_object(Arc::make_mut(schema));
+        }
+    }
+
+    fn redact_object(&self, object: &mut JsonObject) {
+        for (key, value) in object.iter_mut() {
+            if self.redacts(key) {
+                *value = Value::String(REDACTED.to_string());
+            } else {
+                self.redact_value(value);
+            }
+        }
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => self.redact_object(object),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! MCP registry lookup
+//!
+//! Queries a public MCP server registry (the official registry API by default)
+//! to discover servers that are not installed yet, and derives install hints
+//! from the packages and remote endpoints each entry advertises.
+
+use crate::{Runner, ServerConfig, ToolSearchError, TransportConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Base URL of the official MCP registry
+pub const DEFAULT_REGISTRY_URL: &str = "https://registry.modelcontextprotocol.io";
+
+/// A server listed in an MCP registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryServer {
+    /// Registry name of the server (e.g. `io.github.owner/server`)
+    pub name: String,
+    /// Description of the server
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Published version
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Source repository URL
+    #[serde(default)]
+    pub repository: Option<String>,
+    /// Installable packages (npm, PyPI, OCI, ...)
+    #[serde(default)]
+    pub packages: Vec<RegistryPackage>,
+    /// Hosted endpoints that can be connected to directly
+    #[serde(default)]
+    pub remotes: Vec<RegistryRemote>,
+}
+
+/// An installable package for a registry server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryPackage {
+    /// Package ecosystem (`npm`, `pypi`, `oci`, ...)
+    pub registry_type: String,
+    /// Package identifier within the ecosystem
+    pub identifier: String,
+    /// Package version
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// A hosted endpoint for a registry server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryRemote {
+    /// Transport type (`sse`, `streamable-http`, ...)
+    pub transport_type: String,
+    /// Endpoint URL
+    pub url: String,
+}
+
+impl RegistryServer {
+    /// Parse a registry entry, accepting both wrapped (`{"server": {...}}`)
+    /// and flat entry shapes
+    fn from_entry(entry: &Value) -> Option<Self> {
+        let server = entry.get("server").unwrap_or(entry);
+        let name = server.get("name")?.as_str()?.to_string();
+        let str_field = |key: &str| server.get(key).and_then(Value::as_str).map(str::to_string);
+
+        let packages = server
+            .get("packages")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|pkg| {
+                Some(RegistryPackage {
+                    registry_type: pkg
+                        .get("registryType")
+                        .or_else(|| pkg.get("registry_name"))
+                        .and_then(Value::as_str)?
+                        .to_string(),
+                    identifier: pkg
+                        .get("identifier")
+                        .or_else(|| pkg.get("name"))
+                        .and_then(Value::as_str)?
+                        .to_string(),
+                    version: pkg.get("version").and_then(Value::as_str).map(str::to_string),
+                })
+            })
+            .collect();
+
+        let remotes = server
+            .get("remotes")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|remote| {
+                Some(RegistryRemote {
+                    transport_type: remote
+                        .get("type")
+                        .or_else(|| remote.get("transport_type"))
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown")
+                        .to_string(),
+                    url: remote.get("url")?.as_str()?.to_string(),
+                })
+            })
+            .collect();
+
+        Some(Self {
+            name,
+            description: str_field("description"),
+            version: str_field("version"),
+            repository: server
+                .pointer("/repository/url")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            packages,
+            remotes,
+        })
+    }
+
+    /// Human-readable hint describing how to install or connect to the server
+    pub fn install_hint(&self) -> Option<String> {
+        if let Some(config) = self.suggested_config() {
+            return match &config.transport {
+                TransportConfig::Stdio { .. } => config
+                    .command_line()
+                    .map(|(command, args)| format!("{} {}", command, args.join(" "))),
+                TransportConfig::Sse { url, .. } => Some(format!("connect to {}", url)),
+            };
+        }
+        self.packages
+            .first()
+            .map(|pkg| format!("{} package {}", pkg.registry_type, pkg.identifier))
+    }
+
+    /// Server configuration that would run this server, if one can be derived
+    ///
+    /// npm packages run through `npx`, PyPI packages through `uvx`, and OCI
+    /// images through `docker run`; otherwise an SSE remote is used.
+    pub fn suggested_config(&self) -> Option<ServerConfig> {
+        let short_name = self
+            .name
+            .rsplit('/')
+            .next()
+            .unwrap_or(&self.name)
+            .to_string();
+
+        for pkg in &self.packages {
+            let runner = match pkg.registry_type.as_str() {
+                "npm" => Runner::Npx,
+                "pypi" => Runner::Uvx,
+                "oci" | "docker" => Runner::Docker,
+                _ => continue,
+            };
+            return Some(ServerConfig::package(short_name, runner, pkg.identifier.clone()));
+        }
+
+        self.remotes
+            .iter()
+            .find(|remote| remote.transport_type == "sse")
+            .map(|remote| ServerConfig::sse(short_name, remote.url.clone()))
+    }
+
+    /// Whether one of the given servers already runs this registry entry
+    pub fn is_installed(&self, servers: &[ServerConfig]) -> bool {
+        servers.iter().any(|server| match &server.transport {
+            TransportConfig::Stdio { .. } => {
+                let (command, args) = server.command_line().unwrap_or_default();
+                self.packages.iter().any(|pkg| {
+                    command == pkg.identifier || args.contains(&pkg.identifier)
+                })
+            }
+            TransportConfig::Sse { url, .. } => self.remotes.iter().any(|r| &r.url == url),
+        })
+    }
+}
+
+/// Client for an MCP server registry
+#[derive(Debug, Clone)]
+pub struct RegistryClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl Default for RegistryClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_REGISTRY_URL)
+    }
+}
+
+impl RegistryClient {
+    /// Create a client for the registry at the given base URL
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Search the registry for servers matching a query
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<RegistryServer>, ToolSearchError> {
+        let url = format!("{}/v0/servers", self.base_url);
+        let mut request = self.http.get(&url).query(&[("search", query)]);
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit.to_string())]);
+        }
+        if let Some(timeout_dur) = timeout {
+            request = request.timeout(timeout_dur);
+        }
+
+        let response = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ToolSearchError::Connection(format!("Registry request failed: {}", e)))?;
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| ToolSearchError::Transport(format!("Invalid registry response: {}", e)))?;
+
+        let mut servers = parse_registry_response(&body);
+        if let Some(limit) = limit {
+            servers.truncate(limit);
+        }
+        Ok(servers)
+    }
+}
+
+/// Extract servers from a registry list response
+pub fn parse_registry_response(body: &Value) -> Vec<RegistryServer> {
+    body.get("servers")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(RegistryServer::from_entry)
+        .collect()
+}
@@ -0,0 +1,242 @@
+//! Search reports
+//!
+//! A [`SearchReport`] carries the matches of a search together with details
+//! about how the search ran, such as how long each server took.
+
+use crate::{MalformedTool, SearchCriteria, SearchMode, ToolSearchMatch};
+use std::fmt;
+use std::time::Duration;
+
+/// Matches of a search plus details about how it ran
+#[derive(Debug, Clone)]
+pub struct SearchReport {
+    /// Matching tools, sorted and limited per the search options
+    pub matches: Vec<ToolSearchMatch>,
+    /// Fetch timing of each contacted server, in configuration order
+    pub timings: Vec<ServerTiming>,
+    /// Effective criteria the tools were matched with (after auto-detection)
+    pub criteria: SearchCriteria,
+    /// Whether the search deadline expired before every server answered
+    pub truncated: bool,
+    /// Servers without tools, and problems that were skipped over because
+    /// `continue_on_error` is set
+    pub warnings: Vec<SearchWarning>,
+    /// How many of the servers the search covered answered
+    pub health: Health,
+}
+
+/// How many of the servers a search covered answered, so a caller can tell
+/// a full catalog from a partial one
+///
+/// Every server counts once: `queried` is the sum of the others.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Health {
+    /// Servers the search covered
+    pub queried: usize,
+    /// Servers contacted that listed their tools
+    pub succeeded: usize,
+    /// Servers answered from tools fetched earlier, without contacting them
+    pub cached: usize,
+    /// Servers with an invalid configuration, whose fetch failed, or that
+    /// hadn't answered when the deadline expired
+    pub failed: usize,
+    /// Servers left out without contacting them, because they are
+    /// unhealthy or their circuit is open
+    /// ([`ToolSearchClient`](crate::ToolSearchClient) only)
+    pub skipped: usize,
+}
+
+impl Health {
+    /// Servers whose tools were searched, contacted or cached
+    pub fn responded(&self) -> usize {
+        self.succeeded + self.cached
+    }
+
+    /// Fraction of the servers covered that responded; 1.0 when there were
+    /// none
+    pub fn response_rate(&self) -> f32 {
+        if self.queried == 0 {
+            1.0
+        } else {
+            self.responded() as f32 / self.queried as f32
+        }
+    }
+}
+
+impl fmt::Display for Health {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} server(s) responded ({} succeeded, {} cached, {} failed, {} skipped)",
+            self.responded(),
+            self.queried,
+            self.succeeded,
+            self.cached,
+            self.failed,
+            self.skipped
+        )
+    }
+}
+
+/// A problem a search skipped over instead of failing, or a server that
+/// has no tools to search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchWarning {
+    /// A server's configuration is invalid, so it was not contacted
+    InvalidConfig {
+        /// Name of the server
+        server_name: String,
+        /// Why the configuration is invalid
+        message: String,
+    },
+    /// Fetching tools from a server failed
+    ServerFailed {
+        /// Name of the server
+        server_name: String,
+        /// The error, as text
+        error: String,
+    },
+    /// Listing a server's tools stopped at the page or tool limit
+    /// ([`SearchOptions::max_pages`](crate::SearchOptions::max_pages),
+    /// [`max_tools`](crate::SearchOptions::max_tools)), so only the tools
+    /// listed until then were searched
+    ToolListTruncated {
+        /// Name of the server
+        server_name: String,
+        /// Number of tools that were listed
+        tools: usize,
+    },
+    /// The server doesn't declare the tools capability (it may only serve
+    /// resources or prompts), so its tools were not listed
+    NoToolsCapability {
+        /// Name of the server
+        server_name: String,
+    },
+    /// The server listed a tool definition that breaks the MCP schema
+    MalformedTool {
+        /// Name of the server
+        server_name: String,
+        /// The tool and what is wrong with it
+        tool: MalformedTool,
+    },
+    /// The server answered, but used more of its timeout than
+    /// [`SearchOptions::slow_server_fraction`](crate::SearchOptions::slow_server_fraction)
+    /// allows, so it may time out when it is busier
+    SlowServer {
+        /// Name of the server
+        server_name: String,
+        /// Time the fetch took
+        elapsed: Duration,
+        /// Timeout the fetch ran with
+        timeout: Duration,
+        /// Timeout with headroom for the measured time, e.g. for the
+        /// server's `timeout_secs`
+        suggested_timeout: Duration,
+    },
+}
+
+impl SearchWarning {
+    /// Name of the server the warning is about
+    pub fn server_name(&self) -> &str {
+        match self {
+            SearchWarning::InvalidConfig { server_name, .. }
+            | SearchWarning::ServerFailed { server_name, .. }
+            | SearchWarning::ToolListTruncated { server_name, .. }
+            | SearchWarning::NoToolsCapability { server_name }
+            | SearchWarning::MalformedTool { server_name, .. }
+            | SearchWarning::SlowServer { server_name, .. } => server_name,
+        }
+    }
+}
+
+impl fmt::Display for SearchWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchWarning::InvalidConfig {
+                server_name,
+                message,
+            } => write!(f, "Invalid server configuration {}: {}", server_name, message),
+            SearchWarning::ServerFailed { server_name, error } => {
+                write!(f, "Error connecting to server {}: {}", server_name, error)
+            }
+            SearchWarning::ToolListTruncated { server_name, tools } => write!(
+                f,
+                "Stopped listing tools of server {} after {} tools (page or tool limit reached)",
+                server_name, tools
+            ),
+            SearchWarning::NoToolsCapability { server_name } => {
+                write!(f, "Server {} has no tools capability", server_name)
+            }
+            SearchWarning::MalformedTool { server_name, tool } => write!(
+                f,
+                "Server {} listed malformed tool {}{}: {}",
+                server_name,
+                tool.name.as_deref().unwrap_or("(unnamed)"),
+                if tool.dropped { " (left out)" } else { "" },
+                tool.problem
+            ),
+            SearchWarning::SlowServer {
+                server_name,
+                elapsed,
+                timeout,
+                suggested_timeout,
+            } => write!(
+                f,
+                "Server {} took {:.1?} of its {:?} timeout; consider a timeout of {}s",
+                server_name,
+                elapsed,
+                timeout,
+                suggested_timeout.as_secs()
+            ),
+        }
+    }
+}
+
+/// How long fetching tools from one server took
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerTiming {
+    /// Name of the server (or other tool source)
+    pub server_name: String,
+    /// Time taken to connect, for sources that connect to a server
+    pub connect: Option<Duration>,
+    /// Time taken to list tools once connected (absent if the fetch failed)
+    pub list: Option<Duration>,
+    /// Total time spent on the server, including failures
+    pub total: Duration,
+    /// Whether the tools were fetched successfully
+    pub succeeded: bool,
+    /// Timeout the fetch ran with
+    pub timeout: Option<Duration>,
+    /// MCP protocol version agreed in the handshake, for MCP servers
+    pub protocol_version: Option<String>,
+}
+
+impl SearchReport {
+    /// Search mode that actually ran
+    pub fn mode(&self) -> SearchMode {
+        self.criteria.mode
+    }
+
+    /// Timings sorted slowest first
+    pub fn slowest(&self) -> Vec<&ServerTiming> {
+        let mut timings: Vec<_> = self.timings.iter().collect();
+        timings.sort_by_key(|t| std::cmp::Reverse(t.total));
+        timings
+    }
+
+    /// Timeouts suggested for servers that came close to theirs, by server
+    /// name
+    pub fn suggested_timeouts(&self) -> Vec<(&str, Duration)> {
+        self.warnings
+            .iter()
+            .filter_map(|warning| match warning {
+                SearchWarning::SlowServer {
+                    server_name,
+                    suggested_timeout,
+                    ..
+                } => Some((server_name.as_str(), *suggested_timeout)),
+                _ => None,
+            })
+            .collect()
+    }
+}
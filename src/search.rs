@@ -4,14 +4,35 @@
 //! It automatically handles complexity like search mode detection, error handling,
 //! and result formatting.
 
-use crate::{SearchCriteria, SearchOptions, ServerConfig, SortOrder, ToolSearchMatch, ToolSearchError};
+use crate::alias::AliasMap;
+use crate::error::ConfigError;
+use crate::include::{expand_includes, has_includes, IncludeBase};
+use crate::popularity::Popularity;
+use crate::projection::Projection;
+use crate::{
+    Dedupe, Matcher, Observer, SearchCriteria, SearchFields, SearchMode, SearchOptions, SearchReport, ServerConfig, SortOrder, ToolSearchMatch,
+    ToolSearchError, ToolSource, Truncation,
+};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Simple search builder for intuitive tool searching
 pub struct SearchBuilder {
     servers: Vec<ServerConfig>,
+    sources: Vec<Arc<dyn ToolSource>>,
     query: Option<String>,
     keywords: Option<Vec<String>>,
+    name: Option<String>,
+    criteria: Option<SearchCriteria>,
+    tags: Vec<String>,
+    min_description_length: Option<usize>,
+    max_description_length: Option<usize>,
+    require_example: bool,
+    language: Option<String>,
+    aliases: AliasMap,
+    or_queries: Vec<String>,
     options: SearchOptions,
 }
 
@@ -20,15 +41,36 @@ impl SearchBuilder {
     pub fn new(servers: Vec<ServerConfig>) -> Self {
         Self {
             servers,
+            sources: Vec::new(),
             query: None,
             keywords: None,
+            name: None,
+            criteria: None,
+            tags: Vec::new(),
+            min_description_length: None,
+            max_description_length: None,
+            require_example: false,
+            language: None,
+            aliases: AliasMap::new(),
+            or_queries: Vec::new(),
             options: SearchOptions::default(),
         }
     }
 
+    /// Add a non-MCP tool source to search alongside the servers
+    pub fn source(mut self, source: impl ToolSource + 'static) -> Self {
+        self.sources.push(Arc::new(source));
+        self
+    }
+
     /// Set the search query (auto-detects search mode)
+    ///
+    /// A `name:`, `title:`, `description:` or `schema:` prefix searches only
+    /// that field, e.g. `name:^read_`.
     pub fn query(mut self, query: impl Into<String>) -> Self {
         self.query = Some(query.into());
+        self.name = None;
+        self.criteria = None;
         self
     }
 
@@ -36,6 +78,82 @@ impl SearchBuilder {
     pub fn keywords(mut self, keywords: Vec<String>) -> Self {
         self.keywords = Some(keywords);
         self.query = None; // Clear query when using keywords
+        self.name = None;
+        self.criteria = None;
+        self
+    }
+
+    /// Match only the tool with exactly this name (case-insensitive)
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self.query = None;
+        self.keywords = None;
+        self.criteria = None;
+        self
+    }
+
+    /// Match with explicit criteria, such as ones composed with
+    /// [`SearchCriteria::and`], instead of a query
+    pub fn criteria(mut self, criteria: SearchCriteria) -> Self {
+        self.criteria = Some(criteria);
+        self.query = None;
+        self.keywords = None;
+        self.name = None;
+        self
+    }
+
+    /// Also match tools matching `query` (repeatable), auto-detected like
+    /// [`query`](Self::query)
+    ///
+    /// All queries run against one fetch of each server. Each match lists
+    /// the queries it matched in [`ToolSearchMatch::queries`].
+    pub fn or_query(mut self, query: impl Into<String>) -> Self {
+        self.or_queries.push(query.into());
+        self
+    }
+
+    /// Only match tools declaring this tag (repeatable; all must be present)
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Only match tools whose description is at least `len` bytes long
+    pub fn min_description_length(mut self, len: usize) -> Self {
+        self.min_description_length = Some(len);
+        self
+    }
+
+    /// Only match tools whose description is at most `len` bytes long
+    pub fn max_description_length(mut self, len: usize) -> Self {
+        self.max_description_length = Some(len);
+        self
+    }
+
+    /// Only match tools that show an example of their use (see
+    /// [`quality::has_example`](crate::quality::has_example))
+    pub fn require_example(mut self) -> Self {
+        self.require_example = true;
+        self
+    }
+
+    /// Only match tools described in `language`, an ISO 639-1 code such as
+    /// `en` (see [`SearchCriteria::language`])
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Only match tools described in English; short for
+    /// [`language("en")`](Self::language)
+    pub fn english_only(self) -> Self {
+        self.language("en")
+    }
+
+    /// Resolve shorthand through `aliases`: a query or exact name that is
+    /// an alias also matches the tools it stands for
+    pub fn aliases(mut self, aliases: AliasMap) -> Self {
+        self.aliases = aliases;
         self
     }
 
@@ -51,6 +169,81 @@ impl SearchBuilder {
         self
     }
 
+    /// Set a wall-clock budget for the whole search
+    ///
+    /// When it expires, matches from the servers that answered so far are
+    /// returned and the report is marked truncated.
+    pub fn deadline(mut self, budget: Duration) -> Self {
+        self.options.deadline = Some(budget);
+        self
+    }
+
+    /// Warn about servers that use more than `fraction` of their timeout
+    /// (default 0.8)
+    pub fn slow_server_fraction(mut self, fraction: f32) -> Self {
+        self.options.slow_server_fraction = Some(fraction);
+        self
+    }
+
+    /// Fail the search when fewer than `fraction` of the servers respond
+    pub fn min_response_rate(mut self, fraction: f32) -> Self {
+        self.options.min_response_rate = Some(fraction);
+        self
+    }
+
+    /// Stop listing a server's tools after `max` pages (default 1000)
+    pub fn max_pages(mut self, max: usize) -> Self {
+        self.options.max_pages = Some(max);
+        self
+    }
+
+    /// Request up to `pages` pages at once from servers whose cursors are
+    /// offsets (default 4; 1 lists one page at a time)
+    pub fn parallel_pages(mut self, pages: usize) -> Self {
+        self.options.parallel_pages = pages;
+        self
+    }
+
+    /// Set the environment variable `key` for every stdio server the search
+    /// starts, over the server's own `env`
+    pub fn env_override(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.env_overrides.insert(key.into(), value.into());
+        self
+    }
+
+    /// Record the MCP session with each stdio server the search starts in
+    /// `dir`, one file per server (see [`record`](crate::record))
+    pub fn record(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.options.record_dir = Some(dir.into());
+        self
+    }
+
+    /// Stop listing a server's tools once it has returned `max` tools
+    pub fn max_tools(mut self, max: usize) -> Self {
+        self.options.max_tools = Some(max);
+        self
+    }
+
+    /// Keep at most `max` matches from each server, its best ranked unless
+    /// [`first_listed_per_server`](Self::first_listed_per_server) is set
+    pub fn max_per_server(mut self, max: usize) -> Self {
+        self.options.max_per_server = Some(max);
+        self
+    }
+
+    /// Make [`max_per_server`](Self::max_per_server) keep the first matches
+    /// each server lists, so servers stop paging once they have that many
+    pub fn first_listed_per_server(mut self) -> Self {
+        self.options.truncation = Truncation::FirstListed;
+        self
+    }
+
+    /// Drop matches that duplicate a better-ranked one
+    pub fn dedupe(mut self, dedupe: Dedupe) -> Self {
+        self.options.dedupe = dedupe;
+        self
+    }
+
     /// Sort results by tool name first, then server
     pub fn sort_by_tool(mut self) -> Self {
         self.options.sort_order = SortOrder::ToolThenServer;
@@ -63,40 +256,444 @@ impl SearchBuilder {
         self
     }
 
+    /// Attach each server's initialize info (implementation, version,
+    /// capabilities) to its matches
+    pub fn include_server_info(mut self) -> Self {
+        self.options.include_server_info = true;
+        self
+    }
+
+    /// Sort results by server priority (highest first), then server, then tool
+    pub fn sort_by_priority(mut self) -> Self {
+        self.options.sort_order = SortOrder::ServerPriority;
+        self
+    }
+
+    /// Sort results with a custom comparator
+    pub fn sort_with(
+        mut self,
+        compare: impl Fn(&ToolSearchMatch, &ToolSearchMatch) -> std::cmp::Ordering
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.options = self.options.sort_with(compare);
+        self
+    }
+
+    /// Report search events to an observer
+    pub fn observer(mut self, observer: impl Observer + 'static) -> Self {
+        self.options = self.options.with_observer(observer);
+        self
+    }
+
+    /// Filter and score matches with a custom matcher
+    pub fn matcher(mut self, matcher: impl Matcher + 'static) -> Self {
+        self.options = self.options.with_custom_matcher(matcher);
+        self
+    }
+
+    /// Drop matches scoring below `min`, whatever the limit
+    pub fn min_score(mut self, min: f32) -> Self {
+        self.options.min_score = Some(min);
+        self
+    }
+
+    /// Sort results by custom matcher score times configured boosts
+    /// (highest first)
+    pub fn sort_by_score(mut self) -> Self {
+        self.options.sort_order = SortOrder::Score;
+        self
+    }
+
+    /// Hide the tools `entry` names from the results: a tool name pattern,
+    /// `server/pattern`, or `server/*` for a whole server (see
+    /// [`block`](crate::block))
+    pub fn block(mut self, entry: impl Into<String>) -> Self {
+        self.options.blocklist.push(entry);
+        self
+    }
+
+    /// Pin `tool` (a name or `server/tool`): its matches sort before all
+    /// others and are flagged [`pinned`](crate::ToolSearchMatch::pinned)
+    pub fn pin(mut self, tool: impl Into<String>) -> Self {
+        self.options.pinned.push(tool.into());
+        self
+    }
+
+    /// Blend usage weights into scores, so tools that are used more rank
+    /// higher with [`sort_by_score`](Self::sort_by_score)
+    pub fn popularity(mut self, popularity: Popularity) -> Self {
+        self.options.popularity = Some(popularity);
+        self
+    }
+
+    /// Blank out the values of schema keys matching `pattern` (`*` matches
+    /// any run of characters) in each match
+    pub fn redact(mut self, pattern: impl Into<String>) -> Self {
+        self.options.redaction.push(pattern);
+        self
+    }
+
+    /// Keep only the fields of each match that `projection` names
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.options.projection = Some(projection);
+        self
+    }
+
     /// Execute the search
     pub async fn search(self) -> Result<Vec<ToolSearchMatch>, ToolSearchError> {
-        use crate::search_tools_with_options;
+        Ok(self.search_report().await?.matches)
+    }
 
-        // Auto-detect search mode based on query
-        let criteria = if let Some(ref keywords) = self.keywords {
-            // Use keyword matching if keywords are explicitly set
-            SearchCriteria::with_keywords(keywords.clone())
-        } else if let Some(ref query) = self.query {
-            // Auto-detect: if query looks like regex, use regex mode
-            // Otherwise use substring matching
-            if is_likely_regex(query) {
-                SearchCriteria::with_regex(query.clone())
-            } else if query.contains(',') {
-                // Comma-separated values -> keyword matching
-                let keywords: Vec<String> = query
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-                SearchCriteria::with_keywords(keywords)
-            } else {
-                SearchCriteria::with_query(query.clone())
+    /// Execute the search, also returning per-server fetch timings
+    pub async fn search_report(self) -> Result<SearchReport, ToolSearchError> {
+        use crate::search_sources_report;
+
+        let alternatives = self.attributed_alternatives();
+        let (criteria, sources, mut options) = self.into_parts();
+        // Queries are attributed by matching again, which needs whole tools
+        let projection = options.projection.take();
+        let redaction = std::mem::take(&mut options.redaction);
+        let mut report = search_sources_report(&sources, &criteria, &options).await?;
+        attribute(&mut report.matches, &alternatives);
+        report.matches.iter_mut().for_each(|m| redaction.apply(m));
+        if let Some(projection) = projection {
+            report.matches.iter_mut().for_each(|m| projection.apply(m));
+        }
+        Ok(report)
+    }
+
+    /// Find one matching tool, resolving as soon as any server yields a match
+    ///
+    /// Servers still being queried are cancelled once a match is found, so
+    /// this is the fast path for "give me a tool that can do X".
+    pub async fn first(self) -> Result<Option<ToolSearchMatch>, ToolSearchError> {
+        use crate::search_sources_first;
+
+        let alternatives = self.attributed_alternatives();
+        let (criteria, sources, mut options) = self.into_parts();
+        let projection = options.projection.take();
+        let redaction = std::mem::take(&mut options.redaction);
+        let mut found = search_sources_first(&sources, &criteria, &options).await?;
+        attribute(found.as_mut_slice(), &alternatives);
+        if let Some(m) = found.as_mut() {
+            redaction.apply(m);
+            if let Some(projection) = projection {
+                projection.apply(m);
             }
+        }
+        Ok(found)
+    }
+
+    /// Resolve the criteria and collect servers and sources into one list
+    fn into_parts(self) -> (SearchCriteria, Vec<Arc<dyn ToolSource>>, SearchOptions) {
+        let criteria = self.resolved_criteria();
+        let mut sources: Vec<Arc<dyn ToolSource>> = self
+            .servers
+            .into_iter()
+            .map(|server| Arc::new(server) as Arc<dyn ToolSource>)
+            .collect();
+        sources.extend(self.sources);
+        (criteria, sources, self.options)
+    }
+
+    /// Describe what [`search`](Self::search) would do, without connecting
+    ///
+    /// The plan shows the auto-detected mode, the parsed query, and which
+    /// sources would be contacted with what timeout.
+    pub fn explain_plan(&self) -> SearchPlan {
+        let criteria = self.resolved_criteria();
+        let regex_error = match criteria.mode {
+            #[cfg(feature = "regex-mode")]
+            SearchMode::Regex => criteria
+                .query
+                .as_deref()
+                .and_then(|pattern| regex::Regex::new(pattern).err())
+                .map(|e| e.to_string()),
+            #[cfg(not(feature = "regex-mode"))]
+            SearchMode::Regex => Some("built without the regex-mode feature".to_string()),
+            _ => None,
+        };
+        let sources = self
+            .servers
+            .iter()
+            .map(|server| server as &dyn ToolSource)
+            .chain(self.sources.iter().map(|source| source.as_ref()))
+            .map(|source| PlannedSource {
+                name: source.name().to_string(),
+                priority: source.priority(),
+                invalid: source.validate().err(),
+            })
+            .collect();
+
+        SearchPlan {
+            mode: criteria.mode,
+            queries: self
+                .attributed_alternatives()
+                .into_iter()
+                .map(|(query, _)| query)
+                .collect(),
+            criteria,
+            regex_error,
+            sources,
+            timeout: self.options.timeout,
+            sort_order: self.options.sort_order,
+            max_results: self.options.max_results,
+            min_score: self.options.min_score,
+        }
+    }
+
+    /// Build the criteria, auto-detecting the search mode from the query
+    fn resolved_criteria(&self) -> SearchCriteria {
+        let mut alternatives = self.alternatives().into_iter().map(|(_, criteria)| criteria);
+        let criteria = match alternatives.next() {
+            Some(first) => alternatives.fold(first, SearchCriteria::or),
+            None => SearchCriteria::match_all(),
+        };
+        let mut criteria = if self.tags.is_empty() {
+            criteria
+        } else {
+            criteria.with_tags(&self.tags)
+        };
+        if self.min_description_length.is_some() {
+            criteria.min_description_length = self.min_description_length;
+        }
+        if self.max_description_length.is_some() {
+            criteria.max_description_length = self.max_description_length;
+        }
+        criteria.require_example |= self.require_example;
+        match &self.language {
+            Some(language) => criteria.language(language),
+            None => criteria,
+        }
+    }
+
+    /// The criteria any of which a tool must match, labeled with the query
+    /// (or name) each came from, before tags are applied
+    ///
+    /// Empty when neither a query nor anything in its place is set, so every
+    /// tool matches.
+    fn alternatives(&self) -> Vec<(String, SearchCriteria)> {
+        let primary = if let Some(ref criteria) = self.criteria {
+            Some(("criteria".to_string(), criteria.clone()))
+        } else if let Some(ref name) = self.name {
+            let criteria = SearchCriteria::with_name(name.clone());
+            Some((name.clone(), self.with_alias_names(criteria, name)))
+        } else if let Some(ref keywords) = self.keywords {
+            // Use keyword matching if keywords are explicitly set
+            Some((keywords.join(","), SearchCriteria::with_keywords(keywords.clone())))
         } else {
-            // No query -> match all
-            SearchCriteria::match_all()
+            self.query
+                .as_ref()
+                .map(|query| (query.clone(), self.with_alias_names(query_criteria(query), query)))
         };
+        primary
+            .into_iter()
+            .chain(self.or_queries.iter().map(|query| {
+                (query.clone(), self.with_alias_names(query_criteria(query), query))
+            }))
+            .collect()
+    }
 
-        search_tools_with_options(&self.servers, &criteria, &self.options).await
+    /// The alternatives a multi-query search labels its matches with; empty
+    /// for searches with one query
+    fn attributed_alternatives(&self) -> Vec<(String, SearchCriteria)> {
+        if self.or_queries.is_empty() {
+            Vec::new()
+        } else {
+            self.alternatives()
+        }
+    }
+
+    /// `criteria`, also matching the tools `shorthand` is an alias for
+    fn with_alias_names(&self, criteria: SearchCriteria, shorthand: &str) -> SearchCriteria {
+        match self.aliases.resolve(shorthand) {
+            [] => criteria,
+            names => criteria.with_aliases(names.to_vec()),
+        }
+    }
+}
+
+/// Criteria for a query, auto-detecting the search mode
+fn query_criteria(query: &str) -> SearchCriteria {
+    let (fields, query) = match field_prefix(query) {
+        Some((fields, rest)) => (Some(fields), rest),
+        None => (None, query),
+    };
+    // Auto-detect: if query looks like regex, use regex mode
+    // Otherwise use substring matching
+    let criteria = if is_likely_regex(query) {
+        SearchCriteria::with_regex(query.to_string())
+    } else if query.contains(',') {
+        // Comma-separated values -> keyword matching
+        let keywords: Vec<String> = query
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        SearchCriteria::with_keywords(keywords)
+    } else {
+        SearchCriteria::with_query(query.to_string())
+    };
+    match fields {
+        Some(fields) => criteria.with_fields(fields),
+        None => criteria,
     }
 }
 
+/// Record on each match which of the labeled `alternatives` it matched
+fn attribute(matches: &mut [ToolSearchMatch], alternatives: &[(String, SearchCriteria)]) {
+    if alternatives.is_empty() {
+        return;
+    }
+    for m in matches {
+        m.queries = alternatives
+            .iter()
+            .filter(|(_, criteria)| criteria.matches(&m.tool))
+            .map(|(query, _)| query.clone())
+            .collect();
+    }
+}
+
+/// What a [`SearchBuilder`] search would do, from [`SearchBuilder::explain_plan`]
+#[derive(Debug, Clone)]
+pub struct SearchPlan {
+    /// Resolved search mode
+    pub mode: SearchMode,
+    /// Criteria the search would match tools with
+    pub criteria: SearchCriteria,
+    /// Queries of a multi-query search, any of which a tool may match
+    pub queries: Vec<String>,
+    /// Why the regex would fail to compile, in regex mode
+    pub regex_error: Option<String>,
+    /// Servers and other sources that would be contacted, in order
+    pub sources: Vec<PlannedSource>,
+    /// Timeout applied to each source
+    pub timeout: Option<Duration>,
+    /// Result sort order
+    pub sort_order: SortOrder,
+    /// Maximum number of results
+    pub max_results: Option<usize>,
+    /// Lowest score a match may have
+    pub min_score: Option<f32>,
+}
+
+/// A source in a [`SearchPlan`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedSource {
+    /// Source name
+    pub name: String,
+    /// Ranking priority
+    pub priority: u32,
+    /// Validation error; invalid sources are skipped (or fail the search)
+    pub invalid: Option<String>,
+}
+
+impl fmt::Display for SearchPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Mode: {:?}", self.mode)?;
+        if let Some(name) = &self.criteria.name {
+            writeln!(f, "Exact name: {}", name)?;
+        }
+        if let Some(query) = &self.criteria.query {
+            writeln!(f, "Query: {}", query)?;
+        }
+        if !self.criteria.keywords.is_empty() {
+            writeln!(f, "Keywords: {}", self.criteria.keywords.join(", "))?;
+        }
+        if !self.criteria.tags.is_empty() {
+            writeln!(f, "Tags: {}", self.criteria.tags.join(", "))?;
+        }
+        if let Some(min) = self.criteria.min_description_length {
+            writeln!(f, "Minimum description length: {}", min)?;
+        }
+        if let Some(max) = self.criteria.max_description_length {
+            writeln!(f, "Maximum description length: {}", max)?;
+        }
+        if self.criteria.require_example {
+            writeln!(f, "Requires an example")?;
+        }
+        if let Some(language) = &self.criteria.language {
+            writeln!(f, "Description language: {}", language)?;
+        }
+        if !self.criteria.aliases.is_empty() {
+            writeln!(f, "Alias for: {}", self.criteria.aliases.join(", "))?;
+        }
+        if !self.queries.is_empty() {
+            writeln!(f, "Any of queries: {}", self.queries.join(" | "))?;
+        } else if self.criteria.is_composed() {
+            writeln!(f, "Criteria: composed with and/or/not")?;
+        }
+        if let Some(error) = &self.regex_error {
+            writeln!(f, "Invalid regex: {}", error)?;
+        }
+        match self.timeout {
+            Some(timeout) => writeln!(f, "Timeout per source: {:?}", timeout)?,
+            None => writeln!(f, "Timeout per source: none")?,
+        }
+        writeln!(f, "Sort order: {:?}", self.sort_order)?;
+        if let Some(max) = self.max_results {
+            writeln!(f, "Limit: {}", max)?;
+        }
+        if let Some(min) = self.min_score {
+            writeln!(f, "Minimum score: {}", min)?;
+        }
+        writeln!(f, "Sources ({}):", self.sources.len())?;
+        for source in &self.sources {
+            match &source.invalid {
+                Some(error) => writeln!(f, "  - {} (invalid: {})", source.name, error)?,
+                None if source.priority > 0 => {
+                    writeln!(f, "  - {} (priority {})", source.name, source.priority)?
+                }
+                None => writeln!(f, "  - {}", source.name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fields named by a `field:` prefix of a query, and the rest of the query
+///
+/// `name:`, `title:`, `description:` and `schema:` (the input schema) limit
+/// matching to that field.
+fn field_prefix(query: &str) -> Option<(SearchFields, &str)> {
+    let (prefix, rest) = query.split_once(':')?;
+    let none = SearchFields {
+        name: false,
+        title: false,
+        description: false,
+        input_schema: false,
+        output_schema: false,
+        annotations: false,
+    };
+    let fields = match prefix {
+        "name" => SearchFields { name: true, ..none },
+        "title" => SearchFields { title: true, ..none },
+        "description" => SearchFields {
+            description: true,
+            ..none
+        },
+        "schema" => SearchFields {
+            input_schema: true,
+            ..none
+        },
+        _ => return None,
+    };
+    Some((fields, rest))
+}
+
+/// Check if a query string looks like a regex pattern
+///
+/// Always false without the `regex-mode` feature, so such queries fall back
+/// to substring matching.
+#[cfg(not(feature = "regex-mode"))]
+fn is_likely_regex(_query: &str) -> bool {
+    false
+}
+
 /// Check if a query string looks like a regex pattern
+#[cfg(feature = "regex-mode")]
 fn is_likely_regex(query: &str) -> bool {
     // Simple heuristic: if it contains regex-like characters, treat as regex
     query.contains('^') || query.contains('$') || query.contains('*') || 
@@ -110,19 +707,9 @@ fn is_likely_regex(query: &str) -> bool {
 /// ```no_run
 /// use toolsearch::simple_search;
 /// use toolsearch::ServerConfig;
-/// use std::collections::HashMap;
 ///
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-/// let servers = vec![
-///     ServerConfig {
-///         name: "server1".to_string(),
-///         transport: toolsearch::TransportConfig::Stdio {
-///             command: "mcp-server".to_string(),
-///             args: vec![],
-///             env: HashMap::new(),
-///         },
-///     },
-/// ];
+/// let servers = vec![ServerConfig::stdio("server1", "mcp-server")];
 ///
 /// // Simple search - auto-detects mode
 /// let results = simple_search(&servers, "read file").await?;
@@ -139,20 +726,165 @@ pub async fn simple_search(
         .await
 }
 
+/// What [`load_servers_with`] does with servers that share a name
+///
+/// Results and per-server timings are told apart by server name, so two
+/// servers with one name make them ambiguous.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Fail with [`ConfigError::DuplicateServer`]
+    #[default]
+    Error,
+    /// Rename later servers with the first free `-2`, `-3`, ... suffix
+    Rename,
+    /// Keep them as they are, for tools that inspect the file as written
+    Keep,
+}
+
+impl std::str::FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(DuplicatePolicy::Error),
+            "rename" => Ok(DuplicatePolicy::Rename),
+            "keep" => Ok(DuplicatePolicy::Keep),
+            _ => Err(format!(
+                "Unknown duplicate policy \"{}\" (expected error, rename, or keep)",
+                s
+            )),
+        }
+    }
+}
+
 /// Load servers from a JSON configuration file
-pub fn load_servers(config_path: &str) -> Result<Vec<ServerConfig>, Box<dyn std::error::Error>> {
-    use std::fs;
-    use serde_json;
-    
-    let config_data = fs::read_to_string(config_path)?;
-    let servers: Vec<ServerConfig> = serde_json::from_str(&config_data)?;
-    
+///
+/// Every server is validated; failures are reported as
+/// [`ToolSearchError::Config`] so callers can match on the failure kind.
+/// Servers that share a name are an error; see [`load_servers_with`].
+pub fn load_servers(config_path: &str) -> Result<Vec<ServerConfig>, ToolSearchError> {
+    load_servers_with(config_path, DuplicatePolicy::Error)
+}
+
+/// Load servers from a JSON configuration file, handling servers that
+/// share a name per `duplicates`
+pub fn load_servers_with(
+    config_path: &str,
+    duplicates: DuplicatePolicy,
+) -> Result<Vec<ServerConfig>, ToolSearchError> {
+    let config_data = read_config_file(config_path)?;
+    let base = IncludeBase::File(config_path.into());
+    parse_servers(&config_data, config_path, &base, duplicates)
+}
+
+/// Read a configuration file, telling a missing file from other failures
+pub(crate) fn read_config_file(config_path: &str) -> Result<String, ConfigError> {
+    use std::io::ErrorKind;
+
+    std::fs::read_to_string(config_path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => ConfigError::NotFound {
+            path: config_path.to_string(),
+        },
+        _ => ConfigError::Read {
+            path: config_path.to_string(),
+            source: e,
+        },
+    })
+}
+
+/// Parse and validate the servers of a configuration read from `config_path`,
+/// reading the files it includes relative to `base`
+pub(crate) fn parse_servers(
+    config_data: &str,
+    config_path: &str,
+    base: &IncludeBase,
+    duplicates: DuplicatePolicy,
+) -> Result<Vec<ServerConfig>, ToolSearchError> {
+    let parse_error = |e: serde_json::Error| ConfigError::Parse {
+        path: config_path.to_string(),
+        line: e.line(),
+        column: e.column(),
+        message: e.to_string(),
+    };
+    let mut servers: Vec<ServerConfig> = if has_includes(config_data) {
+        let entries = serde_json::from_str(config_data).map_err(parse_error)?;
+        expand_includes(entries, config_path, base)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let server = entry
+                    .get("name")
+                    .and_then(serde_json::Value::as_str)
+                    .map_or_else(|| format!("server {}", i + 1), str::to_string);
+                serde_json::from_value(entry).map_err(|e| ConfigError::Validation {
+                    server,
+                    message: e.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?
+    } else {
+        serde_json::from_str(config_data).map_err(parse_error)?
+    };
+
     // Validate all servers
     for server in &servers {
-        server.validate()
-            .map_err(|e| format!("Invalid server configuration '{}': {}", server.name, e))?;
+        server.validate().map_err(|e| ConfigError::Validation {
+            server: server.name.clone(),
+            message: e,
+        })?;
     }
-    
+
+    match duplicates {
+        DuplicatePolicy::Error => {
+            let mut seen = HashSet::new();
+            if let Some(server) = servers.iter().find(|s| !seen.insert(s.name.as_str())) {
+                return Err(ConfigError::DuplicateServer {
+                    path: config_path.to_string(),
+                    server: server.name.clone(),
+                }
+                .into());
+            }
+        }
+        DuplicatePolicy::Rename => {
+            disambiguate_names(&mut servers);
+        }
+        DuplicatePolicy::Keep => {}
+    }
+
     Ok(servers)
 }
 
+/// Rename servers whose name an earlier server has with the first free
+/// `-2`, `-3`, ... suffix, returning how many were renamed
+pub(crate) fn disambiguate_names(servers: &mut [ServerConfig]) -> usize {
+    let mut taken: HashSet<String> = servers.iter().map(|s| s.name.clone()).collect();
+    let mut seen = HashSet::new();
+    let mut renamed = 0;
+    for server in servers.iter_mut() {
+        if !seen.insert(server.name.clone()) {
+            let name = (2..)
+                .map(|n| format!("{}-{}", server.name, n))
+                .find(|name| !taken.contains(name))
+                .expect("some suffix is free");
+            taken.insert(name.clone());
+            seen.insert(name.clone());
+            server.name = name;
+            renamed += 1;
+        }
+    }
+    renamed
+}
+
+/// Save servers to a JSON configuration file
+///
+/// The output loads back with [`load_servers`] into equal configurations,
+/// including any fields toolsearch doesn't recognize.
+pub fn save_servers(config_path: &str, servers: &[ServerConfig]) -> Result<(), ToolSearchError> {
+    let mut config_data = serde_json::to_string_pretty(servers)?;
+    config_data.push('\n');
+    std::fs::write(config_path, config_data).map_err(|e| ConfigError::Write {
+        path: config_path.to_string(),
+        source: e,
+    })?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! Short description excerpts for list UIs
+
+use std::ops::Range;
+
+/// Characters in an excerpt, not counting ellipses
+const WIDTH: usize = 80;
+/// Characters of context kept before the hit
+const LEAD: usize = 24;
+
+/// Single-line excerpt of about [`WIDTH`] characters of `text` around `hit`
+/// (the start of `text` when there is none)
+///
+/// Cuts fall on word boundaries where possible and are marked with `...`;
+/// runs of whitespace, including paragraph breaks, collapse to one space.
+pub(crate) fn excerpt(text: &str, hit: Option<Range<usize>>) -> String {
+    let hit = hit.unwrap_or(0..0);
+
+    let mut start = text[..hit.start]
+        .char_indices()
+        .rev()
+        .nth(LEAD - 1)
+        .map_or(0, |(i, _)| i);
+    // Don't start mid-word
+    if text[..start].chars().next_back().is_some_and(|c| !c.is_whitespace())
+        && let Some((i, c)) = text[start..hit.start].char_indices().find(|(_, c)| c.is_whitespace())
+    {
+        start += i + c.len_utf8();
+    }
+
+    let mut end = text[start..]
+        .char_indices()
+        .nth(WIDTH)
+        .map_or(text.len(), |(i, _)| start + i);
+    if end < text.len() {
+        let keep = hit.end.clamp(start, end);
+        if let Some(i) = text[keep..end].rfind(char::is_whitespace) {
+            end = keep + i;
+        }
+    }
+
+    let body = text[start..end].split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut excerpt = String::with_capacity(body.len() + 6);
+    if !text[..start].trim().is_empty() {
+        excerpt.push_str("...");
+    }
+    excerpt.push_str(&body);
+    if !text[end..].trim().is_empty() {
+        excerpt.push_str("...");
+    }
+    excerpt
+}
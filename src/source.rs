@@ -0,0 +1,283 @@
+//! Pluggable tool sources
+//!
+//! A [`ToolSource`] is anything that can produce a list of tools to search.
+//! MCP servers (via [`ServerConfig`]) are the built-in source, but adapters
+//! can expose other catalogs and have them searched with the same
+//! [`SearchCriteria`](crate::SearchCriteria) and [`SearchOptions`](crate::SearchOptions).
+
+use crate::{
+    fetch_from_server, glob_matches, Boosts, Matcher, Observer, SearchOptions, ServerConfig,
+    ToolSearchError,
+};
+use futures::future::BoxFuture;
+use rmcp::model::{ServerInfo, Tool};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tools fetched from a source, with metadata about the source
+#[derive(Debug, Clone, Default)]
+pub struct ToolListing {
+    /// All tools the source exposes
+    pub tools: Vec<Tool>,
+    /// Initialize response, for sources that are MCP servers
+    pub server_info: Option<ServerInfo>,
+    /// Time taken to connect, for sources that connect to a server
+    pub connect_time: Option<Duration>,
+    /// Whether a page or tool limit from [`FetchOptions`] stopped the
+    /// listing before the source's last tool
+    pub truncated: bool,
+    /// Tool definitions that break the MCP schema, for MCP servers
+    pub malformed: Vec<MalformedTool>,
+}
+
+/// A tool definition a server sent that breaks the MCP schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedTool {
+    /// Name of the tool, if it has one
+    pub name: Option<String>,
+    /// What is wrong with it
+    pub problem: String,
+    /// Whether the tool couldn't be parsed and was left out of the listing;
+    /// tools with lesser problems are listed anyway
+    pub dropped: bool,
+}
+
+impl ToolListing {
+    /// Whether the source can have tools: `false` for MCP servers whose
+    /// initialize response doesn't declare the tools capability
+    pub fn has_tools_capability(&self) -> bool {
+        self.server_info
+            .as_ref()
+            .is_none_or(|info| info.capabilities.tools.is_some())
+    }
+}
+
+/// Limits and progress reporting for one fetch
+///
+/// Searches build these from [`SearchOptions`], so a server that keeps
+/// returning pagination cursors can't hang them.
+#[derive(Debug, Clone, Default)]
+pub struct FetchOptions {
+    /// Timeout for connecting, and for each request once connected
+    pub timeout: Option<Duration>,
+    /// Stop listing after this many pages
+    pub max_pages: Option<usize>,
+    /// Stop listing once this many tools have been collected
+    pub max_tools: Option<usize>,
+    /// Told about each page of tools as it arrives
+    pub observer: Option<Arc<dyn Observer>>,
+    /// Keep only the tools this accepts (its scores are ignored), dropping
+    /// the others page by page instead of collecting every tool
+    pub filter: Option<Arc<dyn Matcher>>,
+    /// Stop listing once this many tools have been kept
+    pub max_matches: Option<usize>,
+    /// Pages requested at once from servers whose cursors are offsets; 0
+    /// or 1 lists one page at a time
+    pub parallel_pages: usize,
+    /// Environment variables set for stdio servers started by the fetch
+    pub env_overrides: HashMap<String, String>,
+    /// Patterns of tool names to drop (see [`block`](crate::block))
+    pub blocked_tools: Vec<String>,
+    /// Directory stdio servers' sessions are recorded in (see
+    /// [`record`](crate::record))
+    pub record_dir: Option<PathBuf>,
+}
+
+impl FetchOptions {
+    /// Whether `tool` isn't blocked and passes the [`filter`](Self::filter)
+    pub fn keeps(&self, tool: &Tool) -> bool {
+        !self
+            .blocked_tools
+            .iter()
+            .any(|pattern| glob_matches(pattern, &tool.name))
+            && self.filter.as_ref().is_none_or(|filter| filter.matches(tool).is_some())
+    }
+}
+
+impl From<&SearchOptions> for FetchOptions {
+    fn from(options: &SearchOptions) -> Self {
+        Self {
+            timeout: options.timeout,
+            max_pages: options.max_pages,
+            max_tools: options.max_tools,
+            observer: options.observer.clone(),
+            parallel_pages: options.parallel_pages,
+            env_overrides: options.env_overrides.clone(),
+            record_dir: options.record_dir.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A catalog of tools that can be searched
+pub trait ToolSource: Send + Sync {
+    /// Name identifying this source in search results
+    fn name(&self) -> &str;
+
+    /// Validate the source configuration before fetching
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Ranking priority used by [`SortOrder::ServerPriority`](crate::SortOrder::ServerPriority)
+    fn priority(&self) -> u32 {
+        0
+    }
+
+    /// Score multipliers for the source's matches
+    fn boosts(&self) -> Boosts {
+        Boosts::default()
+    }
+
+    /// Timeout replacing the search's for this source
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Patterns of tool names the source hides from every search (see
+    /// [`block`](crate::block)); `*` hides the whole source
+    fn blocked_tools(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Fetch all tools from the source, honoring the optional timeout
+    fn list_tools(
+        &self,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>>;
+
+    /// Fetch all tools together with source metadata
+    ///
+    /// The default implementation wraps [`list_tools`](Self::list_tools);
+    /// sources with extra metadata (like MCP servers) override it.
+    fn fetch(&self, timeout: Option<Duration>) -> BoxFuture<'_, Result<ToolListing, ToolSearchError>> {
+        Box::pin(async move {
+            Ok(ToolListing {
+                tools: self.list_tools(timeout).await?,
+                ..Default::default()
+            })
+        })
+    }
+
+    /// Fetch all tools, honoring the limits in `options`
+    ///
+    /// The default implementation calls [`fetch`](Self::fetch), then cuts
+    /// the result to `max_tools`, applies the filter, and keeps at most
+    /// `max_matches`; paginated sources (like MCP servers) override it to
+    /// stop paging early.
+    fn fetch_with<'a>(
+        &'a self,
+        options: &'a FetchOptions,
+    ) -> BoxFuture<'a, Result<ToolListing, ToolSearchError>> {
+        Box::pin(async move {
+            let mut listing = self.fetch(options.timeout).await?;
+            if let Some(max) = options.max_tools
+                && listing.tools.len() > max
+            {
+                listing.tools.truncate(max);
+                listing.truncated = true;
+            }
+            listing.tools.retain(|tool| options.keeps(tool));
+            if let Some(max) = options.max_matches {
+                listing.tools.truncate(max);
+            }
+            Ok(listing)
+        })
+    }
+}
+
+impl<T: ToolSource + ?Sized> ToolSource for Arc<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        (**self).validate()
+    }
+
+    fn priority(&self) -> u32 {
+        (**self).priority()
+    }
+
+    fn boosts(&self) -> Boosts {
+        (**self).boosts()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        (**self).timeout()
+    }
+
+    fn blocked_tools(&self) -> Vec<String> {
+        (**self).blocked_tools()
+    }
+
+    fn list_tools(
+        &self,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+        (**self).list_tools(timeout)
+    }
+
+    fn fetch(&self, timeout: Option<Duration>) -> BoxFuture<'_, Result<ToolListing, ToolSearchError>> {
+        (**self).fetch(timeout)
+    }
+
+    fn fetch_with<'a>(
+        &'a self,
+        options: &'a FetchOptions,
+    ) -> BoxFuture<'a, Result<ToolListing, ToolSearchError>> {
+        (**self).fetch_with(options)
+    }
+}
+
+impl ToolSource for ServerConfig {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        ServerConfig::validate(self)
+    }
+
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    fn boosts(&self) -> Boosts {
+        ServerConfig::boosts(self)
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        ServerConfig::timeout(self)
+    }
+
+    fn blocked_tools(&self) -> Vec<String> {
+        self.blocked_tools.clone()
+    }
+
+    fn list_tools(
+        &self,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+        Box::pin(async move { Ok(self.fetch(timeout).await?.tools) })
+    }
+
+    fn fetch(&self, timeout: Option<Duration>) -> BoxFuture<'_, Result<ToolListing, ToolSearchError>> {
+        Box::pin(async move {
+            let options = FetchOptions {
+                timeout,
+                ..Default::default()
+            };
+            fetch_from_server(self, &options).await
+        })
+    }
+
+    fn fetch_with<'a>(
+        &'a self,
+        options: &'a FetchOptions,
+    ) -> BoxFuture<'a, Result<ToolListing, ToolSearchError>> {
+        Box::pin(fetch_from_server(self, options))
+    }
+}
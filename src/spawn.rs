@@ -0,0 +1,212 @@
+//! Spawning stdio servers
+//!
+//! Windows only finds `.exe` files on its own, so commands like `npx` and
+//! `uvx` (installed as `npx.cmd` / `uvx.cmd`) fail to start unless they are
+//! resolved against `PATHEXT` first. [`resolve_command`] does that lookup, and
+//! servers configured with `"shell": true` run through `cmd /C` on Windows or
+//! `sh -c` elsewhere, so one config works across operating systems.
+//!
+//! Launchers like `npx` start the real server as a sub-process. Servers are
+//! therefore spawned as the leader of their own process group, and the
+//! [`ProcessTree`] guard stops the whole group when a connection is dropped
+//! or times out, instead of leaving orphans behind.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+#[cfg(all(feature = "stdio", unix))]
+use std::time::Duration;
+
+/// How long a process group gets to exit after SIGTERM before SIGKILL
+#[cfg(all(feature = "stdio", unix))]
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Extensions tried on Windows when `PATHEXT` is not set
+#[cfg(windows)]
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// Find the file `command` would run, searching the directories in `path`
+/// and trying each extension listed in `pathext` (`;`-separated, as in the
+/// Windows `PATHEXT` variable)
+///
+/// Commands containing a path separator are not searched for in `path`.
+/// When `command` has no extension, only the names with an extension from
+/// `pathext` are candidates; `pathext` of `None` tries the bare name, as on
+/// Unix.
+///
+/// ```
+/// use std::ffi::OsStr;
+/// use toolsearch::spawn::resolve_command;
+///
+/// # let dir = std::env::temp_dir().join(format!("toolsearch-doc-{}", std::process::id()));
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("npx.cmd"), "").unwrap();
+/// let found = resolve_command("npx", Some(dir.as_os_str()), Some(OsStr::new(".exe;.cmd")));
+/// assert_eq!(found, Some(dir.join("npx.cmd")));
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn resolve_command(
+    command: &str,
+    path: Option<&OsStr>,
+    pathext: Option<&OsStr>,
+) -> Option<PathBuf> {
+    let extensions: Vec<OsString> = match pathext {
+        Some(pathext) if Path::new(command).extension().is_none() => pathext
+            .to_string_lossy()
+            .split(';')
+            .filter(|ext| !ext.is_empty())
+            .map(OsString::from)
+            .collect(),
+        _ => Vec::new(),
+    };
+    // With extensions the bare name is skipped: npm installs an extensionless
+    // shell script next to `npx.cmd` that Windows can't run
+    let candidates = |base: PathBuf| -> Vec<PathBuf> {
+        if extensions.is_empty() {
+            return vec![base];
+        }
+        extensions
+            .iter()
+            .map(|ext| {
+                let mut name = base.clone().into_os_string();
+                name.push(ext);
+                PathBuf::from(name)
+            })
+            .collect()
+    };
+
+    if command.contains(['/', '\\']) {
+        return candidates(PathBuf::from(command)).into_iter().find(|p| p.is_file());
+    }
+    std::env::split_paths(path?)
+        .flat_map(|dir| candidates(dir.join(command)))
+        .find(|p| p.is_file())
+}
+
+/// Build the process for a stdio server
+///
+/// With `shell`, `command` is handed to the system shell, so it may carry
+/// its own arguments (`"npx -y @scope/server"`); `args` are passed after it
+/// unchanged. Without it, Windows resolves `command` against `PATH` and
+/// `PATHEXT` first.
+#[cfg(feature = "stdio")]
+pub(crate) fn command(command: &str, args: &[String], shell: bool) -> tokio::process::Command {
+    let mut cmd = if shell {
+        shell_command(command, args)
+    } else {
+        let mut cmd = tokio::process::Command::new(program(command));
+        cmd.args(args);
+        cmd
+    };
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd
+}
+
+/// A spawned server and every process it started; dropping it stops them all
+///
+/// On Unix the process group gets SIGTERM, so launchers like `docker run`
+/// can shut down what they started, and SIGKILL after [`KILL_GRACE`]. On
+/// Windows the tree is killed with `taskkill /T`.
+#[cfg(feature = "stdio")]
+#[derive(Debug)]
+pub(crate) struct ProcessTree {
+    child: tokio::process::Child,
+    /// Process (and, on Unix, process group) id, kept even after the child
+    /// has been reaped so the rest of the group can still be stopped
+    id: Option<u32>,
+}
+
+#[cfg(feature = "stdio")]
+impl ProcessTree {
+    /// Guard a child spawned by [`command`]
+    pub(crate) fn new(child: tokio::process::Child) -> Self {
+        let id = child.id();
+        Self { child, id }
+    }
+
+    /// The server's stdin and stdout pipes, if not taken yet
+    pub(crate) fn take_stdio(
+        &mut self,
+    ) -> Option<(tokio::process::ChildStdout, tokio::process::ChildStdin)> {
+        Some((self.child.stdout.take()?, self.child.stdin.take()?))
+    }
+
+    /// Log the server's stderr at debug level as it comes, so a server that
+    /// logs a lot never blocks on a full pipe
+    pub(crate) fn log_stderr(&mut self, server: &str) {
+        use tokio::io::AsyncBufReadExt;
+
+        let Some(stderr) = self.child.stderr.take() else {
+            return;
+        };
+        let server = server.to_string();
+        tokio::spawn(async move {
+            let mut lines = tokio::io::BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::debug!(server = %server, "stderr: {}", line);
+            }
+        });
+    }
+}
+
+#[cfg(all(feature = "stdio", unix))]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        let Some(pgid) = self.id.and_then(|id| i32::try_from(id).ok()) else {
+            return;
+        };
+        // SAFETY: kill(2) has no memory-safety preconditions
+        unsafe { libc::kill(-pgid, libc::SIGTERM) };
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                tokio::time::sleep(KILL_GRACE).await;
+                // SAFETY: as above; fails harmlessly with ESRCH if the group is gone
+                unsafe { libc::kill(-pgid, libc::SIGKILL) };
+            });
+        }
+    }
+}
+
+#[cfg(all(feature = "stdio", windows))]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        if let Some(pid) = self.id {
+            let _ = std::process::Command::new("taskkill")
+                .args(["/PID", &pid.to_string(), "/T", "/F"])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn();
+        }
+    }
+}
+
+#[cfg(all(feature = "stdio", windows))]
+fn shell_command(command: &str, args: &[String]) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("cmd");
+    cmd.arg("/C").arg(command).args(args);
+    cmd
+}
+
+#[cfg(all(feature = "stdio", not(windows)))]
+fn shell_command(command: &str, args: &[String]) -> tokio::process::Command {
+    // "$@" appends the arguments without the shell re-splitting them
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c")
+        .arg(format!("{} \"$@\"", command))
+        .arg(command)
+        .args(args);
+    cmd
+}
+
+/// Program to run for `command`: resolved against `PATHEXT` on Windows
+#[cfg(all(feature = "stdio", windows))]
+fn program(command: &str) -> PathBuf {
+    let pathext = std::env::var_os("PATHEXT").unwrap_or_else(|| DEFAULT_PATHEXT.into());
+    resolve_command(command, std::env::var_os("PATH").as_deref(), Some(&pathext))
+        .unwrap_or_else(|| PathBuf::from(command))
+}
+
+#[cfg(all(feature = "stdio", not(windows)))]
+fn program(command: &str) -> PathBuf {
+    PathBuf::from(command)
+}
@@ -0,0 +1,181 @@
+//! Saved, parameterized queries
+//!
+//! Teams that standardize how tools are discovered can save queries as
+//! templates with `{placeholder}`s and fill them in per search. A
+//! [`TemplateSet`] file is a JSON object of named templates:
+//!
+//! ```json
+//! { "verb-noun": "name:^{verb}_.*{noun}" }
+//! ```
+//!
+//! `toolsearch search --profile verb-noun --param verb=read --param noun=file`
+//! then searches for `name:^read_.*file`. Only `{` and `}` around an
+//! identifier form a placeholder, so regex repetitions like `{2,3}` are left
+//! alone. Values are inserted as they are.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use toolsearch::template::QueryTemplate;
+//!
+//! let template = QueryTemplate::new("name:^{verb}_.*{noun}");
+//! assert_eq!(template.placeholders(), ["verb", "noun"]);
+//!
+//! let params = BTreeMap::from([("verb".to_string(), "read".to_string()), ("noun".to_string(), "file".to_string())]);
+//! assert_eq!(template.render(&params).unwrap(), "name:^read_.*file");
+//! ```
+
+use crate::error::ConfigError;
+use crate::{read_json_file, user_config_file, ToolSearchError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A query with `{placeholder}`s
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct QueryTemplate {
+    pattern: String,
+}
+
+/// A piece of a template: literal text or a placeholder name
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+impl QueryTemplate {
+    /// Template for `pattern`
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+        }
+    }
+
+    /// The template as written
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Names of the placeholders, in order of first use
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for part in self.parts() {
+            if let Part::Placeholder(name) = part
+                && !names.contains(&name)
+            {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// The query with every placeholder replaced by its value in `params`
+    ///
+    /// Fails on the first placeholder without a value; unused parameters
+    /// are ignored.
+    pub fn render(&self, params: &BTreeMap<String, String>) -> Result<String, ConfigError> {
+        let mut query = String::with_capacity(self.pattern.len());
+        for part in self.parts() {
+            match part {
+                Part::Text(text) => query.push_str(text),
+                Part::Placeholder(name) => {
+                    let value = params.get(name).ok_or_else(|| ConfigError::MissingParameter {
+                        name: name.to_string(),
+                    })?;
+                    query.push_str(value);
+                }
+            }
+        }
+        Ok(query)
+    }
+
+    fn parts(&self) -> Vec<Part<'_>> {
+        let mut parts = Vec::new();
+        let mut rest = self.pattern.as_str();
+        while let Some(open) = rest.find('{') {
+            let placeholder = rest[open + 1..]
+                .find('}')
+                .map(|close| &rest[open + 1..open + 1 + close])
+                .filter(|name| is_identifier(name));
+            match placeholder {
+                Some(name) => {
+                    parts.push(Part::Text(&rest[..open]));
+                    parts.push(Part::Placeholder(name));
+                    rest = &rest[open + name.len() + 2..];
+                }
+                None => {
+                    parts.push(Part::Text(&rest[..=open]));
+                    rest = &rest[open + 1..];
+                }
+            }
+        }
+        parts.push(Part::Text(rest));
+        parts
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Named query templates
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TemplateSet {
+    templates: BTreeMap<String, QueryTemplate>,
+}
+
+impl TemplateSet {
+    /// Set without templates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Save `template` under `name`, replacing any template of that name
+    pub fn insert(&mut self, name: impl Into<String>, template: QueryTemplate) {
+        self.templates.insert(name.into(), template);
+    }
+
+    /// The template named `name`
+    pub fn get(&self, name: &str) -> Option<&QueryTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Names of all templates, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// Fill in the template named `name`
+    pub fn render(&self, name: &str, params: &BTreeMap<String, String>) -> Result<String, ConfigError> {
+        self.get(name)
+            .ok_or_else(|| ConfigError::UnknownTemplate {
+                name: name.to_string(),
+            })?
+            .render(params)
+    }
+
+    /// Load a template file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ToolSearchError> {
+        Ok(read_json_file(path.as_ref())?)
+    }
+
+    /// Load the [default template file](Self::default_path); empty if there
+    /// is none
+    pub fn load_default() -> Result<Self, ToolSearchError> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::new()),
+        }
+    }
+
+    /// Default template file: `$XDG_CONFIG_HOME/toolsearch/templates.json`,
+    /// or `~/.config/toolsearch/templates.json`
+    pub fn default_path() -> Option<PathBuf> {
+        user_config_file("templates.json")
+    }
+}
@@ -0,0 +1,224 @@
+//! Test utilities
+//!
+//! [`MockServer`] is an in-memory [`ToolSource`] that serves a fixed tool list,
+//! so search logic can be unit-tested without spawning MCP server processes.
+//! [`ToolFixture`] builds the [`Tool`]s to serve or match against.
+//!
+//! ```
+//! use toolsearch::testing::{MockServer, ToolFixture};
+//! use toolsearch::SearchBuilder;
+//!
+//! # async fn example() -> Result<(), toolsearch::ToolSearchError> {
+//! let tool = ToolFixture::new("read_file").description("Read a file from disk").build();
+//! let results = SearchBuilder::new(vec![])
+//!     .source(MockServer::with_tools(vec![tool]).named("files"))
+//!     .query("read")
+//!     .search()
+//!     .await?;
+//! assert_eq!(results[0].server_name, "files");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::source::{ToolListing, ToolSource};
+use crate::ToolSearchError;
+use futures::future::BoxFuture;
+use rmcp::model::{ServerInfo, Tool, ToolAnnotations};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Builder for [`Tool`] values in tests
+///
+/// ```
+/// use serde_json::json;
+/// use toolsearch::testing::ToolFixture;
+/// use toolsearch::SearchCriteria;
+///
+/// let tool = ToolFixture::new("read_file")
+///     .description("Read a file")
+///     .schema(json!({ "type": "object", "properties": { "path": { "type": "string" } } }))
+///     .build();
+/// assert!(SearchCriteria::with_query("read".to_string()).matches(&tool));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ToolFixture {
+    tool: Tool,
+}
+
+impl ToolFixture {
+    /// Start a tool with the given name, no description and an empty input schema
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            tool: Tool {
+                name: name.into().into(),
+                title: None,
+                description: None,
+                input_schema: Arc::new(Map::new()),
+                output_schema: None,
+                annotations: None,
+                icons: None,
+            },
+        }
+    }
+
+    /// Set the display title
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.tool.title = Some(title.into());
+        self
+    }
+
+    /// Set the description
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.tool.description = Some(description.into().into());
+        self
+    }
+
+    /// Set the input schema
+    ///
+    /// # Panics
+    ///
+    /// Panics if `schema` is not a JSON object.
+    pub fn schema(mut self, schema: Value) -> Self {
+        self.tool.input_schema = Arc::new(into_object(schema, "input schema"));
+        self
+    }
+
+    /// Set the output schema
+    ///
+    /// # Panics
+    ///
+    /// Panics if `schema` is not a JSON object.
+    pub fn output_schema(mut self, schema: Value) -> Self {
+        self.tool.output_schema = Some(Arc::new(into_object(schema, "output schema")));
+        self
+    }
+
+    /// Set the annotations
+    pub fn annotations(mut self, annotations: ToolAnnotations) -> Self {
+        self.tool.annotations = Some(annotations);
+        self
+    }
+
+    /// Finish the tool
+    pub fn build(self) -> Tool {
+        self.tool
+    }
+}
+
+impl From<ToolFixture> for Tool {
+    fn from(fixture: ToolFixture) -> Self {
+        fixture.build()
+    }
+}
+
+fn into_object(value: Value, what: &str) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        other => panic!("{} must be a JSON object, got {}", what, other),
+    }
+}
+
+/// In-memory tool source standing in for an MCP server
+#[derive(Debug, Default)]
+pub struct MockServer {
+    name: String,
+    tools: Vec<Tool>,
+    priority: u32,
+    server_info: Option<ServerInfo>,
+    delay: Option<Duration>,
+    error: Option<String>,
+    fetches: AtomicUsize,
+}
+
+impl MockServer {
+    /// Create a mock server named `mock` that serves `tools`
+    pub fn with_tools(tools: Vec<Tool>) -> Self {
+        Self {
+            name: "mock".to_string(),
+            tools,
+            ..Default::default()
+        }
+    }
+
+    /// Set the server name reported in matches
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set the ranking priority
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the initialize info returned alongside the tools
+    pub fn with_server_info(mut self, info: ServerInfo) -> Self {
+        self.server_info = Some(info);
+        self
+    }
+
+    /// Wait this long before answering (subject to the search timeout)
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Fail every fetch with a connection error carrying `message`
+    pub fn failing(mut self, message: impl Into<String>) -> Self {
+        self.error = Some(message.into());
+        self
+    }
+
+    /// Number of times the tools have been fetched
+    pub fn fetch_count(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+}
+
+impl ToolSource for MockServer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    fn list_tools(
+        &self,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+        Box::pin(async move { Ok(self.fetch(timeout).await?.tools) })
+    }
+
+    fn fetch(&self, timeout: Option<Duration>) -> BoxFuture<'_, Result<ToolListing, ToolSearchError>> {
+        Box::pin(async move {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            if let Some(delay) = self.delay {
+                match timeout {
+                    Some(limit) if limit < delay => {
+                        tokio::time::sleep(limit).await;
+                        return Err(ToolSearchError::Connection(format!(
+                            "Connection timeout after {:?} for server: {}",
+                            limit, self.name
+                        )));
+                    }
+                    _ => tokio::time::sleep(delay).await,
+                }
+            }
+            if let Some(message) = &self.error {
+                return Err(ToolSearchError::Connection(message.clone()));
+            }
+            Ok(ToolListing {
+                tools: self.tools.clone(),
+                server_info: self.server_info.clone(),
+                connect_time: None,
+                truncated: false,
+                malformed: Vec::new(),
+            })
+        })
+    }
+}
@@ -0,0 +1,324 @@
+//! Structured validation of a configuration file
+//!
+//! [`load_servers`](crate::load_servers) stops at the first problem, which
+//! is what a search wants. Checking a config change in CI wants all of
+//! them: [`validate_config`] checks every server of a file on its own and
+//! returns a [`ConfigReport`] with each server's status. Problems carry a
+//! stable [code](codes) to gate on, a message, and where there is an
+//! obvious fix, a suggestion. The report serializes to the JSON that
+//! `toolsearch validate --format json` prints.
+//!
+//! ```
+//! use toolsearch::validate::{codes, validate_config};
+//!
+//! let report = validate_config("no-such-servers.json");
+//! assert!(!report.valid);
+//! assert_eq!(report.error.unwrap().code, codes::NOT_FOUND);
+//! ```
+
+use crate::error::ConfigError;
+use crate::include::{expand_includes, IncludeBase};
+use crate::{ConfigSource, ServerConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+
+/// Codes of the problems [`validate_config`] reports
+pub mod codes {
+    /// The file doesn't exist
+    pub const NOT_FOUND: &str = "not_found";
+    /// The file exists but couldn't be read
+    pub const READ_FAILED: &str = "read_failed";
+    /// The configuration couldn't be fetched from its URL
+    pub const FETCH_FAILED: &str = "fetch_failed";
+    /// The file isn't valid JSON
+    pub const INVALID_JSON: &str = "invalid_json";
+    /// The file is JSON, but not a list of servers
+    pub const NOT_A_LIST: &str = "not_a_list";
+    /// An `include` entry names a missing file, isn't a list of paths, or
+    /// closes a cycle
+    pub const INVALID_INCLUDE: &str = "invalid_include";
+    /// A server entry has a missing field, unknown transport, or a value of
+    /// the wrong type
+    pub const INVALID_SERVER: &str = "invalid_server";
+    /// A server's `name` is empty
+    pub const EMPTY_NAME: &str = "empty_name";
+    /// A server earlier in the file has the same `name`
+    pub const DUPLICATE_NAME: &str = "duplicate_name";
+    /// A server's `protocol_version` is empty
+    pub const EMPTY_PROTOCOL_VERSION: &str = "empty_protocol_version";
+    /// A server's `timeout_secs` is zero
+    pub const ZERO_TIMEOUT: &str = "zero_timeout";
+    /// A server's `idle_timeout_secs` is zero
+    pub const ZERO_IDLE_TIMEOUT: &str = "zero_idle_timeout";
+    /// A server's `rate_limit` isn't positive
+    pub const INVALID_RATE_LIMIT: &str = "invalid_rate_limit";
+    /// A server's `boost` isn't a positive, finite number
+    pub const INVALID_BOOST: &str = "invalid_boost";
+    /// One of a server's `tool_boosts` has an empty pattern or a bad factor
+    pub const INVALID_TOOL_BOOST: &str = "invalid_tool_boost";
+    /// One of a server's `blocked_tools` is empty
+    pub const EMPTY_BLOCKED_TOOL: &str = "empty_blocked_tool";
+    /// A stdio server sets both `command` and `runner` with `package`
+    pub const CONFLICTING_COMMAND: &str = "conflicting_command";
+    /// A stdio server's `package` is empty
+    pub const EMPTY_PACKAGE: &str = "empty_package";
+    /// A stdio server sets only one of `runner` and `package`
+    pub const INCOMPLETE_RUNNER: &str = "incomplete_runner";
+    /// A stdio server has neither `command` nor `runner` and `package`
+    pub const EMPTY_COMMAND: &str = "empty_command";
+    /// A stdio server's `env_policy` names an invalid variable
+    pub const INVALID_ENV_NAME: &str = "invalid_env_name";
+    /// An SSE server's `url` is empty
+    pub const EMPTY_URL: &str = "empty_url";
+    /// An SSE server's `url` isn't an http:// or https:// URL
+    pub const INVALID_URL: &str = "invalid_url";
+    /// An SSE server's header name is empty or has characters other than
+    /// letters, digits, and `!#$%&'*+-.^_`|~`
+    pub const INVALID_HEADER_NAME: &str = "invalid_header_name";
+    /// An SSE server's header value has a line break or other control
+    /// character
+    pub const INVALID_HEADER_VALUE: &str = "invalid_header_value";
+    /// An SSE server sets a header twice, in different case
+    pub const DUPLICATE_HEADER: &str = "duplicate_header";
+}
+
+/// Validation result of a whole configuration file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigReport {
+    /// Path of the file, as given
+    pub path: String,
+    /// Whether the file and every server in it are valid
+    pub valid: bool,
+    /// Problem with the file as a whole, which leaves no servers to check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ConfigIssue>,
+    /// Each server, in file order
+    #[serde(default)]
+    pub servers: Vec<ServerCheck>,
+}
+
+impl ConfigReport {
+    /// Servers with a problem
+    pub fn invalid_servers(&self) -> impl Iterator<Item = &ServerCheck> {
+        self.servers.iter().filter(|server| !server.valid)
+    }
+}
+
+/// Validation result of one server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerCheck {
+    /// Name of the server, or `server N` (counting from 1) for an entry
+    /// without one
+    pub name: String,
+    /// Whether the server is valid
+    pub valid: bool,
+    /// What is wrong with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ConfigIssue>,
+}
+
+/// One problem found by [`validate_config`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    /// Stable identifier of the problem, one of [`codes`]
+    pub code: String,
+    /// Human-readable description
+    pub message: String,
+    /// How to fix it, when that is clear
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// Line of the problem in the file, for JSON syntax errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Column of the problem in the file, for JSON syntax errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl ConfigIssue {
+    fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+            suggestion: suggestion(code).map(str::to_string),
+            line: None,
+            column: None,
+        }
+    }
+}
+
+/// Check the configuration file at `path` and every server in it
+pub fn validate_config(path: &str) -> ConfigReport {
+    match std::fs::read_to_string(path) {
+        Ok(data) => validate_config_data(path, &data),
+        Err(e) if e.kind() == ErrorKind::NotFound => failed(
+            path,
+            ConfigIssue::new(
+                codes::NOT_FOUND,
+                format!("configuration file not found: {}", path),
+            ),
+        ),
+        Err(e) => failed(
+            path,
+            ConfigIssue::new(codes::READ_FAILED, format!("failed to read {}: {}", path, e)),
+        ),
+    }
+}
+
+/// Check a configuration from any [`ConfigSource`] and every server in it
+pub async fn validate_source(source: &ConfigSource) -> ConfigReport {
+    let path = source.to_string();
+    match source {
+        ConfigSource::File(file) => validate_config(file),
+        _ => match source.read().await {
+            Ok(text) => validate_entries(&path, &text.data, &text.base),
+            Err(e) => {
+                let code = if source.is_remote() {
+                    codes::FETCH_FAILED
+                } else {
+                    codes::READ_FAILED
+                };
+                failed(&path, ConfigIssue::new(code, e.to_string()))
+            }
+        },
+    }
+}
+
+fn failed(path: &str, error: ConfigIssue) -> ConfigReport {
+    ConfigReport {
+        path: path.to_string(),
+        valid: false,
+        error: Some(error),
+        servers: Vec::new(),
+    }
+}
+
+/// Check the configuration `data`, read from `path`, and every server in it,
+/// including those of the files it includes relative to `path`
+pub fn validate_config_data(path: &str, data: &str) -> ConfigReport {
+    validate_entries(path, data, &IncludeBase::File(path.into()))
+}
+
+fn validate_entries(path: &str, data: &str, base: &IncludeBase) -> ConfigReport {
+    let failed = |error| failed(path, error);
+    let entries: Vec<Value> = match serde_json::from_str(data) {
+        Ok(Value::Array(entries)) => entries,
+        Ok(_) => {
+            return failed(ConfigIssue::new(
+                codes::NOT_A_LIST,
+                "the configuration must be a JSON array of servers",
+            ));
+        }
+        Err(e) => {
+            return failed(ConfigIssue {
+                line: Some(e.line()),
+                column: Some(e.column()),
+                ..ConfigIssue::new(codes::INVALID_JSON, e.to_string())
+            });
+        }
+    };
+    let entries = match expand_includes(entries, path, base) {
+        Ok(entries) => entries,
+        Err(ConfigError::Parse {
+            line,
+            column,
+            ref message,
+            ..
+        }) => {
+            return failed(ConfigIssue {
+                line: Some(line),
+                column: Some(column),
+                ..ConfigIssue::new(codes::INVALID_JSON, message.clone())
+            });
+        }
+        Err(e @ ConfigError::Read { .. }) => {
+            return failed(ConfigIssue::new(codes::READ_FAILED, e.to_string()));
+        }
+        Err(e) => return failed(ConfigIssue::new(codes::INVALID_INCLUDE, e.to_string())),
+    };
+
+    let mut seen = HashSet::new();
+    let servers: Vec<ServerCheck> = entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let name = entry
+                .get("name")
+                .and_then(Value::as_str)
+                .map_or_else(|| format!("server {}", i + 1), str::to_string);
+            let error = match serde_json::from_value::<ServerConfig>(entry) {
+                Ok(server) => server
+                    .check()
+                    .err()
+                    .map(|(code, message)| ConfigIssue::new(code, message)),
+                Err(e) => Some(ConfigIssue::new(codes::INVALID_SERVER, e.to_string())),
+            };
+            let error = error.or_else(|| {
+                (!seen.insert(name.clone())).then(|| {
+                    ConfigIssue::new(
+                        codes::DUPLICATE_NAME,
+                        format!("more than one server is named '{}'", name),
+                    )
+                })
+            });
+            ServerCheck {
+                name,
+                valid: error.is_none(),
+                error,
+            }
+        })
+        .collect();
+    ConfigReport {
+        path: path.to_string(),
+        valid: servers.iter().all(|server| server.valid),
+        error: None,
+        servers,
+    }
+}
+
+fn suggestion(code: &str) -> Option<&'static str> {
+    Some(match code {
+        codes::NOT_FOUND => "Check the path, or create the file with a JSON array of servers",
+        codes::FETCH_FAILED => "Check the URL, your connection, and any --config-header credentials",
+        codes::INVALID_JSON => "Fix the JSON syntax at the reported line and column",
+        codes::NOT_A_LIST => "Wrap the server objects in [ ]",
+        codes::INVALID_INCLUDE => {
+            "List existing files in \"include\", relative to the including file, without cycles"
+        }
+        codes::INVALID_SERVER => {
+            "Each server needs a \"name\" and a \"transport\" with \"type\": \"stdio\" or \"sse\""
+        }
+        codes::EMPTY_NAME => "Give the server a unique \"name\"",
+        codes::DUPLICATE_NAME => {
+            "Rename the server, or load with --duplicates rename to add a -2, -3, ... suffix"
+        }
+        codes::EMPTY_PROTOCOL_VERSION => {
+            "Remove \"protocol_version\" to negotiate the newest version, or set one"
+        }
+        codes::ZERO_TIMEOUT => "Remove \"timeout_secs\" to use the default, or set it to at least 1",
+        codes::ZERO_IDLE_TIMEOUT => {
+            "Remove \"idle_timeout_secs\" to keep the server running, or set it to at least 1"
+        }
+        codes::INVALID_RATE_LIMIT => {
+            "Set a positive \"requests_per_second\" and a \"max_concurrent\" of at least 1"
+        }
+        codes::INVALID_BOOST | codes::INVALID_TOOL_BOOST => {
+            "Use a positive, finite factor, and a non-empty pattern for tool boosts"
+        }
+        codes::EMPTY_BLOCKED_TOOL => "Remove the empty pattern from \"blocked_tools\"",
+        codes::CONFLICTING_COMMAND => "Remove \"command\", or remove \"runner\" and \"package\"",
+        codes::EMPTY_PACKAGE => "Set \"package\" to the package the runner starts",
+        codes::INCOMPLETE_RUNNER => "Set both \"runner\" and \"package\", or neither",
+        codes::EMPTY_COMMAND => "Set \"command\" to the program that starts the server",
+        codes::INVALID_ENV_NAME => "Remove empty names and names containing '=' from \"env_policy\"",
+        codes::EMPTY_URL | codes::INVALID_URL => "Set \"url\" to an http:// or https:// URL",
+        codes::INVALID_HEADER_NAME => "Use letters, digits, and '-' in header names",
+        codes::INVALID_HEADER_VALUE => {
+            "Remove line breaks from the value; list several values as a JSON array"
+        }
+        codes::DUPLICATE_HEADER => "Merge the values into one header, as a JSON array",
+        _ => return None,
+    })
+}
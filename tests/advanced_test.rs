@@ -1,63 +1,57 @@
-use toolsearch::{SearchCriteria, SearchFields, SearchMode, SearchOptions, ServerConfig, SortOrder, TransportConfig};
-use std::collections::HashMap;
+use toolsearch::{SearchCriteria, SearchFields, SearchOptions, ServerConfig, SortOrder, TransportConfig};
 use std::time::Duration;
 
 #[test]
 fn test_server_config_validation() {
     // Valid config
-    let valid_config = ServerConfig {
-        name: "test_server".to_string(),
-        transport: TransportConfig::Stdio {
-            command: "echo".to_string(),
-            args: vec![],
-            env: HashMap::new(),
-        },
-    };
+    let valid_config = ServerConfig::stdio("test_server", "echo");
     assert!(valid_config.validate().is_ok());
 
     // Invalid: empty name
-    let invalid_config = ServerConfig {
-        name: "".to_string(),
-        transport: TransportConfig::Stdio {
-            command: "echo".to_string(),
-            args: vec![],
-            env: HashMap::new(),
-        },
-    };
+    let invalid_config = ServerConfig::stdio("", "echo");
     assert!(invalid_config.validate().is_err());
 
     // Invalid: empty command
-    let invalid_config2 = ServerConfig {
-        name: "test".to_string(),
-        transport: TransportConfig::Stdio {
-            command: "".to_string(),
-            args: vec![],
-            env: HashMap::new(),
-        },
-    };
+    let invalid_config2 = ServerConfig::stdio("test", "");
     assert!(invalid_config2.validate().is_err());
 
     // Invalid: bad SSE URL
-    let invalid_config3 = ServerConfig {
-        name: "test".to_string(),
-        transport: TransportConfig::Sse {
-            url: "not-a-url".to_string(),
-            headers: HashMap::new(),
-        },
-    };
+    let invalid_config3 = ServerConfig::sse("test", "not-a-url");
     assert!(invalid_config3.validate().is_err());
 
     // Valid: good SSE URL
-    let valid_config2 = ServerConfig {
-        name: "test".to_string(),
-        transport: TransportConfig::Sse {
-            url: "https://example.com/sse".to_string(),
-            headers: HashMap::new(),
-        },
-    };
+    let valid_config2 = ServerConfig::sse("test", "https://example.com/sse");
     assert!(valid_config2.validate().is_ok());
 }
 
+#[test]
+fn test_server_config_builders() {
+    let stdio = ServerConfig::stdio("files", "mcp-file-server")
+        .arg("--verbose")
+        .args(["--root", "/tmp"])
+        .env("RUST_LOG", "debug")
+        .header("Ignored", "stdio has no headers");
+    match &stdio.transport {
+        TransportConfig::Stdio { command, args, env, .. } => {
+            assert_eq!(command, "mcp-file-server");
+            assert_eq!(args, &vec!["--verbose", "--root", "/tmp"]);
+            assert_eq!(env.get("RUST_LOG").map(String::as_str), Some("debug"));
+        }
+        _ => panic!("Expected stdio transport"),
+    }
+
+    let sse = ServerConfig::sse("remote", "https://example.com/sse")
+        .header("Authorization", "Bearer token")
+        .arg("ignored");
+    match &sse.transport {
+        TransportConfig::Sse { url, headers, .. } => {
+            assert_eq!(url, "https://example.com/sse");
+            assert_eq!(headers.get("Authorization"), Some(&"Bearer token".into()));
+        }
+        _ => panic!("Expected SSE transport"),
+    }
+}
+
 #[test]
 fn test_search_options_default() {
     let options = SearchOptions::default();
@@ -96,6 +90,63 @@ fn test_search_fields_default() {
     assert!(fields.title);
     assert!(fields.description);
     assert!(!fields.input_schema);
+    assert!(!fields.output_schema);
+    assert!(!fields.annotations);
+}
+
+#[test]
+fn test_search_annotations() {
+    use rmcp::model::ToolAnnotations;
+    use toolsearch::testing::ToolFixture;
+    use toolsearch::ToolSearchMatch;
+
+    let mut annotations = ToolAnnotations::with_title("Database admin");
+    annotations.destructive_hint = Some(true);
+    annotations.read_only_hint = Some(false);
+    let tool = ToolFixture::new("drop_table").annotations(annotations).build();
+
+    let criteria = SearchCriteria::with_query("destructive".to_string());
+    assert!(!criteria.matches(&tool));
+
+    let fields = SearchFields {
+        annotations: true,
+        ..SearchFields::default()
+    };
+    let criteria = SearchCriteria::with_query("destructive".to_string()).with_fields(fields);
+    assert!(criteria.matches(&tool));
+    let criteria = SearchCriteria::with_query("database admin".to_string()).with_fields(fields);
+    assert!(criteria.matches(&tool));
+
+    let result = ToolSearchMatch::new("db", tool);
+    assert_eq!(
+        result.annotation_labels(),
+        vec!["Database admin", "destructive"]
+    );
+}
+
+#[test]
+fn test_search_output_schema() {
+    use serde_json::json;
+    use toolsearch::testing::ToolFixture;
+
+    let output_schema = json!({
+        "type": "object",
+        "properties": { "rows": { "type": "array", "description": "Result rows" } }
+    });
+    let tool = ToolFixture::new("run_query")
+        .description("Run a SQL query")
+        .output_schema(output_schema)
+        .build();
+
+    // Output schema is not searched by default
+    let criteria = SearchCriteria::with_query("rows".to_string());
+    assert!(!criteria.matches(&tool));
+
+    let criteria = SearchCriteria::with_query("rows".to_string()).with_fields(SearchFields {
+        output_schema: true,
+        ..SearchFields::default()
+    });
+    assert!(criteria.matches(&tool));
 }
 
 #[test]
@@ -115,11 +166,924 @@ fn test_tool_search_match_tool_name() {
         output_schema: None,
     };
 
-    let match_result = ToolSearchMatch {
-        server_name: "test_server".to_string(),
-        tool,
-    };
+    let match_result = ToolSearchMatch::new("test_server", tool);
 
     assert_eq!(match_result.tool_name(), "test_tool");
 }
 
+
+#[tokio::test]
+async fn test_search_custom_tool_source() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::Arc;
+    use toolsearch::{search_sources_with_options, ToolSearchError, ToolSource};
+
+    struct StaticSource(Vec<Tool>);
+
+    impl ToolSource for StaticSource {
+        fn name(&self) -> &str {
+            "static"
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            Box::pin(async move { Ok(self.0.clone()) })
+        }
+    }
+
+    let tool = |name: &str| Tool {
+        name: name.to_string().into(),
+        title: None,
+        description: None,
+        input_schema: Arc::new(Map::new()),
+        annotations: None,
+        icons: None,
+        output_schema: None,
+    };
+    let sources: Vec<Arc<dyn ToolSource>> =
+        vec![Arc::new(StaticSource(vec![tool("read_file"), tool("send_email")]))];
+
+    let criteria = SearchCriteria::with_query("read".to_string());
+    let results = search_sources_with_options(&sources, &criteria, &SearchOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].server_name, "static");
+    assert_eq!(results[0].tool_name(), "read_file");
+}
+
+#[test]
+fn test_structured_schema_scopes() {
+    use serde_json::json;
+    use toolsearch::testing::ToolFixture;
+    use toolsearch::SchemaScope;
+
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": { "type": "string", "description": "File to open" },
+            "mode": { "type": "string", "enum": ["append", "truncate"], "default": "append" },
+            "options": {
+                "type": "object",
+                "properties": { "encoding": { "type": "string", "description": "Text encoding" } },
+                "required": ["encoding"]
+            }
+        },
+        "required": ["path"]
+    });
+    let tool = ToolFixture::new("open").schema(schema).build();
+    let fields = SearchFields {
+        name: false,
+        title: false,
+        description: false,
+        input_schema: true,
+        output_schema: false,
+        annotations: false,
+    };
+    let search = |query: &str, scope: SchemaScope| {
+        SearchCriteria::with_query(query.to_string())
+            .with_fields(fields)
+            .with_schema_scope(scope)
+            .matches(&tool)
+    };
+
+    // Flattened text matches default values, structured scopes do not
+    assert!(search("append", SchemaScope::Flattened));
+    assert!(!search("append", SchemaScope::PropertyNames));
+    assert!(!search("append", SchemaScope::PropertyDescriptions));
+
+    assert!(search("encoding", SchemaScope::PropertyNames));
+    assert!(!search("file to open", SchemaScope::PropertyNames));
+    assert!(search("file to open", SchemaScope::PropertyDescriptions));
+    assert!(!search("mode", SchemaScope::PropertyDescriptions));
+
+    assert!(search("path", SchemaScope::RequiredProperties));
+    assert!(search("encoding", SchemaScope::RequiredProperties));
+    assert!(!search("mode", SchemaScope::RequiredProperties));
+}
+
+#[test]
+fn test_total_order_tie_breaking() {
+    use std::sync::Arc;
+    use serde_json::Map;
+    use rmcp::model::Tool;
+    use toolsearch::ToolSearchMatch;
+
+    let make = |description: &str| {
+        ToolSearchMatch::new(
+            "server",
+            Tool {
+                name: "dup".to_string().into(),
+                title: None,
+                description: Some(description.to_string().into()),
+                input_schema: Arc::new(Map::new()),
+                annotations: None,
+                icons: None,
+                output_schema: None,
+            },
+        )
+    };
+
+    let a = make("first copy");
+    let b = make("second copy");
+    // Same server and name, but different content still gives a strict order
+    assert_ne!(a.total_cmp(&b, SortOrder::ServerThenTool), std::cmp::Ordering::Equal);
+    assert_eq!(
+        a.total_cmp(&b, SortOrder::ServerThenTool),
+        b.total_cmp(&a, SortOrder::ServerThenTool).reverse()
+    );
+
+    // Sorting is independent of the input order
+    let mut forward = [a.clone(), b.clone()];
+    let mut backward = [b, a];
+    forward.sort_by(|x, y| x.total_cmp(y, SortOrder::ToolThenServer));
+    backward.sort_by(|x, y| x.total_cmp(y, SortOrder::ToolThenServer));
+    assert_eq!(forward[0].description_hash(), backward[0].description_hash());
+    assert_eq!(forward[0].content_hash(), backward[0].content_hash());
+
+    // Hashes are stable values, not per-process random
+    assert_eq!(make("").description_hash(), 0xcbf2_9ce4_8422_2325);
+}
+
+#[test]
+fn test_custom_sort_comparator() {
+    let options = SearchOptions::default().sort_with(|a, b| b.server_name.cmp(&a.server_name));
+    assert_eq!(options.sort_order, SortOrder::Custom);
+    assert!(options.comparator.is_some());
+}
+
+#[tokio::test]
+async fn test_server_priority_sort() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::Arc;
+    use toolsearch::{search_sources_with_options, ToolSearchError, ToolSource};
+
+    struct RankedSource(&'static str, u32);
+
+    impl ToolSource for RankedSource {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn priority(&self) -> u32 {
+            self.1
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            Box::pin(async move {
+                Ok(vec![Tool {
+                    name: "tool".to_string().into(),
+                    title: None,
+                    description: None,
+                    input_schema: Arc::new(Map::new()),
+                    annotations: None,
+                    icons: None,
+                    output_schema: None,
+                }])
+            })
+        }
+    }
+
+    let sources: Vec<Arc<dyn ToolSource>> = vec![
+        Arc::new(RankedSource("alpha", 0)),
+        Arc::new(RankedSource("beta", 0)),
+        Arc::new(RankedSource("first_party", 10)),
+    ];
+    let options = SearchOptions {
+        sort_order: SortOrder::ServerPriority,
+        ..SearchOptions::default()
+    };
+    let results = search_sources_with_options(&sources, &SearchCriteria::match_all(), &options)
+        .await
+        .unwrap();
+
+    let servers: Vec<&str> = results.iter().map(|r| r.server_name.as_str()).collect();
+    assert_eq!(servers, vec!["first_party", "alpha", "beta"]);
+}
+
+#[tokio::test]
+async fn test_custom_sort_applied_to_results() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::Arc;
+    use toolsearch::{search_sources_with_options, ToolSearchError, ToolSource};
+
+    struct NamedSource(&'static str);
+
+    impl ToolSource for NamedSource {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            Box::pin(async move {
+                Ok(vec![Tool {
+                    name: "tool".to_string().into(),
+                    title: None,
+                    description: None,
+                    input_schema: Arc::new(Map::new()),
+                    annotations: None,
+                    icons: None,
+                    output_schema: None,
+                }])
+            })
+        }
+    }
+
+    let sources: Vec<Arc<dyn ToolSource>> = vec![
+        Arc::new(NamedSource("alpha")),
+        Arc::new(NamedSource("community")),
+        Arc::new(NamedSource("internal")),
+    ];
+    let options = SearchOptions::default()
+        .sort_with(|a, b| (b.server_name == "internal").cmp(&(a.server_name == "internal")));
+    let results = search_sources_with_options(&sources, &SearchCriteria::match_all(), &options)
+        .await
+        .unwrap();
+
+    let servers: Vec<&str> = results.iter().map(|r| r.server_name.as_str()).collect();
+    assert_eq!(servers, vec!["internal", "alpha", "community"]);
+}
+
+#[tokio::test]
+async fn test_include_server_info() {
+    use futures::future::BoxFuture;
+    use rmcp::model::{Implementation, ServerInfo, Tool};
+    use serde_json::Map;
+    use std::sync::Arc;
+    use toolsearch::{search_sources_with_options, ToolListing, ToolSearchError, ToolSource};
+
+    struct InfoSource;
+
+    impl ToolSource for InfoSource {
+        fn name(&self) -> &str {
+            "info"
+        }
+
+        fn list_tools(
+            &self,
+            timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            Box::pin(async move { Ok(self.fetch(timeout).await?.tools) })
+        }
+
+        fn fetch(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<ToolListing, ToolSearchError>> {
+            Box::pin(async move {
+                Ok(ToolListing {
+                    tools: vec![Tool {
+                        name: "tool".to_string().into(),
+                        title: None,
+                        description: None,
+                        input_schema: Arc::new(Map::new()),
+                        annotations: None,
+                        icons: None,
+                        output_schema: None,
+                    }],
+                    server_info: Some(ServerInfo {
+                        server_info: Implementation {
+                            name: "demo-server".to_string(),
+                            version: "1.2.3".to_string(),
+                            ..Implementation::default()
+                        },
+                        ..ServerInfo::default()
+                    }),
+                    ..Default::default()
+                })
+            })
+        }
+    }
+
+    let sources: Vec<Arc<dyn ToolSource>> = vec![Arc::new(InfoSource)];
+    let criteria = SearchCriteria::match_all();
+
+    let results = search_sources_with_options(&sources, &criteria, &SearchOptions::default())
+        .await
+        .unwrap();
+    assert!(results[0].server_info.is_none());
+
+    let options = SearchOptions {
+        include_server_info: true,
+        ..SearchOptions::default()
+    };
+    let results = search_sources_with_options(&sources, &criteria, &options).await.unwrap();
+    let info = results[0].server_info.as_ref().unwrap();
+    assert_eq!(info.server_info.name, "demo-server");
+    assert_eq!(info.server_info.version, "1.2.3");
+}
+
+#[tokio::test]
+async fn test_search_report_timings() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::Arc;
+    use toolsearch::{search_sources_report, ToolSearchError, ToolSource};
+
+    struct SlowSource(&'static str, Duration);
+
+    impl ToolSource for SlowSource {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            Box::pin(async move {
+                tokio::time::sleep(self.1).await;
+                Ok(vec![Tool {
+                    name: "tool".to_string().into(),
+                    title: None,
+                    description: None,
+                    input_schema: Arc::new(Map::new()),
+                    annotations: None,
+                    icons: None,
+                    output_schema: None,
+                }])
+            })
+        }
+    }
+
+    let sources: Vec<Arc<dyn ToolSource>> = vec![
+        Arc::new(SlowSource("fast", Duration::ZERO)),
+        Arc::new(SlowSource("slow", Duration::from_millis(30))),
+    ];
+    let report = search_sources_report(&sources, &SearchCriteria::match_all(), &SearchOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(report.matches.len(), 2);
+    let names: Vec<_> = report.timings.iter().map(|t| t.server_name.as_str()).collect();
+    assert_eq!(names, ["fast", "slow"]);
+    assert!(report.timings.iter().all(|t| t.succeeded && t.connect.is_none()));
+    assert_eq!(report.slowest()[0].server_name, "slow");
+    assert!(report.slowest()[0].total >= Duration::from_millis(30));
+}
+
+#[tokio::test]
+async fn test_observer_receives_events() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::{Arc, Mutex};
+    use toolsearch::{
+        search_sources_with_options, Observer, SearchReport, ToolSearchError, ToolSearchMatch,
+        ToolSource,
+    };
+
+    struct Source(&'static str, bool);
+
+    impl ToolSource for Source {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            Box::pin(async move {
+                if !self.1 {
+                    return Err(ToolSearchError::Connection("down".to_string()));
+                }
+                Ok(vec![Tool {
+                    name: "read_file".to_string().into(),
+                    title: None,
+                    description: None,
+                    input_schema: Arc::new(Map::new()),
+                    annotations: None,
+                    icons: None,
+                    output_schema: None,
+                }])
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Observer for Recorder {
+        fn on_server_connected(&self, server_name: &str, _elapsed: Duration) {
+            self.0.lock().unwrap().push(format!("connected {server_name}"));
+        }
+
+        fn on_server_failed(&self, server_name: &str, _error: &ToolSearchError) {
+            self.0.lock().unwrap().push(format!("failed {server_name}"));
+        }
+
+        fn on_match(&self, result: &ToolSearchMatch) {
+            self.0.lock().unwrap().push(format!("match {}", result.tool_name()));
+        }
+
+        fn on_complete(&self, report: &SearchReport) {
+            self.0.lock().unwrap().push(format!("complete {}", report.matches.len()));
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let sources: Vec<Arc<dyn ToolSource>> =
+        vec![Arc::new(Source("up", true)), Arc::new(Source("down", false))];
+    let options = SearchOptions::default().with_observer(Arc::clone(&recorder));
+    search_sources_with_options(&sources, &SearchCriteria::match_all(), &options)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        ["connected up", "failed down", "match read_file", "complete 1"]
+    );
+}
+
+#[tokio::test]
+async fn test_search_many_fetches_once() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use toolsearch::{search_sources_many, ToolSearchError, ToolSource};
+
+    struct CountingSource(AtomicUsize);
+
+    impl ToolSource for CountingSource {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            let tool = |name: &str| Tool {
+                name: name.to_string().into(),
+                title: None,
+                description: None,
+                input_schema: Arc::new(Map::new()),
+                annotations: None,
+                icons: None,
+                output_schema: None,
+            };
+            Box::pin(async move { Ok(vec![tool("read_file"), tool("write_file"), tool("send_email")]) })
+        }
+    }
+
+    let source = Arc::new(CountingSource(AtomicUsize::new(0)));
+    let sources: Vec<Arc<dyn ToolSource>> = vec![source.clone()];
+    let criteria = [
+        SearchCriteria::with_query("file".to_string()),
+        SearchCriteria::with_query("email".to_string()),
+        SearchCriteria::with_query("calendar".to_string()),
+    ];
+
+    let results = search_sources_many(&sources, &criteria, &SearchOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(source.0.load(Ordering::SeqCst), 1);
+    let names: Vec<Vec<&str>> = results
+        .iter()
+        .map(|matches| matches.iter().map(|m| m.tool_name()).collect())
+        .collect();
+    assert_eq!(names, vec![vec!["read_file", "write_file"], vec!["send_email"], vec![]]);
+}
+
+#[test]
+fn test_explain_plan() {
+    use toolsearch::{SearchBuilder, SearchMode};
+
+    let servers = vec![
+        ServerConfig::stdio("files", "mcp-files").with_priority(5),
+        ServerConfig::stdio("broken", ""),
+    ];
+
+    let plan = SearchBuilder::new(servers.clone())
+        .query("read, file")
+        .timeout(5)
+        .limit(3)
+        .explain_plan();
+    assert_eq!(plan.mode, SearchMode::Keywords);
+    assert_eq!(plan.criteria.keywords, ["read", "file"]);
+    assert_eq!(plan.timeout, Some(Duration::from_secs(5)));
+    assert_eq!(plan.max_results, Some(3));
+    assert_eq!(plan.sources.len(), 2);
+    assert_eq!(plan.sources[0].priority, 5);
+    assert!(plan.sources[0].invalid.is_none());
+    assert!(plan.sources[1].invalid.is_some());
+
+    // Regex queries are only auto-detected with the regex-mode feature
+    #[cfg(feature = "regex-mode")]
+    {
+        let plan = SearchBuilder::new(servers).query("read(").explain_plan();
+        assert_eq!(plan.mode, SearchMode::Regex);
+        assert!(plan.regex_error.is_some());
+        assert!(plan.to_string().contains("Invalid regex"));
+    }
+}
+
+#[tokio::test]
+async fn test_report_includes_resolved_mode() {
+    use toolsearch::{SearchBuilder, SearchMode};
+
+    let report = SearchBuilder::new(vec![]).query("read, file").search_report().await.unwrap();
+    assert_eq!(report.mode(), SearchMode::Keywords);
+    assert_eq!(report.criteria.keywords, ["read", "file"]);
+
+    #[cfg(feature = "regex-mode")]
+    {
+        let report = SearchBuilder::new(vec![]).query("^read").search_report().await.unwrap();
+        assert_eq!(report.mode(), SearchMode::Regex);
+        assert_eq!(report.criteria.query.as_deref(), Some("^read"));
+    }
+}
+
+#[tokio::test]
+async fn test_search_first_cancels_slow_sources() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::Arc;
+    use std::time::Instant;
+    use toolsearch::{search_sources_first, ToolSearchError, ToolSource};
+
+    struct DelayedSource(&'static str, Duration, &'static str);
+
+    impl ToolSource for DelayedSource {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            Box::pin(async move {
+                tokio::time::sleep(self.1).await;
+                Ok(vec![Tool {
+                    name: self.2.to_string().into(),
+                    title: None,
+                    description: None,
+                    input_schema: Arc::new(Map::new()),
+                    annotations: None,
+                    icons: None,
+                    output_schema: None,
+                }])
+            })
+        }
+    }
+
+    let sources: Vec<Arc<dyn ToolSource>> = vec![
+        Arc::new(DelayedSource("slow", Duration::from_secs(30), "read_file")),
+        Arc::new(DelayedSource("other", Duration::ZERO, "send_email")),
+        Arc::new(DelayedSource("fast", Duration::from_millis(10), "read_file")),
+    ];
+
+    let started = Instant::now();
+    let found = search_sources_first(
+        &sources,
+        &SearchCriteria::with_name("read_file".to_string()),
+        &SearchOptions::default(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(found.server_name, "fast");
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    let missing = search_sources_first(
+        &sources[1..],
+        &SearchCriteria::with_name("write_file".to_string()),
+        &SearchOptions::default(),
+    )
+    .await
+    .unwrap();
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn test_search_builder_first() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::Arc;
+    use toolsearch::{SearchBuilder, ToolSearchError, ToolSource};
+
+    struct StaticSource(Vec<&'static str>);
+
+    impl ToolSource for StaticSource {
+        fn name(&self) -> &str {
+            "static"
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            let tools = self
+                .0
+                .iter()
+                .map(|name| Tool {
+                    name: name.to_string().into(),
+                    title: None,
+                    description: None,
+                    input_schema: Arc::new(Map::new()),
+                    annotations: None,
+                    icons: None,
+                    output_schema: None,
+                })
+                .collect();
+            Box::pin(async move { Ok(tools) })
+        }
+    }
+
+    let found = SearchBuilder::new(vec![])
+        .source(StaticSource(vec!["write_file", "read_file", "send_email"]))
+        .query("file")
+        .first()
+        .await
+        .unwrap()
+        .unwrap();
+    // The first match in sort order is returned
+    assert_eq!(found.tool_name(), "read_file");
+
+    let none = SearchBuilder::new(vec![])
+        .source(StaticSource(vec!["send_email"]))
+        .query("file")
+        .first()
+        .await
+        .unwrap();
+    assert!(none.is_none());
+}
+
+#[tokio::test]
+async fn test_deadline_returns_partial_results() {
+    use futures::future::BoxFuture;
+    use rmcp::model::Tool;
+    use serde_json::Map;
+    use std::sync::Arc;
+    use std::time::Instant;
+    use toolsearch::{SearchBuilder, ToolSearchError, ToolSource};
+
+    struct DelayedSource(&'static str, Duration);
+
+    impl ToolSource for DelayedSource {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn list_tools(
+            &self,
+            _timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<Tool>, ToolSearchError>> {
+            Box::pin(async move {
+                tokio::time::sleep(self.1).await;
+                Ok(vec![Tool {
+                    name: format!("{}_tool", self.0).into(),
+                    title: None,
+                    description: None,
+                    input_schema: Arc::new(Map::new()),
+                    annotations: None,
+                    icons: None,
+                    output_schema: None,
+                }])
+            })
+        }
+    }
+
+    let started = Instant::now();
+    let report = SearchBuilder::new(vec![])
+        .source(DelayedSource("slow", Duration::from_secs(30)))
+        .source(DelayedSource("fast", Duration::ZERO))
+        .deadline(Duration::from_millis(50))
+        .search_report()
+        .await
+        .unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(report.truncated);
+    assert_eq!(report.matches.len(), 1);
+    assert_eq!(report.matches[0].server_name, "fast");
+
+    let report = SearchBuilder::new(vec![])
+        .source(DelayedSource("fast", Duration::ZERO))
+        .deadline(Duration::from_secs(5))
+        .search_report()
+        .await
+        .unwrap();
+    assert!(!report.truncated);
+
+    // A budget too large to end is no deadline, not an overflow
+    let report = SearchBuilder::new(vec![])
+        .source(DelayedSource("fast", Duration::ZERO))
+        .deadline(Duration::MAX)
+        .search_report()
+        .await
+        .unwrap();
+    assert!(!report.truncated);
+    assert_eq!(report.matches.len(), 1);
+    let first = SearchBuilder::new(vec![])
+        .source(DelayedSource("fast", Duration::ZERO))
+        .deadline(Duration::MAX)
+        .first()
+        .await
+        .unwrap();
+    assert_eq!(first.unwrap().server_name, "fast");
+}
+
+#[tokio::test]
+async fn test_warnings_are_reported_not_printed() {
+    use toolsearch::{SearchBuilder, SearchWarning};
+
+    let report = SearchBuilder::new(vec![
+        ServerConfig::stdio("invalid", ""),
+        ServerConfig::stdio("missing", "toolsearch-test-command-that-does-not-exist"),
+    ])
+    .search_report()
+    .await
+    .unwrap();
+
+    assert_eq!(report.warnings.len(), 2);
+    assert!(matches!(&report.warnings[0], SearchWarning::InvalidConfig { server_name, .. } if server_name == "invalid"));
+    assert!(matches!(&report.warnings[1], SearchWarning::ServerFailed { server_name, .. } if server_name == "missing"));
+    assert_eq!(report.warnings[1].server_name(), "missing");
+}
+
+#[test]
+fn test_unicode_case_insensitive_matching() {
+    use toolsearch::testing::ToolFixture;
+
+    let tool = ToolFixture::new("Éditer_Fichier")
+        .description("Ändert die Datei in der Straße; ΣΊΣΥΦΟΣ")
+        .build();
+
+    // Exact names compare beyond ASCII
+    assert!(SearchCriteria::with_name("éditer_fichier".to_string()).matches(&tool));
+    assert!(!SearchCriteria::with_name("éditer_fichier".to_string())
+        .case_sensitive(true)
+        .matches(&tool));
+
+    // Full case folding: ß matches SS, and final sigma matches capital sigma
+    assert!(SearchCriteria::with_query("STRASSE".to_string()).matches(&tool));
+    assert!(SearchCriteria::with_query("σίσυφος".to_string()).matches(&tool));
+    assert!(SearchCriteria::with_keywords(vec!["ändert".to_string(), "DATEI".to_string()]).matches(&tool));
+    assert!(!SearchCriteria::with_query("ändert".to_string())
+        .case_sensitive(true)
+        .matches(&tool));
+}
+
+#[tokio::test]
+async fn test_criteria_combinators() {
+    use rmcp::model::ToolAnnotations;
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::SearchBuilder;
+
+    let mut destructive = ToolAnnotations::new();
+    destructive.destructive_hint = Some(true);
+    let tools = vec![
+        ToolFixture::new("read_file").description("Read a file").build(),
+        ToolFixture::new("delete_file")
+            .description("Delete a file")
+            .annotations(destructive)
+            .build(),
+        ToolFixture::new("send_email").description("Send an email").build(),
+    ];
+
+    let annotations_only = SearchFields {
+        name: false,
+        title: false,
+        description: false,
+        input_schema: false,
+        annotations: true,
+        output_schema: false,
+    };
+    let file = SearchCriteria::with_query("file".to_string());
+    let is_destructive =
+        SearchCriteria::with_query("destructive".to_string()).with_fields(annotations_only);
+    let email = SearchCriteria::with_query("email".to_string());
+
+    let names = |criteria: &SearchCriteria| -> Vec<&str> {
+        tools
+            .iter()
+            .filter(|t| criteria.matches(t))
+            .map(|t| t.name.as_ref())
+            .collect()
+    };
+    assert_eq!(names(&file.clone().and(is_destructive.clone().not())), ["read_file"]);
+    assert_eq!(names(&file.clone().and(is_destructive.clone())), ["delete_file"]);
+    assert_eq!(names(&is_destructive.clone().or(email.clone())), ["delete_file", "send_email"]);
+    assert_eq!(names(&file.clone().or(email).not()), Vec::<&str>::new());
+    assert!(file.clone().not().is_composed());
+
+    let results = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools.clone()))
+        .criteria(file.and(is_destructive.not()))
+        .search()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].tool_name(), "read_file");
+}
+
+#[tokio::test]
+async fn test_custom_matcher_filters_and_scores() {
+    use rmcp::model::Tool;
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::SearchBuilder;
+
+    let tools = vec![
+        ToolFixture::new("team_read").description("Read a file").build(),
+        ToolFixture::new("team_read_write").description("Read and write a file").build(),
+        ToolFixture::new("read_file").description("Read a file").build(),
+    ];
+    // Team tools only, preferring longer (more specific) names
+    let team_only = |tool: &Tool| {
+        tool.name
+            .strip_prefix("team_")
+            .map(|rest| rest.len() as f32)
+    };
+
+    let results = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools.clone()))
+        .query("read")
+        .matcher(team_only)
+        .sort_by_score()
+        .search()
+        .await
+        .unwrap();
+    let names: Vec<&str> = results.iter().map(|r| r.tool_name()).collect();
+    assert_eq!(names, ["team_read_write", "team_read"]);
+    assert_eq!(results[0].score, Some(10.0));
+    assert_eq!(results[1].score, Some(4.0));
+
+    let results = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools.clone()))
+        .query("read")
+        .matcher(team_only)
+        .min_score(5.0)
+        .search()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].tool_name(), "team_read_write");
+
+    // Without a scorer nothing has a score, so nothing is cut
+    let results = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools))
+        .query("read")
+        .min_score(5.0)
+        .search()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 3);
+}
+
+#[tokio::test]
+async fn test_snippets_show_the_hit() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::SearchBuilder;
+
+    let tool = ToolFixture::new("translate")
+        .description(
+            "Translate text between languages.\n\nSupports automatic detection of the \
+             source language, glossaries, formality settings and the handling of the \
+             German STRASSE spelling in addresses.",
+        )
+        .build();
+
+    // Caseless hit past the start, whitespace collapsed, cut at both ends
+    let snippet = SearchCriteria::with_query("Straße".to_string()).snippet(&tool).unwrap();
+    assert!(snippet.starts_with("..."), "{}", snippet);
+    assert!(snippet.contains("German STRASSE spelling"), "{}", snippet);
+    assert!(!snippet.contains('\n'));
+
+    // Hit in the name: the snippet is the opening of the description
+    let snippet = SearchCriteria::with_name("translate".to_string()).snippet(&tool).unwrap();
+    assert!(snippet.starts_with("Translate text between languages. Supports"), "{}", snippet);
+    assert!(snippet.ends_with("..."), "{}", snippet);
+
+    let results = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(vec![tool]))
+        .query("glossaries")
+        .search()
+        .await
+        .unwrap();
+    assert!(results[0].snippet.as_deref().unwrap().contains("glossaries"));
+}
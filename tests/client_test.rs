@@ -0,0 +1,864 @@
+use std::time::Duration;
+use toolsearch::{
+    ping_server, CircuitBreaker, CircuitBreakerConfig, CircuitState, HealthStatus, SearchCriteria,
+    SearchOptions, ServerConfig, ToolSearchClient,
+};
+
+fn missing_server(name: &str) -> ServerConfig {
+    ServerConfig::stdio(name, "toolsearch-test-command-that-does-not-exist")
+}
+
+#[tokio::test]
+async fn test_ping_unreachable_server() {
+    let result = ping_server(&missing_server("missing")).await;
+    assert_eq!(result.server_name, "missing");
+    assert!(matches!(result.status, HealthStatus::Unhealthy { .. }));
+}
+
+#[tokio::test]
+async fn test_client_skips_unhealthy_servers() {
+    let client = ToolSearchClient::new(vec![missing_server("a"), missing_server("b")]);
+    // Never-checked servers are assumed healthy
+    assert!(client.is_healthy("a"));
+
+    let results = client.check_health().await;
+    assert_eq!(results.len(), 2);
+    assert!(!client.is_healthy("a"));
+    assert!(!client.is_healthy("b"));
+    assert_eq!(client.health().len(), 2);
+
+    // Unhealthy servers are skipped rather than retried
+    let matches = client.search(&SearchCriteria::match_all()).await.unwrap();
+    assert!(matches.is_empty());
+}
+
+#[tokio::test]
+async fn test_background_health_checks() {
+    let mut client = ToolSearchClient::new(vec![missing_server("a")]);
+    client.start_health_checks(Duration::from_secs(60));
+
+    // The first tick runs immediately
+    for _ in 0..100 {
+        if !client.is_healthy("a") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!client.is_healthy("a"));
+    client.stop_health_checks();
+}
+
+#[test]
+fn test_circuit_breaker_opens_and_recovers() {
+    let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+        failure_threshold: 2,
+        cooldown: Duration::from_millis(20),
+    });
+
+    assert!(breaker.allow("s"));
+    breaker.record_failure("s");
+    assert_eq!(breaker.state("s"), CircuitState::Closed);
+    breaker.record_failure("s");
+    assert_eq!(breaker.state("s"), CircuitState::Open);
+    assert!(!breaker.allow("s"));
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(breaker.state("s"), CircuitState::HalfOpen);
+    // Only one trial attempt while half-open
+    assert!(breaker.allow("s"));
+    assert!(!breaker.allow("s"));
+
+    // A failed trial re-opens the circuit immediately
+    breaker.record_failure("s");
+    assert_eq!(breaker.state("s"), CircuitState::Open);
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(breaker.allow("s"));
+    breaker.record_success("s");
+    assert_eq!(breaker.state("s"), CircuitState::Closed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limiter_spaces_and_caps_requests() {
+    use toolsearch::{RateLimit, RateLimiter};
+
+    let config: ServerConfig = serde_json::from_str(
+        r#"{"name": "hosted", "rate_limit": {"requests_per_second": 10, "max_concurrent": 2}, "transport": {"type": "stdio", "command": "mcp"}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.rate_limit, Some(RateLimit::per_second(10.0).with_max_concurrent(2)));
+    assert!(missing_server("s").with_rate_limit(RateLimit::per_second(0.0)).validate().is_err());
+    // One request per 1e30 seconds is more than a Duration holds
+    let glacial = missing_server("s").with_rate_limit(RateLimit::per_second(1e-30));
+    assert!(glacial.validate().is_err());
+    RateLimiter::new(RateLimit::per_second(1e-30)).acquire().await;
+    // An interval that fits a Duration but not the clock admits the first
+    // request at once
+    let limiter = RateLimiter::new(RateLimit::per_second(1e-19));
+    assert!(RateLimit::per_second(1e-19).validate().is_ok());
+    let started = tokio::time::Instant::now();
+    limiter.acquire().await;
+    assert_eq!(started.elapsed(), Duration::ZERO);
+    assert!(missing_server("s").with_rate_limit(RateLimit::default().with_max_concurrent(0)).validate().is_err());
+
+    // Five requests at ten per second start over 400ms
+    let limiter = config.rate_limiter();
+    let started = tokio::time::Instant::now();
+    for _ in 0..5 {
+        limiter.acquire().await;
+    }
+    assert_eq!(started.elapsed(), Duration::from_millis(400));
+
+    // A third request waits for one of the two in flight to finish
+    let limiter = RateLimiter::new(RateLimit::default().with_max_concurrent(2));
+    let first = limiter.acquire().await;
+    let _second = limiter.acquire().await;
+    assert!(tokio::time::timeout(Duration::from_secs(1), limiter.acquire()).await.is_err());
+    drop(first);
+    assert!(limiter.acquire().await.is_some());
+}
+
+#[tokio::test]
+async fn test_client_circuit_opens_for_failing_server() {
+    let client = ToolSearchClient::new(vec![missing_server("dead")])
+        .with_options(SearchOptions {
+            continue_on_error: true,
+            ..Default::default()
+        })
+        .with_circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(60),
+        });
+
+    let criteria = SearchCriteria::match_all();
+    client.search(&criteria).await.unwrap();
+    assert_eq!(client.circuit_state("dead"), CircuitState::Closed);
+    client.search(&criteria).await.unwrap();
+    assert_eq!(client.circuit_state("dead"), CircuitState::Open);
+
+    // The open circuit skips the server without connecting
+    assert!(client.search(&criteria).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_prefetch_skips_failing_servers() {
+    let client = ToolSearchClient::new(vec![missing_server("a"), missing_server("b")]);
+    assert_eq!(client.prefetch().await.unwrap(), 0);
+    assert!(client.prefetched_servers().is_empty());
+
+    let strict = ToolSearchClient::new(vec![missing_server("a")]).with_options(SearchOptions {
+        continue_on_error: false,
+        ..Default::default()
+    });
+    assert!(strict.prefetch().await.is_err());
+}
+
+#[tokio::test]
+async fn test_reload_keeps_state_of_unchanged_servers() {
+    use toolsearch::ConfigDelta;
+
+    let mut client = ToolSearchClient::new(vec![
+        missing_server("a"),
+        missing_server("b"),
+        missing_server("d"),
+    ]);
+    client.check_health().await;
+    assert!(!client.is_healthy("a") && !client.is_healthy("b"));
+
+    let delta = client
+        .reload(vec![
+            missing_server("a"),
+            missing_server("b").args(["--verbose"]),
+            missing_server("c"),
+        ])
+        .unwrap();
+    assert_eq!(
+        delta,
+        ConfigDelta {
+            added: vec!["c".to_string()],
+            removed: vec!["d".to_string()],
+            changed: vec!["b".to_string()],
+        }
+    );
+    assert_eq!(delta.to_string(), "added: c; removed: d; changed: b");
+    // Unchanged servers keep their health; changed ones start over
+    assert!(!client.is_healthy("a"));
+    assert!(client.is_healthy("b"));
+    assert!(!client.health().contains_key("d"));
+
+    // An invalid configuration is rejected and the old one kept
+    assert!(client.reload(vec![missing_server("")]).is_err());
+    assert_eq!(client.servers().len(), 3);
+    assert!(client.reload(client.servers().to_vec()).unwrap().is_empty());
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_prefetched_tools_follow_server_identity() {
+    // A "server" that lists one tool and then waits to be stopped
+    let script = r#"
+        read -r line
+        echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"files","version":"1"}}}'
+        read -r line
+        read -r line
+        echo '{"jsonrpc":"2.0","id":1,"result":{"tools":[{"name":"read_file","inputSchema":{"type":"object"}}]}}'
+        cat > /dev/null
+    "#;
+    let server = |name: &str| ServerConfig::stdio(name, "sh").args(["-c", script]);
+    let mut client = ToolSearchClient::new(vec![server("files")]).with_options(SearchOptions {
+        timeout: Some(Duration::from_secs(10)),
+        ..Default::default()
+    });
+    assert_eq!(client.prefetch().await.unwrap(), 1);
+
+    // Renamed, the server is still reached the same way and keeps its tools
+    client.reload(vec![server("fs")]).unwrap();
+    assert_eq!(client.prefetched_servers(), ["fs"]);
+    let matches = client.search(&SearchCriteria::match_all()).await.unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].server_name, "fs");
+
+    // Changing its arguments makes the prefetched tools stale
+    client.reload(vec![server("fs").arg("--verbose")]).unwrap();
+    assert!(client.prefetched_servers().is_empty());
+}
+
+#[cfg(all(target_os = "linux", feature = "stdio"))]
+#[tokio::test]
+async fn test_timeout_stops_server_process_tree() {
+    use toolsearch::SearchBuilder;
+
+    // A "server" that starts a sub-process and never answers the handshake
+    let pid_file = std::env::temp_dir().join(format!("toolsearch-tree-{}", std::process::id()));
+    let script = format!("sleep 60 & echo $! > {}; wait", pid_file.display());
+    let server = ServerConfig::stdio("tree", "sh").args(["-c", script.as_str()]);
+
+    let report = SearchBuilder::new(vec![server]).timeout(1).search_report().await.unwrap();
+    assert_eq!(report.warnings.len(), 1);
+
+    let pid = std::fs::read_to_string(&pid_file).unwrap().trim().to_string();
+    std::fs::remove_file(&pid_file).unwrap();
+    // Gone, or a zombie waiting to be reaped
+    let alive = || {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .is_ok_and(|stat| !stat.contains(") Z "))
+    };
+    for _ in 0..50 {
+        if !alive() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("sub-process {} outlived the timed-out server", pid);
+}
+
+#[tokio::test]
+async fn test_pooled_server_reconnects_after_failure() {
+    use toolsearch::pool::PooledServer;
+    use toolsearch::ToolSource;
+
+    let server = PooledServer::new(missing_server("a"));
+    assert!(server.fetch(None).await.is_err());
+    assert!(!server.is_connected().await);
+    // Failures are not cached: the next fetch tries again
+    assert!(server.fetch(None).await.is_err());
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_pooled_server_replaces_idle_connections() {
+    use toolsearch::pool::PooledServer;
+    use toolsearch::ToolSource;
+
+    let script = r#"
+        while read -r line; do
+            id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+            case "$line" in
+                *notifications/*) ;;
+                *initialize*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"idle","version":"1"}}}' ;;
+                *) echo '{"jsonrpc":"2.0","id":'$id',"result":{"tools":[{"name":"read_file","inputSchema":{"type":"object"}}]}}' ;;
+            esac
+        done
+    "#;
+    let server = PooledServer::new(ServerConfig::stdio("idle", "sh").args(["-c", script]).with_idle_timeout(1));
+    let timeout = Some(Duration::from_secs(10));
+
+    // Only a new connection has a connect time
+    assert!(server.fetch(timeout).await.unwrap().connect_time.is_some());
+    assert!(server.fetch(timeout).await.unwrap().connect_time.is_none());
+    assert!(!server.close_if_idle().await);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(server.fetch(timeout).await.unwrap().connect_time.is_some());
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(server.close_if_idle().await);
+    assert!(!server.is_connected().await);
+    assert!(ServerConfig::stdio("s", "mcp").with_idle_timeout(0).validate().is_err());
+}
+
+#[cfg(all(unix, feature = "daemon"))]
+#[tokio::test]
+async fn test_daemon_answers_over_socket() {
+    use std::sync::Arc;
+    use toolsearch::daemon::{bind, request, Daemon, DaemonRequest};
+    use toolsearch::save_servers;
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-daemon-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("servers.json");
+    save_servers(config.to_str().unwrap(), &[missing_server("a")]).unwrap();
+    let socket = dir.join("daemon.sock");
+
+    let daemon = Arc::new(Daemon::new(&config).unwrap());
+    let serving = tokio::spawn(daemon.serve(bind(&socket).unwrap()));
+    // A second daemon can't take over the socket
+    assert!(bind(&socket).is_err());
+
+    let response = request(&socket, &DaemonRequest::query("read")).await.unwrap();
+    assert!(response.matches.is_empty());
+    assert_eq!(response.warnings.len(), 1);
+    assert!(response.error.is_none());
+
+    // The next search picks up configuration changes
+    save_servers(config.to_str().unwrap(), &[missing_server("a"), missing_server("b")]).unwrap();
+    let response = request(&socket, &DaemonRequest::name("read_file")).await.unwrap();
+    assert_eq!(response.warnings.len(), 2);
+
+    // And changes to the files it includes
+    let included = dir.join("more.json");
+    save_servers(included.to_str().unwrap(), &[missing_server("c")]).unwrap();
+    std::fs::write(&config, r#"[{"include": "more.json"}]"#).unwrap();
+    let response = request(&socket, &DaemonRequest::query("read")).await.unwrap();
+    assert_eq!(response.warnings.len(), 1);
+    let more = [missing_server("c"), missing_server("d"), missing_server("e")];
+    save_servers(included.to_str().unwrap(), &more).unwrap();
+    let response = request(&socket, &DaemonRequest::query("read")).await.unwrap();
+    assert_eq!(response.warnings.len(), 3);
+
+    serving.abort();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_handshake_falls_back_to_older_protocol() {
+    use toolsearch::SearchBuilder;
+
+    // A "server" that only speaks 2024-11-05 and hangs up on anything newer
+    let script = r#"
+        read -r line
+        case "$line" in *'"2024-11-05"'*) ;; *) exit 1 ;; esac
+        echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"old","version":"1"}}}'
+        read -r line
+        read -r line
+        echo '{"jsonrpc":"2.0","id":1,"result":{"tools":[{"name":"read_file","inputSchema":{"type":"object"}}]}}'
+        cat > /dev/null
+    "#;
+    let server = ServerConfig::stdio("old", "sh").args(["-c", script]);
+
+    let report = SearchBuilder::new(vec![server.clone()]).timeout(10).search_report().await.unwrap();
+    assert_eq!(report.matches.len(), 1);
+    assert_eq!(report.timings[0].protocol_version.as_deref(), Some("2024-11-05"));
+
+    // Pinning a newer version disables the fallback
+    let pinned = server.with_protocol_version("2025-03-26");
+    let report = SearchBuilder::new(vec![pinned]).timeout(10).search_report().await.unwrap();
+    assert!(report.matches.is_empty());
+    assert_eq!(report.warnings.len(), 1);
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_server_without_tools_capability_is_not_a_failure() {
+    use toolsearch::{SearchBuilder, SearchWarning};
+
+    // A resources-only "server" that quits if it is asked for tools anyway
+    let script = r#"
+        read -r line
+        echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2025-03-26","capabilities":{"resources":{}},"serverInfo":{"name":"docs","version":"1"}}}'
+        while read -r line; do
+            case "$line" in *tools/list*) exit 1 ;; esac
+        done
+    "#;
+    let server = ServerConfig::stdio("docs", "sh").args(["-c", script]);
+
+    let report = SearchBuilder::new(vec![server]).timeout(10).search_report().await.unwrap();
+    assert!(report.matches.is_empty());
+    assert_eq!(
+        report.warnings,
+        [SearchWarning::NoToolsCapability {
+            server_name: "docs".to_string()
+        }]
+    );
+    assert!(report.timings[0].succeeded);
+    assert_eq!(report.warnings[0].to_string(), "Server docs has no tools capability");
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_pagination_limits() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use toolsearch::{Observer, SearchBuilder, SearchWarning};
+
+    #[derive(Default)]
+    struct Pages(AtomicUsize);
+
+    impl Observer for Pages {
+        fn on_page(&self, _server_name: &str, page: usize, tools: usize) {
+            assert_eq!(page, tools);
+            self.0.store(page, Ordering::SeqCst);
+        }
+    }
+
+    // A "server" whose tool list never ends: one tool per page, and always
+    // another cursor (the same one again if started with `same`)
+    let script = r#"
+        read -r line
+        echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"endless","version":"1"}}}'
+        read -r line
+        n=0
+        while read -r line; do
+            id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+            n=$((n + 1))
+            cursor=page$n
+            [ "$1" = same ] && cursor=same
+            echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tools\":[{\"name\":\"tool_$n\",\"inputSchema\":{\"type\":\"object\"}}],\"nextCursor\":\"$cursor\"}}"
+        done
+    "#;
+    let endless = ServerConfig::stdio("endless", "sh").args(["-c", script, "sh"]);
+    let truncated = |tools| SearchWarning::ToolListTruncated {
+        server_name: "endless".to_string(),
+        tools,
+    };
+
+    let pages = Arc::new(Pages::default());
+    let report = SearchBuilder::new(vec![endless.clone()])
+        .max_pages(3)
+        .observer(pages.clone())
+        .timeout(10)
+        .search_report()
+        .await
+        .unwrap();
+    assert_eq!(report.matches.len(), 3);
+    assert_eq!(report.warnings, [truncated(3)]);
+    assert_eq!(pages.0.load(Ordering::SeqCst), 3);
+
+    let report = SearchBuilder::new(vec![endless.clone()]).max_tools(2).timeout(10).search_report().await.unwrap();
+    assert_eq!(report.matches.len(), 2);
+    assert_eq!(report.warnings, [truncated(2)]);
+
+    // Matching happens page by page, so paging stops at the tenth page,
+    // where the second match (tool_10, after tool_1) turns up
+    let pages = Arc::new(Pages::default());
+    let report = SearchBuilder::new(vec![endless.clone()])
+        .query("tool_1")
+        .max_per_server(2)
+        .first_listed_per_server()
+        .observer(pages.clone())
+        .timeout(10)
+        .search_report()
+        .await
+        .unwrap();
+    let names: Vec<_> = report.matches.iter().map(|m| m.tool_name()).collect();
+    assert_eq!(names, ["tool_1", "tool_10"]);
+    assert!(report.warnings.is_empty());
+    assert_eq!(pages.0.load(Ordering::SeqCst), 10);
+
+    // A cursor handed back unchanged ends the listing without any limit
+    let repeating = endless.arg("same");
+    let report = SearchBuilder::new(vec![repeating]).timeout(10).search_report().await.unwrap();
+    assert_eq!(report.matches.len(), 2);
+    assert_eq!(report.warnings, [truncated(2)]);
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_proxy_lists_namespaced_tools_and_routes_calls() {
+    use rmcp::model::CallToolRequestParam;
+    use rmcp::ServiceExt;
+    use toolsearch::proxy::ToolProxy;
+
+    // A "server" with two tools that answers calls to read_file only
+    let script = r#"
+        while read -r line; do
+            id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+            case "$line" in
+                *notifications/*) ;;
+                *initialize*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"files","version":"1"}}}' ;;
+                *tools/list*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"tools":[{"name":"read_file","inputSchema":{"type":"object"}},{"name":"write_file","inputSchema":{"type":"object"}}]}}' ;;
+                *'"name":"read_file"'*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"content":[{"type":"text","text":"contents"}]}}' ;;
+                *) echo '{"jsonrpc":"2.0","id":'$id',"error":{"code":-32602,"message":"unknown tool"}}' ;;
+            esac
+        done
+    "#;
+    let server = ServerConfig::stdio("files", "sh").args(["-c", script]);
+    let proxy = ToolProxy::new(vec![server]).with_page_size(1);
+
+    let (client_io, proxy_io) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move { proxy.serve(proxy_io).await.unwrap().waiting().await });
+    let client = ().serve(client_io).await.unwrap();
+
+    // Two pages of one tool each
+    let tools = client.list_all_tools().await.unwrap();
+    let names: Vec<_> = tools.iter().map(|tool| tool.name.as_ref()).collect();
+    assert_eq!(names, ["files__read_file", "files__write_file"]);
+
+    let call = |name: &'static str| CallToolRequestParam {
+        name: name.into(),
+        arguments: None,
+    };
+    let result = client.call_tool(call("files__read_file")).await.unwrap();
+    assert_eq!(result.content[0].as_text().unwrap().text, "contents");
+    assert!(client.call_tool(call("files__write_file")).await.is_err());
+    assert!(client.call_tool(call("mail__send_email")).await.is_err());
+    client.cancel().await.unwrap();
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_proxy_pages_come_from_one_snapshot() {
+    use toolsearch::proxy::{ToolProxy, SNAPSHOTS_KEPT};
+
+    // A "server" whose two tools are renamed every time they are listed
+    let script = r#"
+        n=0
+        while read -r line; do
+            id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+            case "$line" in
+                *notifications/*) ;;
+                *initialize*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"churn","version":"1"}}}' ;;
+                *tools/list*)
+                    n=$((n + 1))
+                    echo '{"jsonrpc":"2.0","id":'$id',"result":{"tools":[{"name":"a_'$n'","inputSchema":{"type":"object"}},{"name":"b_'$n'","inputSchema":{"type":"object"}}]}}' ;;
+            esac
+        done
+    "#;
+    let server = ServerConfig::stdio("churn", "sh").args(["-c", script]);
+    let proxy = ToolProxy::new(vec![server]).with_page_size(1);
+    let names = |page: &rmcp::model::ListToolsResult| -> Vec<String> {
+        page.tools.iter().map(|tool| tool.name.to_string()).collect()
+    };
+
+    let first = proxy.list_page(None).await.unwrap();
+    assert_eq!(names(&first), ["churn__a_1"]);
+    let cursor = first.next_cursor.clone().unwrap();
+    // Upstream tools change before the client asks for the next page
+    proxy.tools().await.unwrap();
+    let second = proxy.list_page(Some(&cursor)).await.unwrap();
+    assert_eq!(names(&second), ["churn__b_1"]);
+    assert!(second.next_cursor.is_none());
+
+    // A client pages through the proxy with the offsets in its cursors
+    let (client_io, proxy_io) = tokio::io::duplex(64 * 1024);
+    let serving = ToolProxy::new(vec![ServerConfig::stdio("churn", "sh").args(["-c", script])])
+        .with_page_size(1);
+    tokio::spawn(async move { rmcp::ServiceExt::serve(serving, proxy_io).await.unwrap().waiting().await });
+    let client = rmcp::ServiceExt::serve((), client_io).await.unwrap();
+    let tools = client.list_all_tools().await.unwrap();
+    let listed: Vec<_> = tools.iter().map(|tool| tool.name.as_ref()).collect();
+    assert_eq!(listed, ["churn__a_1", "churn__b_1"]);
+    client.cancel().await.unwrap();
+
+    // Cursors into listings that are no longer kept expire
+    for _ in 0..SNAPSHOTS_KEPT {
+        proxy.list_page(None).await.unwrap();
+    }
+    let error = proxy.list_page(Some(&cursor)).await.unwrap_err();
+    assert!(error.message.contains("Expired cursor"), "{}", error.message);
+    assert!(proxy.list_page(Some("not a cursor")).await.is_err());
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_subscribe_follows_matching_tools() {
+    use futures::StreamExt;
+    use toolsearch::CatalogEvent;
+
+    // Lists "keep_me" and "a_<n>" on its n-th start (counted in the file
+    // named by $1), and fails on its third
+    let script = r#"
+        n=$(( $(cat "$1" 2>/dev/null || echo 0) + 1 ))
+        echo $n > "$1"
+        [ $n -eq 3 ] && exit 1
+        while read -r line; do
+            id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+            case "$line" in
+                *notifications/*) ;;
+                *initialize*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"churn","version":"1"}}}' ;;
+                *tools/list*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"tools":[{"name":"keep_me","inputSchema":{"type":"object"}},{"name":"a_'$n'","inputSchema":{"type":"object"}}]}}' ;;
+            esac
+        done
+    "#;
+    let counter = std::env::temp_dir().join(format!("toolsearch-subscribe-{}", std::process::id()));
+    let _ = std::fs::remove_file(&counter);
+    let server = ServerConfig::stdio("churn", "sh").args(["-c", script, "sh", counter.to_str().unwrap()]);
+    let client = ToolSearchClient::new(vec![server]);
+
+    let events: Vec<String> = client
+        .subscribe(SearchCriteria::with_regex("^a_".to_string()), Duration::from_millis(10))
+        .take(5)
+        .map(|event| match event {
+            CatalogEvent::ToolAdded { tool, .. } => format!("+{}", tool.name),
+            CatalogEvent::ToolRemoved { tool_name, .. } => format!("-{}", tool_name),
+            CatalogEvent::ToolChanged { tool, .. } => format!("~{}", tool.name),
+        })
+        .collect()
+        .await;
+    // The failed third fetch doesn't read as a_2 going away
+    assert_eq!(events, ["+a_1", "+a_2", "-a_1", "+a_4", "-a_2"]);
+    let _ = std::fs::remove_file(&counter);
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_offset_cursors_list_pages_in_parallel() {
+    use toolsearch::SearchBuilder;
+
+    // Seven tools, two per page, with the offset of the next page after the
+    // prefix $2 as the cursor; every requested offset is logged to the file
+    // named by $1
+    let script = r#"
+        read -r line
+        echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"offsets","version":"1"}}}'
+        read -r line
+        while read -r line; do
+            id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+            start=$(echo "$line" | sed -n 's/.*"cursor":"\([^"]*\)".*/\1/p')
+            start=${start##*:}
+            echo "${start:-0}" >> "$1"
+            start=${start:-0}
+            if [ "$start" -ge 7 ]; then
+                echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"error\":{\"code\":-32602,\"message\":\"Invalid cursor\"}}"
+                continue
+            fi
+            tools="{\"name\":\"tool_$start\",\"inputSchema\":{\"type\":\"object\"}}"
+            next=$((start + 1))
+            [ $next -lt 7 ] && tools="$tools,{\"name\":\"tool_$next\",\"inputSchema\":{\"type\":\"object\"}}"
+            next=$((start + 2))
+            cursor=""
+            [ $next -lt 7 ] && cursor=",\"nextCursor\":\"$2$next\""
+            echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tools\":[$tools]$cursor}}"
+        done
+    "#;
+    let dir = std::env::temp_dir().join(format!("toolsearch-offsets-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Snapshot cursors of a proxy, like `3:2`, end in offsets too
+    for (parallel, prefix, requested) in [(4, "", "0 2 4 6 8"), (1, "", "0 2 4 6"), (4, "3:", "0 2 4 6 8")] {
+        let log = dir.join(format!("cursors-{}-{}", parallel, prefix.len()));
+        let server = ServerConfig::stdio("offsets", "sh").args(["-c", script, "sh", log.to_str().unwrap(), prefix]);
+        let report = SearchBuilder::new(vec![server])
+            .parallel_pages(parallel)
+            .timeout(10)
+            .search_report()
+            .await
+            .unwrap();
+        let names: Vec<_> = report.matches.iter().map(|m| m.tool_name().to_string()).collect();
+        assert_eq!(names, (0..7).map(|i| format!("tool_{}", i)).collect::<Vec<_>>());
+        assert!(report.warnings.is_empty());
+        let cursors = std::fs::read_to_string(&log).unwrap();
+        assert_eq!(cursors.split_whitespace().collect::<Vec<_>>().join(" "), requested);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_env_overrides_apply_to_spawned_servers() {
+    use toolsearch::SearchBuilder;
+
+    // A "server" with one tool, named from its environment
+    let script = r#"
+        read -r line
+        echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"env","version":"1"}}}'
+        read -r line
+        read -r line
+        echo "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"tools\":[{\"name\":\"${PREFIX}_${SUFFIX:-none}\",\"inputSchema\":{\"type\":\"object\"}}]}}"
+        read -r line
+    "#;
+    let server = ServerConfig::stdio("env", "sh").args(["-c", script]).env("PREFIX", "own");
+    let names = |report: toolsearch::SearchReport| -> Vec<String> {
+        report.matches.iter().map(|m| m.tool_name().to_string()).collect()
+    };
+
+    let report = SearchBuilder::new(vec![server.clone()]).timeout(10).search_report().await.unwrap();
+    assert_eq!(names(report), ["own_none"]);
+
+    let report = SearchBuilder::new(vec![server.clone()])
+        .env_override("PREFIX", "forced")
+        .env_override("SUFFIX", "set")
+        .timeout(10)
+        .search_report()
+        .await
+        .unwrap();
+    assert_eq!(names(report), ["forced_set"]);
+
+    // A pooled server starts with the overrides of the search starting it
+    let report = SearchBuilder::new(vec![])
+        .source(toolsearch::pool::PooledServer::new(server))
+        .env_override("SUFFIX", "pooled")
+        .timeout(10)
+        .search_report()
+        .await
+        .unwrap();
+    assert_eq!(names(report), ["own_pooled"]);
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_pooled_server_restarts_for_other_env_overrides() {
+    use rmcp::model::CallToolRequestParam;
+    use toolsearch::pool::PooledServer;
+    use toolsearch::{FetchOptions, ToolSource};
+
+    // A server whose tool, and the answer to calls, is named from its
+    // environment
+    let script = r#"
+        while read -r line; do
+            id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+            case "$line" in
+                *notifications/*) ;;
+                *initialize*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"env","version":"1"}}}' ;;
+                *tools/call*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"content":[{"type":"text","text":"'${SUFFIX:-none}'"}]}}' ;;
+                *) echo '{"jsonrpc":"2.0","id":'$id',"result":{"tools":[{"name":"tool_'${SUFFIX:-none}'","inputSchema":{"type":"object"}}]}}' ;;
+            esac
+        done
+    "#;
+    let server = PooledServer::new(ServerConfig::stdio("env", "sh").args(["-c", script]));
+    let fetch = |suffix: Option<&str>| {
+        let options = FetchOptions {
+            timeout: Some(Duration::from_secs(10)),
+            env_overrides: suffix
+                .map(|suffix| [("SUFFIX".to_string(), suffix.to_string())].into())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        let server = &server;
+        async move {
+            let listing = server.fetch_with(&options).await.unwrap();
+            (listing.tools[0].name.to_string(), listing.connect_time.is_some())
+        }
+    };
+
+    assert_eq!(fetch(Some("a")).await, ("tool_a".to_string(), true));
+    // Same overrides, same connection
+    assert_eq!(fetch(Some("a")).await, ("tool_a".to_string(), false));
+    // Other overrides, or none, restart the server
+    assert_eq!(fetch(None).await, ("tool_none".to_string(), true));
+    assert_eq!(fetch(Some("b")).await, ("tool_b".to_string(), true));
+
+    // Calls reconnect with the overrides of the last fetch
+    server.disconnect().await;
+    let call = CallToolRequestParam {
+        name: "tool_b".into(),
+        arguments: None,
+    };
+    let result = server.call_tool(call, Some(Duration::from_secs(10))).await.unwrap();
+    assert_eq!(result.content[0].as_text().unwrap().text, "b");
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_malformed_tools_are_reported_per_tool() {
+    use toolsearch::{MalformedTool, SearchBuilder, SearchWarning};
+
+    // One good tool, one without a name, and one whose schema isn't an object type
+    let script = r#"
+        read -r line
+        echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"sloppy","version":"1"}}}'
+        read -r line
+        read -r line
+        echo '{"jsonrpc":"2.0","id":1,"result":{"tools":[{"name":"read_file","inputSchema":{"type":"object"}},{"description":"Nameless","inputSchema":{"type":"object"}},{"name":"write_file","inputSchema":{"type":"string"}}]}}'
+        read -r line
+    "#;
+    let server = ServerConfig::stdio("sloppy", "sh").args(["-c", script]);
+    let report = SearchBuilder::new(vec![server]).timeout(10).search_report().await.unwrap();
+
+    let names: Vec<_> = report.matches.iter().map(|m| m.tool_name()).collect();
+    assert_eq!(names, ["read_file", "write_file"]);
+    let malformed: Vec<_> = report
+        .warnings
+        .iter()
+        .map(|warning| match warning {
+            SearchWarning::MalformedTool { server_name, tool } => {
+                assert_eq!(server_name, "sloppy");
+                tool.clone()
+            }
+            other => panic!("Expected MalformedTool, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(malformed.len(), 2);
+    assert_eq!(malformed[0].name, None);
+    assert!(malformed[0].dropped);
+    assert!(malformed[0].problem.contains("name"));
+    assert_eq!(
+        malformed[1],
+        MalformedTool {
+            name: Some("write_file".to_string()),
+            problem: "inputSchema has type \"string\", expected \"object\"".to_string(),
+            dropped: false,
+        }
+    );
+    assert_eq!(
+        report.warnings[0].to_string(),
+        format!("Server sloppy listed malformed tool (unnamed) (left out): {}", malformed[0].problem)
+    );
+}
+
+#[cfg(all(unix, feature = "stdio"))]
+#[tokio::test]
+async fn test_recorded_session_replays_without_the_server() {
+    use toolsearch::record::{recording_path, Direction, RecordedMessage, ReplaySource};
+    use toolsearch::{SearchBuilder, ToolSource};
+
+    let script = r#"
+        while read -r line; do
+            id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+            case "$line" in
+                *notifications/*) ;;
+                *initialize*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"files","version":"1"}}}' ;;
+                *) echo '{"jsonrpc":"2.0","id":'$id',"result":{"tools":[{"name":"read_file","inputSchema":{"type":"object"}},{"inputSchema":{"type":"object"}}]}}' ;;
+            esac
+        done
+    "#;
+    let dir = std::env::temp_dir().join(format!("toolsearch-record-{}", std::process::id()));
+    let server = ServerConfig::stdio("files", "sh").args(["-c", script]);
+    let live = SearchBuilder::new(vec![server.clone()])
+        .record(&dir)
+        .timeout(10)
+        .search_report()
+        .await
+        .unwrap();
+    assert_eq!(live.matches.len(), 1);
+
+    let recording = std::fs::read_to_string(recording_path(&dir, "files")).unwrap();
+    let messages: Vec<RecordedMessage> =
+        recording.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(messages[0].direction, Direction::Sent);
+    assert_eq!(messages[0].message["method"], "initialize");
+    assert!(messages.iter().any(|m| m.direction == Direction::Received));
+
+    // The command is gone, but its recording answers the same way, unnamed
+    // tool and all
+    let gone = ServerConfig::stdio("files", "toolsearch-test-command-that-does-not-exist");
+    let replayed = SearchBuilder::new(vec![])
+        .source(ReplaySource::for_server(gone, &dir))
+        .timeout(10)
+        .search_report()
+        .await
+        .unwrap();
+    let names = |report: &toolsearch::SearchReport| -> Vec<String> {
+        report.matches.iter().map(|m| format!("{}/{}", m.server_name, m.tool_name())).collect()
+    };
+    assert_eq!(names(&replayed), names(&live));
+    assert_eq!(replayed.warnings, live.warnings);
+    assert_eq!(replayed.warnings.len(), 1);
+
+    let source = ReplaySource::new(recording_path(&dir, "files"));
+    assert_eq!(source.name(), "files");
+    assert_eq!(source.fetch(None).await.unwrap().server_info.unwrap().server_info.name, "files");
+    assert!(ReplaySource::new(dir.join("missing.jsonl")).validate().is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
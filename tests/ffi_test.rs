@@ -0,0 +1,44 @@
+#![cfg(feature = "ffi")]
+
+use serde_json::Value;
+use std::ffi::{CStr, CString};
+use std::ptr;
+use toolsearch::ffi::{toolsearch_search, toolsearch_string_free, toolsearch_version};
+
+fn take_response(raw: *mut std::ffi::c_char) -> Value {
+    assert!(!raw.is_null());
+    let text = unsafe { CStr::from_ptr(raw) }.to_str().unwrap().to_string();
+    unsafe { toolsearch_string_free(raw) };
+    serde_json::from_str(&text).unwrap()
+}
+
+#[test]
+fn test_ffi_search_skips_unreachable_servers() {
+    let servers = CString::new(
+        r#"[{"name":"missing","transport":{"type":"stdio","command":"toolsearch-test-command-that-does-not-exist","args":[]}}]"#,
+    )
+    .unwrap();
+    let query = CString::new("file").unwrap();
+    let response = take_response(unsafe { toolsearch_search(servers.as_ptr(), query.as_ptr(), 0) });
+    assert_eq!(response["ok"], true);
+    assert_eq!(response["matches"], Value::Array(vec![]));
+}
+
+#[test]
+fn test_ffi_reports_errors_as_json() {
+    let query = CString::new("file").unwrap();
+    let response = take_response(unsafe { toolsearch_search(ptr::null(), query.as_ptr(), 0) });
+    assert_eq!(response["ok"], false);
+    assert!(response["error"].as_str().unwrap().contains("servers_json"));
+
+    let servers = CString::new("not json").unwrap();
+    let response = take_response(unsafe { toolsearch_search(servers.as_ptr(), query.as_ptr(), 0) });
+    assert_eq!(response["ok"], false);
+    assert!(response["error"].as_str().unwrap().starts_with("Invalid servers JSON"));
+}
+
+#[test]
+fn test_ffi_version() {
+    let version = unsafe { CStr::from_ptr(toolsearch_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+}
@@ -0,0 +1,258 @@
+//! End-to-end tests against `examples/golden_server.rs`, a real MCP server
+//! serving the known tool set of `tests/golden/catalog.json`
+#![cfg(all(unix, feature = "stdio"))]
+
+use std::path::PathBuf;
+use toolsearch::{ping_server, HealthStatus, SearchBuilder, SearchWarning, ServerConfig, ToolSource};
+
+const CATALOG: &str = include_str!("golden/catalog.json");
+
+/// Path of the built golden server, building it if `cargo test` hasn't
+fn golden_server() -> PathBuf {
+    // Test binaries are in target/<profile>/deps, examples beside them
+    let exe = std::env::current_exe().unwrap();
+    let path = exe.parent().unwrap().parent().unwrap().join("examples").join("golden_server");
+    if !path.exists() {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let status = std::process::Command::new(cargo)
+            .args(["build", "--example", "golden_server"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    path
+}
+
+fn golden(args: &[&str]) -> ServerConfig {
+    ServerConfig::stdio("golden", golden_server().to_str().unwrap()).args(args.iter().copied())
+}
+
+/// Names of the first `count` tools of the catalog, sorted like search
+/// results
+fn catalog_names(count: usize) -> Vec<String> {
+    let tools: Vec<serde_json::Value> = serde_json::from_str(CATALOG).unwrap();
+    let mut names: Vec<_> =
+        tools.iter().take(count).map(|tool| tool["name"].as_str().unwrap().to_string()).collect();
+    names.sort();
+    names
+}
+
+fn names(report: &toolsearch::SearchReport) -> Vec<String> {
+    report.matches.iter().map(|m| m.tool_name().to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_golden_connect_and_handshake() {
+    let server = golden(&["--name", "golden-e2e"]);
+    assert!(matches!(ping_server(&server).await.status, HealthStatus::Healthy));
+
+    let listing = server.fetch(Some(std::time::Duration::from_secs(10))).await.unwrap();
+    let info = listing.server_info.unwrap();
+    assert_eq!(info.server_info.name, "golden-e2e");
+    assert!(info.capabilities.tools.is_some());
+    assert!(listing.connect_time.is_some());
+    assert!(!listing.truncated);
+    assert!(listing.malformed.is_empty());
+}
+
+#[tokio::test]
+async fn test_golden_lists_the_whole_catalog() {
+    let report = SearchBuilder::new(vec![golden(&[])]).timeout(10).search_report().await.unwrap();
+    assert_eq!(names(&report), catalog_names(usize::MAX));
+    assert!(report.warnings.is_empty());
+    assert_eq!(report.health.succeeded, 1);
+
+    // Annotations and schemas come through as served
+    let delete = report.matches.iter().find(|m| m.tool_name() == "delete_file").unwrap();
+    assert_eq!(delete.tool.annotations.as_ref().unwrap().destructive_hint, Some(true));
+    assert_eq!(delete.tool.input_schema["required"], serde_json::json!(["path"]));
+}
+
+#[tokio::test]
+async fn test_golden_pagination() {
+    // 12 tools, 5 per page: pages at offsets 0, 5 and 10, listed one after
+    // another or all at once
+    for parallel in [1, 4] {
+        let report = SearchBuilder::new(vec![golden(&["--page-size", "5"])])
+            .parallel_pages(parallel)
+            .timeout(10)
+            .search_report()
+            .await
+            .unwrap();
+        assert_eq!(names(&report), catalog_names(usize::MAX));
+        assert!(report.warnings.is_empty());
+    }
+
+    let report = SearchBuilder::new(vec![golden(&["--page-size", "5"])])
+        .max_pages(2)
+        .timeout(10)
+        .search_report()
+        .await
+        .unwrap();
+    assert_eq!(names(&report), catalog_names(10));
+    assert_eq!(
+        report.warnings,
+        [SearchWarning::ToolListTruncated {
+            server_name: "golden".to_string(),
+            tools: 10,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_golden_timeout() {
+    let slow = golden(&["--delay-ms", "5000"]).with_timeout(1);
+    let started = std::time::Instant::now();
+    let fast = ServerConfig {
+        name: "fast".to_string(),
+        ..golden(&[])
+    };
+    let report = SearchBuilder::new(vec![slow, fast])
+        .timeout(10)
+        .search_report()
+        .await
+        .unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(4));
+    assert!(report.matches.iter().all(|m| m.server_name == "fast"));
+    assert_eq!(report.matches.len(), catalog_names(usize::MAX).len());
+    assert!(matches!(
+        &report.warnings[..],
+        [SearchWarning::ServerFailed { server_name, .. }] if server_name == "golden"
+    ));
+}
+
+#[tokio::test]
+async fn test_golden_server_logging_to_stderr() {
+    // Far more than a pipe holds, so the server blocks unless it is read
+    let script = format!(
+        "head -c 1000000 /dev/zero | tr '\\0' x >&2; exec '{}'",
+        golden_server().display()
+    );
+    let server = ServerConfig::stdio("golden", "sh").args(["-c", script.as_str()]);
+    let report = SearchBuilder::new(vec![server]).timeout(10).search_report().await.unwrap();
+    assert_eq!(names(&report), catalog_names(usize::MAX));
+}
+
+#[tokio::test]
+async fn test_golden_search() {
+    let search = |query: &'static str| async move {
+        let report = SearchBuilder::new(vec![golden(&["--page-size", "4"])])
+            .query(query)
+            .timeout(10)
+            .search_report()
+            .await
+            .unwrap();
+        names(&report)
+    };
+    // "List the files and directories" matches too
+    let files = ["delete_file", "list_directory", "read_file", "search_files", "write_file"];
+    assert_eq!(search("file").await, files);
+    assert_eq!(search("database").await, ["list_tables", "query_database"]);
+    assert_eq!(search("weather").await, ["get_weather"]);
+    assert!(search("nonexistent_capability").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_golden_health_checks_and_half_open_circuit() {
+    use std::time::Duration;
+    use toolsearch::{
+        CircuitBreakerConfig, CircuitState, SearchCriteria, SearchOptions, ToolSearchClient,
+    };
+
+    // The server starts once the flag file exists
+    let flag = std::env::temp_dir().join(format!("toolsearch-golden-up-{}", std::process::id()));
+    let _ = std::fs::remove_file(&flag);
+    let script = format!("test -e '{}' && exec '{}'", flag.display(), golden_server().display());
+    let server = ServerConfig::stdio("golden", "sh").args(["-c", script.as_str()]);
+    let client = ToolSearchClient::new(vec![server])
+        .with_options(SearchOptions {
+            continue_on_error: true,
+            timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .with_circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: 1,
+            cooldown: Duration::from_millis(100),
+        });
+    let criteria = SearchCriteria::match_all();
+    assert!(client.search(&criteria).await.unwrap().is_empty());
+    assert_eq!(client.circuit_state("golden"), CircuitState::Open);
+    client.check_health().await;
+    assert!(!client.is_healthy("golden"));
+
+    // A half-open circuit whose server is unhealthy keeps its trial
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(client.circuit_state("golden"), CircuitState::HalfOpen);
+    assert!(client.search(&criteria).await.unwrap().is_empty());
+
+    // ...for when the server is back
+    std::fs::write(&flag, "").unwrap();
+    client.check_health().await;
+    assert!(client.is_healthy("golden"));
+    assert_eq!(client.search(&criteria).await.unwrap().len(), 12);
+    assert_eq!(client.circuit_state("golden"), CircuitState::Closed);
+    std::fs::remove_file(&flag).unwrap();
+}
+
+#[cfg(feature = "chaos")]
+#[tokio::test]
+async fn test_golden_injected_faults() {
+    use std::time::Duration;
+    use toolsearch::chaos::Faults;
+    use toolsearch::{
+        CircuitBreakerConfig, CircuitState, SearchCriteria, SearchOptions, ToolSearchClient,
+    };
+
+    let failed = |report: &toolsearch::SearchReport| {
+        matches!(&report.warnings[..], [SearchWarning::ServerFailed { .. }])
+    };
+
+    // Refused connections open the circuit, which then keeps the server
+    // from being contacted at all
+    let faults = Faults::new().refuse_connections(2);
+    let client = ToolSearchClient::new(vec![golden(&[]).with_faults(faults.clone())])
+        .with_options(SearchOptions {
+            continue_on_error: true,
+            timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .with_circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_millis(200),
+        });
+    let criteria = SearchCriteria::match_all();
+    assert!(failed(&client.search_report(&criteria).await.unwrap()));
+    assert!(failed(&client.search_report(&criteria).await.unwrap()));
+    assert_eq!(client.circuit_state("golden"), CircuitState::Open);
+    assert!(client.search(&criteria).await.unwrap().is_empty());
+    assert_eq!(faults.connections(), 2);
+    // After the cooldown, the trial connection goes through
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(client.search(&criteria).await.unwrap().len(), 12);
+    assert_eq!(client.circuit_state("golden"), CircuitState::Closed);
+    assert_eq!(faults.connections(), 3);
+
+    // A connection lost after the handshake fails the server
+    let dropped = golden(&[]).with_faults(Faults::new().drop_after(1));
+    let report = SearchBuilder::new(vec![dropped]).timeout(10).search_report().await.unwrap();
+    assert!(failed(&report));
+
+    // Held-back messages run into the server's timeout
+    let slow = golden(&[]).with_faults(Faults::new().delay(Duration::from_secs(3))).with_timeout(1);
+    let report = SearchBuilder::new(vec![slow]).timeout(10).search_report().await.unwrap();
+    assert!(failed(&report));
+
+    // A malformed second page leaves the tools of the other pages
+    let faults = Faults::new().malformed_page(2);
+    let broken = golden(&["--page-size", "5"]).with_faults(faults.clone());
+    let report = SearchBuilder::new(vec![broken]).timeout(10).search_report().await.unwrap();
+    assert_eq!(faults.pages(), 3);
+    assert_eq!(report.matches.len(), 7);
+    let malformed = report
+        .warnings
+        .iter()
+        .filter(|warning| matches!(warning, SearchWarning::MalformedTool { .. }))
+        .count();
+    assert_eq!(malformed, 5);
+    assert_eq!(report.warnings.len(), 5);
+}
@@ -1,5 +1,4 @@
-use toolsearch::{list_all_tools, search_tools_with_query, SearchCriteria, ServerConfig, TransportConfig};
-use std::collections::HashMap;
+use toolsearch::{ServerConfig, TransportConfig};
 
 #[tokio::test]
 async fn test_search_criteria() {
@@ -40,14 +39,7 @@ async fn test_search_criteria() {
 
 #[tokio::test]
 async fn test_server_config_serialization() {
-    let config = ServerConfig {
-        name: "test_server".to_string(),
-        transport: TransportConfig::Stdio {
-            command: "echo".to_string(),
-            args: vec!["hello".to_string()],
-            env: HashMap::new(),
-        },
-    };
+    let config = ServerConfig::stdio("test_server", "echo").arg("hello");
 
     let json = serde_json::to_string(&config).unwrap();
     let deserialized: ServerConfig = serde_json::from_str(&json).unwrap();
@@ -63,7 +55,746 @@ async fn test_server_config_serialization() {
     }
 }
 
+#[test]
+fn test_env_policy_config() {
+    use serde_json::json;
+    use toolsearch::EnvPolicy;
+
+    let parse = |policy: serde_json::Value| -> ServerConfig {
+        serde_json::from_value(json!({
+            "name": "github",
+            "transport": { "type": "stdio", "command": "github-mcp", "args": [], "env_policy": policy }
+        }))
+        .unwrap()
+    };
+    let policy = |config: &ServerConfig| match &config.transport {
+        TransportConfig::Stdio { env_policy, .. } => env_policy.clone(),
+        _ => panic!("expected stdio transport"),
+    };
+
+    assert_eq!(policy(&parse(json!("clean"))), EnvPolicy::Clean);
+    let allowlist = parse(json!({ "allowlist": ["PATH", "HOME"] }));
+    assert_eq!(
+        policy(&allowlist),
+        EnvPolicy::Allowlist(vec!["PATH".to_string(), "HOME".to_string()])
+    );
+    assert!(policy(&allowlist).allows("PATH"));
+    assert!(!policy(&allowlist).allows("AWS_SECRET_ACCESS_KEY"));
+    assert!(!EnvPolicy::Clean.allows("PATH"));
+    assert!(EnvPolicy::Inherit.allows("PATH"));
+
+    // Missing means inherit, and inherit is left out when saving
+    let plain = ServerConfig::stdio("github", "github-mcp");
+    assert_eq!(policy(&plain), EnvPolicy::Inherit);
+    assert!(!serde_json::to_string(&plain).unwrap().contains("env_policy"));
+    let saved = serde_json::to_value(&allowlist).unwrap();
+    assert_eq!(saved["transport"]["env_policy"], json!({ "allowlist": ["PATH", "HOME"] }));
+
+    let invalid = plain.env_policy(EnvPolicy::Allowlist(vec!["PATH=/bin".to_string()]));
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_command_resolution_and_shell_flag() {
+    use std::ffi::OsStr;
+    use toolsearch::spawn::resolve_command;
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-spawn-{}", std::process::id()));
+    let bin = dir.join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    std::fs::write(bin.join("uvx"), "#!/bin/sh").unwrap();
+    std::fs::write(bin.join("uvx.cmd"), "").unwrap();
+    std::fs::write(bin.join("node.exe"), "").unwrap();
+    let path = std::env::join_paths([dir.join("empty"), bin.clone()]).unwrap();
+    // Lowercase to match the files on case-sensitive filesystems
+    let pathext = Some(OsStr::new(".com;.exe;.bat;.cmd"));
+
+    assert_eq!(resolve_command("uvx", Some(&path), pathext), Some(bin.join("uvx.cmd")));
+    assert_eq!(resolve_command("node", Some(&path), pathext), Some(bin.join("node.exe")));
+    assert_eq!(resolve_command("node.exe", Some(&path), pathext), Some(bin.join("node.exe")));
+    // Without PATHEXT (Unix) only the exact name is found
+    assert_eq!(resolve_command("uvx", Some(&path), None), Some(bin.join("uvx")));
+    let explicit = bin.join("uvx");
+    assert_eq!(
+        resolve_command(explicit.to_str().unwrap(), None, pathext),
+        Some(bin.join("uvx.cmd"))
+    );
+    assert_eq!(resolve_command("missing", Some(&path), pathext), None);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let config: ServerConfig = serde_json::from_str(
+        r#"{"name": "fs", "transport": {"type": "stdio", "command": "npx -y @scope/fs", "args": [], "shell": true}}"#,
+    )
+    .unwrap();
+    assert_eq!(config, ServerConfig::stdio("fs", "npx -y @scope/fs").shell(true));
+    assert!(!serde_json::to_string(&ServerConfig::stdio("fs", "npx")).unwrap().contains("shell"));
+}
+
+#[test]
+fn test_runner_package_shorthand() {
+    use toolsearch::Runner;
+
+    let servers: Vec<ServerConfig> = serde_json::from_str(
+        r#"[
+            {"name": "fs", "transport": {"type": "stdio", "runner": "npx",
+             "package": "@modelcontextprotocol/server-filesystem", "args": ["/tmp"]}},
+            {"name": "git", "transport": {"type": "stdio", "runner": "uvx", "package": "mcp-server-git"}},
+            {"name": "fetch", "transport": {"type": "stdio", "runner": "pipx", "package": "mcp-server-fetch"}},
+            {"name": "gh", "transport": {"type": "stdio", "runner": "docker", "package": "ghcr.io/github/github-mcp-server"}}
+        ]"#,
+    )
+    .unwrap();
+    let lines: Vec<String> = servers
+        .iter()
+        .map(|s| {
+            assert!(s.validate().is_ok(), "{}", s.name);
+            let (command, args) = s.command_line().unwrap();
+            format!("{} {}", command, args.join(" "))
+        })
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            "npx -y @modelcontextprotocol/server-filesystem /tmp",
+            "uvx mcp-server-git",
+            "pipx run mcp-server-fetch",
+            "docker run -i --rm ghcr.io/github/github-mcp-server",
+        ]
+    );
+    assert_eq!(
+        servers[1],
+        ServerConfig::package("git", Runner::Uvx, "mcp-server-git")
+    );
+    // Saved back in shorthand form
+    let saved = serde_json::to_value(&servers[1]).unwrap();
+    assert_eq!(saved["transport"]["runner"], "uvx");
+    assert!(saved["transport"].get("command").is_none());
+
+    let both: ServerConfig = serde_json::from_str(
+        r#"{"name": "x", "transport": {"type": "stdio", "command": "npx", "runner": "npx", "package": "p"}}"#,
+    )
+    .unwrap();
+    assert!(both.validate().is_err());
+    let no_package: ServerConfig =
+        serde_json::from_str(r#"{"name": "x", "transport": {"type": "stdio", "runner": "npx"}}"#).unwrap();
+    assert!(no_package.validate().is_err());
+    assert!(ServerConfig::stdio("plain", "mcp-server").command_line().is_some());
+    assert!(ServerConfig::sse("remote", "https://example.com/sse").command_line().is_none());
+}
+
+#[test]
+fn test_protocol_version_config() {
+    let pinned: ServerConfig = serde_json::from_str(
+        r#"{"name": "legacy", "protocol_version": "2024-11-05", "transport": {"type": "stdio", "command": "legacy-mcp"}}"#,
+    )
+    .unwrap();
+    assert_eq!(pinned, ServerConfig::stdio("legacy", "legacy-mcp").with_protocol_version("2024-11-05"));
+    let versions: Vec<String> = pinned.protocol_versions().iter().map(|v| v.to_string()).collect();
+    assert_eq!(versions, ["2024-11-05"]);
+    // Unknown versions are passed through as given
+    let future = pinned.clone().with_protocol_version("2099-01-01");
+    assert_eq!(future.protocol_versions()[0].to_string(), "2099-01-01");
+
+    let unpinned = ServerConfig::stdio("modern", "modern-mcp");
+    assert!(unpinned.protocol_versions().len() > 1);
+    assert!(!serde_json::to_string(&unpinned).unwrap().contains("protocol_version"));
+    assert!(unpinned.with_protocol_version("").validate().is_err());
+}
+
 // Note: Integration tests that actually connect to MCP servers would require
 // running MCP servers, which is beyond the scope of unit tests.
 // These would be better suited as example programs or manual tests.
 
+
+#[test]
+fn test_load_servers_config_errors() {
+    use toolsearch::{load_servers, ConfigError, ToolSearchError};
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Missing file
+    let missing = dir.join("missing.json");
+    match load_servers(missing.to_str().unwrap()) {
+        Err(ToolSearchError::Config(ConfigError::NotFound { path })) => {
+            assert!(path.ends_with("missing.json"))
+        }
+        other => panic!("Expected NotFound, got {:?}", other),
+    }
+
+    // Malformed JSON reports the location
+    let malformed = dir.join("malformed.json");
+    std::fs::write(&malformed, "[\n  { \"name\": \"a\",\n    oops }\n]").unwrap();
+    match load_servers(malformed.to_str().unwrap()) {
+        Err(ToolSearchError::Config(ConfigError::Parse { line, .. })) => assert_eq!(line, 3),
+        other => panic!("Expected Parse, got {:?}", other),
+    }
+
+    // Validation failures name the server
+    let invalid = dir.join("invalid.json");
+    std::fs::write(
+        &invalid,
+        r#"[{"name": "broken", "transport": {"type": "stdio", "command": "", "args": []}}]"#,
+    )
+    .unwrap();
+    match load_servers(invalid.to_str().unwrap()) {
+        Err(ToolSearchError::Config(ConfigError::Validation { server, .. })) => {
+            assert_eq!(server, "broken")
+        }
+        other => panic!("Expected Validation, got {:?}", other),
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_duplicate_server_names_at_load() {
+    use toolsearch::validate::{codes, validate_config};
+    use toolsearch::{load_servers, load_servers_with, ConfigError, DuplicatePolicy, ToolSearchError};
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-duplicates-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("servers.json");
+    std::fs::write(
+        &config,
+        r#"[
+            {"name": "files", "transport": {"type": "stdio", "command": "a"}},
+            {"name": "files", "transport": {"type": "stdio", "command": "b"}},
+            {"name": "files-2", "transport": {"type": "stdio", "command": "c"}}
+        ]"#,
+    )
+    .unwrap();
+    let path = config.to_str().unwrap();
+
+    match load_servers(path) {
+        Err(ToolSearchError::Config(ConfigError::DuplicateServer { server, .. })) => {
+            assert_eq!(server, "files")
+        }
+        other => panic!("Expected DuplicateServer, got {:?}", other),
+    }
+    let names = |policy| -> Vec<String> {
+        load_servers_with(path, policy).unwrap().into_iter().map(|s| s.name).collect()
+    };
+    assert_eq!(names(DuplicatePolicy::Rename), ["files", "files-3", "files-2"]);
+    assert_eq!(names(DuplicatePolicy::Keep), ["files", "files", "files-2"]);
+    assert_eq!("rename".parse(), Ok(DuplicatePolicy::Rename));
+
+    let report = validate_config(path);
+    let duplicate = report.servers[1].error.as_ref().unwrap();
+    assert_eq!(duplicate.code, codes::DUPLICATE_NAME);
+    assert!(report.servers[2].valid);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_sse_header_validation() {
+    use toolsearch::validate::{codes, validate_config_data};
+    use toolsearch::HeaderValue;
+
+    let server = ServerConfig::sse("remote", "https://example.com/sse")
+        .header("Authorization", "Bearer token")
+        .header("Accept", vec!["text/event-stream".to_string(), "application/json".to_string()]);
+    assert!(server.validate().is_ok());
+
+    let data = r#"[
+        {"name": "ok", "transport": {"type": "sse", "url": "https://example.com/sse",
+            "headers": {"Accept": ["text/event-stream", "application/json"]}}},
+        {"name": "spaced", "transport": {"type": "sse", "url": "https://example.com/sse",
+            "headers": {"X Api Key": "secret"}}},
+        {"name": "injected", "transport": {"type": "sse", "url": "https://example.com/sse",
+            "headers": {"Authorization": "Bearer secret\r\nX-Admin: 1"}}},
+        {"name": "twice", "transport": {"type": "sse", "url": "https://example.com/sse",
+            "headers": {"Accept": "text/event-stream", "accept": "application/json"}}}
+    ]"#;
+    let report = validate_config_data("servers.json", data);
+    let codes: Vec<_> = report
+        .servers
+        .iter()
+        .map(|server| server.error.as_ref().map(|e| e.code.as_str()))
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            None,
+            Some(codes::INVALID_HEADER_NAME),
+            Some(codes::INVALID_HEADER_VALUE),
+            Some(codes::DUPLICATE_HEADER)
+        ]
+    );
+    // The offending value may be a credential, so it isn't echoed
+    assert!(!report.servers[2].error.as_ref().unwrap().message.contains("secret"));
+
+    // One value stays a string when saved, several a list
+    let servers: Vec<ServerConfig> = serde_json::from_str(data).unwrap();
+    let TransportConfig::Sse { headers, .. } = &servers[0].transport else {
+        panic!("Expected an SSE server");
+    };
+    let mut accept = headers["Accept"].clone();
+    assert_eq!(accept.iter().collect::<Vec<_>>(), ["text/event-stream", "application/json"]);
+    let mut single = HeaderValue::from("text/event-stream");
+    assert_eq!(serde_json::to_value(&single).unwrap(), "text/event-stream");
+    single.push("application/json");
+    accept.push("text/plain");
+    assert_eq!(serde_json::to_value(&single).unwrap()[1], "application/json");
+    assert_eq!(accept.iter().count(), 3);
+}
+
+#[test]
+fn test_config_includes() {
+    use toolsearch::validate::{codes, validate_config};
+    use toolsearch::{load_servers, ConfigError, ToolSearchError};
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-includes-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("fleet")).unwrap();
+    let write = |name: &str, data: &str| std::fs::write(dir.join(name), data).unwrap();
+    write(
+        "servers.json",
+        r#"[
+            {"include": ["./fleet/fs-servers.json"]},
+            {"name": "github", "transport": {"type": "sse", "url": "https://example.com/sse"}}
+        ]"#,
+    );
+    write(
+        "fleet/fs-servers.json",
+        r#"[
+            {"name": "files", "transport": {"type": "stdio", "command": "files-server"}},
+            {"include": "db-servers.json"}
+        ]"#,
+    );
+    write(
+        "fleet/db-servers.json",
+        r#"[{"name": "db", "transport": {"type": "stdio", "command": "db-server"}}]"#,
+    );
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+    let names: Vec<_> = load_servers(&path("servers.json"))
+        .unwrap()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names, ["files", "db", "github"]);
+    assert_eq!(validate_config(&path("servers.json")).servers.len(), 3);
+
+    // db-servers.json including the top-level file closes a cycle
+    write("fleet/db-servers.json", r#"[{"include": ["../servers.json"]}]"#);
+    match load_servers(&path("servers.json")) {
+        Err(ToolSearchError::Config(ConfigError::Include { message, .. })) => {
+            assert!(message.starts_with("include cycle:"), "{}", message);
+            assert!(message.ends_with("servers.json"), "{}", message);
+        }
+        other => panic!("Expected an include cycle, got {:?}", other),
+    }
+
+    write("fleet/db-servers.json", r#"[{"include": ["missing.json"]}]"#);
+    assert!(matches!(
+        load_servers(&path("servers.json")),
+        Err(ToolSearchError::Config(ConfigError::Include { .. }))
+    ));
+    let report = validate_config(&path("servers.json"));
+    assert_eq!(report.error.unwrap().code, codes::INVALID_INCLUDE);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "remote-config")]
+#[tokio::test]
+async fn test_config_from_url() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use toolsearch::{ConfigError, ConfigSource, DuplicatePolicy, ToolSearchError};
+
+    // A one-shot HTTP server on this machine, which plain http:// may reach
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/servers.json", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0; 4096];
+        let n = socket.read(&mut request).await.unwrap();
+        let body = r#"[{"name": "remote", "transport": {"type": "stdio", "command": "server"}}]"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request[..n]).to_lowercase()
+    });
+
+    let source = ConfigSource::parse(&url)
+        .with_header("Authorization", "Bearer secret")
+        .with_cache_dir(None);
+    let servers = source.load(DuplicatePolicy::Error).await.unwrap();
+    assert_eq!(servers[0].name, "remote");
+    assert!(server.await.unwrap().contains("authorization: bearer secret"));
+
+    // Credentials don't travel over plain HTTP to other machines
+    let insecure = ConfigSource::parse("http://config.example.com/servers.json");
+    match insecure.load(DuplicatePolicy::Error).await {
+        Err(ToolSearchError::Config(ConfigError::Fetch { url, .. })) => {
+            assert_eq!(url, "http://config.example.com/servers.json")
+        }
+        other => panic!("Expected Fetch, got {:?}", other),
+    }
+}
+
+#[cfg(feature = "remote-config")]
+#[tokio::test]
+async fn test_remote_config_cache_revalidates_and_works_offline() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use toolsearch::{ConfigSource, DuplicatePolicy};
+
+    let cache = std::env::temp_dir().join(format!("toolsearch-config-cache-{}", std::process::id()));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/servers.json", listener.local_addr().unwrap());
+    // Serves the configuration once, then answers 304 to a matching ETag
+    let server = tokio::spawn(async move {
+        let mut requests = Vec::new();
+        for _ in 0..2 {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let n = socket.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..n]).to_lowercase();
+            let response = if request.contains("if-none-match: \"v1\"") {
+                "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string()
+            } else {
+                let body = r#"[{"name": "shared", "transport": {"type": "stdio", "command": "server"}}]"#;
+                format!(
+                    "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            };
+            socket.write_all(response.as_bytes()).await.unwrap();
+            requests.push(request);
+        }
+        requests
+    });
+
+    let source = ConfigSource::parse(&url).with_cache_dir(Some(cache.clone()));
+    for _ in 0..2 {
+        let text = source.read().await.unwrap();
+        assert!(text.stale.is_none());
+        assert_eq!(text.servers(DuplicatePolicy::Error).unwrap()[0].name, "shared");
+    }
+    let requests = server.await.unwrap();
+    assert!(!requests[0].contains("if-none-match"));
+    assert!(requests[1].contains("if-none-match"));
+
+    // The server is gone: the cached copy stands in, flagged stale
+    let text = source.read().await.unwrap();
+    assert!(text.stale.is_some());
+    assert_eq!(text.servers(DuplicatePolicy::Error).unwrap()[0].name, "shared");
+
+    // Copies are kept per set of headers, so other credentials don't get
+    // this copy back
+    let other = source.clone().with_header("Authorization", "Bearer other");
+    assert!(other.read().await.is_err());
+
+    // Without a cache there is nothing to fall back to
+    assert!(source.with_cache_dir(None).read().await.is_err());
+
+    // A host that answers once, then accepts connections and never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/servers.json", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0; 4096];
+        let _ = socket.read(&mut request).await.unwrap();
+        let body = r#"[{"name": "hanging", "transport": {"type": "stdio", "command": "server"}}]"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        let mut held = Vec::new();
+        loop {
+            held.push(listener.accept().await.unwrap().0);
+        }
+    });
+    let source = ConfigSource::parse(&url)
+        .with_cache_dir(Some(cache.clone()))
+        .with_timeout(std::time::Duration::from_millis(300));
+    assert!(source.read().await.unwrap().stale.is_none());
+    let started = std::time::Instant::now();
+    let text = source.read().await.unwrap();
+    assert!(text.stale.is_some());
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(text.servers(DuplicatePolicy::Error).unwrap()[0].name, "hanging");
+    server.abort();
+    std::fs::remove_dir_all(&cache).unwrap();
+}
+
+#[test]
+fn test_validate_config_reports_every_server() {
+    use toolsearch::validate::{codes, validate_config};
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-validate-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let config = dir.join("servers.json");
+    std::fs::write(
+        &config,
+        r#"[
+            {"name": "ok", "transport": {"type": "stdio", "command": "server"}},
+            {"name": "slow", "transport": {"type": "stdio", "command": "server"}, "timeout_secs": 0},
+            {"name": "web", "transport": {"type": "http", "url": "https://example.com"}},
+            {"transport": {"type": "sse", "url": "example.com"}}
+        ]"#,
+    )
+    .unwrap();
+    let report = validate_config(config.to_str().unwrap());
+    assert!(!report.valid);
+    assert!(report.error.is_none());
+    let statuses: Vec<_> = report
+        .servers
+        .iter()
+        .map(|s| (s.name.as_str(), s.error.as_ref().map(|e| e.code.as_str())))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("ok", None),
+            ("slow", Some(codes::ZERO_TIMEOUT)),
+            ("web", Some(codes::INVALID_SERVER)),
+            ("server 4", Some(codes::INVALID_SERVER)),
+        ]
+    );
+    assert!(report.servers[1].error.as_ref().unwrap().suggestion.is_some());
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["servers"][0], serde_json::json!({ "name": "ok", "valid": true }));
+
+    // Syntax errors leave no servers to check
+    std::fs::write(&config, "[\n  oops\n]").unwrap();
+    let error = validate_config(config.to_str().unwrap()).error.unwrap();
+    assert_eq!((error.code.as_str(), error.line), (codes::INVALID_JSON, Some(2)));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_config_lint_flags_risky_patterns() {
+    use toolsearch::configlint::{fix_config, lint_config, ConfigRule, Severity};
+
+    let mut servers: Vec<ServerConfig> = serde_json::from_value(serde_json::json!([
+        {
+            "name": "github",
+            "transport": {
+                "type": "stdio",
+                "command": "/nonexistent/toolsearch/github-server",
+                "env": { "GITHUB_TOKEN": "ghp_123", "LOG_LEVEL": "debug" }
+            },
+            "timeout_secs": 10
+        },
+        {
+            "name": "github",
+            "transport": { "type": "sse", "url": "http://tools.example.com/sse" }
+        },
+        {
+            "name": "local",
+            "transport": {
+                "type": "sse",
+                "url": "http://localhost:8080/sse",
+                "headers": { "X-API-Key": "abc" }
+            },
+            "timeout_secs": 10
+        }
+    ]))
+    .unwrap();
+    let found: Vec<_> = lint_config(&servers)
+        .into_iter()
+        .map(|issue| (issue.server_name, issue.rule, issue.severity))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("github".to_string(), ConfigRule::PlaintextSecret, Severity::Error),
+            ("github".to_string(), ConfigRule::MissingPath, Severity::Warning),
+            ("github".to_string(), ConfigRule::DuplicateName, Severity::Error),
+            ("github".to_string(), ConfigRule::MissingTimeout, Severity::Info),
+            ("github".to_string(), ConfigRule::InsecureUrl, Severity::Warning),
+            ("local".to_string(), ConfigRule::PlaintextSecret, Severity::Error),
+        ]
+    );
+
+    let fixed: Vec<_> = fix_config(&mut servers).into_iter().map(|issue| issue.rule).collect();
+    assert_eq!(fixed, [ConfigRule::DuplicateName, ConfigRule::MissingTimeout]);
+    assert_eq!(servers[1].name, "github-2");
+    assert_eq!(servers[1].timeout_secs, Some(30));
+    assert!(lint_config(&servers).iter().all(|issue| !issue.fixable));
+}
+
+#[test]
+fn test_server_priority_config() {
+    // Priority defaults to zero and is omitted when serialized
+    let config: ServerConfig = serde_json::from_str(
+        r#"{"name": "s", "transport": {"type": "stdio", "command": "echo", "args": []}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.priority, 0);
+    assert!(!serde_json::to_string(&config).unwrap().contains("priority"));
+
+    let config: ServerConfig = serde_json::from_str(
+        r#"{"name": "s", "priority": 10, "transport": {"type": "stdio", "command": "echo", "args": []}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.priority, 10);
+    assert_eq!(ServerConfig::stdio("s", "echo").with_priority(3).priority, 3);
+}
+
+#[test]
+fn test_server_timeout_config() {
+    use std::time::Duration;
+
+    let config: ServerConfig = serde_json::from_str(
+        r#"{"name": "s", "timeout_secs": 45, "transport": {"type": "stdio", "command": "echo"}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.timeout(), Some(Duration::from_secs(45)));
+    assert_eq!(config, ServerConfig::stdio("s", "echo").with_timeout(45));
+    assert!(ServerConfig::stdio("s", "echo").timeout().is_none());
+    assert!(ServerConfig::stdio("s", "echo").with_timeout(0).validate().is_err());
+}
+
+#[tokio::test]
+async fn test_list_tools_by_server_skips_failures() {
+    use toolsearch::{list_tools_by_server, list_tools_by_server_with_options, SearchOptions};
+
+    let servers = vec![ServerConfig::stdio(
+        "missing",
+        "toolsearch-test-command-that-does-not-exist",
+    )];
+
+    let tools = list_tools_by_server(&servers).await.unwrap();
+    assert!(tools.is_empty());
+
+    let strict = SearchOptions {
+        continue_on_error: false,
+        ..Default::default()
+    };
+    assert!(list_tools_by_server_with_options(&servers, &strict).await.is_err());
+}
+
+#[test]
+fn test_save_servers_preserves_unknown_fields() {
+    use serde_json::{json, Value};
+    use toolsearch::{load_servers, save_servers};
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-roundtrip-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("servers.json");
+    let path = path.to_str().unwrap();
+
+    let original = json!([{
+        "name": "files",
+        "disabled": false,
+        "x-vendor": { "autoApprove": ["read_file"] },
+        "transport": { "type": "stdio", "command": "mcp-file-server", "args": [], "cwd": "/srv" }
+    }]);
+    std::fs::write(path, original.to_string()).unwrap();
+
+    let servers = load_servers(path).unwrap();
+    save_servers(path, &servers).unwrap();
+    let saved: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(saved[0]["disabled"], false);
+    assert_eq!(saved[0]["x-vendor"], original[0]["x-vendor"]);
+    assert_eq!(saved[0]["transport"]["cwd"], "/srv");
+    assert_eq!(saved[0]["transport"]["type"], "stdio");
+    assert_eq!(load_servers(path).unwrap(), servers);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_history_round_trip() {
+    use std::io::Write;
+    use toolsearch::history::{History, HistoryEntry};
+    use toolsearch::SearchMode;
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-history-{}", std::process::id()));
+    let history = History::new(dir.join("nested").join("history.jsonl"));
+    assert!(history.entries().unwrap().is_empty());
+
+    let first = HistoryEntry {
+        query: Some("read file".to_string()),
+        limit: Some(5),
+        ..HistoryEntry::new("/etc/servers.json", SearchMode::Substring, 3)
+    };
+    let second = HistoryEntry {
+        name: Some("send_email".to_string()),
+        ..HistoryEntry::new("/etc/servers.json", SearchMode::Substring, 1)
+    };
+    history.append(&first).unwrap();
+    history.append(&second).unwrap();
+    // A line cut short by a crash is skipped
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(history.path())
+        .unwrap()
+        .write_all(b"{\"timestamp\":")
+        .unwrap();
+
+    assert_eq!(history.entries().unwrap(), [first.clone(), second.clone()]);
+    assert_eq!(history.get(1).unwrap(), Some(first));
+    assert_eq!(history.get(2).unwrap().unwrap().target(), "send_email");
+    assert_eq!(history.get(0).unwrap(), None);
+    assert_eq!(history.get(3).unwrap(), None);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[derive(Debug, Clone)]
+struct ArbitraryServer(ServerConfig);
+
+impl quickcheck::Arbitrary for ArbitraryServer {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        use serde_json::Value;
+
+        // Unknown keys get a prefix so they never collide with known fields
+        let extra = |g: &mut quickcheck::Gen| {
+            Vec::<(String, String)>::arbitrary(g)
+                .into_iter()
+                .map(|(k, v)| (format!("x-{}", k), Value::String(v)))
+                .collect::<serde_json::Map<_, _>>()
+        };
+        let mut config = if bool::arbitrary(g) {
+            let mut config = ServerConfig::stdio(String::arbitrary(g), String::arbitrary(g))
+                .args(Vec::<String>::arbitrary(g));
+            for (k, v) in Vec::<(String, String)>::arbitrary(g) {
+                config = config.env(k, v);
+            }
+            config
+        } else {
+            let mut config = ServerConfig::sse(String::arbitrary(g), String::arbitrary(g));
+            for (k, v) in Vec::<(String, String)>::arbitrary(g) {
+                config = config.header(k, v);
+            }
+            config
+        };
+        match &mut config.transport {
+            TransportConfig::Stdio { extra: e, .. } | TransportConfig::Sse { extra: e, .. } => {
+                *e = extra(g)
+            }
+        }
+        config.extra = extra(g);
+        config.protocol_version = Option::<String>::arbitrary(g);
+        ArbitraryServer(config.with_priority(u32::arbitrary(g)))
+    }
+}
+
+#[test]
+fn test_server_config_round_trip_property() {
+    fn round_trips(servers: Vec<ArbitraryServer>) -> bool {
+        let servers: Vec<ServerConfig> = servers.into_iter().map(|s| s.0).collect();
+        let json = serde_json::to_string(&servers).unwrap();
+        let loaded: Vec<ServerConfig> = serde_json::from_str(&json).unwrap();
+        loaded == servers && serde_json::to_string(&loaded).unwrap() == json
+    }
+    // Small generated sizes keep the nested maps and lists fast in debug builds.
+    // quickcheck 1.1 deprecates `gen` for a `set_rng` it doesn't provide yet.
+    #[allow(deprecated)]
+    quickcheck::QuickCheck::new()
+        .r#gen(quickcheck::Gen::new(8))
+        .quickcheck(round_trips as fn(Vec<ArbitraryServer>) -> bool);
+}
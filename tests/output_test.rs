@@ -0,0 +1,152 @@
+use serde_json::json;
+use toolsearch::output::{
+    writer, CsvWriter, JsonWriter, LangChainWriter, LlamaIndexWriter, MarkdownWriter, ResultWriter,
+    TextWriter,
+};
+use toolsearch::testing::ToolFixture;
+use toolsearch::ToolSearchMatch;
+
+fn sample() -> Vec<ToolSearchMatch> {
+    vec![
+        ToolSearchMatch::new(
+            "files",
+            ToolFixture::new("read_file").description("Read a file, \"safely\"").build(),
+        ),
+        ToolSearchMatch::new("db", ToolFixture::new("query").description("a | b").build()),
+    ]
+}
+
+#[test]
+fn test_writer_lookup() {
+    let formats = ["text", "table", "json", "csv", "markdown", "md", "html"];
+    for format in formats.into_iter().chain(["langchain", "llamaindex"]) {
+        assert!(writer(format).is_some(), "{} should have a writer", format);
+    }
+    assert!(writer("yaml").is_none());
+}
+
+#[test]
+fn test_text_writer() {
+    let text = TextWriter.render(&sample(), "Found 2 tool(s)").unwrap();
+    assert!(text.starts_with("Found 2 tool(s)\n\nServer: files\n  Name: read_file\n"));
+    assert_eq!(TextWriter.render(&[], "unused").unwrap(), "No results found\n");
+
+    let mut pinned = sample();
+    pinned[0].pinned = true;
+    let text = TextWriter.render(&pinned, "").unwrap();
+    assert!(text.contains("  Name: read_file (pinned)\n"));
+    let json = writer("json").unwrap().render(&pinned, "").unwrap();
+    assert_eq!(json.matches("\"pinned\": true").count(), 1);
+}
+
+#[test]
+fn test_json_writer_round_trips() {
+    let json = writer("json").unwrap().render(&sample(), "").unwrap();
+    let parsed: Vec<ToolSearchMatch> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[1].tool_name(), "query");
+
+    let compact = JsonWriter::default().compact(true).render(&sample(), "").unwrap();
+    assert_eq!(compact.lines().count(), 1);
+    let parsed: Vec<ToolSearchMatch> = serde_json::from_str(&compact).unwrap();
+    assert_eq!(parsed.len(), 2);
+}
+
+#[test]
+fn test_csv_writer_quotes_fields() {
+    let csv = CsvWriter.render(&sample(), "").unwrap();
+    let lines: Vec<_> = csv.split("\r\n").collect();
+    assert_eq!(lines[0], "server,tool,title,description,annotations");
+    assert_eq!(lines[1], "files,read_file,,\"Read a file, \"\"safely\"\"\",");
+    assert_eq!(lines[2], "db,query,,a | b,");
+}
+
+#[test]
+fn test_markdown_writer_escapes_pipes() {
+    let markdown = MarkdownWriter.render(&sample(), "Results").unwrap();
+    assert!(markdown.starts_with("## Results\n\n| Server | Tool |"));
+    assert!(markdown.contains("| db | `query` | a \\| b |  |"));
+}
+
+#[test]
+fn test_agent_framework_tool_specs() {
+    let mut results = sample();
+    results[1].tool.description = None;
+    let specs = |writer: &dyn ResultWriter| -> serde_json::Value {
+        serde_json::from_str(&writer.render(&results, "").unwrap()).unwrap()
+    };
+
+    let langchain = specs(&LangChainWriter::default());
+    let read_file = json!({
+        "name": "read_file",
+        "description": "Read a file, \"safely\"",
+        "parameters": {}
+    });
+    assert_eq!(langchain[0], json!({ "type": "function", "function": read_file }));
+    // No description rather than an empty one
+    assert_eq!(langchain[1]["function"], json!({ "name": "query", "parameters": {} }));
+
+    let llamaindex = specs(&LlamaIndexWriter::default());
+    assert_eq!(
+        llamaindex[1],
+        json!({ "name": "query", "description": "", "fn_schema": {}, "return_direct": false })
+    );
+    let namespaced = specs(&LlamaIndexWriter::default().namespaced(true));
+    assert_eq!(namespaced[0]["name"], "files__read_file");
+
+    // Tools of the same name on two servers are namespaced anyway
+    let mut clashing = results.clone();
+    clashing[1].tool.name = "read_file".into();
+    let langchain = LangChainWriter::default().render(&clashing, "").unwrap();
+    let langchain: serde_json::Value = serde_json::from_str(&langchain).unwrap();
+    assert_eq!(langchain[0]["function"]["name"], "files__read_file");
+    assert_eq!(langchain[1]["function"]["name"], "db__read_file");
+
+    let compact = LlamaIndexWriter::default().compact(true).render(&results, "").unwrap();
+    assert_eq!(compact.lines().count(), 1);
+}
+
+#[test]
+fn test_table_writer_truncates_multibyte_descriptions() {
+    use toolsearch::output::TableWriter;
+
+    // 60 two-byte characters: slicing at byte 47 would split one in half
+    let description = "é".repeat(60);
+    let results = vec![ToolSearchMatch::new(
+        "ファイル",
+        ToolFixture::new("read").description(description.as_str()).build(),
+    )];
+
+    let table = TableWriter::default().render(&results, "Results").unwrap();
+    let row = table.lines().last().unwrap();
+    // The server name is 8 columns wide, so 22 spaces pad it to 30
+    assert!(row.starts_with(&format!("ファイル{} read", " ".repeat(22))));
+    assert!(row.ends_with(&format!("{}...", "é".repeat(47))));
+
+    let table = TableWriter::default().description_width(0).render(&results, "Results").unwrap();
+    assert!(table.ends_with(&format!("{}\n", description)));
+}
+
+#[test]
+fn test_matches_page_into_list_tools_results() {
+    use toolsearch::proxy::{list_tools_page, list_tools_result};
+
+    let all = list_tools_result(&sample());
+    assert_eq!(all.tools.len(), 2);
+    assert!(all.next_cursor.is_none());
+
+    let first = list_tools_page(&sample(), None, 1).unwrap();
+    assert_eq!(first.tools[0].name, "read_file");
+    let second = list_tools_page(&sample(), first.next_cursor.as_deref(), 1).unwrap();
+    assert_eq!(second.tools[0].name, "query");
+    assert!(second.next_cursor.is_none());
+
+    // The wire format matches what MCP clients parse
+    let json = serde_json::to_value(&first).unwrap();
+    assert_eq!(json["nextCursor"], "1");
+
+    for cursor in ["3", "page-2"] {
+        let error = list_tools_page(&sample(), Some(cursor), 1).unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+    }
+}
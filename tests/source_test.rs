@@ -0,0 +1,1055 @@
+use serde_json::json;
+use toolsearch::{OpenApiSource, SearchBuilder, ToolSource};
+
+#[tokio::test]
+async fn test_openapi_operations_become_tools() {
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Pet Store", "version": "1.0.0" },
+        "paths": {
+            "/pets/{id}": {
+                "parameters": [
+                    { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } }
+                ],
+                "get": {
+                    "operationId": "getPet",
+                    "summary": "Fetch a pet",
+                    "description": "Returns a single pet by its identifier"
+                },
+                "delete": {
+                    "summary": "Remove a pet"
+                }
+            },
+            "/pets": {
+                "post": {
+                    "operationId": "createPet",
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": { "type": "object", "properties": { "name": { "type": "string" } } }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    let source = OpenApiSource::from_value("petstore", &spec).unwrap();
+    let tools = source.list_tools(None).await.unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
+    assert_eq!(names.len(), 3);
+    assert!(names.contains(&"getPet"));
+    assert!(names.contains(&"delete_pets_id"));
+    assert!(names.contains(&"createPet"));
+
+    let get_pet = tools.iter().find(|t| t.name == "getPet").unwrap();
+    assert_eq!(get_pet.title.as_deref(), Some("Fetch a pet"));
+    assert_eq!(get_pet.input_schema["required"], json!(["id"]));
+
+    let create_pet = tools.iter().find(|t| t.name == "createPet").unwrap();
+    assert_eq!(create_pet.input_schema["required"], json!(["body"]));
+}
+
+#[tokio::test]
+async fn test_openapi_source_searchable_with_builder() {
+    let spec = json!({
+        "info": { "title": "Pet Store" },
+        "paths": {
+            "/pets": { "get": { "operationId": "listPets", "summary": "List all pets" } },
+            "/users": { "get": { "operationId": "listUsers", "summary": "List all users" } }
+        }
+    });
+
+    let results = SearchBuilder::new(vec![])
+        .source(OpenApiSource::from_value("petstore", &spec).unwrap())
+        .query("pets")
+        .search()
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].server_name, "petstore");
+    assert_eq!(results[0].tool_name(), "listPets");
+}
+
+#[test]
+fn test_openapi_requires_paths() {
+    assert!(OpenApiSource::from_value("empty", &json!({ "openapi": "3.0.0" })).is_err());
+}
+
+#[tokio::test]
+async fn test_directory_source_reads_tool_files() {
+    use toolsearch::DirectorySource;
+
+    let dir = std::env::temp_dir().join(format!("toolsearch-dir-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("deploy.tool.json"),
+        r#"{"name": "deploy_service", "description": "Deploy a service", "inputSchema": {"type": "object"}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("batch.tool.json"),
+        r#"[{"name": "rollback", "inputSchema": {}}, {"name": "restart", "inputSchema": {}}]"#,
+    )
+    .unwrap();
+    std::fs::write(dir.join("notes.json"), r#"{"name": "ignored", "inputSchema": {}}"#).unwrap();
+
+    let source = DirectorySource::new(&dir).with_name("internal");
+    assert!(source.validate().is_ok());
+    let tools = source.list_tools(None).await.unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
+    assert_eq!(names, vec!["rollback", "restart", "deploy_service"]);
+
+    let results = SearchBuilder::new(vec![])
+        .source(source)
+        .query("deploy")
+        .search()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].server_name, "internal");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(DirectorySource::new(&dir).validate().is_err());
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_registry_response_parsing_and_install_hints() {
+    use toolsearch::registry::parse_registry_response;
+    use toolsearch::ServerConfig;
+
+    let body = json!({
+        "servers": [
+            {
+                "server": {
+                    "name": "io.github.example/github",
+                    "description": "GitHub API tools",
+                    "version": "1.2.0",
+                    "repository": { "url": "https://github.com/example/github-mcp" },
+                    "packages": [
+                        { "registryType": "npm", "identifier": "@example/github-mcp", "version": "1.2.0" }
+                    ]
+                },
+                "_meta": {}
+            },
+            {
+                "name": "com.example/hosted",
+                "remotes": [ { "type": "sse", "url": "https://mcp.example.com/sse" } ]
+            },
+            { "description": "entry without a name is skipped" }
+        ]
+    });
+
+    let servers = parse_registry_response(&body);
+    assert_eq!(servers.len(), 2);
+
+    let github = &servers[0];
+    assert_eq!(github.repository.as_deref(), Some("https://github.com/example/github-mcp"));
+    assert_eq!(github.install_hint().as_deref(), Some("npx -y @example/github-mcp"));
+    let config = github.suggested_config().unwrap();
+    assert_eq!(config.name, "github");
+    assert!(config.validate().is_ok());
+
+    let hosted = &servers[1];
+    assert_eq!(hosted.install_hint().as_deref(), Some("connect to https://mcp.example.com/sse"));
+
+    let installed = vec![ServerConfig::stdio("gh", "npx").args(["-y", "@example/github-mcp"])];
+    assert!(github.is_installed(&installed));
+    assert!(!hosted.is_installed(&installed));
+    assert!(github.is_installed(&[config]));
+}
+
+#[tokio::test]
+async fn test_mock_server_serves_tools() {
+    use std::sync::Arc;
+    use toolsearch::testing::{MockServer, ToolFixture};
+
+    let mock = Arc::new(
+        MockServer::with_tools(vec![
+            ToolFixture::new("read_file").description("Read a file").build(),
+            ToolFixture::new("send_email").description("Send an email").build(),
+        ])
+        .named("files"),
+    );
+
+    let results = SearchBuilder::new(vec![])
+        .source(mock.clone())
+        .query("file")
+        .search()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].server_name, "files");
+    assert_eq!(results[0].tool_name(), "read_file");
+    assert_eq!(mock.fetch_count(), 1);
+}
+
+#[tokio::test]
+async fn test_mock_server_failures_and_timeouts() {
+    use std::time::Duration;
+    use toolsearch::testing::MockServer;
+    use toolsearch::SearchWarning;
+
+    let report = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(vec![]).named("broken").failing("boom"))
+        .source(
+            MockServer::with_tools(vec![])
+                .named("slow")
+                .with_delay(Duration::from_secs(5)),
+        )
+        .timeout(1)
+        .query("file")
+        .search_report()
+        .await
+        .unwrap();
+
+    assert!(report.matches.is_empty());
+    let failed: Vec<_> = report.warnings.iter().map(SearchWarning::server_name).collect();
+    assert_eq!(failed, ["broken", "slow"]);
+    assert!(report.warnings[0].to_string().contains("boom"));
+}
+
+#[tokio::test]
+async fn test_report_health_and_min_response_rate() {
+    use std::time::Duration;
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::{Health, ToolSearchError};
+
+    let builder = || {
+        SearchBuilder::new(vec![])
+            .source(MockServer::with_tools(vec![ToolFixture::new("read_file").build()]).named("files"))
+            .source(MockServer::with_tools(vec![]).named("broken").failing("boom"))
+            .source(
+                MockServer::with_tools(vec![])
+                    .named("slow")
+                    .with_delay(Duration::from_secs(5)),
+            )
+            .deadline(Duration::from_millis(200))
+    };
+
+    let report = builder().search_report().await.unwrap();
+    assert_eq!(
+        report.health,
+        Health {
+            queried: 3,
+            succeeded: 1,
+            cached: 0,
+            failed: 2,
+            skipped: 0,
+        }
+    );
+    assert_eq!(report.health.to_string(), "1 of 3 server(s) responded (1 succeeded, 0 cached, 2 failed, 0 skipped)");
+
+    assert!(builder().min_response_rate(0.3).search_report().await.is_ok());
+    let error = builder().min_response_rate(0.5).search().await.unwrap_err();
+    assert!(matches!(
+        error,
+        ToolSearchError::TooFewResponded { responded: 1, queried: 3, .. }
+    ));
+    assert_eq!(error.to_string(), "Only 1 of 3 servers responded, fewer than the 50% required");
+}
+
+#[tokio::test]
+async fn test_popularity_ranks_used_tools_first() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::Popularity;
+
+    let tools = ["read_file", "read_config", "read_log"]
+        .map(|name| ToolFixture::new(name).build())
+        .to_vec();
+    let popularity: Popularity =
+        serde_json::from_value(json!({ "read_log": 10, "files/read_config": 5, "other/read_file": 10 }))
+            .unwrap();
+    let matches = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools).named("files"))
+        .query("read")
+        .popularity(popularity)
+        .sort_by_score()
+        .search()
+        .await
+        .unwrap();
+    let ranked: Vec<_> = matches.iter().map(|m| (m.tool_name(), m.score.unwrap())).collect();
+    assert_eq!(ranked, [("read_log", 2.0), ("read_config", 1.5), ("read_file", 1.0)]);
+}
+
+#[tokio::test]
+async fn test_pinned_tools_sort_first() {
+    use toolsearch::pins::Pins;
+    use toolsearch::testing::{MockServer, ToolFixture};
+
+    let tools = ["a_tool", "b_tool", "c_tool"]
+        .map(|name| ToolFixture::new(name).build())
+        .to_vec();
+    let pins = Pins::new(["other/a_tool"]).with_profile("triage", ["c_tool"]);
+    let mut builder = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools).named("files"))
+        .query("tool")
+        .limit(2);
+    for tool in pins.pinned(Some("triage")) {
+        builder = builder.pin(tool);
+    }
+    let matches = builder.search().await.unwrap();
+    let ranked: Vec<_> = matches.iter().map(|m| (m.tool_name(), m.pinned)).collect();
+    // Pinned for another server, a_tool isn't pinned here
+    assert_eq!(ranked, [("c_tool", true), ("a_tool", false)]);
+}
+
+#[tokio::test]
+async fn test_max_tools_applies_to_any_source() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::SearchWarning;
+
+    let tools = (0..5).map(|i| ToolFixture::new(format!("tool_{}", i)).build()).collect();
+    let report = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools).named("big"))
+        .max_tools(2)
+        .search_report()
+        .await
+        .unwrap();
+    assert_eq!(report.matches.len(), 2);
+    assert_eq!(
+        report.warnings,
+        [SearchWarning::ToolListTruncated {
+            server_name: "big".to_string(),
+            tools: 2,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_max_per_server_keeps_first_listed_matches() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+
+    let tools = |names: [&str; 3]| names.map(|name| ToolFixture::new(name).build()).to_vec();
+    let search = || {
+        SearchBuilder::new(vec![])
+            .source(MockServer::with_tools(tools(["read_c", "write", "read_a"])).named("one"))
+            .source(MockServer::with_tools(tools(["read_b", "read_d", "read_e"])).named("two"))
+            .query("read")
+            .max_per_server(1)
+            .sort_by_tool()
+    };
+    let names = |results: Vec<toolsearch::ToolSearchMatch>| -> Vec<String> {
+        results.iter().map(|m| m.tool_name().to_string()).collect()
+    };
+    let first_listed = search().first_listed_per_server().search().await.unwrap();
+    assert_eq!(names(first_listed), ["read_b", "read_c"]);
+
+    // By default each server keeps its best-ranked matches
+    assert_eq!(names(search().search().await.unwrap()), ["read_a", "read_b"]);
+}
+
+#[tokio::test]
+async fn test_limits_apply_after_scoring_sorting_and_dedupe() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::Dedupe;
+
+    let tools = |names: &[&str]| names.iter().map(|name| ToolFixture::new(*name).build()).collect::<Vec<_>>();
+    // Longer names score higher
+    let score = |tool: &rmcp::model::Tool| Some(tool.name.len() as f32);
+    let search = || {
+        SearchBuilder::new(vec![])
+            .source(MockServer::with_tools(tools(&["read", "read_file", "read_file_lines"])).named("one"))
+            .source(MockServer::with_tools(tools(&["read_file", "read_dir"])).named("two"))
+            .query("read")
+            .matcher(score)
+            .sort_by_score()
+    };
+    let ranked = |results: Vec<toolsearch::ToolSearchMatch>| -> Vec<(String, String)> {
+        results
+            .iter()
+            .map(|m| (m.server_name.clone(), m.tool_name().to_string()))
+            .collect()
+    };
+    let pair = |server: &str, tool: &str| (server.to_string(), tool.to_string());
+
+    // The limit keeps the top matches by score, not the first ones found
+    let top = search().max_per_server(1).limit(2).search().await.unwrap();
+    assert_eq!(ranked(top), [pair("one", "read_file_lines"), pair("two", "read_file")]);
+
+    // Duplicates are dropped before limits, keeping the best-ranked one
+    let deduped = search().dedupe(Dedupe::Definition).limit(3).search().await.unwrap();
+    assert_eq!(
+        ranked(deduped),
+        [pair("one", "read_file_lines"), pair("one", "read_file"), pair("two", "read_dir")]
+    );
+}
+
+#[tokio::test]
+async fn test_catalog_searches_one_fetch() {
+    use std::sync::Arc;
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::{fetch_sources_catalog, Catalog, SearchCriteria, SearchOptions, ToolSource};
+
+    let files = Arc::new(
+        MockServer::with_tools(vec![
+            ToolFixture::new("read_file").build(),
+            ToolFixture::new("write_file").build(),
+        ])
+        .named("files"),
+    );
+    let sources: Vec<Arc<dyn ToolSource>> = vec![
+        files.clone(),
+        Arc::new(MockServer::with_tools(vec![]).named("broken").failing("boom")),
+    ];
+    let catalog = fetch_sources_catalog(&sources, &SearchOptions::default()).await.unwrap();
+    assert_eq!(catalog.len(), 2);
+    assert_eq!(catalog.servers().len(), 1);
+    assert_eq!(catalog.warnings().len(), 1);
+    let listed: Vec<_> = catalog.iter().map(|(server, tool)| (server, tool.name.as_ref())).collect();
+    assert_eq!(listed, [("files", "read_file"), ("files", "write_file")]);
+
+    for (query, hits) in [("read", 1), ("file", 2), ("email", 0)] {
+        assert_eq!(catalog.search(&SearchCriteria::with_query(query.to_string())).len(), hits);
+    }
+    assert_eq!(files.fetch_count(), 1);
+
+    // Saved and loaded, the catalog searches the same without fetching
+    let loaded: Catalog = serde_json::from_str(&serde_json::to_string(&catalog).unwrap()).unwrap();
+    assert_eq!(loaded.servers(), catalog.servers());
+    assert_eq!(loaded.search(&SearchCriteria::match_all()).len(), 2);
+}
+
+#[test]
+fn test_catalog_set_operations() {
+    use toolsearch::testing::ToolFixture;
+    use toolsearch::{Catalog, CatalogServer};
+
+    let server = |name: &str, tools: &[(&str, &str)]| CatalogServer {
+        name: name.to_string(),
+        priority: 0,
+        boosts: Default::default(),
+        tools: tools
+            .iter()
+            .map(|(tool, description)| ToolFixture::new(*tool).description(*description).build())
+            .collect(),
+        server_info: None,
+    };
+    let staging = Catalog::new(vec![
+        server("files", &[("read_file", "Read"), ("write_file", "Write")]),
+        server("beta", &[("preview", "Preview")]),
+    ]);
+    let prod = Catalog::new(vec![
+        server("files", &[("read_file", "Read"), ("write_file", "Write a file"), ("delete_file", "Delete")]),
+        server("mail", &[("send_email", "Send")]),
+    ]);
+    let names = |catalog: &Catalog| -> Vec<String> {
+        catalog.iter().map(|(server, tool)| format!("{}/{}", server, tool.name)).collect()
+    };
+
+    let merged = staging.merge(&prod);
+    assert_eq!(
+        names(&merged),
+        ["files/read_file", "files/write_file", "files/delete_file", "beta/preview", "mail/send_email"]
+    );
+    // Tools in both keep the definition of the catalog merged into
+    let write = &merged.server("files").unwrap().tools[1];
+    assert_eq!(write.description.as_deref(), Some("Write"));
+
+    // A changed description makes write_file differ between the two
+    assert_eq!(names(&staging.difference(&prod)), ["files/write_file", "beta/preview"]);
+    assert_eq!(names(&prod.difference(&staging)), ["files/write_file", "files/delete_file", "mail/send_email"]);
+    assert_eq!(names(&staging.intersect(&prod)), ["files/read_file"]);
+    assert!(staging.difference(&staging).is_empty());
+}
+
+#[tokio::test]
+async fn test_catalog_changes_between_fetches() {
+    use toolsearch::testing::ToolFixture;
+    use toolsearch::{fetch_catalog, Catalog, CatalogEvent, CatalogServer, SearchOptions, ServerConfig};
+
+    let server = |name: &str, tools: &[(&str, &str)]| CatalogServer {
+        name: name.to_string(),
+        priority: 0,
+        boosts: Default::default(),
+        tools: tools
+            .iter()
+            .map(|(tool, description)| ToolFixture::new(*tool).description(*description).build())
+            .collect(),
+        server_info: None,
+    };
+    let before = Catalog::new(vec![
+        server("files", &[("read_file", "Read"), ("write_file", "Write"), ("stat", "Stat")]),
+        server("beta", &[("preview", "Preview")]),
+    ]);
+    let after = Catalog::new(vec![
+        server("mail", &[("send_email", "Send")]),
+        server("files", &[("read_file", "Read"), ("write_file", "Write a file"), ("delete_file", "Delete")]),
+    ]);
+
+    let events: Vec<_> = before
+        .changes(&after)
+        .iter()
+        .map(|event| {
+            let kind = match event {
+                CatalogEvent::ToolAdded { .. } => "added",
+                CatalogEvent::ToolRemoved { .. } => "removed",
+                CatalogEvent::ToolChanged { .. } => "changed",
+            };
+            format!("{} {}/{}", kind, event.server_name(), event.tool_name())
+        })
+        .collect();
+    assert_eq!(
+        events,
+        [
+            "added mail/send_email",
+            "changed files/write_file",
+            "added files/delete_file",
+            "removed files/stat",
+            "removed beta/preview",
+        ]
+    );
+    assert!(after.changes(&after).is_empty());
+
+    let event = serde_json::to_value(&before.changes(&after)[3]).unwrap();
+    assert_eq!(event, json!({ "event": "tool_removed", "server_name": "files", "tool_name": "stat" }));
+
+    // A server that couldn't be fetched hasn't lost its tools
+    let unreachable = fetch_catalog(&[ServerConfig::stdio("beta", "")], &SearchOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(unreachable.warnings().len(), 1);
+    assert!(before.changes(&unreachable).iter().all(|event| event.server_name() == "files"));
+}
+
+#[test]
+fn test_lint_catalog_reports_quality_issues() {
+    use serde_json::json;
+    use toolsearch::lint::{lint_catalog, LintRule};
+    use toolsearch::testing::ToolFixture;
+    use toolsearch::{Catalog, CatalogServer};
+
+    let documented = json!({
+        "type": "object",
+        "properties": { "path": { "type": "string", "description": "File to read" } }
+    });
+    let tools = vec![
+        ToolFixture::new("read_file").description("Read a file").schema(documented).build(),
+        ToolFixture::new("Execute").description("Runs things").build(),
+        ToolFixture::new("write_file")
+            .description("  ")
+            .schema(json!({ "type": "object", "properties": { "path": {}, "contents": { "description": "Text" } } }))
+            .build(),
+        ToolFixture::new("list_dir").description("List a directory").build(),
+        ToolFixture::new("list_dir").description("List a directory again").build(),
+    ];
+    let catalog = Catalog::new(vec![
+        CatalogServer {
+            name: "files".to_string(),
+            priority: 0,
+            boosts: Default::default(),
+            tools,
+            server_info: None,
+        },
+        // Shared with another server, list_dir is no duplicate there
+        CatalogServer {
+            name: "other".to_string(),
+            priority: 0,
+            boosts: Default::default(),
+            tools: vec![ToolFixture::new("list_dir").description("List").build()],
+            server_info: None,
+        },
+    ]);
+
+    let issues = lint_catalog(&catalog);
+    let found: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.tool_name.as_deref().unwrap(), issue.rule))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("Execute", LintRule::GenericName),
+            ("write_file", LintRule::MissingDescription),
+            ("write_file", LintRule::UndocumentedProperty),
+            ("list_dir", LintRule::DuplicateName),
+        ]
+    );
+    assert_eq!(issues[2].to_string(), "files/write_file: undocumented-property: Property \"path\" has no description");
+    assert_eq!(issues[3].message, "Listed 2 times by this server");
+    let json = serde_json::to_value(&issues[0]).unwrap();
+    assert_eq!(json["rule"], "generic-name");
+    assert_eq!(json["server_name"], "files");
+    assert!(lint_catalog(&Catalog::default()).is_empty());
+}
+
+#[tokio::test]
+async fn test_tags_filter_tools() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::SearchCriteria;
+
+    let tagged = |name: &str, tags: serde_json::Value| {
+        ToolFixture::new(name)
+            .schema(json!({ "type": "object", "x-tags": tags }))
+            .build()
+    };
+    let tools = vec![
+        tagged("read_file", json!(["Files", "read"])),
+        tagged("write_file", json!("files, write")),
+        ToolFixture::new("send_email").build(),
+    ];
+    let spec = json!({
+        "openapi": "3.0.0",
+        "paths": { "/files": { "get": { "operationId": "listFiles", "tags": ["files"] } } }
+    });
+
+    let results = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools.clone()).named("local"))
+        .source(OpenApiSource::from_value("api", &spec).unwrap())
+        .tag("FILES")
+        .search()
+        .await
+        .unwrap();
+    let names: Vec<_> = results.iter().map(|m| m.tool_name()).collect();
+    assert_eq!(names, ["listFiles", "read_file", "write_file"]);
+    assert_eq!(results[1].tags, ["files", "read"]);
+
+    // Every tag must be present, and tags combine with the query
+    let criteria = SearchCriteria::with_query("file".to_string()).with_tags(["files", "write"]);
+    let matching: Vec<_> = tools.iter().filter(|tool| criteria.matches(tool)).collect();
+    assert_eq!(matching.len(), 1);
+    assert_eq!(matching[0].name, "write_file");
+}
+
+#[tokio::test]
+async fn test_description_quality_filters() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::SearchCriteria;
+
+    let tools = vec![
+        ToolFixture::new("read_file")
+            .description("Read a file from disk, e.g. read_file(\"notes.txt\")")
+            .build(),
+        ToolFixture::new("write_file")
+            .description("Write text to a file")
+            .schema(json!({
+                "type": "object",
+                "properties": { "path": { "type": "string", "examples": ["notes.txt"] } }
+            }))
+            .build(),
+        ToolFixture::new("leer_archivo")
+            .description("Lee un archivo del disco, por ejemplo notas.txt")
+            .build(),
+        ToolFixture::new("copy_file")
+            .description("Copy a file to another place. For instance, back up notes before editing them in the current directory.")
+            .build(),
+        ToolFixture::new("move_file").build(),
+    ];
+
+    let results = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools.clone()))
+        .query("file")
+        .require_example()
+        .english_only()
+        .max_description_length(80)
+        .search()
+        .await
+        .unwrap();
+    let names: Vec<_> = results.iter().map(|m| m.tool_name()).collect();
+    assert_eq!(names, ["read_file", "write_file"]);
+
+    // The filters apply to exact names as well, and a missing description
+    // fails any of them
+    let criteria = SearchCriteria::with_name("move_file".to_string()).with_english_only();
+    assert!(!criteria.matches(&tools[4]));
+    let criteria = SearchCriteria::match_all().with_min_description_length(30);
+    let matching: Vec<_> = tools.iter().filter(|tool| criteria.matches(tool)).map(|tool| &tool.name).collect();
+    assert_eq!(matching, ["read_file", "leer_archivo", "copy_file"]);
+}
+
+#[test]
+fn test_language_filter_detects_description_language() {
+    use toolsearch::quality::detect_language;
+    use toolsearch::testing::ToolFixture;
+    use toolsearch::SearchCriteria;
+
+    let cases = [
+        ("List the files in a directory", Some("en")),
+        ("Liste les fichiers d'un répertoire", Some("fr")),
+        ("Listet die Dateien in einem Verzeichnis und ihre Größe", Some("de")),
+        ("Lista los archivos de un directorio", Some("es")),
+        ("Список файлов в каталоге", Some("ru")),
+        ("列出目录中的文件", Some("zh")),
+        ("ディレクトリ内のファイルを一覧表示します", Some("ja")),
+        ("디렉터리의 파일 목록", Some("ko")),
+        ("List files", Some("en")),
+        ("1234 -- ?", None),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(detect_language(text), expected, "{}", text);
+    }
+
+    let tools: Vec<_> = cases
+        .iter()
+        .enumerate()
+        .map(|(i, (text, _))| ToolFixture::new(format!("tool_{}", i)).description(*text).build())
+        .collect();
+    let matching = |criteria: SearchCriteria| -> Vec<String> {
+        tools
+            .iter()
+            .filter(|tool| criteria.matches(tool))
+            .map(|tool| tool.name.to_string())
+            .collect()
+    };
+    assert_eq!(matching(SearchCriteria::match_all().language("EN-us")), ["tool_0", "tool_8"]);
+    assert_eq!(matching(SearchCriteria::with_query("fichiers".to_string()).language("fr")), ["tool_1"]);
+    assert!(matching(SearchCriteria::match_all().language("sv")).is_empty());
+}
+
+#[test]
+fn test_config_boosts_rank_matches() {
+    use toolsearch::testing::ToolFixture;
+    use toolsearch::{Catalog, CatalogServer, SearchCriteria, SearchOptions, ServerConfig, SortOrder};
+
+    let config: ServerConfig = serde_json::from_value(json!({
+        "name": "internal",
+        "transport": { "type": "stdio", "command": "internal-mcp" },
+        "boost": 2.0,
+        "tool_boosts": [{ "pattern": "legacy_*", "factor": 0.1 }]
+    }))
+    .unwrap();
+    assert!(config.validate().is_ok());
+    assert!(config.clone().with_tool_boost("", 1.0).validate().is_err());
+    assert!(config.clone().with_boost(-1.0).validate().is_err());
+
+    let server = |name: &str, boosts, tools: &[&str]| CatalogServer {
+        name: name.to_string(),
+        priority: 0,
+        boosts,
+        tools: tools.iter().map(|tool| ToolFixture::new(*tool).build()).collect(),
+        server_info: None,
+    };
+    let catalog = Catalog::new(vec![
+        server("external", Default::default(), &["search"]),
+        server("internal", config.boosts(), &["legacy_search", "search"]),
+    ]);
+    let options = SearchOptions {
+        sort_order: SortOrder::Score,
+        ..Default::default()
+    };
+    let results = catalog.search_with_options(&SearchCriteria::with_query("search".to_string()), &options);
+    let ranked: Vec<_> = results
+        .iter()
+        .map(|m| (m.server_name.as_str(), m.tool_name(), m.score))
+        .collect();
+    assert_eq!(
+        ranked,
+        [
+            ("internal", "search", Some(2.0)),
+            ("external", "search", Some(1.0)),
+            ("internal", "legacy_search", Some(0.2)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_blocked_tools_and_servers_are_left_out() {
+    use std::sync::Arc;
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::ServerConfig;
+
+    let tools = ["drop_table", "read_file", "drop_index", "truncate"]
+        .map(|name| ToolFixture::new(name).build())
+        .to_vec();
+    let files = Arc::new(MockServer::with_tools(tools).named("files"));
+    let admin = Arc::new(MockServer::with_tools(vec![ToolFixture::new("reset").build()]).named("admin"));
+    let report = SearchBuilder::new(vec![])
+        .source(Arc::clone(&files))
+        .source(Arc::clone(&admin))
+        .block("drop_*")
+        .block("files/truncate")
+        .block("admin/*")
+        .limit(1)
+        .search_report()
+        .await
+        .unwrap();
+    let names: Vec<_> = report.matches.iter().map(|m| m.tool_name()).collect();
+    // Blocked tools don't take up the limit
+    assert_eq!(names, ["read_file"]);
+    assert_eq!(admin.fetch_count(), 0);
+    assert_eq!(report.health.queried, 1);
+
+    // A server's config can block its own tools, or itself
+    let config: ServerConfig = serde_json::from_value(json!({
+        "name": "retired",
+        "transport": { "type": "stdio", "command": "toolsearch-test-command-that-does-not-exist" },
+        "blocked_tools": ["*"]
+    }))
+    .unwrap();
+    assert!(config.clone().with_blocked_tool("").validate().is_err());
+    let report = SearchBuilder::new(vec![config]).search_report().await.unwrap();
+    assert!(report.warnings.is_empty());
+    assert_eq!(report.health.queried, 0);
+}
+
+#[tokio::test]
+async fn test_redacted_schema_fields_are_blanked() {
+    use std::sync::Arc;
+    use toolsearch::redact::REDACTED;
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::{fetch_sources_catalog, SearchOptions, ToolSource};
+
+    let tool = ToolFixture::new("fetch")
+        .schema(json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "default": "https://wiki.internal/" },
+                "api_token": { "type": "string", "examples": ["sk-123"] }
+            }
+        }))
+        .build();
+    let server = Arc::new(MockServer::with_tools(vec![tool]).named("web"));
+    let matches = SearchBuilder::new(vec![])
+        .source(Arc::clone(&server))
+        .query("fetch")
+        .redact("default")
+        .redact("*_token")
+        .search()
+        .await
+        .unwrap();
+    assert_eq!(matches.len(), 1);
+    let properties = &matches[0].tool.input_schema["properties"];
+    assert_eq!(properties["url"], json!({ "type": "string", "default": REDACTED }));
+    assert_eq!(properties["api_token"], REDACTED);
+
+    let sources: Vec<Arc<dyn ToolSource>> = vec![server];
+    let mut catalog = fetch_sources_catalog(&sources, &SearchOptions::default()).await.unwrap();
+    catalog.redact(&toolsearch::Redaction::new(["default"]));
+    let (_, tool) = catalog.iter().next().unwrap();
+    assert_eq!(tool.input_schema["properties"]["url"]["default"], REDACTED);
+}
+
+#[tokio::test]
+async fn test_aliases_resolve_shorthand_queries() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::AliasMap;
+
+    let aliases: AliasMap =
+        serde_json::from_value(json!({ "grep": "search_files", "LS": ["list_directory", "list_dir"] })).unwrap();
+    assert_eq!(aliases.resolve(" ls "), ["list_directory", "list_dir"]);
+    let saved = serde_json::to_value(&aliases).unwrap();
+    assert_eq!(saved["grep"], "search_files");
+
+    let server = || {
+        MockServer::with_tools(vec![
+            ToolFixture::new("search_files").description("Find text in files").build(),
+            ToolFixture::new("grep_logs").build(),
+            ToolFixture::new("list_dir").build(),
+        ])
+        .named("files")
+    };
+    let search = |query: &str| {
+        SearchBuilder::new(vec![])
+            .source(server())
+            .query(query)
+            .aliases(aliases.clone())
+    };
+
+    let plan = search("grep").explain_plan();
+    assert_eq!(plan.criteria.aliases, ["search_files"]);
+    let names = |results: Vec<toolsearch::ToolSearchMatch>| -> Vec<String> {
+        results.iter().map(|m| m.tool_name().to_string()).collect()
+    };
+    assert_eq!(names(search("grep").search().await.unwrap()), ["grep_logs", "search_files"]);
+    assert_eq!(names(search("ls").search().await.unwrap()), ["list_dir"]);
+    // Queries that aren't aliases search as usual
+    assert_eq!(names(search("find").search().await.unwrap()), ["search_files"]);
+}
+
+#[tokio::test]
+async fn test_query_templates_and_field_prefixes() {
+    use std::collections::BTreeMap;
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::{ConfigError, TemplateSet};
+
+    let templates: TemplateSet =
+        serde_json::from_value(json!({ "verb-noun": "name:^{verb}_.*{noun}", "repeat": "^a{2,3}$" })).unwrap();
+    let params: BTreeMap<String, String> = [("verb", "read"), ("noun", "file")]
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .into();
+    let query = templates.render("verb-noun", &params).unwrap();
+    assert_eq!(query, "name:^read_.*file");
+    // Regex repetitions are not placeholders
+    assert_eq!(templates.render("repeat", &BTreeMap::new()).unwrap(), "^a{2,3}$");
+    assert!(matches!(
+        templates.render("verb-noun", &BTreeMap::new()),
+        Err(ConfigError::MissingParameter { name }) if name == "verb"
+    ));
+    assert!(matches!(
+        templates.render("nope", &params),
+        Err(ConfigError::UnknownTemplate { .. })
+    ));
+
+    let search = |query: &str| {
+        SearchBuilder::new(vec![])
+            .source(
+                MockServer::with_tools(vec![
+                    ToolFixture::new("read_config_file").build(),
+                    ToolFixture::new("read_url").description("Read a file from a URL").build(),
+                    ToolFixture::new("write_file").build(),
+                ])
+                .named("files"),
+            )
+            .query(query)
+            .search()
+    };
+    let names = |results: Vec<toolsearch::ToolSearchMatch>| -> Vec<String> {
+        results.iter().map(|m| m.tool_name().to_string()).collect()
+    };
+    #[cfg(feature = "regex-mode")]
+    assert_eq!(names(search(&query).await.unwrap()), ["read_config_file"]);
+    // A field prefix keeps hits in other fields out
+    assert_eq!(names(search("file").await.unwrap()).len(), 3);
+    assert_eq!(names(search("name:file").await.unwrap()), ["read_config_file", "write_file"]);
+}
+
+#[tokio::test]
+async fn test_or_queries_union_with_attribution() {
+    use std::sync::Arc;
+    use toolsearch::testing::{MockServer, ToolFixture};
+
+    let server = Arc::new(
+        MockServer::with_tools(vec![
+            ToolFixture::new("read_file").build(),
+            ToolFixture::new("write_file").build(),
+            ToolFixture::new("read_write_lock").build(),
+            ToolFixture::new("send_email").build(),
+        ])
+        .named("local"),
+    );
+    let builder = SearchBuilder::new(vec![])
+        .source(server.clone())
+        .query("read")
+        .or_query("write");
+    assert_eq!(builder.explain_plan().queries, ["read", "write"]);
+
+    let results = builder.search().await.unwrap();
+    let attributed: Vec<_> = results
+        .iter()
+        .map(|m| (m.tool_name(), m.queries.join("|")))
+        .collect();
+    assert_eq!(
+        attributed,
+        [
+            ("read_file", "read".to_string()),
+            ("read_write_lock", "read|write".to_string()),
+            ("write_file", "write".to_string()),
+        ]
+    );
+    // One fetch serves every query
+    assert_eq!(server.fetch_count(), 1);
+
+    // Single-query searches carry no attribution
+    let single = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(vec![ToolFixture::new("read_file").build()]))
+        .query("read")
+        .search()
+        .await
+        .unwrap();
+    assert!(single[0].queries.is_empty());
+}
+
+#[tokio::test]
+async fn test_projection_strips_unrequested_fields() {
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::{OutputField, Projection};
+
+    let schema = json!({
+        "type": "object",
+        "x-tags": ["files"],
+        "properties": { "path": { "type": "string", "description": "File to read" } }
+    });
+    let tools = vec![
+        ToolFixture::new("read_file").title("Read").description("Read a file").schema(schema.clone()).build(),
+        ToolFixture::new("write_file").description("Write a file").schema(schema).build(),
+    ];
+
+    let results = SearchBuilder::new(vec![])
+        .source(MockServer::with_tools(tools).named("local"))
+        .query("read")
+        .or_query("write")
+        .projection(Projection::new([OutputField::Description, OutputField::Queries]))
+        .search()
+        .await
+        .unwrap();
+    let json = serde_json::to_value(&results).unwrap();
+    assert_eq!(
+        json,
+        json!([
+            {
+                "server_name": "local",
+                "tool": { "name": "read_file", "description": "Read a file", "inputSchema": {} },
+                "queries": ["read"]
+            },
+            {
+                "server_name": "local",
+                "tool": { "name": "write_file", "description": "Write a file", "inputSchema": {} },
+                "queries": ["write"]
+            }
+        ])
+    );
+
+    assert_eq!(
+        Projection::parse("name, server,Input_Schema").unwrap().fields(),
+        [OutputField::Name, OutputField::Server, OutputField::InputSchema]
+    );
+    let error = Projection::parse("name,schema").unwrap_err();
+    assert!(error.contains("Unknown output field \"schema\""), "{}", error);
+}
+
+#[tokio::test]
+async fn test_slow_servers_get_timeout_suggestions() {
+    use std::time::Duration;
+    use toolsearch::testing::{MockServer, ToolFixture};
+    use toolsearch::SearchWarning;
+
+    let slow = || {
+        MockServer::with_tools(vec![ToolFixture::new("read_file").build()])
+            .named("slow")
+            .with_delay(Duration::from_millis(900))
+    };
+    let report = SearchBuilder::new(vec![])
+        .source(slow())
+        .source(MockServer::with_tools(vec![ToolFixture::new("read_dir").build()]).named("fast"))
+        .timeout(1)
+        .query("read")
+        .search_report()
+        .await
+        .unwrap();
+
+    assert_eq!(report.matches.len(), 2);
+    assert!(matches!(
+        &report.warnings[..],
+        [SearchWarning::SlowServer { server_name, timeout, .. }]
+            if server_name == "slow" && *timeout == Duration::from_secs(1)
+    ));
+    assert_eq!(report.suggested_timeouts(), [("slow", Duration::from_secs(2))]);
+    assert!(report.warnings[0].to_string().contains("consider a timeout of 2s"));
+    let timing = report.timings.iter().find(|t| t.server_name == "slow").unwrap();
+    assert_eq!(timing.timeout, Some(Duration::from_secs(1)));
+
+    let report = SearchBuilder::new(vec![])
+        .source(slow())
+        .timeout(1)
+        .slow_server_fraction(0.95)
+        .search_report()
+        .await
+        .unwrap();
+    assert!(report.warnings.is_empty());
+
+    // Fractions no Duration can be scaled by are compared all the same
+    for (fraction, warned) in [(f32::NAN, false), (f32::MAX, false), (-1.0, true)] {
+        let report = SearchBuilder::new(vec![])
+            .source(MockServer::with_tools(vec![ToolFixture::new("read_dir").build()]))
+            .timeout(1)
+            .slow_server_fraction(fraction)
+            .search_report()
+            .await
+            .unwrap();
+        assert_eq!(report.warnings.len(), usize::from(warned), "fraction {}", fraction);
+    }
+}